@@ -0,0 +1,84 @@
+//! 查询结果分析模块
+//!
+//! 基于 [`DetailedLogResult`](crate::log_query::DetailedLogResult) 计算汇总统计：
+//! 按级别、PSM、Pod 的消息数量，时间直方图，以及重复出现的错误。
+//!
+//! ```no_run
+//! # fn example(result: &logid::DetailedLogResult) {
+//! use logid::analysis::{ResultStats, StatsOptions};
+//!
+//! let stats = ResultStats::from_result(result, &StatsOptions::default().with_top_errors(5));
+//! println!("{}", stats.format_text());
+//! # }
+//! ```
+
+mod stats;
+
+pub use stats::{CountEntry, ErrorSummary, ResultStats, StatsOptions, TimeBucket};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::log_query::{DetailedLogResult, ExtractedLogMessage};
+    use std::time::Duration;
+
+    fn message(psm: &str, pod: &str, level: &str, time: &str, msg: &str) -> ExtractedLogMessage {
+        let mut message = crate::test_support::message(psm, level, msg);
+        message.group.pod_name = Some(pod.to_string());
+        message.time = Some(time.to_string());
+        message
+    }
+
+    #[test]
+    fn test_result_stats() {
+        let result = DetailedLogResult {
+            logid: "logid-1".to_string(),
+            messages: vec![
+                message("pay.api", "pod-a", "INFO", "2024-01-01T12:00:01Z", "request received"),
+                message("pay.api", "pod-a", "error", "2024-01-01T12:00:02Z", "db timeout after 302ms"),
+                message("user.api", "pod-b", "ERROR", "2024-01-01T12:00:25Z", "db timeout after 310ms"),
+                message("pay.api", "pod-b", "ERROR", "2024-01-01T12:00:26Z", "status=500"),
+                message("pay.api", "pod-a", "WARN", "not a time", "slow"),
+            ],
+            meta: None,
+            tag_infos: None,
+            total_items: 5,
+            scan_time_range: None,
+            level_list: None,
+            timestamp: String::new(),
+            region: "us".to_string(),
+            region_display_name: "美区".to_string(),
+            truncated: false,
+            credential_expiry: None,
+            stats: None,
+            level_filtered: 0,
+            scan_widening: None,
+            context: None,
+        };
+
+        let stats = ResultStats::from_result(&result, &StatsOptions::default());
+        assert_eq!(stats.total_messages, 5);
+        assert_eq!(stats.by_level[0], CountEntry { name: "ERROR".to_string(), count: 3 });
+        assert_eq!(stats.by_psm[0], CountEntry { name: "pay.api".to_string(), count: 4 });
+        assert_eq!(stats.untimed, 1);
+
+        // 跨度 25 秒，自动选择 5 秒的桶，中间的空桶也会输出
+        assert_eq!(stats.bucket_width_secs, 5);
+        let counts: Vec<usize> = stats.histogram.iter().map(|b| b.count).collect();
+        assert_eq!(counts, vec![2, 0, 0, 0, 0, 2]);
+
+        // 只有数字不同的错误归为一类
+        assert_eq!(stats.top_errors[0].pattern, "db timeout after <n>ms");
+        assert_eq!(stats.top_errors[0].count, 2);
+        assert_eq!(stats.top_errors[0].psms, vec!["pay.api", "user.api"]);
+        assert_eq!(stats.top_errors.len(), 2);
+
+        let options = StatsOptions::default()
+            .with_bucket_width(Duration::from_secs(60))
+            .with_top_errors(1);
+        let stats = ResultStats::from_result(&result, &options);
+        assert_eq!(stats.histogram.len(), 1);
+        assert_eq!(stats.top_errors.len(), 1);
+        assert!(stats.format_text().contains("高频错误"));
+    }
+}
@@ -0,0 +1,298 @@
+//! 查询结果统计
+
+use crate::log_query::{parse_message_time, DetailedLogResult, ExtractedLogMessage};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::time::Duration;
+
+/// 自动选择时间分桶宽度时的候选值（秒）
+const BUCKET_WIDTHS: &[u64] = &[1, 5, 10, 30, 60, 300, 600, 1800, 3600, 21600, 86400];
+
+/// 自动选择分桶宽度时的最大桶数
+const MAX_AUTO_BUCKETS: u64 = 20;
+
+/// 错误字符串样本的最大长度（字符数）
+const MAX_ERROR_CHARS: usize = 200;
+
+/// 视为错误的日志级别
+const ERROR_LEVELS: &[&str] = &["ERROR", "FATAL", "CRITICAL", "PANIC"];
+
+/// 统计选项
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StatsOptions {
+    /// 时间直方图的分桶宽度，`None` 时根据时间跨度自动选择
+    pub bucket_width: Option<Duration>,
+    /// 输出的高频错误数量
+    pub top_errors: usize,
+}
+
+impl Default for StatsOptions {
+    fn default() -> Self {
+        Self {
+            bucket_width: None,
+            top_errors: 10,
+        }
+    }
+}
+
+impl StatsOptions {
+    /// 设置时间直方图的分桶宽度
+    pub fn with_bucket_width(mut self, width: Duration) -> Self {
+        self.bucket_width = Some(width);
+        self
+    }
+
+    /// 设置输出的高频错误数量
+    pub fn with_top_errors(mut self, top_errors: usize) -> Self {
+        self.top_errors = top_errors;
+        self
+    }
+}
+
+/// 按名称计数的一项
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CountEntry {
+    /// 名称（级别、PSM 或 Pod），缺失时为 `-`
+    pub name: String,
+    /// 消息数量
+    pub count: usize,
+}
+
+/// 时间直方图的一个桶
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TimeBucket {
+    /// 桶的起始时间
+    pub start: DateTime<Utc>,
+    /// 落在 `[start, start + bucket_width)` 内的消息数量
+    pub count: usize,
+}
+
+/// 重复出现的错误
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ErrorSummary {
+    /// 归一化后的错误字符串（数字替换为 `<n>`）
+    pub pattern: String,
+    /// 出现次数
+    pub count: usize,
+    /// 第一次出现时的原始内容
+    pub sample: String,
+    /// 出现该错误的 PSM
+    pub psms: Vec<String>,
+}
+
+/// 查询结果的汇总统计
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ResultStats {
+    /// 参与统计的 logid
+    pub logids: Vec<String>,
+    /// 消息总数
+    pub total_messages: usize,
+    /// 按级别计数
+    pub by_level: Vec<CountEntry>,
+    /// 按 PSM 计数
+    pub by_psm: Vec<CountEntry>,
+    /// 按 Pod 计数
+    pub by_pod: Vec<CountEntry>,
+    /// 分桶宽度（秒），没有可解析时间的消息时为 0
+    pub bucket_width_secs: u64,
+    /// 时间直方图，包含计数为 0 的中间桶
+    pub histogram: Vec<TimeBucket>,
+    /// 无法解析时间的消息数量
+    pub untimed: usize,
+    /// 按出现次数排序的高频错误
+    pub top_errors: Vec<ErrorSummary>,
+}
+
+impl ResultStats {
+    /// 统计单个查询结果
+    pub fn from_result(result: &DetailedLogResult, options: &StatsOptions) -> Self {
+        Self::from_results(std::slice::from_ref(result), options)
+    }
+
+    /// 合并统计多个查询结果
+    pub fn from_results(results: &[DetailedLogResult], options: &StatsOptions) -> Self {
+        let messages: Vec<&ExtractedLogMessage> =
+            results.iter().flat_map(|result| &result.messages).collect();
+
+        let by_level = count_by(&messages, |m| m.level.as_deref().map(str::to_uppercase));
+        let by_psm = count_by(&messages, |m| m.group.psm.clone());
+        let by_pod = count_by(&messages, |m| m.group.pod_name.clone());
+
+        let times: Vec<DateTime<Utc>> = messages
+            .iter()
+            .filter_map(|m| m.time.as_deref().and_then(parse_message_time))
+            .collect();
+        let (bucket_width_secs, histogram) = histogram(&times, options.bucket_width);
+
+        Self {
+            logids: results.iter().map(|result| result.logid.clone()).collect(),
+            total_messages: messages.len(),
+            by_level,
+            by_psm,
+            by_pod,
+            bucket_width_secs,
+            histogram,
+            untimed: messages.len() - times.len(),
+            top_errors: top_errors(&messages, options.top_errors),
+        }
+    }
+
+    /// 格式化为便于阅读的文本
+    pub fn format_text(&self) -> String {
+        let mut lines = vec![format!(
+            "📊 {} ({} 条消息)",
+            self.logids.join(", "),
+            self.total_messages
+        )];
+
+        let sections = [("级别", &self.by_level), ("PSM", &self.by_psm), ("Pod", &self.by_pod)];
+        for (title, entries) in sections {
+            lines.push(String::new());
+            lines.push(format!("{}:", title));
+            lines.extend(entries.iter().map(|e| format!("  {:>6}  {}", e.count, e.name)));
+        }
+
+        if !self.histogram.is_empty() {
+            lines.push(String::new());
+            lines.push(format!("时间分布（每 {} 秒）:", self.bucket_width_secs));
+            let max = self.histogram.iter().map(|b| b.count).max().unwrap_or(0).max(1);
+            for bucket in &self.histogram {
+                let bar = "█".repeat((bucket.count * 40).div_ceil(max));
+                lines.push(format!(
+                    "  {}  {:>6}  {}",
+                    bucket.start.format("%Y-%m-%d %H:%M:%S"),
+                    bucket.count,
+                    bar
+                ));
+            }
+        }
+        if self.untimed > 0 {
+            lines.push(format!("  无法解析时间: {}", self.untimed));
+        }
+
+        if !self.top_errors.is_empty() {
+            lines.push(String::new());
+            lines.push("高频错误:".to_string());
+            for error in &self.top_errors {
+                lines.push(format!("  {:>6}  {} [{}]", error.count, error.pattern, error.psms.join(", ")));
+            }
+        }
+        lines.join("\n")
+    }
+}
+
+/// 按名称计数，按数量降序、名称升序排列
+fn count_by(
+    messages: &[&ExtractedLogMessage],
+    key: impl Fn(&ExtractedLogMessage) -> Option<String>,
+) -> Vec<CountEntry> {
+    let mut counts: HashMap<String, usize> = HashMap::new();
+    for message in messages {
+        *counts.entry(key(message).unwrap_or_else(|| "-".to_string())).or_default() += 1;
+    }
+    let mut entries: Vec<CountEntry> = counts
+        .into_iter()
+        .map(|(name, count)| CountEntry { name, count })
+        .collect();
+    entries.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.name.cmp(&b.name)));
+    entries
+}
+
+/// 生成时间直方图，返回分桶宽度（秒）和连续的桶
+fn histogram(times: &[DateTime<Utc>], width: Option<Duration>) -> (u64, Vec<TimeBucket>) {
+    let (Some(first), Some(last)) = (times.iter().min(), times.iter().max()) else {
+        return (0, Vec::new());
+    };
+
+    let span = (*last - *first).num_seconds().max(0) as u64;
+    let width = width.map(|w| w.as_secs().max(1)).unwrap_or_else(|| {
+        BUCKET_WIDTHS
+            .iter()
+            .copied()
+            .find(|w| span / w < MAX_AUTO_BUCKETS)
+            .unwrap_or(*BUCKET_WIDTHS.last().unwrap())
+    });
+
+    let origin = first.timestamp().div_euclid(width as i64) * width as i64;
+    let bucket_count = (last.timestamp() - origin) as u64 / width + 1;
+    let mut counts = vec![0usize; bucket_count as usize];
+    for time in times {
+        counts[((time.timestamp() - origin) as u64 / width) as usize] += 1;
+    }
+
+    let buckets = counts
+        .into_iter()
+        .enumerate()
+        .filter_map(|(index, count)| {
+            let start = DateTime::from_timestamp(origin + (index as u64 * width) as i64, 0)?;
+            Some(TimeBucket { start, count })
+        })
+        .collect();
+    (width, buckets)
+}
+
+/// 统计错误级别消息中重复出现的内容
+fn top_errors(messages: &[&ExtractedLogMessage], limit: usize) -> Vec<ErrorSummary> {
+    let mut summaries: Vec<ErrorSummary> = Vec::new();
+    let mut index: HashMap<String, usize> = HashMap::new();
+
+    let errors = messages.iter().filter(|m| {
+        m.level
+            .as_deref()
+            .is_some_and(|level| ERROR_LEVELS.contains(&level.to_uppercase().as_str()))
+    });
+    for message in errors {
+        let text = message
+            .values
+            .iter()
+            .map(|value| value.value.trim())
+            .find(|value| !value.is_empty())
+            .unwrap_or_default();
+        let sample: String = text.lines().next().unwrap_or_default().chars().take(MAX_ERROR_CHARS).collect();
+        let pattern = normalize_error(&sample);
+        let psm = message.group.psm.clone().unwrap_or_else(|| "-".to_string());
+
+        match index.get(&pattern) {
+            Some(&position) => {
+                let summary = &mut summaries[position];
+                summary.count += 1;
+                if !summary.psms.contains(&psm) {
+                    summary.psms.push(psm);
+                }
+            }
+            None => {
+                index.insert(pattern.clone(), summaries.len());
+                summaries.push(ErrorSummary {
+                    pattern,
+                    count: 1,
+                    sample,
+                    psms: vec![psm],
+                });
+            }
+        }
+    }
+
+    // 稳定排序，次数相同的错误保持首次出现的顺序
+    summaries.sort_by_key(|summary| std::cmp::Reverse(summary.count));
+    summaries.truncate(limit);
+    summaries
+}
+
+/// 将连续数字替换为 `<n>`，让只有耗时、ID 等不同的错误归为一类
+fn normalize_error(text: &str) -> String {
+    let mut normalized = String::with_capacity(text.len());
+    let mut in_number = false;
+    for c in text.chars() {
+        if c.is_ascii_digit() {
+            if !in_number {
+                normalized.push_str("<n>");
+            }
+            in_number = true;
+        } else {
+            normalized.push(c);
+            in_number = false;
+        }
+    }
+    normalized
+}
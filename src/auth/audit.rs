@@ -0,0 +1,129 @@
+//! 认证审计日志模块
+//!
+//! 设置 `LOGID_AUTH_AUDIT` 后，每次向认证服务获取令牌（含强制刷新、失败和 CAS_SESSION 轮换）
+//! 都以一行 JSON 追加到审计文件，记录使用的凭据来源，CAS_SESSION 只保留脱敏后的值。
+//! 便于多人共用服务账号时排查是谁、在何时、用哪个凭据触发了认证。
+
+use crate::config::{user_config_dir, EnvManager};
+use crate::error::LogidError;
+use crate::log_query::mask_token;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// 启用审计日志的环境变量：`true` 使用默认文件，其他值视为文件路径
+pub const AUDIT_ENV_VAR: &str = "LOGID_AUTH_AUDIT";
+
+/// 默认的审计日志文件名
+const AUDIT_FILE_NAME: &str = "auth_audit.log";
+
+/// 认证事件类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AuthEventKind {
+    /// 没有可用令牌时获取新令牌
+    Fetch,
+    /// 强制刷新令牌
+    Refresh,
+    /// 获取令牌失败
+    Failure,
+    /// 认证服务轮换了 CAS_SESSION
+    SessionRotated,
+}
+
+/// 一条认证审计记录
+#[derive(Debug, Clone, Serialize)]
+pub struct AuthEvent {
+    /// 事件时间
+    pub timestamp: DateTime<Utc>,
+    /// 区域名称
+    pub region: String,
+    /// 事件类型
+    pub event: AuthEventKind,
+    /// 提供 CAS_SESSION 的变量，如 `CAS_SESSION_US` 或回退的 `CAS_SESSION`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub credential_source: Option<String>,
+    /// 脱敏后的 CAS_SESSION
+    pub session: String,
+    /// 认证端点
+    pub auth_url: String,
+    /// 认证请求耗时（毫秒）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub latency_ms: Option<u64>,
+    /// 失败原因
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// 进程 ID，区分并发运行的多个 logid
+    pub pid: u32,
+}
+
+impl AuthEvent {
+    /// 创建审计记录，CAS_SESSION 会被脱敏
+    pub fn new(region: &str, event: AuthEventKind, session: &str, auth_url: &str) -> Self {
+        Self {
+            timestamp: Utc::now(),
+            region: region.to_string(),
+            event,
+            credential_source: None,
+            session: mask_token(session),
+            auth_url: auth_url.to_string(),
+            latency_ms: None,
+            error: None,
+            pid: std::process::id(),
+        }
+    }
+}
+
+/// 只追加的认证审计日志
+#[derive(Debug, Clone)]
+pub struct AuditLog {
+    path: PathBuf,
+}
+
+impl AuditLog {
+    /// 使用指定文件创建审计日志
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    /// 根据 `LOGID_AUTH_AUDIT` 创建审计日志，未设置或为 false 时返回 `None`
+    pub fn from_env(env_manager: &EnvManager) -> Option<Self> {
+        let value = env_manager.get_env(AUDIT_ENV_VAR)?;
+        match value.to_lowercase().as_str() {
+            "" | "false" | "0" | "no" | "off" => None,
+            "true" | "1" | "yes" | "on" => user_config_dir().map(|dir| Self::new(dir.join(AUDIT_FILE_NAME))),
+            _ => Some(Self::new(value)),
+        }
+    }
+
+    /// 审计文件路径
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// 追加一条记录，写入失败只记录日志，不影响认证
+    pub fn record(&self, event: &AuthEvent) {
+        if let Err(e) = self.append(event) {
+            tracing::warn!("写入认证审计日志失败: {} - {}", self.path.display(), e);
+        }
+    }
+
+    fn append(&self, event: &AuthEvent) -> Result<(), LogidError> {
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let mut options = std::fs::OpenOptions::new();
+        options.create(true).append(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            options.mode(0o600);
+        }
+        let mut file = options.open(&self.path)?;
+        // 单次 write 写入整行，多个进程同时追加时不会交错
+        let line = serde_json::to_string(event)? + "\n";
+        file.write_all(line.as_bytes())?;
+        Ok(())
+    }
+}
@@ -0,0 +1,51 @@
+//! 浏览器辅助登录模块
+//!
+//! `logid auth login` 打开区域的 SSO 页面，用户登录后粘贴 CAS_SESSION（或整段 Cookie），
+//! 验证通过后写入 `~/.config/logid/.env`。
+
+use crate::auth::AuthManager;
+use crate::config::Region;
+
+/// CAS_SESSION Cookie 名称
+const CAS_SESSION_COOKIE: &str = "CAS_SESSION";
+
+/// 区域的 SSO 登录页面（认证服务所在站点的首页）
+pub fn login_url(region: Region) -> String {
+    let auth_url = AuthManager::auth_url_for(region);
+    let origin_end = auth_url
+        .find("://")
+        .and_then(|scheme_end| {
+            auth_url[scheme_end + 3..]
+                .find('/')
+                .map(|path_start| scheme_end + 3 + path_start)
+        })
+        .unwrap_or(auth_url.len());
+    format!("{}/", &auth_url[..origin_end])
+}
+
+/// 从用户粘贴的内容中提取 CAS_SESSION
+///
+/// 支持直接粘贴 Cookie 值、`CAS_SESSION=...`，以及浏览器开发者工具中复制的整段
+/// `Cookie:` 请求头；首尾的空白和引号会被去掉。
+pub fn parse_cas_session(input: &str) -> Option<String> {
+    let input = input.trim();
+    let input = input
+        .strip_prefix("Cookie:")
+        .or_else(|| input.strip_prefix("cookie:"))
+        .unwrap_or(input)
+        .trim();
+
+    let named = input.split(';').find_map(|pair| {
+        let (name, value) = pair.split_once('=')?;
+        (name.trim() == CAS_SESSION_COOKIE).then_some(value)
+    });
+    // 整段 Cookie 中没有 CAS_SESSION 时无法确定要用哪个值
+    let value = match named {
+        Some(value) => value,
+        None if input.contains(';') => return None,
+        None => input,
+    };
+
+    let value = value.trim().trim_matches(|c| c == '"' || c == '\'');
+    (!value.is_empty() && !value.contains(char::is_whitespace)).then(|| value.to_string())
+}
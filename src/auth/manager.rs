@@ -1,34 +1,21 @@
 //! JWT 认证管理器模块
 
-use crate::conditional_info;
-use crate::config::{EnvManager, JwtInfo, Region};
+use crate::auth::audit::{AuditLog, AuthEvent, AuthEventKind};
+use crate::auth::metrics::AuthMetrics;
+use crate::auth::token_cache::{credential_fingerprint, CachedToken, TokenCache};
+use crate::config::{
+    apply_proxy, encrypt_secret, get_region_config, is_encrypted, passphrase, upsert_env_var,
+    CredentialExpiry, EnvManager, JwtInfo, Region,
+};
 use crate::error::LogidError;
+use crate::retry::{RetryPolicy, DEFAULT_RETRIES};
+use chrono::{DateTime, Utc};
+use std::path::PathBuf;
 use std::sync::Arc;
 use std::time::Duration;
-use tokio::sync::RwLock;
+use tokio::sync::{Mutex, RwLock};
 use tracing::{debug, error, warn};
 
-/// 从环境变量获取代理地址
-fn get_proxy_from_env() -> Option<reqwest::Proxy> {
-    // 优先使用 HTTPS_PROXY
-    if let Ok(proxy) = std::env::var("HTTPS_PROXY") {
-        if !proxy.is_empty() {
-            if let Ok(p) = reqwest::Proxy::https(&proxy) {
-                return Some(p);
-            }
-        }
-    }
-    // 其次使用 HTTP_PROXY
-    if let Ok(proxy) = std::env::var("HTTP_PROXY") {
-        if !proxy.is_empty() {
-            if let Ok(p) = reqwest::Proxy::http(&proxy) {
-                return Some(p);
-            }
-        }
-    }
-    None
-}
-
 /// 区域 JWT 认证端点配置
 const REGION_AUTH_URLS: &[(Region, &str)] = &[
     (Region::Cn, "https://cloud.bytedance.net/auth/api/v1/jwt"),
@@ -37,6 +24,15 @@ const REGION_AUTH_URLS: &[(Region, &str)] = &[
     (Region::Eu, "https://cloud-i18n.tiktok-eu.org/auth/api/v1/jwt"),
 ];
 
+/// 默认的 HTTP 请求超时时间
+pub const DEFAULT_REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// 预先提供的令牌无法解析 `exp` 时假设的有效期
+const STATIC_TOKEN_DEFAULT_LIFETIME: Duration = Duration::from_secs(24 * 3600);
+
+/// 设为 true 时，认证服务轮换的 CAS_SESSION 会写回提供凭据的 .env 文件
+pub const PERSIST_SESSION_ENV_VAR: &str = "LOGID_PERSIST_SESSION";
+
 /// JWT 认证管理器
 ///
 /// 提供字节跳动内部 API 的 JWT 令牌管理功能，支持多区域认证配置。
@@ -49,10 +45,32 @@ pub struct AuthManager {
     client: reqwest::Client,
     /// 缓存的 JWT 信息
     jwt_cache: Arc<RwLock<Option<JwtInfo>>>,
-    /// CAS_SESSION Cookie 值
-    cas_session: String,
+    /// CAS_SESSION Cookie 值，认证服务在 Set-Cookie 中轮换时更新
+    cas_session: Arc<std::sync::RwLock<String>>,
+    /// 轮换后的 CAS_SESSION 写回的 .env 文件和变量名
+    session_persistence: Option<(PathBuf, String)>,
+    /// 写回的 CAS_SESSION 是否需要加密（.env 中的原值是 `auth encrypt` 加密过的）
+    encrypt_persisted_session: bool,
     /// 认证 URL
     auth_url: String,
+    /// CAS_SESSION 过期时间（从认证响应的 Set-Cookie 中获取）
+    session_expires_at: Arc<RwLock<Option<DateTime<Utc>>>>,
+    /// 令牌获取锁，保证同一时刻每个区域只有一个进行中的认证请求
+    fetch_lock: Arc<Mutex<()>>,
+    /// 请求超时时间
+    timeout: Duration,
+    /// 瞬时失败的重试策略
+    retry_policy: RetryPolicy,
+    /// JWT 令牌磁盘缓存，跨进程复用令牌
+    token_cache: Option<TokenCache>,
+    /// 认证指标，克隆的管理器共享同一份指标
+    metrics: Arc<std::sync::Mutex<AuthMetrics>>,
+    /// 是否使用预先提供的令牌（不访问认证服务，也无法刷新）
+    static_token: bool,
+    /// 提供 CAS_SESSION 的变量名称，记录在审计日志中
+    credential_source: Option<String>,
+    /// 认证审计日志
+    audit_log: Option<AuditLog>,
 }
 
 impl AuthManager {
@@ -67,6 +85,9 @@ impl AuthManager {
     /// # 错误
     /// - 如果无法获取到有效的 Cookie 值
     /// - 如果 HTTP 客户端创建失败
+    ///
+    /// 认证请求默认按 `DEFAULT_RETRIES` 重试超时、连接失败、5xx 和 429；
+    /// 401/403 和重定向到登录页属于明确的拒绝，立即返回 `SessionExpired`。
     pub fn new(region: &str) -> Result<Self, LogidError> {
         let region = Region::from_str(region)
             .ok_or_else(|| LogidError::UnsupportedRegion(region.to_string()))?;
@@ -78,19 +99,113 @@ impl AuthManager {
         let cas_session = env_manager.get_cas_session(region)?;
 
         // 获取认证 URL
-        let auth_url = REGION_AUTH_URLS
+        let auth_url = Self::auth_url_for(region);
+
+        let mut manager = Self::with_credentials(region, cas_session, auth_url)?
+            .with_retry_policy(RetryPolicy::new(DEFAULT_RETRIES));
+        if let Some(var) = env_manager.cas_session_var(region) {
+            manager = manager.with_credential_source(if env_manager.is_profile_var(var) {
+                format!("{} (profile)", var)
+            } else {
+                var.to_string()
+            });
+        }
+        if let Some(audit_log) = AuditLog::from_env(&env_manager) {
+            manager = manager.with_audit_log(audit_log);
+        }
+
+        // 按需把轮换的 CAS_SESSION 写回 .env 文件；配置档提供的凭据不写回
+        let persist = env_manager
+            .get_env(PERSIST_SESSION_ENV_VAR)
+            .is_some_and(|value| matches!(value.to_lowercase().as_str(), "true" | "1" | "yes" | "on"));
+        if persist {
+            let var = env_manager.cas_session_var(region).filter(|var| !env_manager.is_profile_var(var));
+            if let (Some(path), Some(var)) = (env_manager.source(), var) {
+                manager.encrypt_persisted_session = env_manager.get_env(var).is_some_and(|v| is_encrypted(&v));
+                manager = manager.with_session_persistence(path, var);
+            }
+        }
+        Ok(match TokenCache::open_default() {
+            Ok(cache) => manager.with_token_cache(cache),
+            Err(_) => manager,
+        })
+    }
+
+    /// 使用指定的 CAS_SESSION 创建认证管理器，不读取环境变量
+    ///
+    /// 适用于在写入配置之前验证用户输入的凭据，瞬时失败的重试与 `new` 相同。
+    pub fn from_session(region: Region, cas_session: &str) -> Result<Self, LogidError> {
+        Ok(Self::with_credentials(
+            region,
+            cas_session.to_string(),
+            Self::auth_url_for(region),
+        )?
+        .with_retry_policy(RetryPolicy::new(DEFAULT_RETRIES)))
+    }
+
+    /// 使用预先获取的 JWT 令牌创建认证管理器，不读取 CAS_SESSION 也不访问认证服务
+    ///
+    /// 适用于 CI 中已经由其他工具取得令牌、嵌入到其他程序或测试的场景。
+    /// 未指定 `expires_at` 时使用令牌中的 `exp` 声明，无法解析时视为 24 小时内有效。
+    /// 令牌过期后无法刷新，获取令牌时返回 `AuthenticationFailed`。
+    pub fn from_token(
+        region: Region,
+        token: impl Into<String>,
+        expires_at: Option<DateTime<Utc>>,
+    ) -> Result<Self, LogidError> {
+        let token = token.into();
+        let jwt_info = match expires_at {
+            Some(expires_at) => JwtInfo::from_expiry(token, expires_at),
+            None => JwtInfo::from_token(token, STATIC_TOKEN_DEFAULT_LIFETIME.as_secs()),
+        };
+        let mut manager = Self::with_credentials(region, String::new(), Self::builtin_auth_url(region).to_string())?;
+        manager.jwt_cache = Arc::new(RwLock::new(Some(jwt_info)));
+        manager.static_token = true;
+        Ok(manager)
+    }
+
+    /// 获取区域的 JWT 认证端点
+    ///
+    /// 优先级：`LOGID_AUTH_URL_<REGION>` 环境变量 > regions.json 中的 `auth_url` > 内置端点。
+    pub fn auth_url_for(region: Region) -> String {
+        Self::auth_url_with(region, |name| std::env::var(name).ok())
+    }
+
+    /// 与 [`auth_url_for`](Self::auth_url_for) 相同，但环境变量由 `env` 查找
+    pub(crate) fn auth_url_with(region: Region, env: impl Fn(&str) -> Option<String>) -> String {
+        if let Some(url) = env(region.auth_url_env_var()).filter(|url| !url.is_empty()) {
+            conditional_info!("使用 {} 覆盖认证端点: {}", region.auth_url_env_var(), url);
+            return url;
+        }
+        if let Some(url) = get_region_config(region.as_str()).and_then(|config| config.auth_url) {
+            conditional_info!("使用区域配置文件中的认证端点: {}", url);
+            return url;
+        }
+        Self::builtin_auth_url(region).to_string()
+    }
+
+    /// 获取区域内置的 JWT 认证端点
+    pub fn builtin_auth_url(region: Region) -> &'static str {
+        REGION_AUTH_URLS
             .iter()
             .find(|(r, _)| *r == region)
-            .map(|(_, url)| url.to_string())
+            .map(|(_, url)| *url)
             .unwrap_or_else(|| {
                 // 默认使用中国区的 URL
                 warn!("使用默认的中国区认证 URL，可能不是预期的");
-                "https://cloud.bytedance.net/auth/api/v1/jwt".to_string()
-            });
+                "https://cloud.bytedance.net/auth/api/v1/jwt"
+            })
+    }
 
+    /// 使用给定的凭据和认证 URL 创建认证管理器
+    pub(crate) fn with_credentials(
+        region: Region,
+        cas_session: String,
+        auth_url: String,
+    ) -> Result<Self, LogidError> {
         // 配置 HTTP 客户端，模拟浏览器行为
-        let mut client_builder = reqwest::Client::builder()
-            .timeout(Duration::from_secs(30))
+        let client_builder = reqwest::Client::builder()
+            .timeout(DEFAULT_REQUEST_TIMEOUT)
             .user_agent("Mozilla/5.0 (Macintosh; Intel Mac OS X 10_15_7) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/140.0.0.0 Safari/537.36 Edg/140.0.0.0")
             .default_headers({
                 let mut headers = reqwest::header::HeaderMap::new();
@@ -110,15 +225,7 @@ impl AuthManager {
             });
 
         // 添加代理配置
-        if let Some(proxy) = get_proxy_from_env() {
-            client_builder = client_builder.proxy(proxy);
-            let proxy_url = std::env::var("HTTPS_PROXY")
-                .or_else(|_| std::env::var("HTTP_PROXY"))
-                .unwrap_or_default();
-            conditional_info!("使用代理: {}", proxy_url);
-        }
-
-        let client = client_builder
+        let client = apply_proxy(client_builder, Some(region))
             .build()
             .map_err(|e| LogidError::InternalError(format!("创建 HTTP 客户端失败: {}", e)))?;
 
@@ -132,11 +239,70 @@ impl AuthManager {
             region,
             client,
             jwt_cache: Arc::new(RwLock::new(None)),
-            cas_session,
+            cas_session: Arc::new(std::sync::RwLock::new(cas_session)),
+            session_persistence: None,
+            encrypt_persisted_session: false,
             auth_url,
+            session_expires_at: Arc::new(RwLock::new(None)),
+            fetch_lock: Arc::new(Mutex::new(())),
+            timeout: DEFAULT_REQUEST_TIMEOUT,
+            retry_policy: RetryPolicy::default(),
+            token_cache: None,
+            metrics: Arc::new(std::sync::Mutex::new(AuthMetrics::default())),
+            static_token: false,
+            credential_source: None,
+            audit_log: None,
         })
     }
 
+    /// 设置认证请求的超时时间
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// 设置认证请求遇到超时、连接失败、5xx 或 429 时的重试策略，401/403 不会重试
+    pub fn with_retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = retry_policy;
+        self
+    }
+
+    /// 认证服务轮换 CAS_SESSION 时，把新值写回 .env 文件中的指定变量
+    pub fn with_session_persistence(mut self, path: impl Into<PathBuf>, var: impl Into<String>) -> Self {
+        self.session_persistence = Some((path.into(), var.into()));
+        self
+    }
+
+    /// 记录提供 CAS_SESSION 的变量名称，用于审计日志
+    pub fn with_credential_source(mut self, source: impl Into<String>) -> Self {
+        self.credential_source = Some(source.into());
+        self
+    }
+
+    /// 将令牌获取、刷新、失败和 CAS_SESSION 轮换写入审计日志
+    pub fn with_audit_log(mut self, audit_log: AuditLog) -> Self {
+        self.audit_log = Some(audit_log);
+        self
+    }
+
+    /// 是否使用预先提供的令牌（由 `from_token` 创建）
+    pub fn is_static_token(&self) -> bool {
+        self.static_token
+    }
+
+    /// 当前使用的 CAS_SESSION
+    fn cas_session(&self) -> String {
+        self.cas_session.read().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    /// 使用磁盘缓存保存和复用 JWT 令牌
+    ///
+    /// 内存中没有有效令牌时先读取缓存文件，获取新令牌后写回缓存文件。
+    pub fn with_token_cache(mut self, cache: TokenCache) -> Self {
+        self.token_cache = Some(cache);
+        self
+    }
+
     /// 获取 JWT 令牌，必要时进行刷新
     ///
     /// 如果当前令牌有效且未强制刷新，则返回缓存的令牌。
@@ -152,44 +318,167 @@ impl AuthManager {
     /// - 如果令牌获取失败
     /// - 如果网络请求失败
     pub async fn get_jwt_token(&self, force_refresh: bool) -> Result<String, LogidError> {
+        if self.static_token {
+            return self.static_jwt_token().await;
+        }
+
         // 检查缓存令牌是否有效
+        let seen_token = self.cached_valid_token().await;
         if !force_refresh {
-            {
-                let cache = self.jwt_cache.read().await;
-                if let Some(ref jwt_info) = *cache {
-                    if jwt_info.is_valid() {
-                        debug!("使用缓存的 JWT 令牌");
-                        return Ok(jwt_info.token.clone());
-                    }
-                }
+            if let Some(token) = &seen_token {
+                debug!("使用缓存的 JWT 令牌");
+                self.update_metrics(AuthMetrics::record_cache_hit);
+                return Ok(token.clone());
+            }
+        }
+
+        // 同一区域只允许一个进行中的认证请求，其余调用等待其结果
+        let _guard = self.fetch_lock.lock().await;
+
+        // 等待期间其他调用可能已经获取到新令牌；强制刷新时只复用等待后新获取的令牌
+        if let Some(token) = self.cached_valid_token().await {
+            if !force_refresh || seen_token.as_ref() != Some(&token) {
+                debug!("复用并发请求获取的 JWT 令牌");
+                self.update_metrics(AuthMetrics::record_cache_hit);
+                return Ok(token);
+            }
+        }
+
+        // 其次使用磁盘缓存中仍然有效的令牌
+        if !force_refresh {
+            if let Some(token) = self.load_disk_token().await {
+                self.update_metrics(AuthMetrics::record_cache_hit);
+                return Ok(token);
             }
         }
 
         // 获取新令牌
         conditional_info!("正在获取新的 JWT 令牌");
-        let jwt_info = self.fetch_jwt_token().await?;
+        let started = std::time::Instant::now();
+        let session = self.cas_session();
+        let fetched = self.fetch_jwt_token().await;
+        let latency = started.elapsed();
+        self.update_metrics(|metrics| metrics.record_fetch(latency, fetched.is_ok()));
+        let kind = match (&fetched, force_refresh) {
+            (Err(_), _) => AuthEventKind::Failure,
+            (Ok(_), true) => AuthEventKind::Refresh,
+            (Ok(_), false) => AuthEventKind::Fetch,
+        };
+        self.audit(kind, &session, |event| {
+            event.latency_ms = Some(latency.as_millis() as u64);
+            event.error = fetched.as_ref().err().map(|e| e.to_string());
+        });
+        let jwt_info = fetched?;
 
         // 更新缓存
         {
             let mut cache = self.jwt_cache.write().await;
             *cache = Some(jwt_info.clone());
         }
+        self.store_disk_token(&jwt_info).await;
 
         conditional_info!("JWT 令牌获取成功");
         Ok(jwt_info.token)
     }
 
+    /// 预先提供的令牌，过期前（不预留刷新缓冲）一直可用
+    async fn static_jwt_token(&self) -> Result<String, LogidError> {
+        let cache = self.jwt_cache.read().await;
+        match cache.as_ref().filter(|jwt_info| !jwt_info.remaining().is_zero()) {
+            Some(jwt_info) => {
+                self.update_metrics(AuthMetrics::record_cache_hit);
+                Ok(jwt_info.token.clone())
+            }
+            None => Err(LogidError::AuthenticationFailed(format!(
+                "{} 区域预先提供的 JWT 令牌已过期，无法刷新",
+                self.region.as_str()
+            ))),
+        }
+    }
+
+    /// 写入一条审计记录，未启用审计日志时不做任何事
+    fn audit(&self, kind: AuthEventKind, session: &str, fill: impl FnOnce(&mut AuthEvent)) {
+        let Some(audit_log) = &self.audit_log else {
+            return;
+        };
+        let mut event = AuthEvent::new(self.region.as_str(), kind, session, &self.auth_url);
+        event.credential_source = self.credential_source.clone();
+        fill(&mut event);
+        audit_log.record(&event);
+    }
+
+    /// 获取认证指标快照
+    pub fn metrics(&self) -> AuthMetrics {
+        self.metrics.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    /// 更新认证指标
+    fn update_metrics(&self, update: impl FnOnce(&mut AuthMetrics)) {
+        update(&mut self.metrics.lock().unwrap_or_else(|e| e.into_inner()));
+    }
+
+    /// 从磁盘缓存恢复令牌，同时恢复 CAS_SESSION 的过期时间
+    async fn load_disk_token(&self) -> Option<String> {
+        let cache = self.token_cache.as_ref()?;
+        let cached = cache.load(self.region, &self.credential_fingerprint())?;
+        debug!("使用磁盘缓存的 JWT 令牌: {}", cache.path().display());
+        if cached.session_expires_at.is_some() {
+            *self.session_expires_at.write().await = cached.session_expires_at;
+        }
+        *self.jwt_cache.write().await = Some(cached.jwt_info());
+        Some(cached.token)
+    }
+
+    /// 将新令牌写入磁盘缓存，写入失败只记录日志
+    async fn store_disk_token(&self, jwt_info: &JwtInfo) {
+        let Some(cache) = &self.token_cache else {
+            return;
+        };
+        let session_expires_at = *self.session_expires_at.read().await;
+        let cached = CachedToken::new(jwt_info, session_expires_at, self.credential_fingerprint());
+        if let Err(e) = cache.store(self.region, cached) {
+            warn!("写入令牌缓存失败: {} - {}", cache.path().display(), e);
+        }
+    }
+
+    /// 当前凭据的指纹，用于识别缓存令牌是否属于当前凭据
+    fn credential_fingerprint(&self) -> String {
+        credential_fingerprint(&self.cas_session(), &self.auth_url)
+    }
+
+    /// 获取缓存中仍然有效的令牌
+    async fn cached_valid_token(&self) -> Option<String> {
+        let cache = self.jwt_cache.read().await;
+        cache
+            .as_ref()
+            .filter(|jwt_info| jwt_info.is_valid())
+            .map(|jwt_info| jwt_info.token.clone())
+    }
+
     /// 向认证服务获取新的 JWT 令牌
     async fn fetch_jwt_token(&self) -> Result<JwtInfo, LogidError> {
         // 准备认证请求头，包含 Cookie 信息
-        let cookie_header = format!("CAS_SESSION={}", self.cas_session);
+        let cookie_header = format!("CAS_SESSION={}", self.cas_session());
 
-        let response = self
+        let request = self
             .client
             .get(&self.auth_url)
             .header("Cookie", cookie_header)
-            .send()
-            .await?;
+            .timeout(self.timeout);
+        let response = self.retry_policy.send("JWT 认证", request).await?;
+
+        // 401/403 或被重定向到登录页说明 CAS_SESSION 已失效，不把登录页 HTML 放进错误信息
+        if is_session_expired(&response, &self.auth_url) {
+            warn!(
+                "CAS_SESSION 已失效: region={}, status={}, url={}",
+                self.region.as_str(),
+                response.status(),
+                response.url()
+            );
+            return Err(LogidError::SessionExpired {
+                region: self.region.as_str().to_string(),
+            });
+        }
 
         // 检查 HTTP 状态码
         if !response.status().is_success() {
@@ -207,6 +496,15 @@ impl AuthManager {
             )));
         }
 
+        // 记录 CAS_SESSION 的过期时间（如果认证服务下发了 Set-Cookie）
+        if let Some(expires_at) = session_expiry_from_cookies(&response) {
+            conditional_info!("CAS_SESSION 过期时间: {}", expires_at.to_rfc3339());
+            *self.session_expires_at.write().await = Some(expires_at);
+        }
+        if let Some(session) = rotated_session(&response, &self.cas_session()) {
+            self.rotate_session(session);
+        }
+
         // 从响应头获取 JWT 令牌
         let jwt_token = response
             .headers()
@@ -217,7 +515,26 @@ impl AuthManager {
             })?;
 
         conditional_info!("JWT 令牌获取成功");
-        Ok(JwtInfo::new(jwt_token.to_string(), 3600)) // 假设有效期为 1 小时
+        // 优先使用令牌中的 exp 声明，无法解析时假设有效期为 1 小时
+        Ok(JwtInfo::from_token(jwt_token.to_string(), 3600))
+    }
+
+    /// 使用认证服务轮换的 CAS_SESSION，配置了写回时同时更新 .env 文件
+    fn rotate_session(&self, session: String) {
+        conditional_info!("{} 区域的 CAS_SESSION 已由认证服务轮换", self.region.as_str());
+        self.audit(AuthEventKind::SessionRotated, &session, |_| {});
+        if let Some((path, var)) = &self.session_persistence {
+            let value = if self.encrypt_persisted_session {
+                passphrase(false).and_then(|passphrase| encrypt_secret(&session, &passphrase))
+            } else {
+                Ok(session.clone())
+            };
+            match value.and_then(|value| upsert_env_var(path, var, &value)) {
+                Ok(()) => conditional_info!("已将新的 CAS_SESSION 写入 {} ({})", path.display(), var),
+                Err(e) => warn!("写回 CAS_SESSION 失败: {} - {}", path.display(), e),
+            }
+        }
+        *self.cas_session.write().unwrap_or_else(|e| e.into_inner()) = session;
     }
 
     /// 检查当前令牌是否有效
@@ -231,6 +548,28 @@ impl AuthManager {
         }
     }
 
+    /// 获取缓存的 JWT 令牌过期时间
+    pub async fn token_expires_at(&self) -> Option<DateTime<Utc>> {
+        self.jwt_cache
+            .read()
+            .await
+            .as_ref()
+            .map(|jwt_info| jwt_info.expires_at_utc)
+    }
+
+    /// 获取 CAS_SESSION 的过期信息
+    ///
+    /// 只有认证服务在 Set-Cookie 中返回过期时间时才可用；
+    /// `warning_window` 内即将过期时 `expiring_soon` 为 true。
+    pub async fn credential_expiry(&self, warning_window: Duration) -> Option<CredentialExpiry> {
+        let expires_at = (*self.session_expires_at.read().await)?;
+        Some(CredentialExpiry::new(
+            self.region.as_str(),
+            expires_at,
+            warning_window,
+        ))
+    }
+
     /// 强制刷新令牌
     #[allow(dead_code)]
     pub async fn refresh_token(&self) -> Result<String, LogidError> {
@@ -242,12 +581,64 @@ impl AuthManager {
         self.region
     }
 
+    /// 获取认证 URL
+    pub fn auth_url(&self) -> &str {
+        &self.auth_url
+    }
+
     /// 获取区域字符串表示
     pub fn region_str(&self) -> &'static str {
         self.region.as_str()
     }
 }
 
+/// 判断认证响应是否表示 CAS_SESSION 已失效
+///
+/// 认证服务对失效的会话返回 401/403，或者重定向到 SSO 登录页（reqwest 会跟随重定向，
+/// 最终得到不带 `x-jwt-token` 的 HTML 页面）。
+fn is_session_expired(response: &reqwest::Response, auth_url: &str) -> bool {
+    let status = response.status();
+    if status == reqwest::StatusCode::UNAUTHORIZED || status == reqwest::StatusCode::FORBIDDEN {
+        return true;
+    }
+    if status.is_redirection() {
+        return true;
+    }
+    if !status.is_success() || response.headers().contains_key("x-jwt-token") {
+        return false;
+    }
+
+    let redirected = response.url().as_str() != auth_url;
+    let is_html = response
+        .headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| value.contains("text/html"));
+    redirected || is_html
+}
+
+/// 认证服务在 Set-Cookie 中下发的新 CAS_SESSION，与当前值相同时返回 `None`
+fn rotated_session(response: &reqwest::Response, current: &str) -> Option<String> {
+    response
+        .cookies()
+        .find(|c| c.name() == "CAS_SESSION")
+        .map(|c| c.value().to_string())
+        .filter(|value| !value.is_empty() && value != current)
+}
+
+/// 从响应的 Set-Cookie 中解析 CAS_SESSION 的过期时间
+fn session_expiry_from_cookies(response: &reqwest::Response) -> Option<DateTime<Utc>> {
+    let cookie = response.cookies().find(|c| c.name() == "CAS_SESSION")?;
+
+    if let Some(max_age) = cookie.max_age() {
+        return chrono::Duration::from_std(max_age)
+            .ok()
+            .map(|age| Utc::now() + age);
+    }
+
+    cookie.expires().map(DateTime::<Utc>::from)
+}
+
 impl Drop for AuthManager {
     fn drop(&mut self) {
         conditional_info!("销毁 JWT 认证管理器: region={}", self.region.as_str());
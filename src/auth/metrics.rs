@@ -0,0 +1,55 @@
+//! 认证指标模块
+//!
+//! 记录每个区域认证管理器的令牌获取次数、耗时和缓存命中情况，
+//! 用于判断查询变慢是因为认证服务还是日志服务。
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::time::Duration;
+
+/// 认证指标快照
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct AuthMetrics {
+    /// 最近一次成功获取新令牌的时间
+    pub last_refresh_at: Option<DateTime<Utc>>,
+    /// 向认证服务成功获取令牌的次数
+    pub refresh_count: u64,
+    /// 向认证服务获取令牌失败的次数
+    pub failure_count: u64,
+    /// 最近一次认证请求的耗时（毫秒），包括重试
+    pub last_latency_ms: Option<u64>,
+    /// 令牌请求总数
+    pub token_requests: u64,
+    /// 使用内存或磁盘缓存中令牌的次数
+    pub cache_hits: u64,
+}
+
+impl AuthMetrics {
+    /// 缓存命中率，尚无令牌请求时返回 `None`
+    pub fn cache_hit_ratio(&self) -> Option<f64> {
+        (self.token_requests > 0).then(|| self.cache_hits as f64 / self.token_requests as f64)
+    }
+
+    /// 最近一次认证请求的耗时
+    pub fn last_latency(&self) -> Option<Duration> {
+        self.last_latency_ms.map(Duration::from_millis)
+    }
+
+    /// 记录一次使用缓存令牌的请求
+    pub(crate) fn record_cache_hit(&mut self) {
+        self.token_requests += 1;
+        self.cache_hits += 1;
+    }
+
+    /// 记录一次向认证服务发出的请求
+    pub(crate) fn record_fetch(&mut self, latency: Duration, success: bool) {
+        self.token_requests += 1;
+        self.last_latency_ms = Some(latency.as_millis() as u64);
+        if success {
+            self.refresh_count += 1;
+            self.last_refresh_at = Some(Utc::now());
+        } else {
+            self.failure_count += 1;
+        }
+    }
+}
@@ -3,11 +3,25 @@
 //! 处理字节跳动内部 API 的 JWT 令牌获取和管理，支持多区域认证配置。
 //! 提供基于 Cookie 的 JWT 认证功能，支持自动令牌刷新和过期检测。
 
+mod audit;
+mod login;
 mod manager;
+mod metrics;
 mod multi_region;
+mod pre_refresh;
+mod preflight;
+mod provider;
+mod token_cache;
 
-pub use manager::AuthManager;
+pub use audit::{AuditLog, AuthEvent, AuthEventKind, AUDIT_ENV_VAR};
+pub use login::{login_url, parse_cas_session};
+pub use manager::{AuthManager, DEFAULT_REQUEST_TIMEOUT, PERSIST_SESSION_ENV_VAR};
+pub use metrics::AuthMetrics;
 pub use multi_region::MultiRegionAuthManager;
+pub use pre_refresh::{TokenPreRefresher, DEFAULT_PRE_REFRESH_LEAD};
+pub use preflight::{preflight_check, PreflightFailure, PreflightReport};
+pub use provider::{AuthProvider, EnvTokenProvider, FileTokenProvider};
+pub use token_cache::{user_token_cache_path, CachedToken, TokenCache};
 
 #[cfg(test)]
 mod tests {
@@ -38,4 +52,403 @@ mod tests {
         let jwt_info = JwtInfo::new("test_token".to_string(), 200); // 不到 5 分钟
         assert!(!jwt_info.is_valid());
     }
+
+    #[test]
+    fn test_jwt_info_from_token_exp_claim() {
+        use base64::Engine;
+
+        let exp = chrono::Utc::now().timestamp() + 7200;
+        let payload = base64::engine::general_purpose::URL_SAFE_NO_PAD
+            .encode(format!(r#"{{"exp":{}}}"#, exp));
+        let token = format!("header.{}.signature", payload);
+
+        let jwt_info = JwtInfo::from_token(token, 60);
+        assert_eq!(jwt_info.expires_at_utc.timestamp(), exp);
+        assert!(jwt_info.is_valid());
+
+        // 无法解析时使用默认有效期
+        let jwt_info = JwtInfo::from_token("not-a-jwt".to_string(), 200);
+        assert!(!jwt_info.is_valid());
+    }
+
+    #[test]
+    fn test_credential_expiry_window() {
+        use crate::config::CredentialExpiry;
+        use std::time::Duration;
+
+        let expires_at = chrono::Utc::now() + chrono::Duration::hours(2);
+        let expiry = CredentialExpiry::new("us", expires_at, Duration::from_secs(12 * 3600));
+        assert!(expiry.expiring_soon);
+
+        let expiry = CredentialExpiry::new("us", expires_at, Duration::from_secs(3600));
+        assert!(!expiry.expiring_soon);
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_token_requests_share_one_fetch() {
+        use crate::test_support::{http_response, mock_auth, MockServer};
+        use std::time::Duration;
+
+        let server = MockServer::start(Duration::from_millis(50), |n, _| {
+            http_response(200, &[("x-jwt-token", &format!("token-{}", n))], "")
+        })
+        .await;
+
+        let manager = mock_auth(&server);
+
+        let tokens = futures::future::join_all((0..8).map(|_| manager.get_jwt_token(false))).await;
+        assert!(tokens.iter().all(|t| t.as_deref().ok() == Some("token-1")));
+        assert_eq!(server.request_count(), 1);
+
+        // 强制刷新会重新获取令牌
+        assert_eq!(manager.refresh_token().await.unwrap(), "token-2");
+        assert_eq!(server.request_count(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_preflight_reports_all_failed_regions() {
+        use super::{preflight_check, AuthManager};
+        use crate::test_support::{http_response, MockServer};
+        use std::time::Duration;
+
+        let server = MockServer::start(Duration::ZERO, |_, head| {
+            if head.contains("/ok") {
+                http_response(200, &[("x-jwt-token", "token")], "")
+            } else {
+                http_response(401, &[], "expired")
+            }
+        })
+        .await;
+
+        let us = AuthManager::with_credentials(Region::Us, "s".to_string(), server.url("/ok")).unwrap();
+        let i18n =
+            AuthManager::with_credentials(Region::I18n, "s".to_string(), server.url("/expired")).unwrap();
+        let eu = AuthManager::with_credentials(Region::Eu, "s".to_string(), server.url("/expired")).unwrap();
+
+        let report = preflight_check(&[&us, &i18n, &eu]).await;
+        assert_eq!(report.checked, vec!["us", "i18n", "eu"]);
+        let failed: Vec<_> = report.failures.iter().map(|f| f.region.as_str()).collect();
+        assert_eq!(failed, vec!["i18n", "eu"]);
+
+        let message = report.into_result().unwrap_err().to_string();
+        assert!(message.contains("2/3") && message.contains("i18n") && message.contains("eu"));
+
+        // 预检获取的令牌会被后续查询复用
+        assert_eq!(us.get_jwt_token(false).await.unwrap(), "token");
+        assert_eq!(server.request_count(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_token_cache_persists_across_managers() {
+        use super::{AuthManager, TokenCache};
+        use crate::test_support::{http_response, MockServer};
+        use std::time::Duration;
+
+        let server = MockServer::start(Duration::ZERO, |n, _| {
+            http_response(200, &[("x-jwt-token", &format!("token-{}", n))], "")
+        })
+        .await;
+        let dir = tempfile::tempdir().unwrap();
+        let cache = TokenCache::new(dir.path().join("token_cache.json"));
+        let manager = |session: &str| {
+            AuthManager::with_credentials(Region::Us, session.to_string(), server.url("/jwt"))
+                .unwrap()
+                .with_token_cache(cache.clone())
+        };
+
+        assert_eq!(manager("session").get_jwt_token(false).await.unwrap(), "token-1");
+        // 新的管理器（相当于下一次运行）直接使用磁盘缓存
+        assert_eq!(manager("session").get_jwt_token(false).await.unwrap(), "token-1");
+        assert_eq!(server.request_count(), 1);
+
+        let content = std::fs::read_to_string(cache.path()).unwrap();
+        assert!(content.contains("token-1") && !content.contains("\"session\""));
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = std::fs::metadata(cache.path()).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+
+        // 凭据变化后不复用旧令牌
+        assert_eq!(manager("other").get_jwt_token(false).await.unwrap(), "token-2");
+        assert_eq!(server.request_count(), 2);
+    }
+
+    #[test]
+    fn test_login_parses_pasted_cookie_and_updates_env_file() {
+        use super::{login_url, parse_cas_session};
+        use crate::config::upsert_env_var;
+
+        assert_eq!(login_url(Region::Us), "https://cloud-ttp-us.bytedance.net/");
+        assert_eq!(parse_cas_session("  abc123  ").as_deref(), Some("abc123"));
+        assert_eq!(parse_cas_session("CAS_SESSION=\"abc123\"").as_deref(), Some("abc123"));
+        assert_eq!(
+            parse_cas_session("Cookie: lang=zh; CAS_SESSION=abc123; theme=dark").as_deref(),
+            Some("abc123")
+        );
+        assert_eq!(parse_cas_session("lang=zh; theme=dark"), None);
+        assert_eq!(parse_cas_session(""), None);
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("logid").join(".env");
+        upsert_env_var(&path, "CAS_SESSION_US", "old").unwrap();
+        std::fs::write(&path, "# comment\nCAS_SESSION_US=old\nENABLE_LOGGING=false\n").unwrap();
+        upsert_env_var(&path, "CAS_SESSION_US", "new").unwrap();
+        upsert_env_var(&path, "CAS_SESSION_EU", "eu").unwrap();
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            "# comment\nCAS_SESSION_US=new\nENABLE_LOGGING=false\nCAS_SESSION_EU=eu\n"
+        );
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+        assert!(!path.with_file_name(".env.tmp").exists());
+    }
+
+    #[tokio::test]
+    async fn test_expired_session_is_classified() {
+        use super::AuthManager;
+        use crate::error::{exit_code, LogidError};
+        use crate::test_support::{http_response, MockServer};
+        use std::time::Duration;
+
+        let server = MockServer::start(Duration::ZERO, |_, head| {
+            if head.starts_with("GET /unauthorized") {
+                http_response(401, &[], "<html>login</html>")
+            } else if head.starts_with("GET /redirect") {
+                http_response(302, &[("location", "/sso/login")], "")
+            } else {
+                http_response(200, &[("content-type", "text/html")], "<html>login</html>")
+            }
+        })
+        .await;
+
+        for path in ["/unauthorized", "/redirect"] {
+            let manager =
+                AuthManager::with_credentials(Region::Us, "stale".to_string(), server.url(path)).unwrap();
+            let error = manager.get_jwt_token(false).await.unwrap_err();
+            assert!(
+                matches!(&error, LogidError::SessionExpired { region } if region == "us"),
+                "{}: {:?}",
+                path,
+                error
+            );
+            assert!(!error.to_string().contains("<html>"));
+            assert_eq!(error.error_code(), "session_expired");
+            assert_eq!(error.region(), Some("us"));
+            assert_eq!(error.exit_code(), exit_code::AUTH);
+        }
+    }
+
+    #[test]
+    fn test_multi_region_manager_initializes_lazily() {
+        use super::MultiRegionAuthManager;
+        use crate::error::LogidError;
+
+        // 创建时不读取凭据，只校验区域名称
+        let manager = MultiRegionAuthManager::new(&["us", "eu"]).unwrap();
+        assert_eq!(manager.managed_regions(), vec!["eu", "us"]);
+        assert!(matches!(
+            MultiRegionAuthManager::new(&["us", "mars"]),
+            Err(LogidError::UnsupportedRegion(_))
+        ));
+
+        // 不可用的区域单独报告，与能否取得认证管理器一致
+        let unavailable: Vec<String> = manager.unavailable_regions().into_iter().map(|(r, _)| r).collect();
+        for region in manager.managed_regions() {
+            assert_eq!(manager.get_manager(&region).is_none(), unavailable.contains(&region));
+        }
+        assert!(manager.manager("cn").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_pre_refresher_fetches_token_in_background() {
+        use super::{TokenPreRefresher, DEFAULT_PRE_REFRESH_LEAD};
+        use crate::test_support::{http_response, mock_auth, MockServer};
+        use std::time::Duration;
+
+        let server = MockServer::start(Duration::ZERO, |n, _| {
+            http_response(200, &[("x-jwt-token", &format!("token-{}", n))], "")
+        })
+        .await;
+        let manager = mock_auth(&server);
+
+        let refresher = TokenPreRefresher::spawn([manager.clone()], DEFAULT_PRE_REFRESH_LEAD);
+        for _ in 0..50 {
+            if manager.is_token_valid().await {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        assert_eq!(refresher.len(), 1);
+        // 查询时直接使用后台获取的令牌，不再请求认证服务
+        assert_eq!(manager.get_jwt_token(false).await.unwrap(), "token-1");
+        assert_eq!(server.request_count(), 1);
+
+        drop(refresher);
+    }
+
+    #[tokio::test]
+    async fn test_auth_fetch_retries_transient_failures_only() {
+        use crate::error::LogidError;
+        use crate::retry::RetryPolicy;
+        use crate::test_support::{http_response, mock_auth, MockServer};
+        use std::time::Duration;
+
+        let policy = RetryPolicy {
+            max_retries: 2,
+            base_delay: Duration::from_millis(1),
+            max_delay: Duration::from_millis(5),
+        };
+
+        // 5xx 和 429 重试后成功
+        let server = MockServer::start(Duration::ZERO, |n, _| match n {
+            1 => http_response(503, &[], "unavailable"),
+            2 => http_response(429, &[], "slow down"),
+            _ => http_response(200, &[("x-jwt-token", "token")], ""),
+        })
+        .await;
+        let manager = mock_auth(&server).with_retry_policy(policy);
+        assert_eq!(manager.get_jwt_token(false).await.unwrap(), "token");
+        assert_eq!(server.request_count(), 3);
+
+        // 401 是明确的拒绝，不重试
+        let server = MockServer::start(Duration::ZERO, |_, _| http_response(401, &[], "")).await;
+        let manager = mock_auth(&server).with_retry_policy(policy);
+        assert!(matches!(
+            manager.get_jwt_token(false).await,
+            Err(LogidError::SessionExpired { .. })
+        ));
+        assert_eq!(server.request_count(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_auth_metrics_track_fetches_and_cache_hits() {
+        use crate::test_support::{http_response, mock_auth, MockServer};
+        use std::time::Duration;
+
+        let server = MockServer::start(Duration::from_millis(20), |n, _| match n {
+            1 => http_response(200, &[("x-jwt-token", "token")], ""),
+            _ => http_response(401, &[], ""),
+        })
+        .await;
+        let manager = mock_auth(&server);
+        assert_eq!(manager.metrics().cache_hit_ratio(), None);
+
+        for _ in 0..3 {
+            manager.get_jwt_token(false).await.unwrap();
+        }
+        // 克隆的管理器共享指标
+        let metrics = manager.clone().metrics();
+        assert_eq!((metrics.refresh_count, metrics.token_requests, metrics.cache_hits), (1, 3, 2));
+        assert!(metrics.last_refresh_at.is_some());
+        assert!(metrics.last_latency().unwrap() >= Duration::from_millis(20));
+        assert!((metrics.cache_hit_ratio().unwrap() - 2.0 / 3.0).abs() < f64::EPSILON);
+
+        assert!(manager.refresh_token().await.is_err());
+        let metrics = manager.metrics();
+        assert_eq!((metrics.refresh_count, metrics.failure_count), (1, 1));
+    }
+
+    #[tokio::test]
+    async fn test_rotated_session_is_used_and_persisted() {
+        use super::AuthManager;
+        use crate::test_support::{http_response, MockServer};
+        use std::time::Duration;
+
+        let server = MockServer::start(Duration::ZERO, |n, head| {
+            let expected = if n == 1 { "CAS_SESSION=old-session" } else { "CAS_SESSION=new-session" };
+            assert!(head.contains(expected), "请求 {} 应携带 {}: {}", n, expected, head);
+            http_response(
+                200,
+                &[
+                    ("x-jwt-token", &format!("token-{}", n)),
+                    ("set-cookie", "CAS_SESSION=new-session; Path=/; HttpOnly"),
+                ],
+                "",
+            )
+        })
+        .await;
+        let dir = tempfile::tempdir().unwrap();
+        let env_path = dir.path().join(".env");
+        std::fs::write(&env_path, "# logid\nCAS_SESSION_US=old-session\n").unwrap();
+
+        let manager = AuthManager::with_credentials(Region::Us, "old-session".to_string(), server.url("/jwt"))
+            .unwrap()
+            .with_session_persistence(&env_path, "CAS_SESSION_US");
+        manager.get_jwt_token(false).await.unwrap();
+        assert_eq!(
+            std::fs::read_to_string(&env_path).unwrap(),
+            "# logid\nCAS_SESSION_US=new-session\n"
+        );
+
+        // 后续请求使用轮换后的 CAS_SESSION
+        assert_eq!(manager.refresh_token().await.unwrap(), "token-2");
+        assert_eq!(server.request_count(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_from_token_skips_cas_and_auth_service() {
+        use super::{AuthManager, AuthProvider};
+
+        let expires_at = chrono::Utc::now() + chrono::Duration::minutes(3);
+        let manager = AuthManager::from_token(Region::Us, "ci-token", Some(expires_at)).unwrap();
+        assert!(manager.is_static_token());
+        assert_eq!(manager.name(), "static-token");
+        // 剩余时间少于刷新缓冲时仍然可用，强制刷新也不访问认证服务
+        assert_eq!(manager.get_jwt_token(false).await.unwrap(), "ci-token");
+        assert_eq!(manager.refresh_token().await.unwrap(), "ci-token");
+        assert_eq!(manager.token_expires_at().await, Some(expires_at));
+
+        let expired = AuthManager::from_token(
+            Region::Us,
+            "old-token",
+            Some(chrono::Utc::now() - chrono::Duration::minutes(1)),
+        )
+        .unwrap();
+        assert!(matches!(
+            expired.get_jwt_token(false).await,
+            Err(crate::error::LogidError::AuthenticationFailed(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_audit_log_records_auth_events_with_masked_session() {
+        use super::{AuditLog, AuthManager};
+        use crate::test_support::{http_response, MockServer};
+        use std::time::Duration;
+
+        let server = MockServer::start(Duration::ZERO, |n, _| match n {
+            1 => http_response(200, &[("x-jwt-token", "token")], ""),
+            _ => http_response(401, &[], ""),
+        })
+        .await;
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("audit").join("auth_audit.log");
+        let session = "shared-account-session-0123456789";
+
+        let manager = AuthManager::with_credentials(Region::Us, session.to_string(), server.url("/jwt"))
+            .unwrap()
+            .with_credential_source("CAS_SESSION")
+            .with_audit_log(AuditLog::new(&path));
+        manager.get_jwt_token(false).await.unwrap();
+        // 缓存命中不访问认证服务，不写审计记录
+        manager.get_jwt_token(false).await.unwrap();
+        assert!(manager.refresh_token().await.is_err());
+
+        let content = std::fs::read_to_string(&path).unwrap();
+        assert!(!content.contains(session));
+        let events: Vec<serde_json::Value> =
+            content.lines().map(|line| serde_json::from_str(line).unwrap()).collect();
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0]["event"], "fetch");
+        assert_eq!(events[0]["credential_source"], "CAS_SESSION");
+        assert_eq!(events[0]["session"], "shar****6789");
+        assert_eq!(events[1]["event"], "failure");
+        assert!(events[1]["error"].as_str().unwrap().contains("CAS_SESSION"));
+    }
 }
@@ -1,90 +1,109 @@
 //! 多区域认证管理模块
 
 use crate::auth::AuthManager;
+use crate::config::Region;
 use crate::error::LogidError;
 use std::collections::HashMap;
-use std::sync::Arc;
+use std::sync::{Arc, OnceLock};
 use tracing::info;
 
 /// 多区域认证管理器
 ///
 /// 管理多个区域的 JWT 认证，提供统一的认证接口。
+/// 每个区域的 `AuthManager` 在第一次使用时才创建，缺少某个区域的凭据不会影响其他区域；
+/// 每个区域共享同一个 `AuthManager`，并发的令牌请求会合并为一次认证调用。
 #[derive(Debug)]
 #[allow(dead_code)]
 pub struct MultiRegionAuthManager {
-    /// 区域认证管理器映射
-    managers: HashMap<String, Arc<AuthManager>>,
+    /// 区域认证管理器映射，创建成功后缓存
+    managers: HashMap<String, OnceLock<Arc<AuthManager>>>,
 }
 
 #[allow(dead_code)]
 impl MultiRegionAuthManager {
     /// 创建多区域认证管理器
     ///
+    /// 只校验区域名称，各区域的凭据在第一次使用时才读取。
+    ///
     /// # 参数
-    /// - `regions`: 要初始化的区域列表
+    /// - `regions`: 要管理的区域列表
     ///
     /// # 返回
-    /// - `Result<Self, LogidError>`: 创建的管理器或错误
+    /// - `Result<Self, LogidError>`: 创建的管理器，区域名称无效时返回 `UnsupportedRegion`
     pub fn new(regions: &[&str]) -> Result<Self, LogidError> {
         let mut managers = HashMap::new();
-
         for &region in regions {
-            let manager = AuthManager::new(region)
-                .map_err(|e| LogidError::AuthenticationFailed(format!("初始化 {} 区域认证失败: {}", region, e)))?;
-            managers.insert(region.to_string(), Arc::new(manager));
-            info!("已初始化 {} 区域认证管理器", region);
+            let region = Region::from_str(region)
+                .ok_or_else(|| LogidError::UnsupportedRegion(region.to_string()))?;
+            managers.insert(region.as_str().to_string(), OnceLock::new());
         }
 
         info!("多区域认证管理器初始化完成，共 {} 个区域", managers.len());
         Ok(Self { managers })
     }
 
-    /// 获取指定区域的认证管理器
+    /// 获取指定区域的认证管理器，必要时创建
+    ///
+    /// 区域缺少凭据时返回对应的错误（如 `MissingCredentials`），下次调用会重新尝试。
+    pub fn manager(&self, region: &str) -> Result<Arc<AuthManager>, LogidError> {
+        let slot = self.managers.get(region).ok_or_else(|| {
+            LogidError::AuthenticationFailed(format!("未找到 {} 区域的认证管理器", region))
+        })?;
+        if let Some(manager) = slot.get() {
+            return Ok(manager.clone());
+        }
+
+        let manager = Arc::new(AuthManager::new(region)?);
+        info!("已初始化 {} 区域认证管理器", region);
+        Ok(slot.get_or_init(|| manager).clone())
+    }
+
+    /// 获取指定区域的认证管理器，区域未管理或不可用时返回 `None`
     pub fn get_manager(&self, region: &str) -> Option<Arc<AuthManager>> {
-        self.managers.get(region).cloned()
+        self.manager(region).ok()
     }
 
     /// 获取指定区域的 JWT 令牌
     pub async fn get_jwt_token(&self, region: &str, force_refresh: bool) -> Result<String, LogidError> {
-        let manager = self.get_manager(region).ok_or_else(|| {
-            LogidError::AuthenticationFailed(format!("未找到 {} 区域的认证管理器", region))
-        })?;
-
-        manager.get_jwt_token(force_refresh).await
+        self.manager(region)?.get_jwt_token(force_refresh).await
     }
 
     /// 检查指定区域的令牌是否有效
     pub async fn is_token_valid(&self, region: &str) -> Result<bool, LogidError> {
-        let manager = self.get_manager(region).ok_or_else(|| {
-            LogidError::AuthenticationFailed(format!("未找到 {} 区域的认证管理器", region))
-        })?;
-
-        Ok(manager.is_token_valid().await)
+        Ok(self.manager(region)?.is_token_valid().await)
     }
 
     /// 刷新指定区域的令牌
     pub async fn refresh_token(&self, region: &str) -> Result<String, LogidError> {
-        let manager = self.get_manager(region).ok_or_else(|| {
-            LogidError::AuthenticationFailed(format!("未找到 {} 区域的认证管理器", region))
-        })?;
-
-        manager.refresh_token().await
+        self.manager(region)?.refresh_token().await
     }
 
     /// 刷新所有区域的令牌
+    ///
+    /// 不可用的区域（如缺少凭据）记为该区域的错误，不影响其他区域。
     pub async fn refresh_all_tokens(&self) -> HashMap<String, Result<String, LogidError>> {
         let mut results = HashMap::new();
 
-        for (region, manager) in &self.managers {
-            let result = manager.refresh_token().await;
-            results.insert(region.clone(), result);
+        for region in self.managed_regions() {
+            let result = self.refresh_token(&region).await;
+            results.insert(region, result);
         }
 
         results
     }
 
-    /// 获取所有已管理的区域列表
+    /// 列出当前无法使用的区域及原因
+    pub fn unavailable_regions(&self) -> Vec<(String, LogidError)> {
+        self.managed_regions()
+            .into_iter()
+            .filter_map(|region| self.manager(&region).err().map(|e| (region, e)))
+            .collect()
+    }
+
+    /// 获取所有已管理的区域列表（按名称排序）
     pub fn managed_regions(&self) -> Vec<String> {
-        self.managers.keys().cloned().collect()
+        let mut regions: Vec<String> = self.managers.keys().cloned().collect();
+        regions.sort();
+        regions
     }
 }
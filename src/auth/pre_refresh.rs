@@ -0,0 +1,92 @@
+//! 令牌后台预刷新模块
+//!
+//! 监视模式等长时间运行的场景下，后台任务在每个区域的 JWT 过期前提前刷新，
+//! 查询过程中不会因为令牌过期而等待一次认证请求。
+
+use crate::auth::AuthManager;
+use crate::error::LogidError;
+use std::time::Duration;
+use tokio::task::JoinHandle;
+
+/// 默认在令牌过期前 10 分钟刷新（早于 `JwtInfo::is_valid` 的 5 分钟缓冲）
+pub const DEFAULT_PRE_REFRESH_LEAD: Duration = Duration::from_secs(600);
+
+/// 两次刷新之间的最短间隔，避免令牌有效期短于提前量时反复请求认证服务；
+/// 刷新失败后也按该间隔重试
+const MIN_REFRESH_INTERVAL: Duration = Duration::from_secs(30);
+
+/// 令牌后台预刷新任务
+///
+/// 每个区域一个 tokio 任务，丢弃时停止所有任务。认证管理器的克隆共享令牌缓存，
+/// 刷新后的令牌对查询客户端立即可见。
+#[derive(Debug)]
+pub struct TokenPreRefresher {
+    handles: Vec<JoinHandle<()>>,
+}
+
+impl TokenPreRefresher {
+    /// 为各区域的认证管理器启动预刷新任务
+    ///
+    /// # 参数
+    /// - `managers`: 要保持令牌有效的认证管理器
+    /// - `lead`: 在令牌过期前多久刷新
+    pub fn spawn<I>(managers: I, lead: Duration) -> Self
+    where
+        I: IntoIterator<Item = AuthManager>,
+    {
+        let handles = managers
+            .into_iter()
+            .map(|manager| tokio::spawn(refresh_loop(manager, lead)))
+            .collect();
+        Self { handles }
+    }
+
+    /// 正在运行的预刷新任务数量
+    pub fn len(&self) -> usize {
+        self.handles.iter().filter(|handle| !handle.is_finished()).count()
+    }
+
+    /// 是否没有正在运行的预刷新任务
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl Drop for TokenPreRefresher {
+    fn drop(&mut self) {
+        for handle in &self.handles {
+            handle.abort();
+        }
+    }
+}
+
+/// 单个区域的预刷新循环，CAS_SESSION 过期后退出
+async fn refresh_loop(manager: AuthManager, lead: Duration) {
+    let region = manager.region_str();
+    // 预先提供的令牌无法刷新
+    if manager.is_static_token() {
+        return;
+    }
+    let mut min_delay = Duration::ZERO;
+    loop {
+        // 尚未获取令牌时立即获取
+        let delay = match manager.token_expires_at().await {
+            Some(expires_at) => (expires_at - chrono::Utc::now())
+                .to_std()
+                .unwrap_or(Duration::ZERO)
+                .saturating_sub(lead),
+            None => Duration::ZERO,
+        };
+        tokio::time::sleep(delay.max(min_delay)).await;
+        min_delay = MIN_REFRESH_INTERVAL;
+
+        match manager.refresh_token().await {
+            Ok(_) => conditional_info!("已在后台刷新 {} 区域的令牌", region),
+            Err(e @ LogidError::SessionExpired { .. }) => {
+                conditional_info!("{} 区域停止后台刷新: {}", region, e);
+                return;
+            }
+            Err(e) => conditional_info!("{} 区域后台刷新令牌失败，稍后重试: {}", region, e),
+        }
+    }
+}
@@ -0,0 +1,80 @@
+//! 批量查询前的凭据预检模块
+
+use crate::auth::AuthManager;
+use crate::error::LogidError;
+use futures::future::join_all;
+
+/// 单个区域的预检失败信息
+#[derive(Debug, Clone)]
+pub struct PreflightFailure {
+    /// 区域名称
+    pub region: String,
+    /// 失败原因
+    pub error: String,
+}
+
+/// 凭据预检结果
+#[derive(Debug, Clone, Default)]
+pub struct PreflightReport {
+    /// 已检查的区域
+    pub checked: Vec<String>,
+    /// 检查失败的区域
+    pub failures: Vec<PreflightFailure>,
+}
+
+impl PreflightReport {
+    /// 所有区域是否都通过检查
+    pub fn is_ok(&self) -> bool {
+        self.failures.is_empty()
+    }
+
+    /// 将失败的区域汇总为一个认证错误
+    pub fn into_result(self) -> Result<(), LogidError> {
+        if self.is_ok() {
+            return Ok(());
+        }
+
+        let details: Vec<String> = self
+            .failures
+            .iter()
+            .map(|failure| format!("  {}: {}", failure.region, failure.error))
+            .collect();
+        Err(LogidError::AuthenticationFailed(format!(
+            "预检发现 {}/{} 个区域的凭据不可用，已取消查询:\n{}",
+            self.failures.len(),
+            self.checked.len(),
+            details.join("\n")
+        )))
+    }
+}
+
+/// 并发验证各区域的令牌
+///
+/// 在大批量查询开始前调用，避免查询进行到一半才发现会话已过期。
+/// 获取到的令牌会缓存在 `AuthManager` 中，后续查询直接复用。
+pub async fn preflight_check(managers: &[&AuthManager]) -> PreflightReport {
+    let results = join_all(managers.iter().map(|manager| async move {
+        let region = manager.region_str().to_string();
+        let result = manager.get_jwt_token(false).await;
+        (region, result)
+    }))
+    .await;
+
+    let mut report = PreflightReport::default();
+    for (region, result) in results {
+        if let Err(e) = result {
+            report.failures.push(PreflightFailure {
+                region: region.clone(),
+                error: e.to_string(),
+            });
+        }
+        report.checked.push(region);
+    }
+
+    conditional_info!(
+        "凭据预检完成: 检查 {} 个区域，失败 {} 个",
+        report.checked.len(),
+        report.failures.len()
+    );
+    report
+}
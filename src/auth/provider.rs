@@ -0,0 +1,119 @@
+//! 认证提供者模块
+//!
+//! `LogQueryClient` 通过 `AuthProvider` 获取 JWT 令牌。默认使用基于 CAS_SESSION 的
+//! `AuthManager`，库的使用者也可以直接提供令牌（环境变量、文件）或实现自己的认证方式。
+
+use crate::auth::AuthManager;
+use crate::error::LogidError;
+use futures::future::BoxFuture;
+use std::fmt::Debug;
+use std::path::PathBuf;
+
+/// JWT 令牌提供者
+pub trait AuthProvider: Debug + Send + Sync {
+    /// 提供者名称，用于日志和诊断
+    fn name(&self) -> &str;
+
+    /// 获取 JWT 令牌，`force_refresh` 为 true 时忽略缓存重新获取
+    fn get_token(&self, force_refresh: bool) -> BoxFuture<'_, Result<String, LogidError>>;
+
+    /// 基于 CAS_SESSION 的认证管理器，用于凭据预检和过期提醒；其他提供者返回 `None`
+    fn auth_manager(&self) -> Option<&AuthManager> {
+        None
+    }
+}
+
+impl AuthProvider for AuthManager {
+    fn name(&self) -> &str {
+        if self.is_static_token() {
+            "static-token"
+        } else {
+            "cas-session"
+        }
+    }
+
+    fn get_token(&self, force_refresh: bool) -> BoxFuture<'_, Result<String, LogidError>> {
+        Box::pin(self.get_jwt_token(force_refresh))
+    }
+
+    fn auth_manager(&self) -> Option<&AuthManager> {
+        Some(self)
+    }
+}
+
+/// 从环境变量读取 JWT 令牌
+///
+/// 每次获取时重新读取，外部工具更新环境变量后立即生效。
+#[derive(Debug, Clone)]
+pub struct EnvTokenProvider {
+    var: String,
+}
+
+impl EnvTokenProvider {
+    /// 使用指定的环境变量创建提供者
+    pub fn new(var: impl Into<String>) -> Self {
+        Self { var: var.into() }
+    }
+
+    /// 环境变量名称
+    pub fn var(&self) -> &str {
+        &self.var
+    }
+}
+
+impl AuthProvider for EnvTokenProvider {
+    fn name(&self) -> &str {
+        "env"
+    }
+
+    fn get_token(&self, _force_refresh: bool) -> BoxFuture<'_, Result<String, LogidError>> {
+        let token = std::env::var(&self.var)
+            .ok()
+            .map(|token| token.trim().to_string())
+            .filter(|token| !token.is_empty())
+            .ok_or_else(|| LogidError::MissingCredentials(format!("未找到 {} 环境变量", self.var)));
+        Box::pin(std::future::ready(token))
+    }
+}
+
+/// 从文件读取 JWT 令牌
+///
+/// 每次获取时重新读取文件，适合由其他进程定期轮换令牌的场景；首尾空白会被去掉。
+#[derive(Debug, Clone)]
+pub struct FileTokenProvider {
+    path: PathBuf,
+}
+
+impl FileTokenProvider {
+    /// 使用指定的令牌文件创建提供者
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    /// 令牌文件路径
+    pub fn path(&self) -> &std::path::Path {
+        &self.path
+    }
+}
+
+impl AuthProvider for FileTokenProvider {
+    fn name(&self) -> &str {
+        "file"
+    }
+
+    fn get_token(&self, _force_refresh: bool) -> BoxFuture<'_, Result<String, LogidError>> {
+        Box::pin(async move {
+            let content = tokio::fs::read_to_string(&self.path).await.map_err(|e| {
+                LogidError::MissingCredentials(format!("读取令牌文件 {} 失败: {}", self.path.display(), e))
+            })?;
+            let token = content.trim();
+            if token.is_empty() {
+                return Err(LogidError::MissingCredentials(format!(
+                    "令牌文件 {} 为空",
+                    self.path.display()
+                )));
+            }
+            Ok(token.to_string())
+        })
+    }
+}
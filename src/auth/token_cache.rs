@@ -0,0 +1,154 @@
+//! JWT 令牌磁盘缓存模块
+//!
+//! 将各区域的 JWT 令牌保存在 `~/.config/logid/token_cache.json`（权限 0600），
+//! 多次运行 CLI 时复用仍然有效的令牌，避免每次都请求认证服务。
+//! 每个令牌记录所用 CAS_SESSION 和认证地址的指纹，凭据变化后旧令牌不再使用。
+
+use crate::config::{user_config_dir, JwtInfo, Region};
+use crate::error::LogidError;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// 令牌缓存文件名
+const TOKEN_CACHE_FILE_NAME: &str = "token_cache.json";
+
+/// 串行化同一进程内对缓存文件的写入，避免并发的区域认证互相覆盖
+static WRITE_LOCK: Mutex<()> = Mutex::new(());
+
+/// 获取用户级别令牌缓存文件路径 (~/.config/logid/token_cache.json)
+pub fn user_token_cache_path() -> Option<PathBuf> {
+    user_config_dir().map(|dir| dir.join(TOKEN_CACHE_FILE_NAME))
+}
+
+/// 缓存的令牌
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CachedToken {
+    /// JWT 令牌
+    pub token: String,
+    /// 令牌过期时间
+    pub expires_at: DateTime<Utc>,
+    /// CAS_SESSION 过期时间（认证服务在 Set-Cookie 中下发时记录）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub session_expires_at: Option<DateTime<Utc>>,
+    /// 获取令牌时使用的 CAS_SESSION 和认证地址的指纹
+    pub fingerprint: String,
+}
+
+/// 缓存文件内容
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct TokenCacheFile {
+    /// 按区域名称索引的令牌
+    #[serde(default)]
+    tokens: BTreeMap<String, CachedToken>,
+}
+
+/// JWT 令牌磁盘缓存
+#[derive(Debug, Clone)]
+pub struct TokenCache {
+    path: PathBuf,
+}
+
+impl TokenCache {
+    /// 使用指定文件创建令牌缓存
+    pub fn new(path: PathBuf) -> Self {
+        Self { path }
+    }
+
+    /// 使用用户配置目录下的默认文件创建令牌缓存
+    pub fn open_default() -> Result<Self, LogidError> {
+        user_token_cache_path()
+            .map(Self::new)
+            .ok_or_else(|| LogidError::InternalError("无法确定用户主目录".to_string()))
+    }
+
+    /// 缓存文件路径
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// 读取区域仍然有效的令牌
+    ///
+    /// 指纹不一致（凭据已更换）、令牌即将过期或文件损坏时返回 `None`。
+    pub fn load(&self, region: Region, fingerprint: &str) -> Option<CachedToken> {
+        let cached = self.read().tokens.remove(region.as_str())?;
+        if cached.fingerprint != fingerprint {
+            conditional_info!("{} 区域缓存的令牌属于其他凭据，忽略", region.as_str());
+            return None;
+        }
+        if !cached.jwt_info().is_valid() {
+            conditional_info!("{} 区域缓存的令牌已过期", region.as_str());
+            return None;
+        }
+        Some(cached)
+    }
+
+    /// 保存区域的令牌，替换之前缓存的令牌
+    pub fn store(&self, region: Region, token: CachedToken) -> Result<(), LogidError> {
+        let _guard = WRITE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let mut file = self.read();
+        file.tokens.insert(region.as_str().to_string(), token);
+
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        // 先写临时文件再替换，避免中断时损坏缓存
+        let tmp_path = self.path.with_extension("json.tmp");
+        write_private(&tmp_path, serde_json::to_string_pretty(&file)?.as_bytes())?;
+        std::fs::rename(&tmp_path, &self.path)?;
+        Ok(())
+    }
+
+    /// 读取缓存文件，文件不存在或无法解析时视为空缓存
+    fn read(&self) -> TokenCacheFile {
+        let Ok(content) = std::fs::read_to_string(&self.path) else {
+            return TokenCacheFile::default();
+        };
+        serde_json::from_str(&content).unwrap_or_else(|e| {
+            conditional_info!("令牌缓存文件无法解析，将重新获取令牌: {} - {}", self.path.display(), e);
+            TokenCacheFile::default()
+        })
+    }
+}
+
+impl CachedToken {
+    /// 根据令牌信息创建缓存条目
+    pub fn new(jwt_info: &JwtInfo, session_expires_at: Option<DateTime<Utc>>, fingerprint: String) -> Self {
+        Self {
+            token: jwt_info.token.clone(),
+            expires_at: jwt_info.expires_at_utc,
+            session_expires_at,
+            fingerprint,
+        }
+    }
+
+    /// 转换为内存中的令牌信息
+    pub fn jwt_info(&self) -> JwtInfo {
+        JwtInfo::from_expiry(self.token.clone(), self.expires_at)
+    }
+}
+
+/// CAS_SESSION 和认证地址的指纹，缓存文件中不保存凭据本身
+pub(crate) fn credential_fingerprint(cas_session: &str, auth_url: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(cas_session.as_bytes());
+    hasher.update(b"\n");
+    hasher.update(auth_url.as_bytes());
+    format!("{:x}", hasher.finalize())
+}
+
+/// 以仅当前用户可读写的权限写入文件
+fn write_private(path: &Path, content: &[u8]) -> std::io::Result<()> {
+    use std::io::Write;
+
+    let mut file = std::fs::File::create(path)?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        file.set_permissions(std::fs::Permissions::from_mode(0o600))?;
+    }
+    file.write_all(content)
+}
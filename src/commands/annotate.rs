@@ -0,0 +1,31 @@
+use anyhow::Result;
+use logid::store::{Annotation, AnnotationStore};
+
+pub fn annotate_command(query_id: &str, message_id: Option<&str>, note: Option<&str>) -> Result<()> {
+    let store = AnnotationStore::open_default()?;
+
+    match (message_id, note) {
+        (Some(message_id), Some(note)) => {
+            store.add(&Annotation::new(query_id, message_id, note))?;
+            println!("📝 已为消息 {} 添加批注", message_id);
+            println!("💾 批注文件: {}", store.path().display());
+        }
+        (None, None) => {
+            let annotations = store.for_query(query_id)?;
+            if annotations.is_empty() {
+                println!("查询 {} 暂无批注", query_id);
+            }
+            for annotation in annotations {
+                println!(
+                    "[{}] {}: {}",
+                    annotation.created_at, annotation.message_id, annotation.note
+                );
+            }
+        }
+        _ => {
+            return Err(anyhow::anyhow!("添加批注需要同时提供消息 ID 和批注内容"));
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,327 @@
+use anyhow::Result;
+use futures::future::join_all;
+use logid::auth::{
+    login_url, parse_cas_session, AuthManager, AuthMetrics, MultiRegionAuthManager, TokenCache,
+};
+use logid::config::{
+    active_profile, decrypt_secret, encrypt_secret, env_file_path, is_encrypted, passphrase,
+    resolve_regions, upsert_env_var, user_env_path, EnvManager, Region, FALLBACK_CAS_SESSION_VAR,
+};
+use logid::error::LogidError;
+use serde::Serialize;
+use std::path::Path;
+
+/// 单个区域的认证检查结果
+struct AuthCheck {
+    region: Region,
+    cas_found: bool,
+    /// 令牌过期时间和认证请求耗时
+    token: std::result::Result<(Option<String>, Option<u64>), String>,
+}
+
+pub async fn check_command() -> Result<()> {
+    println!("🔍 检查各区域认证凭据...");
+
+    let env_manager = EnvManager::new()?;
+    let regions: Vec<Region> = resolve_regions("all")?
+        .iter()
+        .filter_map(|region| Region::from_str(region))
+        .collect();
+
+    let checks = join_all(regions.into_iter().map(|region| {
+        let cas_found = env_manager.get_cas_session(region).is_ok();
+        check_region(region, cas_found)
+    }))
+    .await;
+
+    let headers = ["REGION", "CAS_VAR", "AUTH_URL", "TOKEN", "LATENCY", "EXPIRES"];
+    let rows: Vec<[String; 6]> = checks
+        .iter()
+        .map(|check| {
+            let cas_var = format!(
+                "{} {}",
+                check.region.cas_session_env_var(),
+                if check.cas_found { "✓" } else { "✗" }
+            );
+            let (token, latency, expires) = match &check.token {
+                Ok((expires, latency)) => (
+                    "✓".to_string(),
+                    latency.map_or_else(|| "-".to_string(), |ms| format!("{}ms", ms)),
+                    expires.clone().unwrap_or_else(|| "-".to_string()),
+                ),
+                Err(_) => ("✗".to_string(), "-".to_string(), "-".to_string()),
+            };
+            [
+                check.region.as_str().to_string(),
+                cas_var,
+                AuthManager::auth_url_for(check.region),
+                token,
+                latency,
+                expires,
+            ]
+        })
+        .collect();
+
+    let mut widths = headers.map(|h| h.chars().count());
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+
+    let format_row = |cells: [&str; 6]| {
+        cells
+            .iter()
+            .zip(widths)
+            .map(|(cell, width)| format!("{:<width$}", cell, width = width))
+            .collect::<Vec<_>>()
+            .join("  ")
+            .trim_end()
+            .to_string()
+    };
+
+    println!();
+    println!("{}", format_row(headers));
+    for row in &rows {
+        println!("{}", format_row([&row[0], &row[1], &row[2], &row[3], &row[4], &row[5]]));
+    }
+
+    let failures: Vec<_> = checks
+        .iter()
+        .filter_map(|check| check.token.as_ref().err().map(|e| (check.region, e)))
+        .collect();
+
+    if failures.is_empty() {
+        println!();
+        println!("✅ 所有区域认证正常");
+        return Ok(());
+    }
+
+    println!();
+    for (region, error) in &failures {
+        println!("❌ {}: {}", region.as_str(), error);
+    }
+
+    Err(LogidError::AuthenticationFailed(format!("{} 个区域认证失败", failures.len())).into())
+}
+
+/// 尝试获取区域的 JWT 令牌，成功时返回令牌过期时间和认证请求耗时
+async fn check_region(region: Region, cas_found: bool) -> AuthCheck {
+    let token = if cas_found {
+        fetch_token_expiry(region).await.map_err(|e| e.to_string())
+    } else {
+        Err(format!("未设置 {} 或 CAS_SESSION", region.cas_session_env_var()))
+    };
+
+    AuthCheck {
+        region,
+        cas_found,
+        token,
+    }
+}
+
+async fn fetch_token_expiry(region: Region) -> Result<(Option<String>, Option<u64>), LogidError> {
+    // 强制向认证服务验证凭据，不使用磁盘缓存中的令牌
+    let manager = AuthManager::new(region.as_str())?;
+    manager.get_jwt_token(true).await?;
+    let expires_at = manager
+        .token_expires_at()
+        .await
+        .map(|expires_at| expires_at.to_rfc3339());
+    Ok((expires_at, manager.metrics().last_latency_ms))
+}
+
+/// 单个区域的令牌刷新结果
+#[derive(Debug, Serialize)]
+struct RefreshReport {
+    region: String,
+    refreshed: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    token_expires_at: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    token_expires_in_seconds: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    session_expires_at: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    auth_metrics: Option<AuthMetrics>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+impl RefreshReport {
+    fn failed(region: &str, error: String) -> Self {
+        Self {
+            region: region.to_string(),
+            refreshed: false,
+            token_expires_at: None,
+            token_expires_in_seconds: None,
+            session_expires_at: None,
+            auth_metrics: None,
+            error: Some(error),
+        }
+    }
+}
+
+pub async fn refresh_command(region: Option<&str>) -> Result<()> {
+    let regions = resolve_regions(region.unwrap_or("all"))?;
+
+    // 缺少凭据的区域由多区域认证管理器记为该区域的失败，不影响其他区域
+    let region_refs: Vec<&str> = regions.iter().map(|r| r.as_str()).collect();
+    let manager = MultiRegionAuthManager::new(&region_refs)?;
+    let mut reports = Vec::new();
+    for (region, result) in manager.refresh_all_tokens().await {
+        let report = match result {
+            Ok(_) => {
+                let auth = manager.get_manager(&region);
+                let token_expires_at = match &auth {
+                    Some(auth) => auth.token_expires_at().await,
+                    None => None,
+                };
+                let session_expires_at = match &auth {
+                    Some(auth) => auth
+                        .credential_expiry(std::time::Duration::ZERO)
+                        .await
+                        .map(|expiry| expiry.expires_at.to_rfc3339()),
+                    None => None,
+                };
+                RefreshReport {
+                    region,
+                    refreshed: true,
+                    token_expires_at: token_expires_at.map(|t| t.to_rfc3339()),
+                    token_expires_in_seconds: token_expires_at
+                        .map(|t| (t - chrono::Utc::now()).num_seconds()),
+                    session_expires_at,
+                    auth_metrics: auth.map(|auth| auth.metrics()),
+                    error: None,
+                }
+            }
+            Err(e) => RefreshReport::failed(&region, e.to_string()),
+        };
+        reports.push(report);
+    }
+    reports.sort_by(|a, b| a.region.cmp(&b.region));
+
+    let output = serde_json::json!({
+        "regions": reports,
+        "timestamp": chrono::Utc::now().to_rfc3339(),
+    });
+    println!("{}", serde_json::to_string_pretty(&output)?);
+
+    let failures = reports.iter().filter(|report| !report.refreshed).count();
+    if failures > 0 {
+        return Err(LogidError::AuthenticationFailed(format!("{} 个区域刷新失败", failures)).into());
+    }
+
+    Ok(())
+}
+
+pub async fn login_command(region: &str, session: Option<String>, no_browser: bool) -> Result<()> {
+    let region = Region::from_str(region).ok_or_else(|| LogidError::UnsupportedRegion(region.to_string()))?;
+    let env_path = env_file_path()
+        .or_else(user_env_path)
+        .ok_or_else(|| LogidError::InternalError("无法确定用户主目录".to_string()))?;
+
+    let input = match session {
+        Some(session) => session,
+        None => {
+            let url = login_url(region);
+            println!("🔑 登录 {} ({})", region.display_name(), url);
+            if no_browser {
+                println!("   请在浏览器中打开上述地址并完成登录");
+            } else if let Err(e) = super::open_in_browser(&url) {
+                println!("⚠️  {}，请手动打开上述地址并完成登录", e);
+            }
+            println!("   登录后在浏览器开发者工具中复制 CAS_SESSION Cookie 的值（或整段 Cookie 请求头）");
+            super::config::prompt_secret("CAS_SESSION: ")?
+        }
+    };
+    let session = parse_cas_session(&input)
+        .ok_or_else(|| LogidError::MissingCredentials("未能从输入中识别出 CAS_SESSION".to_string()))?;
+
+    println!("🔍 验证认证凭据...");
+    let mut manager = AuthManager::from_session(region, &session)?;
+    if let Ok(cache) = TokenCache::open_default() {
+        manager = manager.with_token_cache(cache);
+    }
+    manager.get_jwt_token(true).await?;
+
+    // .env 中的凭据已加密时，新的凭据也加密保存
+    let encrypted = session_vars(&env_path)
+        .unwrap_or_default()
+        .into_iter()
+        .find_map(|(_, value)| is_encrypted(&value).then_some(value));
+    let value = match encrypted {
+        Some(existing) => encrypt_secret(&session, &encryption_passphrase(Some(&existing))?)?,
+        None => session,
+    };
+    upsert_env_var(&env_path, region.cas_session_env_var(), &value)?;
+    println!("✅ {} 认证成功，已写入 {}", region.as_str(), env_path.display());
+    if let Some((name, _)) = active_profile().filter(|(_, profile)| profile.cas_sessions.contains_key(region.as_str())) {
+        println!("⚠️  配置档 {} 中设置了 {} 区域的凭据，启用该配置档时优先于 .env", name, region.as_str());
+    }
+    Ok(())
+}
+
+/// 加密 .env 文件中明文保存的 CAS_SESSION
+pub fn encrypt_command() -> Result<()> {
+    let env_path = env_file_path()
+        .or_else(user_env_path)
+        .ok_or_else(|| LogidError::InternalError("无法确定用户主目录".to_string()))?;
+    if !env_path.is_file() {
+        return Err(LogidError::EnvFileNotFound(env_path.display().to_string()).into());
+    }
+
+    let vars = session_vars(&env_path)?;
+    let existing = vars.iter().map(|(_, value)| value).find(|value| is_encrypted(value));
+    let plaintext: Vec<_> = vars
+        .iter()
+        .filter(|(_, value)| !value.is_empty() && !is_encrypted(value))
+        .collect();
+    if plaintext.is_empty() {
+        println!("✅ {} 中没有明文保存的 CAS_SESSION", env_path.display());
+        return Ok(());
+    }
+
+    let passphrase = encryption_passphrase(existing.map(String::as_str))?;
+    for (key, value) in &plaintext {
+        upsert_env_var(&env_path, key, &encrypt_secret(value, &passphrase)?)?;
+        println!("🔐 {}", key);
+    }
+    println!("✅ 已加密 {} 个变量，写入 {}", plaintext.len(), env_path.display());
+    if let Some((name, _)) = active_profile().filter(|(_, profile)| {
+        profile.cas_sessions.values().any(|session| !session.is_empty() && !is_encrypted(session))
+    }) {
+        println!("⚠️  配置档 {} 中仍有明文的 CAS_SESSION，可以用 enc:v1: 形式的密文替换", name);
+    }
+    Ok(())
+}
+
+/// .env 文件中的 CAS_SESSION 变量：`CAS_SESSION*` 和各区域（含自定义区域）的凭据变量
+fn session_vars(path: &Path) -> Result<Vec<(String, String)>, LogidError> {
+    let region_vars: Vec<&str> = Region::all()
+        .into_iter()
+        .map(|region| region.cas_session_env_var())
+        .collect();
+    let mut vars = Vec::new();
+    for item in dotenvy::from_path_iter(path)? {
+        let (key, value) = item?;
+        if key.starts_with(FALLBACK_CAS_SESSION_VAR) || region_vars.contains(&key.as_str()) {
+            vars.push((key, value));
+        }
+    }
+    Ok(vars)
+}
+
+/// 加密凭据使用的口令
+///
+/// 文件中已有密文时用它校验口令，保证同一个文件中的凭据使用同一个口令；否则交互输入时需要确认。
+fn encryption_passphrase(existing: Option<&str>) -> Result<String, LogidError> {
+    match existing {
+        Some(value) => {
+            let passphrase = passphrase(false)?;
+            decrypt_secret(value, &passphrase)?;
+            Ok(passphrase)
+        }
+        None => passphrase(true),
+    }
+}
@@ -0,0 +1,69 @@
+use super::{read_stdin_logids, RequestArgs};
+use anyhow::Result;
+use futures::StreamExt;
+use logid::auth::preflight_check;
+use logid::conditional_info;
+use logid::config::{ConfigFile, EnvTarget};
+use logid::log_query::{parse_logid_list, BatchQuery, LogQueryClient, ResultCache};
+use logid::output::{OutputConfig, OutputFormatter};
+use std::sync::Arc;
+
+pub async fn batch_command(
+    file: Option<&str>,
+    region: &str,
+    psm_list: &[String],
+    raw: bool,
+    concurrency: usize,
+    request: &RequestArgs,
+    env_target: EnvTarget,
+) -> Result<()> {
+    let logids = match file {
+        Some(path) if path != "-" => std::fs::read_to_string(path)
+            .map(|content| parse_logid_list(&content))
+            .map_err(|e| anyhow::anyhow!("读取 logid 文件 {} 失败: {}", path, e))?,
+        _ => read_stdin_logids()?,
+    };
+
+    if logids.is_empty() {
+        return Err(anyhow::anyhow!("没有需要查询的 logid"));
+    }
+
+    conditional_info!(
+        "开始批量查询: count={}, region={}, env={}, concurrency={}",
+        logids.len(),
+        region,
+        env_target,
+        concurrency
+    );
+
+    // 在发起查询前读取输出偏好，config.toml 损坏时使用默认值
+    let preferences = ConfigFile::load_default_lenient().output;
+    let formatter = OutputFormatter::new(preferences.apply(OutputConfig::new()));
+
+    // 列表中重复的 logid 直接复用缓存的结果
+    let client = LogQueryClient::for_region_env(region, env_target).await?;
+    let client = Arc::new(
+        request
+            .apply(client)
+            .with_raw(raw)
+            .with_result_cache(Arc::new(ResultCache::default())),
+    );
+
+    // 开始前验证凭据，避免大批量查询进行到一半才发现会话过期
+    preflight_check(&client.auth_manager().into_iter().collect::<Vec<_>>()).await.into_result()?;
+
+    let batch = BatchQuery::new(client, concurrency);
+
+    let total = logids.len();
+    let mut failed = 0;
+    let mut results = batch.stream(logids, psm_list);
+    while let Some(item) = results.next().await {
+        if item.result.is_err() {
+            failed += 1;
+        }
+        println!("{}", formatter.format_ndjson_line(&item)?);
+    }
+
+    eprintln!("✅ 批量查询完成: 共 {} 个，失败 {} 个", total, failed);
+    Ok(())
+}
@@ -0,0 +1,42 @@
+use anyhow::Result;
+use clap::builder::{PossibleValue, TypedValueParser};
+use clap_complete::{generate, Shell};
+use logid::config::Region;
+
+/// 区域参数解析器
+///
+/// 补全候选值由 `Region::all()` 生成，包括 config.toml 中声明的自定义区域；解析时不做校验，
+/// 以便继续支持逗号分隔的区域列表，区域名称由 `resolve_regions` 检查。
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RegionValueParser;
+
+impl TypedValueParser for RegionValueParser {
+    type Value = String;
+
+    fn parse_ref(
+        &self,
+        _cmd: &clap::Command,
+        _arg: Option<&clap::Arg>,
+        value: &std::ffi::OsStr,
+    ) -> Result<Self::Value, clap::Error> {
+        Ok(value.to_string_lossy().into_owned())
+    }
+
+    fn possible_values(&self) -> Option<Box<dyn Iterator<Item = PossibleValue> + '_>> {
+        let regions = Region::all()
+            .into_iter()
+            .map(|region| {
+                PossibleValue::new(region.as_str())
+                    .help(region.display_name())
+                    .aliases(region.aliases())
+            });
+        let all = PossibleValue::new("all").help("所有已配置的区域");
+        Some(Box::new(regions.chain(std::iter::once(all))))
+    }
+}
+
+pub fn completions_command(shell: Shell, mut command: clap::Command) -> Result<()> {
+    let name = command.get_name().to_string();
+    generate(shell, &mut command, name, &mut std::io::stdout());
+    Ok(())
+}
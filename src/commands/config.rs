@@ -0,0 +1,330 @@
+use anyhow::Result;
+use logid::auth::AuthManager;
+use logid::config::{
+    active_profile, config_key_path, create_message_filters_lenient, get_config_value,
+    get_region_config, is_encrypted, load_filter_patterns, mask_proxy_url, proxy_setting,
+    region_proxy, set_config_value, set_proxy_setting, user_config_file_path, user_env_path,
+    user_profiles_path, ConfigFile, EnvManager, FileRegionProvider, ProfileConfig, ProxySetting,
+    Region, PROXY_ENV_VAR,
+};
+use logid::error::LogidError;
+use std::fs;
+use std::io::{self, Write};
+use std::path::PathBuf;
+
+/// 输出 config.toml 中的配置项
+pub fn get_command(key: &str) -> Result<()> {
+    let path = config_file_path()?;
+    match get_config_value(&path, key)? {
+        Some(value) => {
+            println!("{}", value);
+            Ok(())
+        }
+        None => Err(LogidError::ConfigError {
+            path: path.display().to_string(),
+            line: None,
+            key: Some(config_key_path(key)),
+            message: "未设置".to_string(),
+        }
+        .into()),
+    }
+}
+
+/// 修改 config.toml 中的配置项
+pub fn set_command(key: &str, value: &str) -> Result<()> {
+    let path = config_file_path()?;
+    set_config_value(&path, key, value)?;
+    println!("✅ {} = {}（{}）", config_key_path(key), value, path.display());
+    Ok(())
+}
+
+fn config_file_path() -> Result<PathBuf> {
+    user_config_file_path()
+        .ok_or_else(|| LogidError::InternalError("无法确定用户主目录".to_string()).into())
+}
+
+pub fn validate_command() -> Result<()> {
+    println!("🔍 检查配置文件...");
+    if let Some(path) = user_config_file_path().filter(|path| path.exists()) {
+        ConfigFile::load(&path)?;
+        println!("✅ {}", path.display());
+    }
+    if let Some(path) = user_profiles_path().filter(|path| path.exists()) {
+        ProfileConfig::load(&path)?;
+        println!("✅ {}", path.display());
+    }
+    if let Some(path) = FileRegionProvider::default_path().filter(|path| path.exists()) {
+        FileRegionProvider::load(&path)?;
+        println!("✅ {}", path.display());
+    }
+
+    println!("🔍 检查消息过滤规则...");
+
+    let compiled = create_message_filters_lenient(None)?;
+    println!("✅ 有效规则: {} 条", compiled.filters.len());
+
+    if compiled.diagnostics.is_empty() {
+        println!("✅ 配置检查通过");
+        return Ok(());
+    }
+
+    println!("⚠️  无效规则: {} 条（查询时将被跳过）", compiled.diagnostics.len());
+    for diagnostic in &compiled.diagnostics {
+        println!("   - {}", diagnostic.pattern);
+        if let Some(position) = diagnostic.position {
+            println!("     位置: 第 {} 个字符", position);
+        }
+        println!("     {}", diagnostic.error.replace('\n', "\n     "));
+    }
+
+    Err(LogidError::FilterConfigError(format!(
+        "发现 {} 条无效的过滤规则",
+        compiled.diagnostics.len()
+    ))
+    .into())
+}
+
+pub fn show_command() -> Result<()> {
+    let env_manager = EnvManager::new()?;
+
+    println!("📄 .env 文件");
+    match (env_manager.source(), env_manager.origin()) {
+        (Some(path), Some(origin)) => println!("   {}（{}）", path.display(), origin),
+        (Some(path), None) => println!("   {}", path.display()),
+        (None, _) => println!("   未加载"),
+    }
+
+    let config_file = ConfigFile::load_default()?;
+    println!();
+    println!("🧾 config.toml");
+    match user_config_file_path().filter(|path| path.exists()) {
+        Some(path) => {
+            println!("   {}", path.display());
+            if let Some(region) = &config_file.region {
+                println!("   默认区域           {}", region);
+            }
+            if !config_file.psm.is_empty() {
+                println!("   默认 PSM           {}", config_file.psm.join(", "));
+            }
+            if let Some(format) = &config_file.format {
+                println!("   默认输出格式       {}", format);
+            }
+            if let Some(timeout) = &config_file.timeout {
+                println!("   超时               {}", timeout);
+            }
+            if let Some(retries) = config_file.retries {
+                println!("   重试次数           {}", retries);
+            }
+        }
+        None => println!("   未加载"),
+    }
+
+    if let Some((name, profile)) = active_profile() {
+        println!();
+        println!("👤 配置档: {}", name);
+        if let Some(region) = &profile.region {
+            println!("   默认区域           {}", region);
+        }
+        if !profile.psm.is_empty() {
+            println!("   默认 PSM           {}", profile.psm.join(", "));
+        }
+        if let Some(format) = &profile.format {
+            println!("   默认输出格式       {}", format);
+        }
+    }
+
+    println!();
+    println!("🔐 认证凭据");
+    let cas_vars = Region::all()
+        .into_iter()
+        .map(|region| region.cas_session_env_var())
+        .chain(std::iter::once("CAS_SESSION"));
+    for var in cas_vars {
+        let value = env_manager
+            .get_env(var)
+            .filter(|v| !v.is_empty())
+            .map(|v| if is_encrypted(&v) { "已加密".to_string() } else { mask_secret(&v) })
+            .unwrap_or_else(|| "未设置".to_string());
+        println!("   {:<18} {}", var, value);
+    }
+
+    println!();
+    println!("🌐 代理");
+    match proxy_setting() {
+        ProxySetting::Url(url) => println!("   {:<18} {}", "--proxy", mask_proxy_url(&url)),
+        ProxySetting::Disabled => println!("   {:<18} 已禁用", "--no-proxy"),
+        ProxySetting::Auto => {}
+    }
+    for region in Region::all() {
+        if let Some(proxy) = region_proxy(region) {
+            println!("   {:<18} {}", format!("区域 {}", region.as_str()), mask_proxy_url(&proxy));
+        }
+    }
+    if let Some(proxy) = active_profile().and_then(|(_, profile)| profile.proxy) {
+        println!("   {:<18} {}", "配置档", mask_proxy_url(&proxy));
+    }
+    for (key, value) in config_file.proxy.iter().flat_map(|proxy| proxy.entries()) {
+        let label = format!("config.toml {}", key.trim_start_matches("proxy").trim_start_matches('.'));
+        println!("   {:<18} {}", label.trim_end(), mask_proxy_url(value));
+    }
+    for var in [PROXY_ENV_VAR, "HTTPS_PROXY", "HTTP_PROXY", "NO_PROXY"] {
+        let value = env_manager
+            .get_env(var)
+            .filter(|v| !v.is_empty())
+            .map(|v| mask_proxy_url(&v))
+            .unwrap_or_else(|| "未设置".to_string());
+        println!("   {:<18} {}", var, value);
+    }
+
+    println!();
+    let (patterns, source) = load_filter_patterns(None)?;
+    println!("🔍 消息过滤规则（来源: {}）", source);
+    for pattern in &patterns {
+        println!("   {}", pattern);
+    }
+
+    println!();
+    println!("🌍 区域端点");
+    for region in Region::all() {
+        match get_region_config(region.as_str()) {
+            Some(config) if config.is_configured() => println!(
+                "   {:<5} {} (vregion: {})",
+                region.as_str(),
+                config.log_service_url,
+                config.vregion
+            ),
+            _ => println!("   {:<5} 未配置", region.as_str()),
+        }
+        println!("         认证: {}", AuthManager::auth_url_for(region));
+    }
+
+    Ok(())
+}
+
+/// 遮盖敏感值，仅保留首尾少量字符
+fn mask_secret(value: &str) -> String {
+    let chars: Vec<char> = value.chars().collect();
+    if chars.len() <= 8 {
+        return "*".repeat(chars.len());
+    }
+
+    let head: String = chars[..4].iter().collect();
+    let tail: String = chars[chars.len() - 4..].iter().collect();
+    format!("{}****{}（{} 字符）", head, tail, chars.len())
+}
+
+pub async fn init_command() -> Result<()> {
+    let env_path = user_env_path()
+        .ok_or_else(|| LogidError::InternalError("无法确定用户主目录".to_string()))?;
+
+    println!("🛠️  logid 配置向导");
+    println!("   配置将写入 {}", env_path.display());
+
+    if env_path.exists() && !confirm(&format!("{} 已存在，是否覆盖？(y/N)", env_path.display()))? {
+        println!("❌ 已取消");
+        return Ok(());
+    }
+
+    println!();
+    println!("请输入各区域的 CAS_SESSION（直接回车跳过该区域）:");
+    let mut sessions = Vec::new();
+    for region in Region::all() {
+        let value = prompt_secret(&format!(
+            "  {} ({}): ",
+            region.cas_session_env_var(),
+            region.display_name()
+        ))?;
+        if !value.is_empty() {
+            sessions.push((region, value));
+        }
+    }
+
+    let proxy = prompt("代理地址（如 http://127.0.0.1:7890，直接回车跳过）: ")?;
+    let logging = confirm("是否启用详细日志？(y/N)")?;
+
+    if !proxy.is_empty() && proxy_setting() == ProxySetting::Auto {
+        // 让随后的凭据验证也经过代理，命令行的 --proxy/--no-proxy 优先
+        set_proxy_setting(ProxySetting::Url(proxy.clone()));
+    }
+
+    if !sessions.is_empty() {
+        println!();
+        println!("🔍 验证认证凭据...");
+    }
+    for (region, session) in &sessions {
+        let result = match AuthManager::from_session(*region, session) {
+            Ok(manager) => manager.get_jwt_token(true).await.map(|_| ()),
+            Err(e) => Err(e),
+        };
+        match result {
+            Ok(()) => println!("   ✅ {}: 认证成功", region.as_str()),
+            Err(e) => println!("   ❌ {}: {}", region.as_str(), e),
+        }
+    }
+
+    let content = render_env_file(&sessions, Some(proxy.as_str()).filter(|p| !p.is_empty()), logging);
+    if let Some(parent) = env_path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let mut file = options.open(&env_path)?;
+    // mode 仅对新建文件生效，覆盖已有文件时同样收紧权限
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        file.set_permissions(fs::Permissions::from_mode(0o600))?;
+    }
+    file.write_all(content.as_bytes())?;
+
+    println!();
+    println!("✅ 配置已写入 {}", env_path.display());
+    if sessions.is_empty() {
+        println!("⚠️  未配置任何 CAS_SESSION，查询前请编辑该文件补充凭据");
+    }
+
+    Ok(())
+}
+
+/// 生成 .env 文件内容
+fn render_env_file(sessions: &[(Region, String)], proxy: Option<&str>, logging: bool) -> String {
+    let mut lines = vec!["# logid 配置文件（由 logid config init 生成）".to_string()];
+
+    for (region, session) in sessions {
+        lines.push(format!("{}={}", region.cas_session_env_var(), session));
+    }
+
+    if let Some(proxy) = proxy {
+        lines.push(format!("HTTPS_PROXY={}", proxy));
+    }
+
+    lines.push(format!("ENABLE_LOGGING={}", logging));
+    lines.push(String::new());
+    lines.join("\n")
+}
+
+pub fn prompt(message: &str) -> Result<String> {
+    print!("{}", message);
+    io::stdout().flush()?;
+
+    let mut input = String::new();
+    io::stdin().read_line(&mut input)?;
+    Ok(input.trim().to_string())
+}
+
+/// 读取敏感输入，不在终端回显
+pub fn prompt_secret(message: &str) -> Result<String> {
+    let input = rpassword::prompt_password(message)
+        .map_err(|e| LogidError::InternalError(format!("读取输入失败: {}", e)))?;
+    Ok(input.trim().to_string())
+}
+
+fn confirm(message: &str) -> Result<bool> {
+    println!("{}", message);
+    Ok(prompt("")?.eq_ignore_ascii_case("y"))
+}
@@ -0,0 +1,47 @@
+use anyhow::Result;
+use logid::output::{
+    diff_messages_from_json, write_text_to_file, DiffMessage, OutputFormat, ResultDiff,
+};
+
+pub async fn diff_command(
+    a: String,
+    b: String,
+    region: Option<&str>,
+    psm_list: &[String],
+    format: OutputFormat,
+    output: Option<&str>,
+) -> Result<()> {
+    let a_messages = load_diff_side(&a, region, psm_list).await?;
+    let b_messages = load_diff_side(&b, region, psm_list).await?;
+    let diff = ResultDiff::compare(a, &a_messages, b, &b_messages);
+    let text = match format {
+        OutputFormat::Json => serde_json::to_string_pretty(&diff)?,
+        OutputFormat::Table => diff.format_text(),
+    };
+    match output {
+        Some(path) => write_text_to_file(&text, path)?,
+        None => println!("{}", text),
+    }
+    Ok(())
+}
+
+/// 读取对比的一侧：指定区域时按 logid 查询，否则读取已保存的结果文件
+async fn load_diff_side(
+    source: &str,
+    region: Option<&str>,
+    psm_list: &[String],
+) -> Result<Vec<DiffMessage>> {
+    match region {
+        Some(region) => {
+            let (_, result) = crate::query_fresh_results(vec![source.to_string()], region, psm_list)
+                .await?
+                .remove(0);
+            Ok(DiffMessage::from_result(&result?))
+        }
+        None => {
+            let content = std::fs::read_to_string(source)
+                .map_err(|e| anyhow::anyhow!("读取结果文件 {} 失败: {}", source, e))?;
+            Ok(diff_messages_from_json(&content)?)
+        }
+    }
+}
@@ -0,0 +1,39 @@
+use anyhow::Result;
+use logid::output::{rows_from_json, write_export, ExportFormat, ExportRow};
+
+pub async fn export_command(
+    input: Option<String>,
+    logids: Vec<String>,
+    region: Option<String>,
+    psm_list: &[String],
+    format: ExportFormat,
+    output: Option<&str>,
+) -> Result<()> {
+    let rows = match input {
+        Some(path) => {
+            let content = std::fs::read_to_string(&path)
+                .map_err(|e| anyhow::anyhow!("读取结果文件 {} 失败: {}", path, e))?;
+            rows_from_json(&content)?
+        }
+        None => query_export_rows(logids, &region.unwrap_or_default(), psm_list).await?,
+    };
+    write_export(&rows, format, output)?;
+    eprintln!("✅ 已导出 {} 行", rows.len());
+    Ok(())
+}
+
+/// 查询 logid 并展开为导出行，查询失败的 logid 只给出警告
+async fn query_export_rows(
+    logids: Vec<String>,
+    region: &str,
+    psm_list: &[String],
+) -> Result<Vec<ExportRow>> {
+    let mut rows = Vec::new();
+    for (logid, result) in crate::query_fresh_results(logids, region, psm_list).await? {
+        match result {
+            Ok(log_details) => rows.extend(ExportRow::from_result(&log_details)),
+            Err(e) => eprintln!("⚠️  查询 {} 失败: {}", logid, e),
+        }
+    }
+    Ok(rows)
+}
@@ -0,0 +1,180 @@
+use anyhow::Result;
+use logid::config::{
+    apply_filters, compile_filters_lenient, configured_filter_sets, filter_config_path,
+    filter_profile_names, load_filter_patterns, load_filter_rules, load_key_filter,
+    user_filters_path, FilterConfig, FilterMode,
+};
+use logid::error::LogidError;
+use regex::Regex;
+use std::path::PathBuf;
+
+pub fn list_command() -> Result<()> {
+    let (patterns, mode, source) = load_filter_rules(None)?;
+    let compiled = compile_filters_lenient(&patterns);
+
+    println!("🔍 当前生效的过滤规则（来源: {}）", source);
+    if mode == FilterMode::Keep {
+        println!("  模式: {}", mode);
+    }
+    for (index, pattern) in patterns.iter().enumerate() {
+        let invalid = compiled.diagnostics.iter().any(|d| &d.pattern == pattern);
+        println!(
+            "  {:>3}. {}{}",
+            index + 1,
+            pattern,
+            if invalid { "  ⚠️ 无效，查询时跳过" } else { "" }
+        );
+    }
+
+    let keys = load_key_filter(None)?;
+    if !keys.keep_keys.is_empty() {
+        println!("  额外提取的键: {}", keys.keep_keys.join(", "));
+    }
+    if !keys.drop_keys.is_empty() {
+        println!("  丢弃的键: {}", keys.drop_keys.join(", "));
+    }
+
+    println!();
+    println!("📚 可用的过滤规则集（--filter-profile）: {}", filter_profile_names(None).join(", "));
+
+    Ok(())
+}
+
+pub fn add_command(pattern: &str) -> Result<()> {
+    Regex::new(pattern)
+        .map_err(|e| LogidError::FilterConfigError(format!("无效的正则表达式 '{}': {}", pattern, e)))?;
+
+    let (path, mut patterns) = load_user_patterns()?;
+    if patterns.iter().any(|p| p == pattern) {
+        println!("ℹ️  规则已存在: {}", pattern);
+        return Ok(());
+    }
+
+    patterns.push(pattern.to_string());
+    save_user_patterns(&path, patterns)?;
+
+    println!("✅ 已添加规则: {}", pattern);
+    println!("💾 规则文件: {}", path.display());
+    Ok(())
+}
+
+pub fn remove_command(pattern: &str) -> Result<()> {
+    let (path, mut patterns) = load_user_patterns()?;
+
+    // 优先按规则文本匹配，其次按 list 输出中的序号匹配
+    let position = patterns.iter().position(|p| p == pattern).or_else(|| {
+        pattern
+            .parse::<usize>()
+            .ok()
+            .filter(|n| (1..=patterns.len()).contains(n))
+            .map(|n| n - 1)
+    });
+
+    let Some(position) = position else {
+        return Err(LogidError::FilterConfigError(format!("未找到规则: {}", pattern)).into());
+    };
+
+    let removed = patterns.remove(position);
+    save_user_patterns(&path, patterns)?;
+
+    println!("🗑️  已移除规则: {}", removed);
+    println!("💾 规则文件: {}", path.display());
+    Ok(())
+}
+
+pub fn test_command(sample: &str) -> Result<()> {
+    let (patterns, mode, source) = load_filter_rules(None)?;
+    let compiled = compile_filters_lenient(&patterns);
+
+    println!("🔍 使用过滤规则（来源: {}，模式: {}）", source, mode);
+    let mut matched = 0;
+    for regex in &compiled.filters {
+        let hits: Vec<&str> = regex.find_iter(sample).map(|m| m.as_str()).collect();
+        if hits.is_empty() {
+            continue;
+        }
+        matched += 1;
+        println!("  ✂️  {}", regex.as_str());
+        for hit in hits {
+            println!("      - {}", hit);
+        }
+    }
+    if matched == 0 {
+        println!("  没有规则匹配该样例");
+    }
+
+    println!();
+    println!("过滤后:");
+    println!("{}", apply_filters(&compiled.filters, mode, sample));
+    Ok(())
+}
+
+pub fn validate_command(sample: Option<&str>) -> Result<()> {
+    let sets = configured_filter_sets(None)?;
+    let mut invalid = 0;
+
+    if sets.is_empty() {
+        println!("ℹ️  未配置过滤规则，使用内置默认规则");
+    }
+    for (source, patterns) in &sets {
+        let compiled = compile_filters_lenient(patterns);
+        invalid += compiled.diagnostics.len();
+        if compiled.diagnostics.is_empty() {
+            println!("✅ {}: {} 条规则", source, patterns.len());
+            continue;
+        }
+
+        println!(
+            "⚠️  {}: {} 条规则，{} 条无效（查询时将被跳过）",
+            source,
+            patterns.len(),
+            compiled.diagnostics.len()
+        );
+        for diagnostic in &compiled.diagnostics {
+            println!("   - {}", diagnostic.pattern);
+            if let Some(position) = diagnostic.position {
+                println!("     位置: 第 {} 个字符", position);
+            }
+            println!("     {}", diagnostic.error.replace('\n', "\n     "));
+        }
+    }
+
+    if let Some(sample) = sample {
+        let (patterns, mode, source) = load_filter_rules(None)?;
+        let compiled = compile_filters_lenient(&patterns);
+        println!();
+        println!("🔍 使用当前生效的规则（来源: {}，模式: {}）", source, mode);
+        println!("过滤前:");
+        println!("{}", sample);
+        println!("过滤后:");
+        println!("{}", apply_filters(&compiled.filters, mode, sample));
+    }
+
+    if invalid > 0 {
+        return Err(LogidError::FilterConfigError(format!("发现 {} 条无效的过滤规则", invalid)).into());
+    }
+    Ok(())
+}
+
+/// 读取要编辑的规则文件（`--filter-config`/`LOGID_FILTERS` 指定的文件，否则为用户级别文件）；
+/// 文件不存在时以当前生效的规则为起点
+fn load_user_patterns() -> Result<(PathBuf, Vec<String>)> {
+    let path = filter_config_path()
+        .or_else(user_filters_path)
+        .ok_or_else(|| LogidError::InternalError("无法确定用户主目录".to_string()))?;
+
+    let patterns = match FilterConfig::from_file(&path)? {
+        Some(config) => config.get_filters(),
+        None => load_filter_patterns(None)?.0,
+    };
+
+    Ok((path, patterns))
+}
+
+/// 保存默认规则，保留文件中已有的过滤规则集和键名筛选配置
+fn save_user_patterns(path: &PathBuf, patterns: Vec<String>) -> Result<()> {
+    let mut config = FilterConfig::from_file(path)?.unwrap_or_default();
+    config.msg_filters = Some(patterns);
+    config.save(path)?;
+    Ok(())
+}
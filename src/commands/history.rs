@@ -0,0 +1,54 @@
+use anyhow::Result;
+use logid::store::{HistoryEntry, HistoryStore};
+
+pub fn list_command(limit: usize) -> Result<()> {
+    let store = HistoryStore::open_default()?;
+    let entries = store.list()?;
+
+    if entries.is_empty() {
+        println!("暂无查询历史");
+        return Ok(());
+    }
+
+    let skip = entries.len().saturating_sub(limit);
+    print_entries(&entries[skip..]);
+    Ok(())
+}
+
+pub fn search_command(keyword: &str) -> Result<()> {
+    let store = HistoryStore::open_default()?;
+    let entries = store.search(keyword)?;
+
+    if entries.is_empty() {
+        println!("没有匹配 '{}' 的查询历史", keyword);
+        return Ok(());
+    }
+
+    print_entries(&entries);
+    Ok(())
+}
+
+fn print_entries(entries: &[(usize, HistoryEntry)]) {
+    for (number, entry) in entries {
+        let recorded_at = chrono::DateTime::parse_from_rfc3339(&entry.recorded_at)
+            .map(|time| time.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M").to_string())
+            .unwrap_or_else(|_| entry.recorded_at.clone());
+        let failed = if entry.failed > 0 {
+            format!("，失败 {} 个", entry.failed)
+        } else {
+            String::new()
+        };
+        println!(
+            "#{:<4} {} [{}] {} — {} 条消息{}",
+            number,
+            recorded_at,
+            entry.regions.join(","),
+            entry.logids.join(" "),
+            entry.result_count,
+            failed
+        );
+        if !entry.psm_list.is_empty() {
+            println!("      PSM: {}", entry.psm_list.join(", "));
+        }
+    }
+}
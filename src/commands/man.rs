@@ -0,0 +1,39 @@
+use anyhow::Result;
+use clap_mangen::Man;
+use std::path::Path;
+
+/// 生成 roff 格式的手册页
+///
+/// 指定目录时为主命令和每个子命令各生成一个页面（logid.1、logid-query.1 等），
+/// 否则将主命令的页面输出到标准输出。
+pub fn man_command(mut command: clap::Command, dir: Option<&str>) -> Result<()> {
+    let source = format!("logid {}", env!("CARGO_PKG_VERSION"));
+    match dir {
+        Some(dir) => {
+            std::fs::create_dir_all(dir)
+                .map_err(|e| anyhow::anyhow!("创建目录 {} 失败: {}", dir, e))?;
+            // 构建后子命令才有 logid-query 这样的完整名称
+            command = command.disable_help_subcommand(true);
+            command.build();
+            let count = generate_pages(&command, Path::new(dir), &source)?;
+            eprintln!("✅ 已生成 {} 个手册页: {}", count, dir);
+        }
+        None => Man::new(command)
+            .source(source)
+            .render(&mut std::io::stdout().lock())?,
+    }
+    Ok(())
+}
+
+/// 递归生成命令及其未隐藏的子命令的手册页，返回生成的页面数量
+fn generate_pages(command: &clap::Command, dir: &Path, source: &str) -> Result<usize> {
+    let mut count = 0;
+    for subcommand in command.get_subcommands().filter(|sub| !sub.is_hide_set()) {
+        count += generate_pages(subcommand, dir, source)?;
+    }
+    Man::new(command.clone())
+        .source(source)
+        .generate_to(dir)
+        .map_err(|e| anyhow::anyhow!("写入手册页失败: {}", e))?;
+    Ok(count + 1)
+}
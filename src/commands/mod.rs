@@ -1 +1,129 @@
-pub mod update;
\ No newline at end of file
+pub mod annotate;
+pub mod auth;
+pub mod batch;
+pub mod completions;
+pub mod config;
+pub mod diff;
+pub mod export;
+pub mod filters;
+pub mod history;
+pub mod man;
+pub mod queue;
+pub mod regions;
+pub mod stats;
+pub mod timeline;
+#[cfg(feature = "tui")]
+pub mod tui;
+pub mod update;
+
+use anyhow::Result;
+use logid::log_query::{parse_interval, parse_logid_list, LogQueryClient, MultiRegionLogQuery};
+use logid::retry::{RetryPolicy, DEFAULT_RETRIES};
+use std::io::Read;
+use std::time::Duration;
+
+/// 从系统剪贴板读取 logid，去掉首尾空白并校验格式
+#[cfg(feature = "clipboard")]
+pub fn read_clipboard_logid() -> Result<String> {
+    let text = arboard::Clipboard::new()
+        .and_then(|mut clipboard| clipboard.get_text())
+        .map_err(|e| anyhow::anyhow!("读取剪贴板失败: {}", e))?;
+    Ok(logid::log_query::normalize_logid(&text)?)
+}
+
+/// 未启用 clipboard 特性时无法读取剪贴板
+#[cfg(not(feature = "clipboard"))]
+pub fn read_clipboard_logid() -> Result<String> {
+    Err(anyhow::anyhow!("当前构建未启用 clipboard 特性，无法读取剪贴板"))
+}
+
+/// 在默认浏览器中打开链接
+pub fn open_in_browser(url: &str) -> Result<()> {
+    let mut command = if cfg!(target_os = "macos") {
+        std::process::Command::new("open")
+    } else if cfg!(target_os = "windows") {
+        let mut command = std::process::Command::new("cmd");
+        command.args(["/C", "start", ""]);
+        command
+    } else {
+        std::process::Command::new("xdg-open")
+    };
+    let status = command
+        .arg(url)
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .status()
+        .map_err(|e| anyhow::anyhow!("启动浏览器失败: {}", e))?;
+    if !status.success() {
+        return Err(anyhow::anyhow!("启动浏览器失败: {}", status));
+    }
+    Ok(())
+}
+
+/// 从标准输入读取 logid 列表（每行一个或以空白分隔，支持 # 注释）
+pub fn read_stdin_logids() -> Result<Vec<String>> {
+    let mut input = String::new();
+    std::io::stdin()
+        .read_to_string(&mut input)
+        .map_err(|e| anyhow::anyhow!("读取标准输入失败: {}", e))?;
+    Ok(parse_logid_list(&input))
+}
+
+/// 将参数中的 `-` 替换为从标准输入读取的 logid，结果按出现顺序去重
+pub fn expand_stdin_logids(logids: Vec<String>) -> Result<Vec<String>> {
+    if !logids.iter().any(|logid| logid == "-") {
+        return Ok(logids);
+    }
+
+    let stdin_logids = read_stdin_logids()?;
+    if stdin_logids.is_empty() {
+        return Err(anyhow::anyhow!("标准输入中没有 logid"));
+    }
+
+    let mut expanded = Vec::new();
+    for logid in logids {
+        let items = if logid == "-" {
+            stdin_logids.clone()
+        } else {
+            vec![logid]
+        };
+        for item in items {
+            if !expanded.contains(&item) {
+                expanded.push(item);
+            }
+        }
+    }
+    Ok(expanded)
+}
+
+/// 查询类命令共用的 HTTP 请求参数
+#[derive(clap::Args, Debug, Clone)]
+pub struct RequestArgs {
+    /// 单个 HTTP 请求（认证和查询）的超时时间，如 60s、2m（不带单位时按秒计算）
+    #[arg(
+        long,
+        value_name = "DURATION",
+        default_value = "30s",
+        value_parser = |v: &str| parse_interval(v).map_err(|e| e.to_string())
+    )]
+    pub timeout: Duration,
+    /// 超时、连接失败、5xx 或 429 时的最大重试次数（指数退避），0 表示不重试
+    #[arg(long, value_name = "N", default_value_t = DEFAULT_RETRIES)]
+    pub retries: u32,
+}
+
+impl RequestArgs {
+    /// 将超时和重试设置应用到单区域客户端
+    pub fn apply(&self, client: LogQueryClient) -> LogQueryClient {
+        client
+            .with_timeout(self.timeout)
+            .with_retry_policy(RetryPolicy::new(self.retries))
+    }
+
+    /// 将超时和重试设置应用到多区域查询器
+    pub fn apply_multi(&self, query: MultiRegionLogQuery) -> MultiRegionLogQuery {
+        query
+            .with_timeout(self.timeout)
+            .with_retry_policy(RetryPolicy::new(self.retries))
+    }
+}
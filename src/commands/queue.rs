@@ -0,0 +1,167 @@
+use anyhow::Result;
+use logid::log_query::{LogQueryClient, QueryContext};
+use logid::output::{write_to_file, ExportManifest, OutputConfig, QueryParameters};
+use logid::store::{QueueEntry, QueueStatus, QueueStore};
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::time::Duration;
+
+pub fn add_command(logids: &[String], region: &str, psm_list: &[String]) -> Result<()> {
+    let store = QueueStore::open_default()?;
+
+    for logid in logids {
+        let entry = store.enqueue(logid, region, psm_list)?;
+        println!("📥 已加入队列 #{}: {} ({})", entry.id, entry.logid, entry.region);
+    }
+    println!("💡 运行 'logid queue run' 执行排队的查询");
+
+    Ok(())
+}
+
+pub fn list_command() -> Result<()> {
+    let store = QueueStore::open_default()?;
+    let entries = store.load()?;
+
+    if entries.is_empty() {
+        println!("队列为空");
+        return Ok(());
+    }
+
+    for entry in &entries {
+        let status = match entry.status {
+            QueueStatus::Pending => "⏳ 等待",
+            QueueStatus::Done => "✅ 完成",
+            QueueStatus::Failed => "❌ 失败",
+        };
+        println!(
+            "#{:<4} {} {} [{}] 尝试 {} 次",
+            entry.id, status, entry.logid, entry.region, entry.attempts
+        );
+        if let Some(path) = &entry.result_path {
+            println!("      结果: {}", path);
+        }
+        if let Some(error) = entry.last_error.as_ref().filter(|_| entry.status != QueueStatus::Done) {
+            println!("      错误: {}", error);
+        }
+    }
+
+    Ok(())
+}
+
+pub fn clear_command() -> Result<()> {
+    let store = QueueStore::open_default()?;
+    let removed = store.clear_done()?;
+    println!("🧹 已移除 {} 个已完成的条目", removed);
+    Ok(())
+}
+
+pub async fn run_command(max_attempts: u32, rounds: u32, interval_secs: u64) -> Result<()> {
+    let store = QueueStore::open_default()?;
+    let results_dir = store.results_dir();
+
+    for round in 1..=rounds.max(1) {
+        let mut entries = store.load()?;
+
+        // 按区域分组待查询的条目，每个区域共用一个客户端
+        let mut by_region: BTreeMap<String, Vec<usize>> = BTreeMap::new();
+        for (index, entry) in entries.iter().enumerate() {
+            if entry.status == QueueStatus::Pending {
+                by_region.entry(entry.region.clone()).or_default().push(index);
+            }
+        }
+
+        if by_region.is_empty() {
+            break;
+        }
+
+        println!("🔄 第 {} 轮: {} 个待查询", round, by_region.values().map(Vec::len).sum::<usize>());
+
+        for (region, indices) in by_region {
+            let client = match LogQueryClient::for_region(&region).await {
+                Ok(client) => client,
+                Err(e) => {
+                    println!("⚠️  {} 区域暂不可用: {}", region, e);
+                    // 区域客户端无法创建时请求没有发出，不计入尝试次数
+                    for index in indices {
+                        entries[index].last_error = Some(e.to_string());
+                    }
+                    store.save(&entries)?;
+                    continue;
+                }
+            };
+
+            for index in indices {
+                let entry = &mut entries[index];
+                match client.get_log_details(&entry.logid, &entry.psm_list).await {
+                    Ok(mut log_details) => {
+                        log_details.context = Some(QueryContext {
+                            psm_list: entry.psm_list.clone(),
+                            ..QueryContext::new(vec![entry.logid.clone()], vec![entry.region.clone()])
+                        });
+                        let path = results_dir.join(format!("{}-{}.json", entry.id, entry.logid));
+                        let path = path.to_string_lossy().to_string();
+                        write_to_file(&log_details, &path, OutputConfig::new())?;
+                        entry.attempts += 1;
+                        entry.status = QueueStatus::Done;
+                        entry.last_error = None;
+                        println!("✅ #{} {}: {} 条消息 → {}", entry.id, entry.logid, log_details.messages.len(), path);
+                        entry.result_path = Some(path);
+                    }
+                    Err(e) => {
+                        entry.record_failure(&e, max_attempts);
+                        println!("⚠️  #{} {}: {}", entry.id, entry.logid, e);
+                    }
+                }
+                // 每个条目完成后立即保存，中断时不会丢失进度
+                store.save(&entries)?;
+            }
+        }
+
+        let pending = entries.iter().filter(|e| e.status == QueueStatus::Pending).count();
+        if pending == 0 {
+            break;
+        }
+        if round < rounds {
+            println!("⏳ 仍有 {} 个待查询，{} 秒后重试", pending, interval_secs);
+            tokio::time::sleep(Duration::from_secs(interval_secs)).await;
+        }
+    }
+
+    let entries = store.load()?;
+    write_results_manifest(&entries, &results_dir)?;
+
+    let count = |status| entries.iter().filter(|e| e.status == status).count();
+    println!(
+        "📋 队列状态: 完成 {}，等待 {}，失败 {}",
+        count(QueueStatus::Done),
+        count(QueueStatus::Pending),
+        count(QueueStatus::Failed)
+    );
+
+    Ok(())
+}
+
+/// 为结果目录中所有已完成条目的结果文件写入导出清单
+fn write_results_manifest(entries: &[QueueEntry], results_dir: &Path) -> Result<()> {
+    let mut manifest = ExportManifest::new(None);
+    for entry in entries.iter().filter(|e| e.status == QueueStatus::Done) {
+        let Some(path) = entry.result_path.as_deref().map(Path::new) else {
+            continue;
+        };
+        if !path.exists() {
+            continue;
+        }
+        let query = QueryParameters {
+            logids: vec![entry.logid.clone()],
+            region: entry.region.clone(),
+            psm_list: entry.psm_list.clone(),
+        };
+        manifest.add_file(results_dir, path, Some(query))?;
+    }
+
+    if !manifest.artifacts.is_empty() {
+        let path = manifest.write(results_dir)?;
+        println!("🧾 结果清单: {}", path.display());
+    }
+    Ok(())
+}
@@ -0,0 +1,64 @@
+use anyhow::Result;
+use logid::config::{get_region_config, EnvManager, Region, FALLBACK_CAS_SESSION_VAR};
+
+pub fn regions_command() -> Result<()> {
+    let env_manager = EnvManager::new()?;
+
+    println!("🌍 支持的区域");
+    for region in Region::all() {
+        let config = get_region_config(region.as_str());
+        let configured = config.as_ref().is_some_and(|c| c.is_configured());
+        let environments: Vec<String> = config
+            .iter()
+            .flat_map(|c| c.environments.iter())
+            .map(|(env, endpoint)| format!("{} ({})", env, endpoint.log_service_url))
+            .collect();
+
+        println!();
+        println!("   {} — {}", region.as_str(), region.display_name());
+        let aliases = region.aliases();
+        if !aliases.is_empty() {
+            println!("     别名:     {}", aliases.join(", "));
+        }
+        match config.filter(|c| c.is_configured()) {
+            Some(config) => {
+                println!("     端点:     ✓ 已配置");
+                println!("     日志服务: {}", config.log_service_url);
+                println!("     vregion:  {}", config.vregion);
+                if !config.zones.is_empty() {
+                    println!("     zones:    {}", config.zones.join(", "));
+                }
+                if !config.default_psms.is_empty() {
+                    println!("     默认 PSM: {}", config.default_psms.join(", "));
+                }
+            }
+            None => println!(
+                "     端点:     ✗ 未配置（可设置 {} 和 {}，或在 ~/.config/logid/regions.json 中提供）",
+                region.log_service_url_env_var(),
+                region.vregion_env_var()
+            ),
+        }
+        for environment in &environments {
+            println!("     环境:     {}", environment);
+        }
+
+        // 与 EnvManager::get_cas_session 一致：区域变量优先，其次旧版本的变量名，最后回退到 CAS_SESSION
+        let session_var = env_manager.cas_session_var(region);
+        let has_credentials = session_var.is_some();
+        let credentials = match session_var {
+            Some(FALLBACK_CAS_SESSION_VAR) => "✓ CAS_SESSION（回退）".to_string(),
+            Some(var) => format!("✓ {}", var),
+            None => format!("✗ 未设置 {} 或 CAS_SESSION", region.cas_session_env_var()),
+        };
+        println!("     凭据:     {}", credentials);
+
+        let status = match (configured, has_credentials) {
+            (true, true) => "✓ 可查询",
+            (false, _) => "✗ 不可查询：日志服务端点未配置",
+            (true, false) => "✗ 不可查询：缺少认证凭据",
+        };
+        println!("     状态:     {}", status);
+    }
+
+    Ok(())
+}
@@ -0,0 +1,29 @@
+use anyhow::Result;
+use logid::output::OutputFormat;
+use logid::{ResultStats, StatsOptions};
+use std::time::Duration;
+
+pub async fn stats_command(
+    logids: Vec<String>,
+    region: &str,
+    psm_list: &[String],
+    bucket: Option<Duration>,
+    top: usize,
+    format: OutputFormat,
+) -> Result<()> {
+    let results = crate::query_fresh_results(logids, region, psm_list)
+        .await?
+        .into_iter()
+        .map(|(_, result)| result)
+        .collect::<Result<Vec<_>>>()?;
+    let mut options = StatsOptions::default().with_top_errors(top);
+    if let Some(width) = bucket {
+        options = options.with_bucket_width(width);
+    }
+    let stats = ResultStats::from_results(&results, &options);
+    match format {
+        OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&stats)?),
+        OutputFormat::Table => println!("{}", stats.format_text()),
+    }
+    Ok(())
+}
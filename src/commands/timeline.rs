@@ -0,0 +1,43 @@
+use anyhow::Result;
+use futures::future::join_all;
+use logid::config::ConfigFile;
+use logid::log_query::LogQueryClient;
+use logid::output::{format_timeline, ColorMode};
+
+pub async fn timeline_command(
+    logids: &[String],
+    region: &str,
+    psm_list: &[String],
+    no_color: bool,
+    raw: bool,
+) -> Result<()> {
+    let client = LogQueryClient::for_region(region).await?.with_raw(raw);
+
+    let results = join_all(
+        logids
+            .iter()
+            .map(|logid| client.get_log_details(logid, psm_list)),
+    )
+    .await;
+
+    let mut details = Vec::new();
+    for (logid, result) in logids.iter().zip(results) {
+        match result {
+            Ok(result) => details.push(result),
+            Err(e) => eprintln!("⚠️  查询 {} 失败: {}", logid, e),
+        }
+    }
+
+    if details.is_empty() {
+        return Err(anyhow::anyhow!("所有 logid 均查询失败"));
+    }
+
+    let color = if no_color {
+        ColorMode::Never
+    } else {
+        ConfigFile::load_default()?.output.color.unwrap_or_default()
+    };
+    println!("{}", format_timeline(&details, color.enabled()));
+
+    Ok(())
+}
@@ -0,0 +1,14 @@
+use anyhow::Result;
+use logid::log_query::LogQueryClient;
+
+pub async fn tui_command(logid: &str, region: &str, psm_list: &[String]) -> Result<()> {
+    let client = LogQueryClient::for_region(region).await?;
+    let result = client.get_log_details(logid, psm_list).await?;
+
+    if result.messages.is_empty() {
+        return Err(anyhow::anyhow!("logid {} 没有查询到消息", logid));
+    }
+
+    logid::tui::run(result)?;
+    Ok(())
+}
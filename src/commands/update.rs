@@ -68,12 +68,19 @@ pub async fn update_command(check_only: bool, force: bool) -> Result<()> {
     Ok(())
 }
 
-async fn get_latest_release() -> Result<GitHubRelease> {
-    let client = reqwest::Client::builder()
-        .user_agent("logid-update")
-        .timeout(std::time::Duration::from_secs(30))
+/// 创建更新用的 HTTP 客户端，与查询使用相同的代理设置
+fn http_client(timeout: Option<std::time::Duration>) -> Result<reqwest::Client> {
+    let mut builder = reqwest::Client::builder().user_agent("logid-update");
+    if let Some(timeout) = timeout {
+        builder = builder.timeout(timeout);
+    }
+    logid::config::apply_proxy(builder, None)
         .build()
-        .map_err(|e| anyhow::anyhow!("创建 HTTP 客户端失败: {}", e))?;
+        .map_err(|e| anyhow::anyhow!("创建 HTTP 客户端失败: {}", e))
+}
+
+async fn get_latest_release() -> Result<GitHubRelease> {
+    let client = http_client(Some(std::time::Duration::from_secs(30)))?;
 
     let url = "https://api.github.com/repos/DreamCats/logid/releases/latest";
 
@@ -169,7 +176,7 @@ async fn perform_update(release: &GitHubRelease) -> Result<()> {
 }
 
 async fn download_file(asset: &GitHubAsset) -> Result<PathBuf> {
-    let client = reqwest::Client::new();
+    let client = http_client(None)?;
     let response = client
         .get(&asset.browser_download_url)
         .send()
@@ -259,7 +266,7 @@ async fn verify_checksum(download_path: &Path, release: &GitHubRelease) -> Resul
         .find(|asset| asset.name == "SHA256SUMS")
         .ok_or_else(|| anyhow::anyhow!("找不到 SHA256SUMS 文件"))?;
 
-    let client = reqwest::Client::new();
+    let client = http_client(None)?;
     let response = client
         .get(&checksum_asset.browser_download_url)
         .send()
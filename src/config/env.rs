@@ -1,23 +1,169 @@
 //! 环境变量管理模块
 
-use crate::conditional_info;
+use crate::config::profile::{active_profile, Profile};
+use crate::config::secret::{decrypt_secret, is_encrypted, passphrase};
 use crate::config::Region;
 use crate::error::LogidError;
 use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
 
-/// 用户配置目录名称
+/// 用户配置目录名称（相对于主目录）
 const USER_CONFIG_DIR: &str = ".config/logid";
+/// 平台配置目录下的应用目录名称
+const APP_CONFIG_DIR_NAME: &str = "logid";
 /// 环境变量文件名
-const ENV_FILE_NAME: &str = ".env";
+pub(super) const ENV_FILE_NAME: &str = ".env";
+
+/// 获取用户级别配置目录
+///
+/// 设置了 `XDG_CONFIG_HOME` 时为 `$XDG_CONFIG_HOME/logid`，Windows 上为 `%APPDATA%\logid`，
+/// 否则为 `~/.config/logid`。为兼容旧版本，首选目录不存在而 `~/.config/logid` 已存在时
+/// 继续使用 `~/.config/logid`。
+pub fn user_config_dir() -> Option<PathBuf> {
+    resolve_config_dir(platform_config_dir(), dirs::home_dir())
+}
+
+/// 平台约定的配置目录：Windows 上为 `%APPDATA%`，其他平台为 `XDG_CONFIG_HOME`（仅接受绝对路径）
+fn platform_config_dir() -> Option<PathBuf> {
+    if cfg!(windows) {
+        return dirs::config_dir();
+    }
+    std::env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .filter(|dir| dir.is_absolute())
+}
+
+/// 在平台配置目录和旧的 `~/.config/logid` 之间选择配置目录
+pub(crate) fn resolve_config_dir(platform_dir: Option<PathBuf>, home: Option<PathBuf>) -> Option<PathBuf> {
+    let legacy = home.map(|home| home.join(USER_CONFIG_DIR));
+    match (platform_dir.map(|dir| dir.join(APP_CONFIG_DIR_NAME)), legacy) {
+        (Some(preferred), Some(legacy)) if !preferred.exists() && legacy.exists() => Some(legacy),
+        (Some(preferred), _) => Some(preferred),
+        (None, legacy) => legacy,
+    }
+}
+
+/// 命令行 `--env-file` 指定的 .env 文件
+static ENV_FILE_PATH: RwLock<Option<PathBuf>> = RwLock::new(None);
+
+/// 获取用户级别 .env 文件路径 (~/.config/logid/.env)
+pub fn user_env_path() -> Option<PathBuf> {
+    user_config_dir().map(|dir| dir.join(ENV_FILE_NAME))
+}
+
+/// 设置进程内使用的 .env 文件，优先于可执行文件目录和用户配置目录中的 .env
+pub fn set_env_file_path(path: Option<PathBuf>) {
+    *ENV_FILE_PATH.write().unwrap_or_else(|e| e.into_inner()) = path;
+}
+
+/// 命令行 `--env-file` 指定的 .env 文件
+pub fn env_file_path() -> Option<PathBuf> {
+    ENV_FILE_PATH.read().unwrap_or_else(|e| e.into_inner()).clone()
+}
+
+/// 加载的 .env 文件的来源
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EnvFileOrigin {
+    /// 命令行 `--env-file` 指定
+    Explicit,
+    /// 可执行文件所在目录
+    ExeDir,
+    /// 用户配置目录 (~/.config/logid)
+    UserConfig,
+}
+
+impl std::fmt::Display for EnvFileOrigin {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Explicit => write!(f, "--env-file 指定"),
+            Self::ExeDir => write!(f, "可执行文件所在目录"),
+            Self::UserConfig => write!(f, "用户配置目录"),
+        }
+    }
+}
+
+/// 在 .env 文件中设置变量
+///
+/// 已有同名变量时替换该行，否则追加到文件末尾；其他行（包括注释）保持不变。
+/// 文件不存在时创建文件及其父目录。文件包含凭据，以 0600 权限写入临时文件后替换原文件。
+pub fn upsert_env_var(path: &Path, key: &str, value: &str) -> Result<(), LogidError> {
+    let content = match std::fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+        Err(e) => return Err(e.into()),
+    };
+
+    let assignment = format!("{}={}", key, value);
+    let mut replaced = false;
+    let mut lines: Vec<String> = content
+        .lines()
+        .map(|line| {
+            let name = line.trim_start().trim_start_matches("export ").split('=').next().unwrap_or("");
+            if !replaced && line.contains('=') && name.trim() == key {
+                replaced = true;
+                assignment.clone()
+            } else {
+                line.to_string()
+            }
+        })
+        .collect();
+    if !replaced {
+        lines.push(assignment);
+    }
+
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    write_private_atomic(path, &(lines.join("\n") + "\n"))
+}
+
+/// 以 0600 权限写入临时文件，再重命名替换目标文件，避免中断时损坏原文件
+fn write_private_atomic(path: &Path, content: &str) -> Result<(), LogidError> {
+    use std::io::Write;
+
+    let file_name = path.file_name().unwrap_or_default().to_string_lossy();
+    let tmp_path = path.with_file_name(format!("{}.tmp", file_name));
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let mut file = options.open(&tmp_path)?;
+    // mode 仅对新建文件生效，残留的临时文件同样收紧权限
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        file.set_permissions(std::fs::Permissions::from_mode(0o600))?;
+    }
+    file.write_all(content.as_bytes())?;
+    file.sync_all()?;
+    std::fs::rename(&tmp_path, path)?;
+    Ok(())
+}
+
+/// 通用的 CAS_SESSION 变量，区域变量未设置时使用
+pub const FALLBACK_CAS_SESSION_VAR: &str = "CAS_SESSION";
 
 /// 环境变量管理器
 #[derive(Debug)]
 pub struct EnvManager {
     env_vars: HashMap<String, String>,
+    /// 实际加载的 .env 文件路径
+    source: Option<PathBuf>,
+    /// 加载的 .env 文件的来源
+    origin: Option<EnvFileOrigin>,
+    /// 由配置档提供的 CAS_SESSION 变量
+    profile_vars: Vec<String>,
 }
 
 impl EnvManager {
     /// 创建环境变量管理器，自动加载 .env 文件
+    ///
+    /// 指定了 `--env-file` 时只加载该文件，文件不存在或格式错误时返回错误；
+    /// 否则依次查找可执行文件所在目录和用户配置目录，使用第一个存在的文件。
     pub fn new() -> Result<Self, LogidError> {
         // 获取可执行文件所在目录
         let exe_path = std::env::current_exe()
@@ -29,18 +175,30 @@ impl EnvManager {
         let exe_env_path = exe_dir.join(ENV_FILE_NAME);
 
         // 构建用户级别目录的 .env 文件路径 (~/.config/logid/.env)
-        let user_env_path = dirs::home_dir()
-            .map(|home| home.join(USER_CONFIG_DIR).join(ENV_FILE_NAME))
+        let user_env_path = user_env_path()
             .ok_or_else(|| LogidError::InternalError("无法确定用户主目录".to_string()))?;
 
-        let mut env_loaded = false;
+        let mut source = None;
+        let mut origin = None;
 
-        // 优先尝试加载可执行文件同级目录的 .env 文件
-        if exe_env_path.exists() {
+        // 命令行指定的 .env 文件优先
+        if let Some(path) = env_file_path() {
+            if !path.is_file() {
+                return Err(LogidError::EnvFileNotFound(path.display().to_string()));
+            }
+            dotenvy::from_path(&path)?;
+            conditional_info!("成功加载指定的 .env 文件: {}", path.display());
+            source = Some(path);
+            origin = Some(EnvFileOrigin::Explicit);
+        }
+
+        // 其次尝试加载可执行文件同级目录的 .env 文件
+        if source.is_none() && exe_env_path.exists() {
             match dotenvy::from_path(&exe_env_path) {
                 Ok(_) => {
                     conditional_info!("成功加载 .env 文件: {}", exe_env_path.display());
-                    env_loaded = true;
+                    source = Some(exe_env_path.clone());
+                    origin = Some(EnvFileOrigin::ExeDir);
                 }
                 Err(e) => {
                     conditional_info!("加载可执行文件同级目录的 .env 文件失败: {} - {}", exe_env_path.display(), e);
@@ -49,11 +207,12 @@ impl EnvManager {
         }
 
         // 如果可执行文件目录没有 .env 文件，尝试用户级别目录
-        if !env_loaded && user_env_path.exists() {
+        if source.is_none() && user_env_path.exists() {
             match dotenvy::from_path(&user_env_path) {
                 Ok(_) => {
                     conditional_info!("成功加载用户级别 .env 文件: {}", user_env_path.display());
-                    env_loaded = true;
+                    source = Some(user_env_path.clone());
+                    origin = Some(EnvFileOrigin::UserConfig);
                 }
                 Err(e) => {
                     conditional_info!("加载用户级别 .env 文件失败: {} - {}", user_env_path.display(), e);
@@ -61,15 +220,17 @@ impl EnvManager {
             }
         }
 
-        // 如果两个位置都没有找到 .env 文件，显示友好的警告和设置指导
-        if !env_loaded {
+        // 如果两个位置都没有找到 .env 文件（且配置档中也没有凭据），显示友好的警告和设置指导
+        let profile = active_profile().map(|(_, profile)| profile);
+        let profile_has_sessions = profile.as_ref().is_some_and(|p| !p.cas_sessions.is_empty());
+        if source.is_none() && !profile_has_sessions {
             eprintln!("⚠️  未找到 .env 配置文件");
             eprintln!("   搜索位置:");
             eprintln!("   1. {}", exe_env_path.display());
             eprintln!("   2. {}", user_env_path.display());
             eprintln!("   请在以上任一位置创建 .env 文件并配置以下内容：");
             eprintln!("   CAS_SESSION_US=your_us_session_cookie_here");
-            eprintln!("   CAS_SESSION_I18n=your_i18n_session_cookie_here");
+            eprintln!("   CAS_SESSION_I18N=your_i18n_session_cookie_here");
             eprintln!("   ENABLE_LOGGING=false");
             eprintln!("   详细配置请参考项目文档");
         }
@@ -81,38 +242,85 @@ impl EnvManager {
             env_vars.insert(key, value);
         }
 
-        Ok(Self { env_vars })
+        let manager = Self {
+            env_vars,
+            source,
+            origin,
+            profile_vars: Vec::new(),
+        };
+        Ok(match profile {
+            Some(profile) => manager.with_profile(&profile),
+            None => manager,
+        })
     }
 
-    /// 获取区域的 CAS_SESSION 值
-    /// 优先使用区域特定的环境变量，然后回退到通用的 CAS_SESSION
-    pub fn get_cas_session(&self, region: Region) -> Result<String, LogidError> {
-        let region_var = region.cas_session_env_var();
-
-        // 优先使用区域特定的环境变量
-        if let Some(session) = self.env_vars.get(region_var) {
-            if !session.is_empty() {
-                conditional_info!("使用区域特定的 CAS_SESSION: {}", region_var);
-                return Ok(session.clone());
+    /// 使用配置档中的凭据和代理覆盖环境变量
+    pub fn with_profile(mut self, profile: &Profile) -> Self {
+        for (var, session) in profile.cas_session_vars() {
+            self.env_vars.insert(var.to_string(), session);
+            self.profile_vars.push(var.to_string());
+        }
+        if let Some(proxy) = profile.proxy.as_ref().filter(|proxy| !proxy.is_empty()) {
+            for var in ["HTTPS_PROXY", "HTTP_PROXY"] {
+                self.env_vars.insert(var.to_string(), proxy.clone());
             }
         }
+        self
+    }
+
+    /// 获取实际加载的 .env 文件路径，未加载时返回 None
+    pub fn source(&self) -> Option<&Path> {
+        self.source.as_deref()
+    }
 
-        // 回退到通用的 CAS_SESSION
-        if let Some(session) = self.env_vars.get("CAS_SESSION") {
-            if !session.is_empty() {
-                conditional_info!("使用通用的 CAS_SESSION (回退)");
-                return Ok(session.clone());
+    /// 获取加载的 .env 文件的来源，未加载时返回 None
+    pub fn origin(&self) -> Option<EnvFileOrigin> {
+        self.origin
+    }
+
+    /// 获取区域的 CAS_SESSION 值
+    /// 优先使用区域特定的环境变量，然后回退到通用的 CAS_SESSION；
+    /// `auth encrypt` 加密过的值在这里用口令解密
+    pub fn get_cas_session(&self, region: Region) -> Result<String, LogidError> {
+        match self.cas_session_var(region) {
+            Some(var) => {
+                if var == FALLBACK_CAS_SESSION_VAR {
+                    conditional_info!("使用通用的 CAS_SESSION (回退)");
+                } else {
+                    conditional_info!("使用区域特定的 CAS_SESSION: {}", var);
+                }
+                let session = &self.env_vars[var];
+                if is_encrypted(session) {
+                    return decrypt_secret(session, &passphrase(false)?);
+                }
+                Ok(session.clone())
             }
+            None => Err(LogidError::MissingCredentials(format!(
+                "未找到 {} 或 CAS_SESSION 环境变量",
+                region.cas_session_env_var()
+            ))),
         }
+    }
+
+    /// 提供区域 CAS_SESSION 的变量名称：区域变量，其次旧版本的区域变量名，最后是通用的 CAS_SESSION
+    pub fn cas_session_var(&self, region: Region) -> Option<&'static str> {
+        let candidates = [
+            Some(region.cas_session_env_var()),
+            region.legacy_cas_session_env_var(),
+            Some(FALLBACK_CAS_SESSION_VAR),
+        ];
+        candidates
+            .into_iter()
+            .flatten()
+            .find(|var| self.env_vars.get(*var).is_some_and(|session| !session.is_empty()))
+    }
 
-        Err(LogidError::MissingCredentials(format!(
-            "未找到 {} 或 CAS_SESSION 环境变量",
-            region_var
-        )))
+    /// 变量是否由当前配置档提供（而不是 .env 文件或进程环境变量）
+    pub fn is_profile_var(&self, var: &str) -> bool {
+        self.profile_vars.iter().any(|profile_var| profile_var == var)
     }
 
     /// 获取任意环境变量
-    #[allow(dead_code)]
     pub fn get_env(&self, key: &str) -> Option<String> {
         self.env_vars.get(key).cloned()
     }
@@ -0,0 +1,410 @@
+//! 结构化配置文件模块
+//!
+//! `~/.config/logid/config.toml` 集中保存默认区域、默认 PSM、输出格式、超时、代理、
+//! 过滤规则集和自定义区域端点：
+//!
+//! ```toml
+//! region = "us"
+//! psm = ["pay.api"]
+//! format = "table"
+//! timeout = "60s"
+//! retries = 3
+//! scan_span = 30
+//! max_response_size = 128
+//! proxy = "http://127.0.0.1:7890"
+//! filter_profile = "quiet"
+//!
+//! [filter_profiles]
+//! quiet = ["_compliance_nlp_log", '(?m)"LogID":\s*"[^"]*"']
+//! errors = { mode = "keep", patterns = ["error|panic|status="] }
+//!
+//! [output]
+//! show_tag_infos = true
+//! color = "always"
+//!
+//! [region_aliases]
+//! dc = "cn"
+//!
+//! [regions.cn]
+//! log_service_url = "https://example.com/streamlog/platform/microservice/v1/query/trace"
+//! zones = ["CN-North", "CN-East"]
+//! ```
+//!
+//! 与其他配置来源的优先级：
+//!
+//! - 区域、PSM、输出格式、超时和重试：命令行参数 > 配置档 > config.toml > 内置默认值；
+//!   输出格式 `output.format` 优先于顶层的 `format`
+//! - 其他输出偏好（`[output]`）：命令行参数（如 `--fields`、`--no-color`）> config.toml > 内置默认值
+//! - 代理：见 [`apply_proxy`](crate::config::apply_proxy)，config.toml 位于 `LOGID_PROXY` 之后、
+//!   `HTTPS_PROXY`/`HTTP_PROXY` 之前；也可以写成 `[proxy]` 表，分别设置 `https`、`http` 和 `no_proxy`
+//! - 扫描时间范围和响应体大小上限：命令行参数 > 区域的 `scan_span`/`max_response_size` >
+//!   config.toml 顶层的同名配置 > 内置默认值
+//! - 区域端点：regions.json > config.toml 的 `[regions]` > 内置配置
+//! - 过滤规则：`--filter-profile` > 选中的 `filter_profile` > message_filters.json > 内置默认规则；
+//!   `filter_profile` 也可以是内置规则集 `default` 或 `none`
+
+use crate::config::env::user_config_dir;
+use crate::config::filter::{builtin_filter_profile, FilterProfile};
+use crate::config::profile::Profile;
+use crate::config::provider::{validate_limits, RegionEntry};
+use crate::config::region::Region;
+use crate::config::proxy::ProxyConfig;
+use crate::config::schema::{toml_error, validate_regex, SchemaIssue};
+use crate::error::LogidError;
+use crate::log_query::parse_interval;
+use crate::output::{ColorMode, OutputConfig};
+use serde::Deserialize;
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use tracing::warn;
+
+/// 配置文件名
+pub(super) const CONFIG_FILE_NAME: &str = "config.toml";
+
+/// 获取用户级别配置文件路径 (~/.config/logid/config.toml)
+pub fn user_config_file_path() -> Option<PathBuf> {
+    user_config_dir().map(|dir| dir.join(CONFIG_FILE_NAME))
+}
+
+/// config.toml 的内容
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ConfigFile {
+    /// 未指定 --region 时使用的区域
+    pub region: Option<String>,
+    /// 未指定 --psm 时使用的 PSM 列表
+    #[serde(default)]
+    pub psm: Vec<String>,
+    /// 未指定 --format 时使用的输出格式
+    pub format: Option<String>,
+    /// 单个 HTTP 请求的超时时间，如 `60s`、`2m`
+    pub timeout: Option<String>,
+    /// 瞬时失败时的最大重试次数
+    pub retries: Option<u32>,
+    /// 默认的扫描时间范围（分钟），区域配置中的 `scan_span` 优先
+    pub scan_span: Option<i32>,
+    /// 默认的响应体大小上限（MB），区域配置中的 `max_response_size` 优先
+    pub max_response_size: Option<usize>,
+    /// HTTP(S) 代理，单个地址或按协议分别设置的 `[proxy]` 表
+    pub proxy: Option<ProxyConfig>,
+    /// 使用的过滤规则集名称
+    pub filter_profile: Option<String>,
+    /// 按名称索引的过滤规则集
+    #[serde(default)]
+    pub filter_profiles: BTreeMap<String, FilterProfile>,
+    /// 自定义区域端点，格式与 regions.json 中的区域条目相同
+    #[serde(default)]
+    pub(crate) regions: HashMap<String, RegionEntry>,
+    /// 区域别名，如 `sg = "i18n"`，补充或覆盖内置别名
+    #[serde(default)]
+    pub region_aliases: BTreeMap<String, String>,
+    /// 输出偏好
+    #[serde(default)]
+    pub output: OutputPreferences,
+}
+
+/// config.toml 的 `[output]`：默认的输出偏好，命令行参数优先
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct OutputPreferences {
+    /// 未指定 --format 时使用的输出格式，优先于顶层的 `format`
+    pub format: Option<String>,
+    /// 是否输出元数据
+    pub show_metadata: Option<bool>,
+    /// 是否输出扫描时间范围
+    pub show_scan_time_range: Option<bool>,
+    /// 是否输出标签信息
+    pub show_tag_infos: Option<bool>,
+    /// 颜色输出模式（auto/always/never）
+    pub color: Option<ColorMode>,
+    /// 未指定 --fields 时 JSON 输出中每条消息保留的字段
+    #[serde(default)]
+    pub fields: Vec<String>,
+}
+
+impl OutputPreferences {
+    /// 把已设置的偏好应用到输出配置上，输出格式由命令行参数的默认值处理
+    pub fn apply(&self, mut config: OutputConfig) -> OutputConfig {
+        if let Some(show) = self.show_metadata {
+            config = config.with_show_metadata(show);
+        }
+        if let Some(show) = self.show_scan_time_range {
+            config = config.with_show_scan_time_range(show);
+        }
+        if let Some(show) = self.show_tag_infos {
+            config = config.with_show_tag_infos(show);
+        }
+        if let Some(color) = self.color {
+            config = config.with_color(color);
+        }
+        if !self.fields.is_empty() {
+            config = config.with_fields(self.fields.clone());
+        }
+        config
+    }
+}
+
+impl ConfigFile {
+    /// 从文件加载配置，文件不存在时返回空配置
+    pub fn load(path: &Path) -> Result<Self, LogidError> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = std::fs::read_to_string(path)?;
+        Self::parse(path, &content)
+    }
+
+    /// 解析并校验配置内容，`path` 只用于错误信息
+    fn parse(path: &Path, content: &str) -> Result<Self, LogidError> {
+        let config: Self = toml::from_str(content).map_err(|e| toml_error(path, content, &e))?;
+        config
+            .validate()
+            .map_err(|issue| issue.into_toml_error(path, content))?;
+        Ok(config)
+    }
+
+    /// 从用户配置目录加载配置
+    pub fn load_default() -> Result<Self, LogidError> {
+        match user_config_file_path() {
+            Some(path) => Self::load(&path),
+            None => Ok(Self::default()),
+        }
+    }
+
+    /// 从文件加载配置，出错时记录警告并返回空配置
+    pub fn load_lenient(path: &Path) -> Self {
+        Self::load(path).unwrap_or_else(|e| {
+            warn!("{}", e);
+            Self::default()
+        })
+    }
+
+    /// 加载用户配置，出错时记录警告并返回空配置
+    ///
+    /// 供区域、过滤规则、代理和输出偏好等非关键的配置读取使用，配置损坏时查询照常进行；
+    /// 配置错误由以严格模式加载的命令（如 `config validate`）报告。
+    pub fn load_default_lenient() -> Self {
+        match user_config_file_path() {
+            Some(path) => Self::load_lenient(&path),
+            None => Self::default(),
+        }
+    }
+
+    /// 选中的过滤规则集名称和规则
+    pub fn selected_filters(&self) -> Option<(&str, &[String])> {
+        let name = self.filter_profile.as_deref()?;
+        self.filter_profiles
+            .get(name)
+            .map(|profile| (name, profile.patterns()))
+    }
+
+    /// `[regions]` 中声明的自定义区域：名称、显示名称和 CAS_SESSION 环境变量
+    pub(crate) fn custom_regions(&self) -> Vec<(String, Option<String>, Option<String>)> {
+        self.regions
+            .iter()
+            .filter(|(name, _)| Region::builtin_from_str(name).is_none())
+            .map(|(name, entry)| {
+                let (display_name, cas_session_env) = entry.custom_region_names();
+                (name.to_lowercase(), display_name, cas_session_env)
+            })
+            .collect()
+    }
+
+    /// 名称是否为内置区域或 `[regions]` 中声明的自定义区域
+    fn is_region_name(&self, name: &str) -> bool {
+        Region::builtin_from_str(name).is_some()
+            || self.regions.keys().any(|region| region.eq_ignore_ascii_case(name))
+    }
+
+    /// 转换为命令行参数的默认值
+    pub fn command_defaults(&self) -> CommandDefaults {
+        CommandDefaults {
+            region: self.region.clone(),
+            psm: self.psm.clone(),
+            format: self.output.format.clone().or_else(|| self.format.clone()),
+            timeout: self.timeout.clone(),
+            retries: self.retries,
+        }
+    }
+
+    fn validate(&self) -> Result<(), SchemaIssue> {
+        if let Some(timeout) = &self.timeout {
+            parse_interval(timeout)
+                .map_err(|e| SchemaIssue::new("timeout", format!("timeout 无效: {}", e)))?;
+        }
+        validate_limits(self.scan_span, self.max_response_size)?;
+        if let Some(proxy) = &self.proxy {
+            proxy.validate()?;
+        }
+        for (name, entry) in &self.regions {
+            entry.validate_limits(name)?;
+            entry.validate_urls(name)?;
+            if Region::builtin_from_str(name).is_none() {
+                entry.validate_custom(&name.to_lowercase())?;
+            }
+        }
+        for (alias, target) in &self.region_aliases {
+            let key = format!("region_aliases.{}", alias);
+            if alias.eq_ignore_ascii_case("all") || self.is_region_name(alias) {
+                return Err(SchemaIssue::new(key, format!("区域别名 {} 与区域名称重名", alias)));
+            }
+            if !self.is_region_name(target) {
+                return Err(SchemaIssue::new(key, format!("区域别名 {} 指向未知区域: {}", alias, target)));
+            }
+        }
+        for (name, profile) in &self.filter_profiles {
+            for pattern in profile.patterns() {
+                validate_regex(&format!("filter_profiles.{}", name), pattern)?;
+            }
+        }
+        if let Some(name) = &self.filter_profile {
+            if !self.filter_profiles.contains_key(name) && builtin_filter_profile(name).is_none() {
+                return Err(SchemaIssue::new(
+                    "filter_profile",
+                    format!("filter_profile 引用了不存在的过滤规则集: {}", name),
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// `config get/set` 接受的键名前缀别名，替换为 config.toml 中实际的键
+const CONFIG_KEY_ALIASES: [(&str, &str); 2] = [("defaults.", ""), ("filters.profile", "filter_profile")];
+
+/// 把 `config get/set` 的键名转换为 config.toml 中的点分路径
+///
+/// 除实际的键（`region`、`proxy.https`、`regions.us.auth_url`）外，也接受
+/// `defaults.region`、`filters.profile` 这样的分组写法。
+pub fn config_key_path(key: &str) -> String {
+    let key = key.trim();
+    CONFIG_KEY_ALIASES
+        .iter()
+        .find_map(|(alias, target)| key.strip_prefix(alias).map(|rest| format!("{}{}", target, rest)))
+        .unwrap_or_else(|| key.to_string())
+}
+
+/// 读取 config.toml 中点分路径对应的值，未设置时返回 `None`
+///
+/// 字符串直接返回内容，其他类型（数字、数组、表）返回 TOML 写法。
+pub fn get_config_value(path: &Path, key: &str) -> Result<Option<String>, LogidError> {
+    let key = config_key_path(key);
+    if !path.exists() {
+        return Ok(None);
+    }
+    let content = std::fs::read_to_string(path)?;
+    let document = parse_document(path, &content)?;
+
+    let mut item = document.as_item();
+    for segment in key.split('.') {
+        match item.as_table_like().and_then(|table| table.get(segment)) {
+            Some(child) => item = child,
+            None => return Ok(None),
+        }
+    }
+    Ok(Some(match item.as_str() {
+        Some(text) => text.to_string(),
+        None => match item {
+            toml_edit::Item::Table(table) => table.to_string().trim_end().to_string(),
+            _ => item.to_string().trim().to_string(),
+        },
+    }))
+}
+
+/// 设置 config.toml 中点分路径对应的值，保留文件中的注释和格式
+///
+/// 值按 TOML 解析（`3`、`true`、`["a", "b"]`），无法解析时作为字符串。
+/// 修改后的配置先经过与加载时相同的校验，再写入临时文件并替换原文件，
+/// 校验失败时原文件保持不变。
+pub fn set_config_value(path: &Path, key: &str, value: &str) -> Result<(), LogidError> {
+    let key = config_key_path(key);
+    let content = match std::fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+        Err(e) => return Err(e.into()),
+    };
+    let mut document = parse_document(path, &content)?;
+
+    let invalid_key = |message: String| LogidError::ConfigError {
+        path: path.display().to_string(),
+        line: None,
+        key: Some(key.clone()),
+        message,
+    };
+    let segments: Vec<&str> = key.split('.').collect();
+    if segments.iter().any(|segment| segment.is_empty()) {
+        return Err(invalid_key(format!("无效的键: {}", key)));
+    }
+    let (last, parents) = segments.split_last().expect("split 至少返回一段");
+    let mut table: &mut dyn toml_edit::TableLike = document.as_table_mut();
+    for segment in parents {
+        let mut implicit = toml_edit::Table::new();
+        implicit.set_implicit(true);
+        table = table
+            .entry(segment)
+            .or_insert(toml_edit::Item::Table(implicit))
+            .as_table_like_mut()
+            .ok_or_else(|| invalid_key(format!("{} 不是表", segment)))?;
+    }
+    let mut value = value
+        .parse::<toml_edit::Value>()
+        .unwrap_or_else(|_| toml_edit::Value::from(value));
+    // 替换已有的值时保留键前和值后的注释
+    match table.get_mut(last).and_then(toml_edit::Item::as_value_mut) {
+        Some(existing) => {
+            *value.decor_mut() = existing.decor().clone();
+            *existing = value;
+        }
+        None => {
+            table.insert(last, toml_edit::Item::Value(value));
+        }
+    }
+
+    let updated = document.to_string();
+    ConfigFile::parse(path, &updated)?;
+
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    // 先写临时文件再替换，避免中断时损坏配置
+    let tmp_path = path.with_extension("toml.tmp");
+    std::fs::write(&tmp_path, updated)?;
+    std::fs::rename(&tmp_path, path)?;
+    Ok(())
+}
+
+/// 以保留格式的方式解析 TOML 文档
+fn parse_document(path: &Path, content: &str) -> Result<toml_edit::DocumentMut, LogidError> {
+    content.parse().map_err(|e: toml_edit::TomlError| LogidError::ConfigError {
+        path: path.display().to_string(),
+        line: e.span().map(|span| content[..span.start.min(content.len())].matches('\n').count() + 1),
+        key: None,
+        message: e.message().trim().to_string(),
+    })
+}
+
+/// 命令行参数的默认值，来自 config.toml 或配置档
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CommandDefaults {
+    /// `--region` 的默认值
+    pub region: Option<String>,
+    /// `--psm` 的默认值
+    pub psm: Vec<String>,
+    /// `--format` 的默认值
+    pub format: Option<String>,
+    /// `--timeout` 的默认值
+    pub timeout: Option<String>,
+    /// `--retries` 的默认值
+    pub retries: Option<u32>,
+}
+
+impl From<&Profile> for CommandDefaults {
+    fn from(profile: &Profile) -> Self {
+        Self {
+            region: profile.region.clone(),
+            psm: profile.psm.clone(),
+            format: profile.format.clone(),
+            ..Self::default()
+        }
+    }
+}
@@ -1,22 +1,226 @@
 //! 消息过滤配置模块
 
-use crate::conditional_info;
+use crate::config::file::{user_config_file_path, ConfigFile};
+use crate::config::user_config_dir;
 use crate::error::LogidError;
 use regex::Regex;
-use serde::Deserialize;
-use std::path::PathBuf;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+use std::time::SystemTime;
 use tracing::warn;
 
+/// 用户级别过滤规则文件名
+pub(super) const USER_FILTERS_FILE: &str = "message_filters.json";
+
+/// YAML 格式的用户级别过滤规则文件名，JSON 文件不存在时依次查找
+const USER_YAML_FILTERS_FILES: [&str; 2] = ["message_filters.yaml", "message_filters.yml"];
+
+/// 项目内置的过滤规则文件路径
+const PROJECT_FILTERS_PATH: &str = "reference/message_filters.json";
+
+/// 指定过滤规则文件的环境变量
+pub const FILTERS_ENV_VAR: &str = "LOGID_FILTERS";
+
+/// 内置的过滤规则集：`default` 为内置默认规则，`none` 不过滤任何内容
+pub const BUILTIN_FILTER_PROFILES: [&str; 2] = ["default", "none"];
+
+/// 命令行 `--filter-config` 指定的过滤规则文件
+static FILTER_CONFIG_PATH: RwLock<Option<PathBuf>> = RwLock::new(None);
+
+/// 命令行 `--filter-profile` 选择的过滤规则集
+static FILTER_PROFILE: RwLock<Option<String>> = RwLock::new(None);
+
+/// 获取用户级别过滤规则文件路径
+///
+/// 依次查找 ~/.config/logid 下的 message_filters.json、message_filters.yaml 和
+/// message_filters.yml，都不存在时返回 JSON 文件的路径。
+pub fn user_filters_path() -> Option<PathBuf> {
+    let dir = user_config_dir()?;
+    std::iter::once(USER_FILTERS_FILE)
+        .chain(USER_YAML_FILTERS_FILES)
+        .map(|name| dir.join(name))
+        .find(|path| path.is_file())
+        .or_else(|| Some(dir.join(USER_FILTERS_FILE)))
+}
+
+/// 过滤规则文件是否为 YAML 格式（按扩展名 .yaml/.yml 判断）
+fn is_yaml_file(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("yaml") || ext.eq_ignore_ascii_case("yml"))
+}
+
+/// 设置进程内所有查询客户端使用的过滤规则文件，`None` 时恢复默认的查找顺序
+pub fn set_filter_config_path(path: Option<PathBuf>) {
+    *FILTER_CONFIG_PATH
+        .write()
+        .unwrap_or_else(|e| e.into_inner()) = path;
+}
+
+/// 显式指定的过滤规则文件：`--filter-config`，其次 `LOGID_FILTERS` 环境变量
+pub fn filter_config_path() -> Option<PathBuf> {
+    FILTER_CONFIG_PATH
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .clone()
+        .or_else(|| {
+            std::env::var(FILTERS_ENV_VAR)
+                .ok()
+                .filter(|path| !path.is_empty())
+                .map(PathBuf::from)
+        })
+}
+
+/// 设置进程内所有查询客户端使用的过滤规则集，优先于 config.toml 中的 `filter_profile`
+pub fn set_filter_profile(name: Option<String>) {
+    *FILTER_PROFILE.write().unwrap_or_else(|e| e.into_inner()) = name;
+}
+
+/// 命令行 `--filter-profile` 选择的过滤规则集
+pub fn filter_profile() -> Option<String> {
+    FILTER_PROFILE.read().unwrap_or_else(|e| e.into_inner()).clone()
+}
+
+/// 内置过滤规则集的规则
+pub fn builtin_filter_profile(name: &str) -> Option<Vec<String>> {
+    match name {
+        "default" => Some(get_default_filters()),
+        "none" => Some(Vec::new()),
+        _ => None,
+    }
+}
+
 /// 过滤配置
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
 pub struct FilterConfig {
     /// 消息过滤规则列表
     #[serde(rename = "msg_filters", alias = "_msg_filters", alias = "patterns")]
     pub msg_filters: Option<Vec<String>>,
+    /// 按名称索引的过滤规则集，通过 `--filter-profile` 选择
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub profiles: BTreeMap<String, FilterProfile>,
+    /// 按键名筛选 kv 字段
+    #[serde(flatten)]
+    pub keys: KeyFilter,
+}
+
+/// 过滤模式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FilterMode {
+    /// 删除匹配规则的内容
+    #[default]
+    Remove,
+    /// 只保留匹配任一规则的行，适合输出量极大的服务
+    Keep,
+}
+
+impl std::fmt::Display for FilterMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Remove => write!(f, "删除匹配的内容"),
+            Self::Keep => write!(f, "只保留匹配的行"),
+        }
+    }
+}
+
+/// 过滤规则集：规则列表，或指定了过滤模式的规则
+///
+/// ```json
+/// {"profiles": {"quiet": ["_compliance_nlp_log"], "errors": {"mode": "keep", "patterns": ["error|panic|status="]}}}
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(untagged)]
+pub enum FilterProfile {
+    /// 删除模式的规则列表
+    Patterns(Vec<String>),
+    /// 指定过滤模式的规则
+    WithMode {
+        /// 过滤模式，默认为删除模式
+        #[serde(default)]
+        mode: FilterMode,
+        /// 过滤规则
+        patterns: Vec<String>,
+    },
+}
+
+impl FilterProfile {
+    /// 规则列表
+    pub fn patterns(&self) -> &[String] {
+        match self {
+            Self::Patterns(patterns) | Self::WithMode { patterns, .. } => patterns,
+        }
+    }
+
+    /// 过滤模式
+    pub fn mode(&self) -> FilterMode {
+        match self {
+            Self::Patterns(_) => FilterMode::Remove,
+            Self::WithMode { mode, .. } => *mode,
+        }
+    }
+
+    fn into_parts(self) -> (Vec<String>, FilterMode) {
+        match self {
+            Self::Patterns(patterns) => (patterns, FilterMode::Remove),
+            Self::WithMode { mode, patterns } => (patterns, mode),
+        }
+    }
+}
+
+/// 按键名筛选日志的 kv 字段
+///
+/// 默认只提取 `_msg`；`keep_keys` 中的键会一并提取，`drop_keys` 中的键不提取，
+/// 两者都支持 `*` 通配符（如 `_compliance_*`），`drop_keys` 优先。
+/// 直接按键名整体丢弃比用正则改写消息文本更省开销，也不会误删相似的内容。
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub struct KeyFilter {
+    /// 除 `_msg` 外额外提取的键
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub keep_keys: Vec<String>,
+    /// 不提取的键
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub drop_keys: Vec<String>,
+}
+
+impl KeyFilter {
+    /// 是否没有配置任何键
+    pub fn is_empty(&self) -> bool {
+        self.keep_keys.is_empty() && self.drop_keys.is_empty()
+    }
+
+    /// 是否提取该键的值
+    pub fn extracts(&self, key: &str) -> bool {
+        if self.drop_keys.iter().any(|pattern| key_matches(pattern, key)) {
+            return false;
+        }
+        key == "_msg" || self.keep_keys.iter().any(|pattern| key_matches(pattern, key))
+    }
+}
+
+/// 匹配带 `*` 通配符的键名
+fn key_matches(pattern: &str, key: &str) -> bool {
+    let mut parts = pattern.split('*');
+    let Some(mut rest) = key.strip_prefix(parts.next().unwrap_or_default()) else {
+        return false;
+    };
+    let mut parts: Vec<&str> = parts.collect();
+    let Some(last) = parts.pop() else {
+        return rest.is_empty();
+    };
+    for part in parts {
+        match rest.find(part) {
+            Some(index) => rest = &rest[index + part.len()..],
+            None => return false,
+        }
+    }
+    rest.ends_with(last)
 }
 
 impl FilterConfig {
-    /// 从文件加载过滤配置
+    /// 从文件加载过滤配置，扩展名为 .yaml/.yml 时按 YAML 解析，否则按 JSON 解析
     pub fn from_file(path: &PathBuf) -> Result<Option<Self>, LogidError> {
         if !path.exists() {
             conditional_info!("过滤配置文件不存在: {}", path.display());
@@ -24,25 +228,57 @@ impl FilterConfig {
         }
 
         let content = std::fs::read_to_string(path)?;
-        let config: serde_json::Value = serde_json::from_str(&content)?;
+        let config: serde_json::Value = if is_yaml_file(path) {
+            serde_yaml::from_str(&content).map_err(|e| {
+                LogidError::FilterConfigError(format!("无法解析 {}: {}", path.display(), e))
+            })?
+        } else {
+            serde_json::from_str(&content)?
+        };
+        if config.is_null() {
+            warn!("过滤配置文件为空: {}", path.display());
+            return Ok(None);
+        }
 
         // 尝试解析不同格式的配置
-        if let Some(filters) = config.get("msg_filters") {
-            Ok(Some(FilterConfig {
-                msg_filters: Some(serde_json::from_value(filters.clone())?),
-            }))
-        } else if let Some(filters) = config.get("_msg_filters") {
-            Ok(Some(FilterConfig {
-                msg_filters: Some(serde_json::from_value(filters.clone())?),
-            }))
-        } else if let Some(filters) = config.get("patterns") {
-            Ok(Some(FilterConfig {
-                msg_filters: Some(serde_json::from_value(filters.clone())?),
-            }))
-        } else {
+        let msg_filters = match ["msg_filters", "_msg_filters", "patterns"]
+            .into_iter()
+            .find_map(|key| config.get(key))
+        {
+            Some(filters) => Some(serde_json::from_value(filters.clone())?),
+            None => None,
+        };
+        let profiles = match config.get("profiles") {
+            Some(profiles) => serde_json::from_value(profiles.clone())?,
+            None => BTreeMap::new(),
+        };
+        let keys: KeyFilter = serde_json::from_value(config)?;
+
+        if msg_filters.is_none() && profiles.is_empty() && keys.is_empty() {
             warn!("过滤配置文件格式不正确，缺少有效的过滤规则字段");
-            Ok(None)
+            return Ok(None);
         }
+        Ok(Some(FilterConfig {
+            msg_filters,
+            profiles,
+            keys,
+        }))
+    }
+
+    /// 保存过滤配置到文件，父目录不存在时自动创建；格式与 [`FilterConfig::from_file`] 相同，按扩展名确定
+    pub fn save(&self, path: &PathBuf) -> Result<(), LogidError> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let content = if is_yaml_file(path) {
+            serde_yaml::to_string(self).map_err(|e| {
+                LogidError::FilterConfigError(format!("无法写入 {}: {}", path.display(), e))
+            })?
+        } else {
+            serde_json::to_string_pretty(self)?
+        };
+        std::fs::write(path, content)?;
+        Ok(())
     }
 
     /// 获取过滤规则列表，如果配置为空则返回默认规则
@@ -66,35 +302,343 @@ pub fn get_default_filters() -> Vec<String> {
     ]
 }
 
-/// 创建消息过滤器
-pub fn create_message_filters(
+/// 过滤规则来源
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum FilterSource {
+    /// 从配置文件加载
+    File(PathBuf),
+    /// config.toml 中选中的过滤规则集
+    ConfigProfile(String),
+    /// 过滤规则文件中的过滤规则集
+    FileProfile(PathBuf, String),
+    /// 内置的过滤规则集
+    BuiltinProfile(String),
+    /// 内置默认规则
+    #[default]
+    Builtin,
+    /// 调用方直接提供的规则
+    Inline,
+}
+
+impl std::fmt::Display for FilterSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::File(path) => write!(f, "{}", path.display()),
+            Self::ConfigProfile(name) => write!(f, "config.toml 过滤规则集 {}", name),
+            Self::FileProfile(path, name) => write!(f, "{} 过滤规则集 {}", path.display(), name),
+            Self::BuiltinProfile(name) => write!(f, "内置过滤规则集 {}", name),
+            Self::Builtin => write!(f, "内置默认规则"),
+            Self::Inline => write!(f, "直接提供的规则"),
+        }
+    }
+}
+
+/// 过滤规则诊断信息
+#[derive(Debug, Clone, Serialize)]
+pub struct FilterDiagnostic {
+    /// 无效的正则表达式
+    pub pattern: String,
+    /// 编译错误信息
+    pub error: String,
+    /// 出错位置（从 1 开始的字符序号），无法定位时为空
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub position: Option<usize>,
+}
+
+/// 定位正则表达式语法错误的位置（从 1 开始的字符序号）
+fn syntax_error_position(pattern: &str) -> Option<usize> {
+    let span = match regex_syntax::Parser::new().parse(pattern).err()? {
+        regex_syntax::Error::Parse(e) => *e.span(),
+        regex_syntax::Error::Translate(e) => *e.span(),
+        _ => return None,
+    };
+    Some(pattern.get(..span.start.offset)?.chars().count() + 1)
+}
+
+/// 宽松模式下的过滤规则编译结果
+#[derive(Debug, Clone, Default)]
+pub struct CompiledFilters {
+    /// 编译成功的过滤器
+    pub filters: Vec<Regex>,
+    /// 被跳过的无效规则
+    pub diagnostics: Vec<FilterDiagnostic>,
+    /// 规则来源
+    pub source: FilterSource,
+    /// 过滤模式
+    pub mode: FilterMode,
+}
+
+/// 加载过滤规则文本及其来源
+///
+/// 指定路径（参数、`--filter-config` 或 `LOGID_FILTERS`）时只读取该文件；
+/// 否则 config.toml 中选中的 `filter_profile` 优先，其次依次尝试用户级别配置
+/// (~/.config/logid/message_filters.json) 和当前目录下的 reference/message_filters.json，
+/// 都不存在时使用内置默认规则。
+///
+/// 通过 `--filter-profile` 选择了过滤规则集时，按 [`load_filter_profile`] 的顺序查找该规则集。
+pub fn load_filter_patterns(
     config_path: Option<&PathBuf>,
-) -> Result<Vec<Regex>, LogidError> {
-    let patterns = if let Some(path) = config_path {
-        match FilterConfig::from_file(path)? {
-            Some(config) => {
-                conditional_info!("从配置文件加载过滤规则: {}", path.display());
-                config.get_filters()
-            }
-            None => {
-                conditional_info!("使用默认过滤规则");
-                get_default_filters()
-            }
+) -> Result<(Vec<String>, FilterSource), LogidError> {
+    load_filter_rules(config_path).map(|(patterns, _, source)| (patterns, source))
+}
+
+/// 加载过滤规则文本、过滤模式及其来源，查找顺序同 [`load_filter_patterns`]
+///
+/// 只有过滤规则集可以使用保留模式，其余来源都是删除模式。
+pub fn load_filter_rules(
+    config_path: Option<&PathBuf>,
+) -> Result<(Vec<String>, FilterMode, FilterSource), LogidError> {
+    let explicit = config_path.cloned().or_else(filter_config_path);
+    if let Some(name) = filter_profile() {
+        return load_filter_profile(&name, config_path);
+    }
+    if explicit.is_none() {
+        if let Some(name) = ConfigFile::load_default_lenient().filter_profile {
+            return load_filter_profile(&name, None);
         }
-    } else {
-        // 尝试从项目根目录加载配置文件
-        let default_path = PathBuf::from("reference/message_filters.json");
-        match FilterConfig::from_file(&default_path)? {
-            Some(config) => {
-                conditional_info!("从默认配置文件加载过滤规则: {}", default_path.display());
-                config.get_filters()
-            }
-            None => {
-                conditional_info!("使用默认过滤规则");
-                get_default_filters()
+    }
+
+    for path in filter_file_candidates(explicit) {
+        if let Some(config) = FilterConfig::from_file(&path)? {
+            conditional_info!("从配置文件加载过滤规则: {}", path.display());
+            return Ok((config.get_filters(), FilterMode::Remove, FilterSource::File(path)));
+        }
+    }
+
+    conditional_info!("使用默认过滤规则");
+    Ok((get_default_filters(), FilterMode::Remove, FilterSource::Builtin))
+}
+
+/// 加载按键名筛选 kv 字段的配置
+///
+/// 与 [`load_filter_patterns`] 查找相同的过滤规则文件，使用第一个存在的文件中的
+/// `keep_keys` 和 `drop_keys`；都不存在时只提取 `_msg`。
+pub fn load_key_filter(config_path: Option<&PathBuf>) -> Result<KeyFilter, LogidError> {
+    let explicit = config_path.cloned().or_else(filter_config_path);
+    for path in filter_file_candidates(explicit) {
+        if let Some(config) = FilterConfig::from_file(&path)? {
+            return Ok(config.keys);
+        }
+    }
+    Ok(KeyFilter::default())
+}
+
+/// 按名称加载过滤规则集
+///
+/// 依次查找 config.toml 的 `[filter_profiles]`、过滤规则文件中的 `profiles`
+/// 和内置规则集（`default`、`none`）；指定了过滤规则文件（参数、`--filter-config`
+/// 或 `LOGID_FILTERS`）时只查找该文件和内置规则集。
+pub fn load_filter_profile(
+    name: &str,
+    config_path: Option<&PathBuf>,
+) -> Result<(Vec<String>, FilterMode, FilterSource), LogidError> {
+    let explicit = config_path.cloned().or_else(filter_config_path);
+    if explicit.is_none() {
+        let mut config = ConfigFile::load_default_lenient();
+        if let Some(profile) = config.filter_profiles.remove(name) {
+            conditional_info!("使用 config.toml 中的过滤规则集: {}", name);
+            let (patterns, mode) = profile.into_parts();
+            return Ok((patterns, mode, FilterSource::ConfigProfile(name.to_string())));
+        }
+    }
+
+    for path in filter_file_candidates(explicit) {
+        let profile = FilterConfig::from_file(&path)?.and_then(|mut c| c.profiles.remove(name));
+        if let Some(profile) = profile {
+            conditional_info!("使用 {} 中的过滤规则集: {}", path.display(), name);
+            let (patterns, mode) = profile.into_parts();
+            return Ok((patterns, mode, FilterSource::FileProfile(path, name.to_string())));
+        }
+    }
+
+    if let Some(patterns) = builtin_filter_profile(name) {
+        conditional_info!("使用内置过滤规则集: {}", name);
+        return Ok((patterns, FilterMode::Remove, FilterSource::BuiltinProfile(name.to_string())));
+    }
+
+    Err(LogidError::FilterConfigError(format!(
+        "未找到过滤规则集: {}（可用: {}）",
+        name,
+        filter_profile_names(config_path).join(", ")
+    )))
+}
+
+/// 所有可用的过滤规则集名称
+pub fn filter_profile_names(config_path: Option<&PathBuf>) -> Vec<String> {
+    let explicit = config_path.cloned().or_else(filter_config_path);
+    let mut names: Vec<String> = BUILTIN_FILTER_PROFILES
+        .iter()
+        .map(|name| name.to_string())
+        .collect();
+    if explicit.is_none() {
+        names.extend(ConfigFile::load_default_lenient().filter_profiles.into_keys());
+    }
+    for path in filter_file_candidates(explicit) {
+        if let Ok(Some(config)) = FilterConfig::from_file(&path) {
+            names.extend(config.profiles.into_keys());
+        }
+    }
+    names.sort();
+    names.dedup();
+    names
+}
+
+/// 所有已配置的过滤规则：config.toml 的过滤规则集、过滤规则文件中的 `msg_filters` 和过滤规则集
+///
+/// 供 `filters validate` 逐一检查，包括当前未被选中的规则。
+pub fn configured_filter_sets(
+    config_path: Option<&PathBuf>,
+) -> Result<Vec<(FilterSource, Vec<String>)>, LogidError> {
+    let explicit = config_path.cloned().or_else(filter_config_path);
+    let mut sets = Vec::new();
+    if explicit.is_none() {
+        for (name, profile) in ConfigFile::load_default()?.filter_profiles {
+            sets.push((FilterSource::ConfigProfile(name), profile.into_parts().0));
+        }
+    }
+    for path in filter_file_candidates(explicit) {
+        let Some(config) = FilterConfig::from_file(&path)? else {
+            continue;
+        };
+        if let Some(patterns) = config.msg_filters {
+            sets.push((FilterSource::File(path.clone()), patterns));
+        }
+        for (name, profile) in config.profiles {
+            sets.push((FilterSource::FileProfile(path.clone(), name), profile.into_parts().0));
+        }
+    }
+    Ok(sets)
+}
+
+/// 过滤规则相关文件的修改时间快照
+///
+/// 监视模式等长时间运行的场景中定期调用 [`FilterFileWatcher::changed`]，
+/// 文件被创建、修改或删除后重新加载过滤规则，无需重启。
+#[derive(Debug, Clone)]
+pub struct FilterFileWatcher {
+    stamps: Vec<(PathBuf, Option<SystemTime>)>,
+}
+
+impl FilterFileWatcher {
+    /// 记录当前过滤规则文件和 config.toml 的修改时间
+    pub fn new() -> Self {
+        let explicit = filter_config_path();
+        let config_file = explicit.is_none().then(user_config_file_path).flatten();
+        Self::watching(filter_file_candidates(explicit).into_iter().chain(config_file))
+    }
+
+    /// 记录指定文件的修改时间
+    pub fn watching(paths: impl IntoIterator<Item = PathBuf>) -> Self {
+        let stamps = paths
+            .into_iter()
+            .map(|path| {
+                let stamp = modified_time(&path);
+                (path, stamp)
+            })
+            .collect();
+        Self { stamps }
+    }
+
+    /// 自上次检查以来是否有文件发生变化
+    pub fn changed(&mut self) -> bool {
+        let mut changed = false;
+        for (path, stamp) in &mut self.stamps {
+            let current = modified_time(path);
+            if current != *stamp {
+                *stamp = current;
+                changed = true;
             }
         }
-    };
+        changed
+    }
+}
+
+impl Default for FilterFileWatcher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn modified_time(path: &PathBuf) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|meta| meta.modified()).ok()
+}
+
+/// 待查找的过滤规则文件：指定路径时只有该文件，否则为用户级别和项目目录下的文件
+fn filter_file_candidates(explicit: Option<PathBuf>) -> Vec<PathBuf> {
+    match explicit {
+        Some(path) => vec![path],
+        None => user_filters_path()
+            .into_iter()
+            .chain(std::iter::once(PathBuf::from(PROJECT_FILTERS_PATH)))
+            .collect(),
+    }
+}
+
+/// 使用过滤器清理消息内容
+///
+/// 依次移除所有过滤规则匹配的内容，再合并多余的空格和空行。
+pub fn apply_message_filters(filters: &[Regex], message: &str) -> String {
+    let mut filtered = message.to_string();
+
+    for regex in filters {
+        filtered = regex.replace_all(&filtered, "").to_string();
+    }
+
+    // 清理多余空格和换行符
+    filtered = Regex::new(r"[ \t]{2,}")
+        .map(|re| re.replace_all(&filtered, " ").to_string())
+        .unwrap_or(filtered.clone());
+
+    filtered = Regex::new(r"\n\s*\n\s*\n")
+        .map(|re| re.replace_all(&filtered, "\n\n").to_string())
+        .unwrap_or(filtered);
+
+    filtered.trim().to_string()
+}
+
+/// 只保留匹配任一规则的行
+///
+/// 保留模式下使用；没有任何行匹配时返回空字符串。
+pub fn keep_matching_lines(filters: &[Regex], message: &str) -> String {
+    message
+        .lines()
+        .filter(|line| filters.iter().any(|regex| regex.is_match(line)))
+        .collect::<Vec<_>>()
+        .join("\n")
+        .trim()
+        .to_string()
+}
+
+/// 按过滤模式处理消息
+pub fn apply_filters(filters: &[Regex], mode: FilterMode, message: &str) -> String {
+    match mode {
+        FilterMode::Remove => apply_message_filters(filters, message),
+        FilterMode::Keep => keep_matching_lines(filters, message),
+    }
+}
+
+/// 统计每条过滤规则从消息中移除的字节数
+///
+/// 与 [`apply_message_filters`] 一样按顺序应用规则，返回值与 `filters` 一一对应。
+pub fn measure_message_filters(filters: &[Regex], message: &str) -> Vec<usize> {
+    let mut filtered = message.to_string();
+    filters
+        .iter()
+        .map(|regex| {
+            let before = filtered.len();
+            filtered = regex.replace_all(&filtered, "").to_string();
+            before - filtered.len()
+        })
+        .collect()
+}
+
+/// 创建消息过滤器
+///
+/// 遇到无效的正则表达式时返回错误。
+pub fn create_message_filters(
+    config_path: Option<&PathBuf>,
+) -> Result<Vec<Regex>, LogidError> {
+    let (patterns, _) = load_filter_patterns(config_path)?;
 
     // 预编译正则表达式
     let mut compiled_filters = Vec::new();
@@ -107,3 +651,46 @@ pub fn create_message_filters(
     conditional_info!("已加载 {} 条消息过滤规则", compiled_filters.len());
     Ok(compiled_filters)
 }
+
+/// 以宽松模式创建消息过滤器
+///
+/// 跳过无效的正则表达式并记录到诊断列表中，其余规则照常生效。
+pub fn create_message_filters_lenient(
+    config_path: Option<&PathBuf>,
+) -> Result<CompiledFilters, LogidError> {
+    let (patterns, mode, source) = load_filter_rules(config_path)?;
+    Ok(CompiledFilters {
+        source,
+        mode,
+        ..compile_filters_lenient(&patterns)
+    })
+}
+
+/// 宽松地编译一组过滤规则
+pub fn compile_filters_lenient(patterns: &[String]) -> CompiledFilters {
+    let mut compiled = CompiledFilters {
+        source: FilterSource::Inline,
+        ..CompiledFilters::default()
+    };
+
+    for pattern in patterns {
+        match Regex::new(pattern) {
+            Ok(regex) => compiled.filters.push(regex),
+            Err(e) => {
+                warn!("跳过无效的过滤规则 '{}': {}", pattern, e);
+                compiled.diagnostics.push(FilterDiagnostic {
+                    pattern: pattern.clone(),
+                    error: e.to_string(),
+                    position: syntax_error_position(pattern),
+                });
+            }
+        }
+    }
+
+    conditional_info!(
+        "已加载 {} 条消息过滤规则，跳过 {} 条无效规则",
+        compiled.filters.len(),
+        compiled.diagnostics.len()
+    );
+    compiled
+}
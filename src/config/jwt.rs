@@ -1,5 +1,8 @@
 //! JWT 认证信息模块
 
+use base64::Engine;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
 use std::time::{Duration, Instant};
 
 /// JWT 认证信息
@@ -9,6 +12,8 @@ pub struct JwtInfo {
     pub token: String,
     /// 过期时间
     pub expires_at: Instant,
+    /// 过期时间（UTC 时间，用于展示）
+    pub expires_at_utc: DateTime<Utc>,
 }
 
 impl JwtInfo {
@@ -17,11 +22,89 @@ impl JwtInfo {
         Self {
             token,
             expires_at: Instant::now() + Duration::from_secs(expires_in_seconds),
+            expires_at_utc: Utc::now() + chrono::Duration::seconds(expires_in_seconds as i64),
         }
     }
 
+    /// 根据已知的过期时间创建 JWT 信息（如从磁盘缓存恢复的令牌）
+    pub fn from_expiry(token: String, expires_at_utc: DateTime<Utc>) -> Self {
+        let remaining = (expires_at_utc - Utc::now()).to_std().unwrap_or(Duration::ZERO);
+        Self {
+            token,
+            expires_at: Instant::now() + remaining,
+            expires_at_utc,
+        }
+    }
+
+    /// 根据令牌中的 `exp` 声明创建 JWT 信息
+    ///
+    /// 无法解析 `exp` 时使用 `default_expires_in_seconds` 作为有效期。
+    pub fn from_token(token: String, default_expires_in_seconds: u64) -> Self {
+        let expires_in = decode_exp_claim(&token)
+            .map(|exp| (exp - Utc::now().timestamp()).max(0) as u64)
+            .unwrap_or(default_expires_in_seconds);
+        Self::new(token, expires_in)
+    }
+
     /// 检查令牌是否有效（5 分钟缓冲时间）
     pub fn is_valid(&self) -> bool {
         Instant::now() < (self.expires_at - Duration::from_secs(300))
     }
+
+    /// 距离过期的剩余时间
+    pub fn remaining(&self) -> Duration {
+        self.expires_at.saturating_duration_since(Instant::now())
+    }
+}
+
+/// 解析 JWT 载荷中的 `exp` 声明（Unix 时间戳，秒）
+fn decode_exp_claim(token: &str) -> Option<i64> {
+    let payload = token.split('.').nth(1)?;
+    let decoded = base64::engine::general_purpose::URL_SAFE_NO_PAD
+        .decode(payload.trim_end_matches('='))
+        .ok()?;
+    let claims: serde_json::Value = serde_json::from_slice(&decoded).ok()?;
+    claims.get("exp")?.as_i64()
+}
+
+/// 凭据过期信息
+///
+/// 描述 CAS_SESSION 的过期时间，以及是否处于需要提醒续期的窗口内。
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct CredentialExpiry {
+    /// 区域标识符
+    pub region: String,
+    /// 过期时间
+    pub expires_at: DateTime<Utc>,
+    /// 剩余秒数
+    pub remaining_seconds: i64,
+    /// 是否即将过期
+    pub expiring_soon: bool,
+}
+
+impl CredentialExpiry {
+    /// 根据过期时间和提醒窗口创建过期信息
+    pub fn new(region: &str, expires_at: DateTime<Utc>, warning_window: Duration) -> Self {
+        let remaining_seconds = (expires_at - Utc::now()).num_seconds();
+        Self {
+            region: region.to_string(),
+            expires_at,
+            remaining_seconds,
+            expiring_soon: remaining_seconds <= warning_window.as_secs() as i64,
+        }
+    }
+
+    /// 格式化剩余时间，例如 `3h12m`
+    pub fn remaining_display(&self) -> String {
+        if self.remaining_seconds <= 0 {
+            return "已过期".to_string();
+        }
+        let hours = self.remaining_seconds / 3600;
+        let minutes = (self.remaining_seconds % 3600) / 60;
+        if hours > 0 {
+            format!("{}h{}m", hours, minutes)
+        } else {
+            format!("{}m", minutes)
+        }
+    }
 }
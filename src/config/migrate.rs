@@ -0,0 +1,193 @@
+//! 配置迁移模块
+//!
+//! 用户配置目录中的 `schema_version` 文件记录配置格式的版本。每次启动时按顺序执行
+//! 版本号更高的迁移，修改文件前先备份为 `<文件名>.v<旧版本>.bak`，使 `logid update`
+//! 升级后旧的配置继续可用。
+//!
+//! 新增迁移时在 [`MIGRATIONS`] 末尾追加一项并增大 [`CONFIG_SCHEMA_VERSION`]，
+//! 迁移必须是幂等的：文件已经是新格式时不做修改。
+
+use crate::config::env::ENV_FILE_NAME;
+use crate::config::filter::USER_FILTERS_FILE;
+use crate::error::LogidError;
+use std::path::{Path, PathBuf};
+use tracing::warn;
+
+/// 当前的配置格式版本
+pub const CONFIG_SCHEMA_VERSION: u32 = 2;
+
+/// 记录配置格式版本的文件名
+const SCHEMA_VERSION_FILE_NAME: &str = "schema_version";
+
+/// 一次配置迁移
+struct Migration {
+    /// 迁移后的版本
+    version: u32,
+    /// 迁移内容说明
+    description: &'static str,
+    /// 执行迁移，返回被修改的文件
+    apply: fn(&Path, u32) -> Result<Vec<PathBuf>, LogidError>,
+}
+
+/// 按版本排列的所有迁移
+const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        description: ".env 中的 CAS_SESSION_I18n 改名为 CAS_SESSION_I18N",
+        apply: rename_i18n_session_var,
+    },
+    Migration {
+        version: 2,
+        description: "message_filters.json 中的旧键 _msg_filters/patterns 改名为 msg_filters",
+        apply: rename_legacy_filter_keys,
+    },
+];
+
+/// 执行过的一次迁移
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AppliedMigration {
+    /// 迁移后的版本
+    pub version: u32,
+    /// 迁移内容说明
+    pub description: &'static str,
+    /// 被修改的文件
+    pub changed_files: Vec<PathBuf>,
+    /// 修改前的备份文件
+    pub backups: Vec<PathBuf>,
+}
+
+/// 配置迁移结果
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MigrationReport {
+    /// 迁移前的版本
+    pub from_version: u32,
+    /// 迁移后的版本
+    pub to_version: u32,
+    /// 执行过的迁移（不含没有修改任何文件的迁移）
+    pub applied: Vec<AppliedMigration>,
+}
+
+/// 读取配置目录的格式版本，没有记录时为 0
+pub fn config_schema_version(dir: &Path) -> u32 {
+    std::fs::read_to_string(dir.join(SCHEMA_VERSION_FILE_NAME))
+        .ok()
+        .and_then(|content| content.trim().parse().ok())
+        .unwrap_or(0)
+}
+
+/// 把配置目录迁移到当前版本
+///
+/// 已是当前版本时返回 `None`；目录由更新版本的 logid 写入时不做修改并返回 `None`。
+pub fn migrate_config_dir(dir: &Path) -> Result<Option<MigrationReport>, LogidError> {
+    let from_version = config_schema_version(dir);
+    if from_version >= CONFIG_SCHEMA_VERSION {
+        if from_version > CONFIG_SCHEMA_VERSION {
+            warn!(
+                "配置目录 {} 的格式版本 {} 高于当前支持的版本 {}，跳过迁移",
+                dir.display(),
+                from_version,
+                CONFIG_SCHEMA_VERSION
+            );
+        }
+        return Ok(None);
+    }
+
+    let mut applied = Vec::new();
+    for migration in MIGRATIONS.iter().filter(|migration| migration.version > from_version) {
+        let previous = migration.version - 1;
+        let changed_files = (migration.apply)(dir, previous)?;
+        std::fs::write(dir.join(SCHEMA_VERSION_FILE_NAME), format!("{}\n", migration.version))?;
+        if !changed_files.is_empty() {
+            applied.push(AppliedMigration {
+                version: migration.version,
+                description: migration.description,
+                backups: changed_files.iter().map(|path| backup_path(path, previous)).collect(),
+                changed_files,
+            });
+        }
+    }
+
+    Ok(Some(MigrationReport {
+        from_version,
+        to_version: CONFIG_SCHEMA_VERSION,
+        applied,
+    }))
+}
+
+/// 文件在迁移前的备份路径
+fn backup_path(path: &Path, version: u32) -> PathBuf {
+    let name = path.file_name().map(|name| name.to_string_lossy()).unwrap_or_default();
+    path.with_file_name(format!("{}.v{}.bak", name, version))
+}
+
+/// 备份后写入新内容，同一版本的备份已存在时保留最早的备份
+fn write_with_backup(path: &Path, version: u32, content: &str) -> Result<(), LogidError> {
+    let backup = backup_path(path, version);
+    if !backup.exists() {
+        std::fs::copy(path, &backup)?;
+    }
+    std::fs::write(path, content)?;
+    Ok(())
+}
+
+/// 版本 1：统一 I18N 区域凭据变量的大小写
+///
+/// 新名称已存在时保留原样，避免覆盖用户手动添加的值。
+fn rename_i18n_session_var(dir: &Path, version: u32) -> Result<Vec<PathBuf>, LogidError> {
+    const OLD: &str = "CAS_SESSION_I18n";
+    const NEW: &str = "CAS_SESSION_I18N";
+
+    let path = dir.join(ENV_FILE_NAME);
+    let Ok(content) = std::fs::read_to_string(&path) else {
+        return Ok(Vec::new());
+    };
+    let key_of = |line: &str| -> String {
+        let line = line.trim_start();
+        let line = line.strip_prefix("export ").unwrap_or(line);
+        line.split('=').next().unwrap_or("").trim().to_string()
+    };
+    let lines: Vec<&str> = content.lines().collect();
+    let has_key = |key: &str| lines.iter().any(|line| line.contains('=') && key_of(line) == key);
+    if !has_key(OLD) || has_key(NEW) {
+        return Ok(Vec::new());
+    }
+
+    let migrated: Vec<String> = lines
+        .iter()
+        .map(|line| {
+            if line.contains('=') && key_of(line) == OLD {
+                line.replacen(OLD, NEW, 1)
+            } else {
+                line.to_string()
+            }
+        })
+        .collect();
+    write_with_backup(&path, version, &(migrated.join("\n") + "\n"))?;
+    Ok(vec![path])
+}
+
+/// 版本 2：过滤规则文件使用统一的 `msg_filters` 键
+fn rename_legacy_filter_keys(dir: &Path, version: u32) -> Result<Vec<PathBuf>, LogidError> {
+    let path = dir.join(USER_FILTERS_FILE);
+    let Ok(content) = std::fs::read_to_string(&path) else {
+        return Ok(Vec::new());
+    };
+    // 无法解析的文件留给过滤规则加载时报告
+    let Ok(serde_json::Value::Object(mut object)) = serde_json::from_str(&content) else {
+        return Ok(Vec::new());
+    };
+    if object.contains_key("msg_filters") {
+        return Ok(Vec::new());
+    }
+    let legacy = ["_msg_filters", "patterns"]
+        .into_iter()
+        .find_map(|key| object.remove(key));
+    let Some(patterns) = legacy else {
+        return Ok(Vec::new());
+    };
+
+    object.insert("msg_filters".to_string(), patterns);
+    let migrated = serde_json::to_string_pretty(&serde_json::Value::Object(object))?;
+    write_with_backup(&path, version, &(migrated + "\n"))?;
+    Ok(vec![path])
+}
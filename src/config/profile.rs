@@ -0,0 +1,154 @@
+//! 命名配置档模块
+//!
+//! 在 `~/.config/logid/profiles.json` 中定义多个配置档，每个配置档包含各区域的
+//! CAS_SESSION、默认区域、默认 PSM、代理和输出格式，通过 `--profile` 或
+//! `LOGID_PROFILE` 环境变量选择：
+//!
+//! ```json
+//! {
+//!   "profiles": {
+//!     "work-us": {
+//!       "cas_sessions": {"us": "...", "i18n": "..."},
+//!       "region": "us",
+//!       "psm": ["pay.api"],
+//!       "proxy": "http://127.0.0.1:7890",
+//!       "format": "table"
+//!     }
+//!   }
+//! }
+//! ```
+
+use crate::config::env::user_config_dir;
+use crate::config::schema::{json_error, validate_proxy, SchemaIssue};
+use crate::config::Region;
+use crate::error::LogidError;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+
+/// 选择配置档的环境变量
+pub const PROFILE_ENV_VAR: &str = "LOGID_PROFILE";
+
+/// 配置档文件名
+const PROFILES_FILE_NAME: &str = "profiles.json";
+
+/// 当前生效的配置档
+static ACTIVE_PROFILE: RwLock<Option<(String, Profile)>> = RwLock::new(None);
+
+/// 获取用户级别配置档文件路径 (~/.config/logid/profiles.json)
+pub fn user_profiles_path() -> Option<PathBuf> {
+    user_config_dir().map(|dir| dir.join(PROFILES_FILE_NAME))
+}
+
+/// 一个命名配置档
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Profile {
+    /// 各区域的 CAS_SESSION，键为区域名称（us/i18n/cn/eu）
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub cas_sessions: BTreeMap<String, String>,
+    /// 未指定 --region 时使用的区域
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub region: Option<String>,
+    /// 未指定 --psm 时使用的 PSM 列表
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub psm: Vec<String>,
+    /// HTTP(S) 代理，优先于 HTTPS_PROXY/HTTP_PROXY 环境变量
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub proxy: Option<String>,
+    /// 未指定 --format 时使用的输出格式
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub format: Option<String>,
+}
+
+impl Profile {
+    /// 配置档中的凭据对应的环境变量（如 `CAS_SESSION_US`），忽略未知区域和空值
+    pub fn cas_session_vars(&self) -> Vec<(&'static str, String)> {
+        self.cas_sessions
+            .iter()
+            .filter(|(_, session)| !session.is_empty())
+            .filter_map(|(region, session)| {
+                Region::from_str(region).map(|region| (region.cas_session_env_var(), session.clone()))
+            })
+            .collect()
+    }
+}
+
+/// 配置档文件
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ProfileConfig {
+    /// 按名称索引的配置档
+    #[serde(default)]
+    pub profiles: BTreeMap<String, Profile>,
+}
+
+impl ProfileConfig {
+    /// 从文件加载配置档，文件不存在时返回空配置
+    pub fn load(path: &Path) -> Result<Self, LogidError> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = std::fs::read_to_string(path)?;
+        let config: Self = serde_json::from_str(&content).map_err(|e| json_error(path, &e))?;
+        config
+            .validate()
+            .map_err(|issue| issue.into_json_error(path, &content))?;
+        Ok(config)
+    }
+
+    /// 从用户配置目录加载配置档
+    pub fn load_default() -> Result<Self, LogidError> {
+        match user_profiles_path() {
+            Some(path) => Self::load(&path),
+            None => Ok(Self::default()),
+        }
+    }
+
+    /// 检查配置档中的代理地址
+    fn validate(&self) -> Result<(), SchemaIssue> {
+        for (name, profile) in &self.profiles {
+            if let Some(proxy) = &profile.proxy {
+                validate_proxy(&format!("profiles.{}.proxy", name), proxy)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// 按名称获取配置档
+    pub fn get(&self, name: &str) -> Result<&Profile, LogidError> {
+        self.profiles
+            .get(name)
+            .ok_or_else(|| LogidError::ProfileNotFound(name.to_string()))
+    }
+}
+
+/// 确定要使用的配置档名称：显式指定的名称优先，其次是 `LOGID_PROFILE` 环境变量
+pub fn requested_profile(explicit: Option<&str>) -> Option<String> {
+    explicit
+        .map(str::to_string)
+        .or_else(|| std::env::var(PROFILE_ENV_VAR).ok())
+        .filter(|name| !name.is_empty())
+}
+
+/// 从用户配置目录加载并启用配置档
+///
+/// 启用后 [`EnvManager`](crate::config::EnvManager) 使用配置档中的凭据，
+/// 认证和查询客户端使用配置档中的代理。
+pub fn activate_profile(name: &str) -> Result<Profile, LogidError> {
+    let profile = ProfileConfig::load_default()?.get(name)?.clone();
+    set_active_profile(name, profile.clone());
+    Ok(profile)
+}
+
+/// 启用给定的配置档，替换之前启用的配置档
+pub fn set_active_profile(name: &str, profile: Profile) {
+    conditional_info!("使用配置档: {}", name);
+    *ACTIVE_PROFILE.write().unwrap_or_else(|e| e.into_inner()) = Some((name.to_string(), profile));
+}
+
+/// 当前启用的配置档名称和内容
+pub fn active_profile() -> Option<(String, Profile)> {
+    ACTIVE_PROFILE.read().unwrap_or_else(|e| e.into_inner()).clone()
+}
@@ -1,5 +1,13 @@
 //! 区域配置模块
 
+use crate::config::file::ConfigFile;
+use crate::config::provider::{default_region_provider, RegionProvider};
+use crate::config::schema::{validate_proxy, validate_url, SchemaIssue};
+use crate::error::LogidError;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::{Once, OnceLock, RwLock};
+use std::time::Duration;
 use tracing::warn;
 
 /// 区域标识符
@@ -13,12 +21,183 @@ pub enum Region {
     Us,
     /// 欧洲区
     Eu,
+    /// config.toml 中声明的自定义区域，值为小写的区域名称
+    Custom(&'static str),
+}
+
+/// 自定义区域的名称和相关环境变量
+#[derive(Debug, Clone, Copy)]
+struct CustomRegion {
+    name: &'static str,
+    display_name: &'static str,
+    cas_session_env_var: &'static str,
+    auth_url_env_var: &'static str,
+    proxy_env_var: &'static str,
+    log_service_url_env_var: &'static str,
+    vregion_env_var: &'static str,
+}
+
+/// 已注册的自定义区域
+static CUSTOM_REGIONS: RwLock<Vec<CustomRegion>> = RwLock::new(Vec::new());
+
+/// 保证只从 config.toml 加载一次自定义区域
+static CUSTOM_REGIONS_LOADED: Once = Once::new();
+
+/// 已注册的自定义区域，首次调用时从 config.toml 加载
+fn custom_regions() -> Vec<CustomRegion> {
+    CUSTOM_REGIONS_LOADED.call_once(load_custom_regions);
+    CUSTOM_REGIONS.read().unwrap_or_else(|e| e.into_inner()).clone()
+}
+
+/// 注册 config.toml 中声明的自定义区域
+fn load_custom_regions() {
+    for (name, display_name, cas_session_env_var) in ConfigFile::load_default_lenient().custom_regions() {
+        if let Err(e) = insert_custom_region(&name, display_name.as_deref(), cas_session_env_var.as_deref()) {
+            warn!("忽略自定义区域 {}: {}", name, e);
+        }
+    }
+}
+
+/// 内置的区域别名：别名和目标区域，config.toml 的 `[region_aliases]` 可以补充或覆盖
+pub const REGION_ALIASES: &[(&str, &str)] = &[
+    ("sg", "i18n"),
+    ("singapore", "i18n"),
+    ("row", "i18n"),
+    ("ttp", "us"),
+    ("america", "us"),
+    ("europe", "eu"),
+    ("china", "cn"),
+];
+
+/// config.toml 中配置的区域别名，别名已转为小写
+static CONFIGURED_ALIASES: RwLock<Vec<(String, String)>> = RwLock::new(Vec::new());
+
+/// 保证只从 config.toml 加载一次区域别名
+static CONFIGURED_ALIASES_LOADED: Once = Once::new();
+
+/// 加载 config.toml 中的 `[region_aliases]`
+fn load_configured_aliases() {
+    let aliases = ConfigFile::load_default_lenient()
+        .region_aliases
+        .into_iter()
+        .map(|(alias, target)| (alias.to_lowercase(), target))
+        .collect();
+    *CONFIGURED_ALIASES.write().unwrap_or_else(|e| e.into_inner()) = aliases;
+}
+
+/// 所有区域别名及其目标区域，config.toml 中的别名优先于内置别名，指向未知区域的别名被忽略
+pub fn region_aliases() -> Vec<(String, Region)> {
+    CONFIGURED_ALIASES_LOADED.call_once(load_configured_aliases);
+    let configured = CONFIGURED_ALIASES.read().unwrap_or_else(|e| e.into_inner()).clone();
+    let builtin: Vec<(String, String)> = REGION_ALIASES
+        .iter()
+        .filter(|(alias, _)| !configured.iter().any(|(name, _)| name == alias))
+        .map(|(alias, target)| (alias.to_string(), target.to_string()))
+        .collect();
+
+    configured
+        .into_iter()
+        .chain(builtin)
+        .filter_map(|(alias, target)| Region::from_name(&target).map(|region| (alias, region)))
+        .collect()
+}
+
+/// 校验自定义区域名称：小写字母、数字、`-` 和 `_`，且不能与内置区域或 `all` 重名
+pub(crate) fn validate_custom_region_name(name: &str) -> Result<(), LogidError> {
+    let valid_chars = !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '_');
+    if !valid_chars || name == "all" || Region::builtin_from_str(name).is_some() {
+        return Err(LogidError::UnsupportedRegion(format!(
+            "{}（自定义区域名称只能包含小写字母、数字、- 和 _，且不能与内置区域或 all 重名）",
+            name
+        )));
+    }
+    Ok(())
+}
+
+fn insert_custom_region(
+    name: &str,
+    display_name: Option<&str>,
+    cas_session_env_var: Option<&str>,
+) -> Result<Region, LogidError> {
+    validate_custom_region_name(name)?;
+    let mut regions = CUSTOM_REGIONS.write().unwrap_or_else(|e| e.into_inner());
+    if let Some(existing) = regions.iter().find(|region| region.name == name) {
+        return Ok(Region::Custom(existing.name));
+    }
+
+    // 区域在进程内只注册一次，名称和变量名泄漏为 'static 以保持 Region 为 Copy
+    let leak = |value: String| -> &'static str { Box::leak(value.into_boxed_str()) };
+    let suffix = name.to_uppercase().replace('-', "_");
+    let region = CustomRegion {
+        name: leak(name.to_string()),
+        display_name: leak(display_name.unwrap_or(name).to_string()),
+        cas_session_env_var: leak(
+            cas_session_env_var
+                .map(str::to_string)
+                .unwrap_or_else(|| format!("CAS_SESSION_{}", suffix)),
+        ),
+        auth_url_env_var: leak(format!("LOGID_AUTH_URL_{}", suffix)),
+        proxy_env_var: leak(format!("LOGID_PROXY_{}", suffix)),
+        log_service_url_env_var: leak(format!("LOGID_LOG_SERVICE_URL_{}", suffix)),
+        vregion_env_var: leak(format!("LOGID_VREGION_{}", suffix)),
+    };
+    regions.push(region);
+    Ok(Region::Custom(region.name))
 }
 
 impl Region {
-    /// 从字符串解析区域
+    /// 所有内置区域
+    pub const ALL: [Region; 4] = [Region::Cn, Region::I18n, Region::Us, Region::Eu];
+
+    /// 所有区域：内置区域和已注册的自定义区域
+    pub fn all() -> Vec<Region> {
+        Self::ALL
+            .into_iter()
+            .chain(custom_regions().iter().map(|region| Self::Custom(region.name)))
+            .collect()
+    }
+
+    /// 注册自定义区域
+    ///
+    /// config.toml 中 `[regions.<name>]` 声明的非内置区域会自动注册；
+    /// 未指定 `cas_session_env_var` 时使用 `CAS_SESSION_<NAME>`。重复注册返回已有的区域。
+    pub fn register_custom(
+        name: &str,
+        display_name: Option<&str>,
+        cas_session_env_var: Option<&str>,
+    ) -> Result<Self, LogidError> {
+        CUSTOM_REGIONS_LOADED.call_once(load_custom_regions);
+        insert_custom_region(&name.to_lowercase(), display_name, cas_session_env_var)
+    }
+
+    /// 从字符串解析区域，包括已注册的自定义区域和区域别名（如 `sg` → `i18n`）
     #[allow(clippy::should_implement_trait)]
     pub fn from_str(region: &str) -> Option<Self> {
+        Self::from_name(region).or_else(|| {
+            let alias = region.trim().to_lowercase();
+            region_aliases()
+                .into_iter()
+                .find(|(name, _)| *name == alias)
+                .map(|(_, region)| region)
+        })
+    }
+
+    /// 按区域名称解析内置区域和自定义区域，不解析别名
+    fn from_name(region: &str) -> Option<Self> {
+        Self::builtin_from_str(region).or_else(|| {
+            let name = region.to_lowercase();
+            custom_regions()
+                .iter()
+                .find(|custom| custom.name == name)
+                .map(|custom| Self::Custom(custom.name))
+        })
+    }
+
+    /// 只解析内置区域
+    pub(crate) fn builtin_from_str(region: &str) -> Option<Self> {
         match region.to_lowercase().as_str() {
             "cn" => Some(Self::Cn),
             "i18n" => Some(Self::I18n),
@@ -35,16 +214,80 @@ impl Region {
             Self::I18n => "i18n",
             Self::Us => "us",
             Self::Eu => "eu",
+            Self::Custom(name) => name,
         }
     }
 
+    /// 自定义区域的注册信息
+    fn custom(&self) -> Option<CustomRegion> {
+        let Self::Custom(name) = self else {
+            return None;
+        };
+        custom_regions().into_iter().find(|region| region.name == *name)
+    }
+
     /// 获取区域特定的 CAS_SESSION 环境变量名
     pub fn cas_session_env_var(&self) -> &'static str {
         match self {
             Self::Cn => "CAS_SESSION_CN",
-            Self::I18n => "CAS_SESSION_I18n",
+            Self::I18n => "CAS_SESSION_I18N",
             Self::Us => "CAS_SESSION_US",
             Self::Eu => "CAS_SESSION_EU",
+            Self::Custom(_) => self.custom().map_or("CAS_SESSION", |region| region.cas_session_env_var),
+        }
+    }
+
+    /// 旧版本使用的 CAS_SESSION 环境变量名，仍然作为区域变量的回退
+    pub fn legacy_cas_session_env_var(&self) -> Option<&'static str> {
+        match self {
+            Self::I18n => Some("CAS_SESSION_I18n"),
+            _ => None,
+        }
+    }
+
+    /// 覆盖区域 JWT 认证端点的环境变量名
+    pub fn auth_url_env_var(&self) -> &'static str {
+        match self {
+            Self::Cn => "LOGID_AUTH_URL_CN",
+            Self::I18n => "LOGID_AUTH_URL_I18N",
+            Self::Us => "LOGID_AUTH_URL_US",
+            Self::Eu => "LOGID_AUTH_URL_EU",
+            Self::Custom(_) => self.custom().map_or("LOGID_AUTH_URL", |region| region.auth_url_env_var),
+        }
+    }
+
+    /// 覆盖区域代理的环境变量名称
+    pub fn proxy_env_var(&self) -> &'static str {
+        match self {
+            Self::Cn => "LOGID_PROXY_CN",
+            Self::I18n => "LOGID_PROXY_I18N",
+            Self::Us => "LOGID_PROXY_US",
+            Self::Eu => "LOGID_PROXY_EU",
+            Self::Custom(_) => self.custom().map_or("LOGID_PROXY", |region| region.proxy_env_var),
+        }
+    }
+
+    /// 提供区域日志服务 URL 的环境变量名
+    pub fn log_service_url_env_var(&self) -> &'static str {
+        match self {
+            Self::Cn => "LOGID_LOG_SERVICE_URL_CN",
+            Self::I18n => "LOGID_LOG_SERVICE_URL_I18N",
+            Self::Us => "LOGID_LOG_SERVICE_URL_US",
+            Self::Eu => "LOGID_LOG_SERVICE_URL_EU",
+            Self::Custom(_) => self
+                .custom()
+                .map_or("LOGID_LOG_SERVICE_URL", |region| region.log_service_url_env_var),
+        }
+    }
+
+    /// 提供区域虚拟区域（逗号分隔的可用区）的环境变量名
+    pub fn vregion_env_var(&self) -> &'static str {
+        match self {
+            Self::Cn => "LOGID_VREGION_CN",
+            Self::I18n => "LOGID_VREGION_I18N",
+            Self::Us => "LOGID_VREGION_US",
+            Self::Eu => "LOGID_VREGION_EU",
+            Self::Custom(_) => self.custom().map_or("LOGID_VREGION", |region| region.vregion_env_var),
         }
     }
 
@@ -55,10 +298,84 @@ impl Region {
             Self::I18n => "国际化区域（新加坡）",
             Self::Us => "美区",
             Self::Eu => "欧洲区",
+            Self::Custom(name) => self.custom().map_or(name, |region| region.display_name),
+        }
+    }
+
+    /// 指向该区域的别名
+    pub fn aliases(&self) -> Vec<String> {
+        region_aliases()
+            .into_iter()
+            .filter(|(_, region)| region == self)
+            .map(|(alias, _)| alias)
+            .collect()
+    }
+
+    /// 是否是自定义区域
+    pub fn is_custom(&self) -> bool {
+        matches!(self, Self::Custom(_))
+    }
+}
+
+/// 部署环境
+///
+/// 预发布环境的日志存放在独立的日志服务端点上，需要在配置文件中为区域单独提供。
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    Default,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Serialize,
+    Deserialize,
+    clap::ValueEnum,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum EnvTarget {
+    /// 线上环境（默认）
+    #[default]
+    Prod,
+    /// BOE 线下环境
+    Boe,
+    /// PPE 预发布环境
+    Ppe,
+}
+
+impl EnvTarget {
+    /// 转换为字符串
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Prod => "prod",
+            Self::Boe => "boe",
+            Self::Ppe => "ppe",
         }
     }
 }
 
+impl std::fmt::Display for EnvTarget {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// 某个部署环境的日志服务端点
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct EnvironmentEndpoint {
+    /// 日志服务 URL
+    pub log_service_url: String,
+    /// 虚拟区域（缺省时由 zones 拼接，zones 也为空时沿用线上配置）
+    #[serde(default)]
+    pub vregion: Option<String>,
+    /// 可用区域列表
+    #[serde(default)]
+    pub zones: Vec<String>,
+}
+
 /// 区域配置信息
 #[derive(Debug, Clone)]
 pub struct RegionConfig {
@@ -70,13 +387,82 @@ pub struct RegionConfig {
     /// 虚拟区域
     pub vregion: String,
     /// 可用区域列表
-    #[allow(dead_code)]
     pub zones: Vec<String>,
     /// 是否已配置（cn 区域可能未配置）
     pub configured: bool,
+    /// 查询请求附加的请求头
+    pub headers: HeaderProfile,
+    /// 非线上环境的端点
+    pub environments: BTreeMap<EnvTarget, EnvironmentEndpoint>,
+    /// Web 控制台链接模板，支持 `{logid}`、`{region}` 和 `{vregion}` 占位符
+    pub web_url_template: Option<String>,
+    /// JWT 认证端点，`None` 时使用内置端点
+    pub auth_url: Option<String>,
+    /// 区域专用代理，`direct` 表示不使用代理
+    pub proxy: Option<String>,
+    /// 查询未指定 PSM 时使用的 PSM 列表
+    pub default_psms: Vec<String>,
+    /// 默认的扫描时间范围（分钟），`None` 时使用内置默认值
+    pub scan_span: Option<i32>,
+    /// 默认的响应体大小上限（MB），`None` 时使用内置默认值
+    pub max_response_size_mb: Option<usize>,
+    /// 默认的请求超时时间，`None` 时使用内置默认值
+    pub request_timeout: Option<Duration>,
+}
+
+/// 区域请求头配置
+///
+/// 部分区域网关会根据请求头做不同处理，可以为每个区域单独配置。
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct HeaderProfile {
+    /// 覆盖默认的 User-Agent
+    #[serde(default)]
+    pub user_agent: Option<String>,
+    /// Referer 请求头
+    #[serde(default)]
+    pub referer: Option<String>,
+    /// Origin 请求头
+    #[serde(default)]
+    pub origin: Option<String>,
+    /// 其他自定义请求头
+    #[serde(default)]
+    pub custom: BTreeMap<String, String>,
+}
+
+impl HeaderProfile {
+    /// 按顺序列出需要附加的请求头（不含 User-Agent）
+    pub fn extra_headers(&self) -> Vec<(&str, &str)> {
+        let mut headers = Vec::new();
+        if let Some(referer) = &self.referer {
+            headers.push(("Referer", referer.as_str()));
+        }
+        if let Some(origin) = &self.origin {
+            headers.push(("Origin", origin.as_str()));
+        }
+        for (name, value) in &self.custom {
+            headers.push((name.as_str(), value.as_str()));
+        }
+        headers
+    }
+
+    /// 是否没有任何配置
+    pub fn is_empty(&self) -> bool {
+        self.user_agent.is_none()
+            && self.referer.is_none()
+            && self.origin.is_none()
+            && self.custom.is_empty()
+    }
 }
 
 impl RegionConfig {
+    /// 创建自定义区域配置的构建器，不读取 regions.json 或 config.toml
+    pub fn builder(region: Region) -> RegionConfigBuilder {
+        RegionConfigBuilder {
+            config: Self::new(region, String::new(), String::new(), Vec::new()),
+        }
+    }
+
     /// 创建区域配置
     pub fn new(
         region: Region,
@@ -90,7 +476,171 @@ impl RegionConfig {
             vregion,
             zones,
             configured: true,
+            headers: HeaderProfile::default(),
+            environments: BTreeMap::new(),
+            web_url_template: None,
+            auth_url: None,
+            proxy: None,
+            default_psms: Vec::new(),
+            scan_span: None,
+            max_response_size_mb: None,
+            request_timeout: None,
+        }
+    }
+
+    /// 设置 Web 控制台链接模板
+    pub fn with_web_url_template(mut self, template: Option<String>) -> Self {
+        self.web_url_template = template;
+        self
+    }
+
+    /// 设置 JWT 认证端点
+    pub fn with_auth_url(mut self, auth_url: Option<String>) -> Self {
+        self.auth_url = auth_url;
+        self
+    }
+
+    /// 设置区域专用代理
+    pub fn with_proxy(mut self, proxy: Option<String>) -> Self {
+        self.proxy = proxy;
+        self
+    }
+
+    /// 设置查询未指定 PSM 时使用的 PSM 列表
+    pub fn with_default_psms(mut self, default_psms: Vec<String>) -> Self {
+        self.default_psms = default_psms;
+        self
+    }
+
+    /// 设置默认的扫描时间范围（分钟）
+    pub fn with_scan_span(mut self, minutes: Option<i32>) -> Self {
+        self.scan_span = minutes;
+        self
+    }
+
+    /// 设置默认的响应体大小上限（MB）
+    pub fn with_max_response_size_mb(mut self, max_mb: Option<usize>) -> Self {
+        self.max_response_size_mb = max_mb;
+        self
+    }
+
+    /// 设置默认的请求超时时间
+    pub fn with_request_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.request_timeout = timeout;
+        self
+    }
+
+    /// 查询实际使用的 PSM 列表：未指定时使用区域的默认 PSM
+    pub fn effective_psms<'a>(&'a self, psm_list: &'a [String]) -> &'a [String] {
+        if psm_list.is_empty() {
+            &self.default_psms
+        } else {
+            psm_list
+        }
+    }
+
+    /// 生成指定 logid 在 Web 控制台中的链接，未配置模板时返回 `None`
+    pub fn web_url(&self, logid: &str) -> Option<String> {
+        let template = self.web_url_template.as_ref()?;
+        Some(
+            template
+                .replace("{logid}", &percent_encode(logid))
+                .replace("{region}", self.region.as_str())
+                .replace("{vregion}", &percent_encode(&self.vregion)),
+        )
+    }
+
+    /// 设置请求头配置
+    pub fn with_headers(mut self, headers: HeaderProfile) -> Self {
+        self.headers = headers;
+        self
+    }
+
+    /// 设置非线上环境的端点
+    pub fn with_environments(mut self, environments: BTreeMap<EnvTarget, EnvironmentEndpoint>) -> Self {
+        self.environments = environments;
+        self
+    }
+
+    /// 获取指定部署环境的区域配置
+    ///
+    /// 线上环境未单独配置时返回当前配置；其他环境必须在配置文件中提供端点，
+    /// 否则返回 `RegionNotConfigured`。请求头配置在各环境间共用。
+    pub fn for_environment(&self, env: EnvTarget) -> Result<Self, LogidError> {
+        let Some(endpoint) = self.environments.get(&env) else {
+            if env == EnvTarget::Prod {
+                return Ok(self.clone());
+            }
+            return Err(LogidError::RegionNotConfigured(format!(
+                "{}（{} 环境）",
+                self.region.as_str(),
+                env
+            )));
+        };
+
+        let vregion = match (&endpoint.vregion, endpoint.zones.is_empty()) {
+            (Some(vregion), _) => vregion.clone(),
+            (None, false) => endpoint.zones.join(","),
+            (None, true) => self.vregion.clone(),
+        };
+        let zones = if endpoint.zones.is_empty() {
+            self.zones.clone()
+        } else {
+            endpoint.zones.clone()
+        };
+
+        Ok(Self {
+            log_service_url: endpoint.log_service_url.clone(),
+            vregion,
+            zones,
+            configured: true,
+            ..self.clone()
+        })
+    }
+
+    /// 给定的可用区中属于该区域的部分，名称不区分大小写，返回配置中的写法
+    pub fn owned_zones(&self, zones: &[String]) -> Vec<String> {
+        let mut owned: Vec<String> = Vec::new();
+        for zone in zones {
+            let known = self.zones.iter().find(|known| known.eq_ignore_ascii_case(zone.trim()));
+            if let Some(known) = known.filter(|known| !owned.contains(known)) {
+                owned.push(known.clone());
+            }
         }
+        owned
+    }
+
+    /// 只查询指定的可用区
+    ///
+    /// 可用区必须属于该区域的 `zones`（不区分大小写），否则返回 `InvalidZone`；
+    /// 查询使用的 vregion 改为所选可用区的拼接。未指定可用区时返回当前配置。
+    pub fn select_zones(&self, zones: &[String]) -> Result<Self, LogidError> {
+        let unknown = zones
+            .iter()
+            .find(|zone| self.owned_zones(std::slice::from_ref(zone)).is_empty());
+        if let Some(unknown) = unknown {
+            let available = if self.zones.is_empty() {
+                "未配置 zones".to_string()
+            } else {
+                self.zones.join(", ")
+            };
+            return Err(LogidError::InvalidZone(format!(
+                "{} 不属于区域 {}（可用: {}）",
+                unknown,
+                self.region.as_str(),
+                available
+            )));
+        }
+
+        let selected = self.owned_zones(zones);
+        if selected.is_empty() {
+            return Ok(self.clone());
+        }
+        Ok(Self {
+            vregion: selected.join(","),
+            zones: selected,
+            ..self.clone()
+        })
     }
 
     /// 创建未配置的区域（主要用于 cn 区域）
@@ -101,6 +651,15 @@ impl RegionConfig {
             vregion: String::new(),
             zones: Vec::new(),
             configured: false,
+            headers: HeaderProfile::default(),
+            environments: BTreeMap::new(),
+            web_url_template: None,
+            auth_url: None,
+            proxy: None,
+            default_psms: Vec::new(),
+            scan_span: None,
+            max_response_size_mb: None,
+            request_timeout: None,
         }
     }
 
@@ -110,48 +669,190 @@ impl RegionConfig {
     }
 }
 
-/// 获取区域配置
-pub fn get_region_config(region_str: &str) -> Option<RegionConfig> {
-    let region = Region::from_str(region_str)?;
+/// [`RegionConfig`] 的构建器
+///
+/// 供把 logid 作为库使用的调用方构造完全自定义的区域配置，构建结果可以直接传给
+/// [`LogQueryClient::new`](crate::log_query::LogQueryClient::new)，
+/// 不经过 [`get_region_config`]。
+///
+/// ```no_run
+/// # fn example() -> Result<(), logid::LogidError> {
+/// use logid::config::{Region, RegionConfig};
+/// use std::time::Duration;
+///
+/// let config = RegionConfig::builder(Region::Us)
+///     .log_service_url("https://logservice.example.com/streamlog/platform/microservice/v1/query/trace")
+///     .zones(["US-East", "US-West"])
+///     .timeout(Duration::from_secs(60))
+///     .build()?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct RegionConfigBuilder {
+    config: RegionConfig,
+}
+
+impl RegionConfigBuilder {
+    /// 日志服务 URL（必填）
+    pub fn log_service_url(mut self, url: impl Into<String>) -> Self {
+        self.config.log_service_url = url.into();
+        self
+    }
+
+    /// 虚拟区域，未设置时由可用区拼接
+    pub fn vregion(mut self, vregion: impl Into<String>) -> Self {
+        self.config.vregion = vregion.into();
+        self
+    }
+
+    /// 可用区域列表
+    pub fn zones<I, S>(mut self, zones: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.config.zones = zones.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// 请求超时时间
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.config.request_timeout = Some(timeout);
+        self
+    }
+
+    /// 扫描时间范围（分钟）
+    pub fn scan_span(mut self, minutes: i32) -> Self {
+        self.config.scan_span = Some(minutes);
+        self
+    }
+
+    /// 响应体大小上限（MB）
+    pub fn max_response_size_mb(mut self, max_mb: usize) -> Self {
+        self.config.max_response_size_mb = Some(max_mb);
+        self
+    }
+
+    /// 查询请求附加的请求头
+    pub fn headers(mut self, headers: HeaderProfile) -> Self {
+        self.config.headers = headers;
+        self
+    }
+
+    /// 区域专用代理，`direct` 表示不使用代理
+    pub fn proxy(mut self, proxy: impl Into<String>) -> Self {
+        self.config.proxy = Some(proxy.into());
+        self
+    }
+
+    /// 查询未指定 PSM 时使用的 PSM 列表
+    pub fn default_psms<I, S>(mut self, psms: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.config.default_psms = psms.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Web 控制台链接模板
+    pub fn web_url_template(mut self, template: impl Into<String>) -> Self {
+        self.config.web_url_template = Some(template.into());
+        self
+    }
+
+    /// 生成区域配置
+    ///
+    /// 日志服务 URL 或代理地址无效、vregion 和可用区都未设置时返回 `InvalidRegionConfig`。
+    pub fn build(self) -> Result<RegionConfig, LogidError> {
+        let mut config = self.config;
+        let region = config.region;
+        let invalid =
+            |issue: SchemaIssue| LogidError::InvalidRegionConfig(format!("{}: {}", region.as_str(), issue));
+
+        validate_url("log_service_url", &config.log_service_url).map_err(invalid)?;
+        if let Some(proxy) = &config.proxy {
+            validate_proxy("proxy", proxy).map_err(invalid)?;
+        }
+        if config.vregion.is_empty() {
+            if config.zones.is_empty() {
+                return Err(invalid(SchemaIssue::new("vregion", "需要设置 vregion 或 zones")));
+            }
+            config.vregion = config.zones.join(",");
+        }
+        Ok(config)
+    }
+}
+
+/// 对 URL 查询参数中的值进行百分号编码
+fn percent_encode(value: &str) -> String {
+    value
+        .bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                (byte as char).to_string()
+            }
+            _ => format!("%{:02X}", byte),
+        })
+        .collect()
+}
 
-    match region {
-        Region::Cn => {
-            // CN 区域暂未提供配置，返回未配置状态
-            warn!("CN 区域日志服务配置待补充");
-            Some(RegionConfig::unconfigured(Region::Cn))
-        }
-        Region::I18n => {
-            Some(RegionConfig::new(
-                Region::I18n,
-                "https://logservice-sg.tiktok-row.org/streamlog/platform/microservice/v1/query/trace".to_string(),
-                "Singapore-Common,US-East,Singapore-Central".to_string(),
-                vec![
-                    "Singapore-Common".to_string(),
-                    "US-East".to_string(),
-                    "Singapore-Central".to_string(),
-                ],
-            ))
-        }
-        Region::Us => {
-            Some(RegionConfig::new(
-                Region::Us,
-                "https://logservice-tx.tiktok-us.org/streamlog/platform/microservice/v1/query/trace".to_string(),
-                "US-TTP,US-TTP2".to_string(),
-                vec!["US-TTP".to_string(), "US-TTP2".to_string()],
-            ))
-        }
-        Region::Eu => {
-            Some(RegionConfig::new(
-                Region::Eu,
-                "https://logservice-eu-ttp.tiktok-eu.org/streamlog/platform/microservice/v1/query/trace".to_string(),
-                "US-EastRed,EU-TTP2,EU-TTP-PPE,EU-TTP".to_string(),
-                vec![
-                    "US-EastRed".to_string(),
-                    "EU-TTP2".to_string(),
-                    "EU-TTP-PPE".to_string(),
-                    "EU-TTP".to_string(),
-                ],
-            ))
+/// 解析区域参数
+///
+/// 支持单个区域 (`us`)、逗号分隔的区域列表 (`us,i18n`) 以及 `all`（所有已配置的区域，
+/// 包括自定义区域）。返回去重后的区域名称列表。
+pub fn resolve_regions(spec: &str) -> Result<Vec<String>, LogidError> {
+    if spec.trim().eq_ignore_ascii_case("all") {
+        let regions: Vec<String> = Region::all()
+            .iter()
+            .filter(|region| {
+                get_region_config(region.as_str())
+                    .map(|config| config.is_configured())
+                    .unwrap_or(false)
+            })
+            .map(|region| region.as_str().to_string())
+            .collect();
+        return Ok(regions);
+    }
+
+    let mut regions = Vec::new();
+    for name in spec.split(',').map(str::trim).filter(|s| !s.is_empty()) {
+        let region = Region::from_str(name)
+            .ok_or_else(|| LogidError::UnsupportedRegion(name.to_string()))?;
+        let name = region.as_str().to_string();
+        if !regions.contains(&name) {
+            regions.push(name);
         }
     }
+
+    if regions.is_empty() {
+        return Err(LogidError::UnsupportedRegion(spec.to_string()));
+    }
+    Ok(regions)
+}
+
+/// 扫描时间范围的有效取值（分钟）
+pub const SCAN_SPAN_RANGE: std::ops::RangeInclusive<i32> = 1..=1440;
+
+/// config.toml 顶层的 `scan_span` 和 `max_response_size`，首次使用时加载
+static CONFIG_FILE_LIMITS: OnceLock<(Option<i32>, Option<usize>)> = OnceLock::new();
+
+/// 获取区域配置
+///
+/// 配置由默认的区域提供者链给出，见 [`default_region_provider`]。
+/// 区域未单独配置扫描时间范围和响应体大小上限时，使用 config.toml 顶层的 `scan_span` 和
+/// `max_response_size`（每个进程只读取一次）。
+///
+/// [`default_region_provider`]: crate::config::default_region_provider
+pub fn get_region_config(region_str: &str) -> Option<RegionConfig> {
+    let region = Region::from_str(region_str)?;
+    let config = default_region_provider().region_config(region)?;
+    let (file_scan_span, file_max_response_size) = *CONFIG_FILE_LIMITS.get_or_init(|| {
+        let file = ConfigFile::load_default_lenient();
+        (file.scan_span, file.max_response_size)
+    });
+    let scan_span = config.scan_span.or(file_scan_span);
+    let max_response_size_mb = config.max_response_size_mb.or(file_max_response_size);
+    Some(config.with_scan_span(scan_span).with_max_response_size_mb(max_response_size_mb))
 }
@@ -0,0 +1,163 @@
+//! 配置文件校验模块
+//!
+//! 在加载 config.toml、profiles.json 和 regions.json 时提前检查未知键、类型错误以及
+//! 无效的 URL 和正则表达式，并把文件、行号和出错的键统一报告为
+//! [`LogidError::ConfigError`]，避免在查询深处才失败。
+
+use crate::config::proxy::DIRECT_PROXY;
+use crate::error::LogidError;
+use std::path::Path;
+
+/// 校验发现的问题：出错的键（点分路径，如 `regions.us.auth_url`）和原因
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct SchemaIssue {
+    pub key: String,
+    pub message: String,
+}
+
+impl SchemaIssue {
+    pub(crate) fn new(key: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            key: key.into(),
+            message: message.into(),
+        }
+    }
+
+    /// 转换为 TOML 文件的配置错误，行号由键在文件中的位置确定
+    pub(crate) fn into_toml_error(self, path: &Path, content: &str) -> LogidError {
+        LogidError::ConfigError {
+            path: path.display().to_string(),
+            line: locate_toml_key(content, &self.key),
+            key: Some(self.key),
+            message: self.message,
+        }
+    }
+
+    /// 转换为 JSON 文件的配置错误，行号由键在文件中首次出现的位置确定
+    pub(crate) fn into_json_error(self, path: &Path, content: &str) -> LogidError {
+        let name = self.key.rsplit('.').next().unwrap_or(&self.key);
+        let quoted = format!("\"{}\"", name);
+        LogidError::ConfigError {
+            path: path.display().to_string(),
+            line: content.lines().position(|line| line.contains(&quoted)).map(|index| index + 1),
+            key: Some(self.key),
+            message: self.message,
+        }
+    }
+}
+
+impl std::fmt::Display for SchemaIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+/// 把 TOML 解析错误转换为配置错误
+///
+/// 未知键的错误位置指向键本身，类型错误指向值，两种情况都取所在行 `=` 之前的文本作为键。
+pub(crate) fn toml_error(path: &Path, content: &str, error: &toml::de::Error) -> LogidError {
+    let line = error
+        .span()
+        .map(|span| content[..span.start.min(content.len())].matches('\n').count() + 1);
+    let key = unknown_field(error.message()).or_else(|| {
+        let text = content.lines().nth(line? - 1)?;
+        let (key, _) = text.split_once('=')?;
+        Some(key.trim().trim_matches('"').to_string()).filter(|key| !key.is_empty())
+    });
+    LogidError::ConfigError {
+        path: path.display().to_string(),
+        line,
+        key,
+        message: error.message().trim().to_string(),
+    }
+}
+
+/// 把 JSON 解析错误转换为配置错误
+pub(crate) fn json_error(path: &Path, error: &serde_json::Error) -> LogidError {
+    let message = error.to_string();
+    // serde_json 的错误信息末尾带有 "at line X column Y"，行号单独报告
+    let message = match message.rfind(" at line ") {
+        Some(index) => message[..index].to_string(),
+        None => message,
+    };
+    LogidError::ConfigError {
+        path: path.display().to_string(),
+        line: Some(error.line()).filter(|line| *line > 0),
+        key: unknown_field(&message),
+        message,
+    }
+}
+
+/// 从 serde 的 "unknown field `name`" 错误信息中取出键名
+fn unknown_field(message: &str) -> Option<String> {
+    let rest = message.split("unknown field `").nth(1)?;
+    rest.split('`').next().map(str::to_string)
+}
+
+/// 查找点分路径的键在 TOML 文件中所在的行（从 1 开始）
+///
+/// 只识别 `[table]` 表头下的 `key = value` 形式，找不到时返回表头所在的行。
+pub(crate) fn locate_toml_key(content: &str, dotted_key: &str) -> Option<usize> {
+    let mut table = String::new();
+    let mut table_line = None;
+    for (index, line) in content.lines().enumerate() {
+        let line = line.trim();
+        if let Some(header) = line.strip_prefix('[').and_then(|rest| rest.strip_suffix(']')) {
+            table = header
+                .trim_matches(|c| c == '[' || c == ']')
+                .split('.')
+                .map(|part| part.trim().trim_matches('"'))
+                .collect::<Vec<_>>()
+                .join(".");
+            if table == dotted_key {
+                table_line = Some(index + 1);
+            }
+            continue;
+        }
+        let Some((key, _)) = line.split_once('=') else {
+            continue;
+        };
+        let key = key.trim().trim_matches('"');
+        let full_key = if table.is_empty() {
+            key.to_string()
+        } else {
+            format!("{}.{}", table, key)
+        };
+        if full_key == dotted_key {
+            return Some(index + 1);
+        }
+    }
+    table_line.or_else(|| {
+        let (parent, _) = dotted_key.rsplit_once('.')?;
+        locate_toml_key(content, parent)
+    })
+}
+
+/// 检查服务端点 URL：必须是 http 或 https 的绝对地址
+pub(crate) fn validate_url(key: &str, url: &str) -> Result<(), SchemaIssue> {
+    match reqwest::Url::parse(url) {
+        Ok(parsed) if matches!(parsed.scheme(), "http" | "https") && parsed.has_host() => Ok(()),
+        Ok(parsed) => Err(SchemaIssue::new(
+            key,
+            format!("{} 不是有效的 http(s) 地址: {}", key, parsed),
+        )),
+        Err(e) => Err(SchemaIssue::new(key, format!("{} 不是有效的 URL ({}): {}", key, e, url))),
+    }
+}
+
+/// 检查代理地址，`direct` 表示不使用代理
+pub(crate) fn validate_proxy(key: &str, url: &str) -> Result<(), SchemaIssue> {
+    if url.is_empty() || url.eq_ignore_ascii_case(DIRECT_PROXY) {
+        return Ok(());
+    }
+    reqwest::Proxy::all(url)
+        .map(|_| ())
+        .map_err(|e| SchemaIssue::new(key, format!("{} 不是有效的代理地址 ({}): {}", key, e, url)))
+}
+
+/// 检查正则表达式
+pub(crate) fn validate_regex(key: &str, pattern: &str) -> Result<(), SchemaIssue> {
+    regex::Regex::new(pattern).map(|_| ()).map_err(|e| {
+        SchemaIssue::new(key, format!("{} 中的正则表达式无效: {}\n{}", key, pattern, e))
+    })
+}
@@ -0,0 +1,148 @@
+//! 凭据加密模块
+//!
+//! `logid auth encrypt` 把 .env（或配置档）中的 CAS_SESSION 加密为 `enc:v1:<base64>` 形式，
+//! 查询时再用口令解密。口令依次来自：
+//!
+//! 1. `--key-file` 或 `LOGID_KEY_FILE` 指定的密钥文件（文件内容即口令）
+//! 2. `LOGID_PASSPHRASE` 环境变量
+//! 3. 终端交互输入（同一进程内只询问一次）
+//!
+//! 密钥由口令经 Argon2id 派生，每个值使用独立的随机盐和随机 nonce，以 XChaCha20-Poly1305 加密。
+
+use crate::error::LogidError;
+use argon2::Argon2;
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use chacha20poly1305::aead::{Aead, KeyInit};
+use chacha20poly1305::{XChaCha20Poly1305, XNonce};
+use std::io::IsTerminal;
+use std::path::PathBuf;
+use std::sync::RwLock;
+
+/// 加密值的前缀
+pub const ENCRYPTED_PREFIX: &str = "enc:v1:";
+
+/// 提供口令的环境变量
+pub const PASSPHRASE_ENV_VAR: &str = "LOGID_PASSPHRASE";
+
+/// 指定密钥文件的环境变量
+pub const KEY_FILE_ENV_VAR: &str = "LOGID_KEY_FILE";
+
+/// 盐的长度（字节）
+const SALT_LEN: usize = 16;
+/// XChaCha20-Poly1305 nonce 的长度（字节）
+const NONCE_LEN: usize = 24;
+
+/// 命令行 `--key-file` 指定的密钥文件
+static KEY_FILE_PATH: RwLock<Option<PathBuf>> = RwLock::new(None);
+
+/// 本进程中交互输入过的口令，避免多个区域重复询问
+static PROMPTED_PASSPHRASE: RwLock<Option<String>> = RwLock::new(None);
+
+/// 设置进程内使用的密钥文件，优先于 `LOGID_KEY_FILE` 和 `LOGID_PASSPHRASE`
+pub fn set_key_file_path(path: Option<PathBuf>) {
+    *KEY_FILE_PATH.write().unwrap_or_else(|e| e.into_inner()) = path;
+}
+
+/// 当前使用的密钥文件：`--key-file` 优先，其次是 `LOGID_KEY_FILE` 环境变量
+pub fn key_file_path() -> Option<PathBuf> {
+    KEY_FILE_PATH
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .clone()
+        .or_else(|| std::env::var_os(KEY_FILE_ENV_VAR).filter(|path| !path.is_empty()).map(PathBuf::from))
+}
+
+/// 值是否为 `auth encrypt` 加密后的形式
+pub fn is_encrypted(value: &str) -> bool {
+    value.starts_with(ENCRYPTED_PREFIX)
+}
+
+/// 用口令加密一个值，返回 `enc:v1:<base64>`
+pub fn encrypt_secret(plaintext: &str, passphrase: &str) -> Result<String, LogidError> {
+    let mut salt = [0u8; SALT_LEN];
+    let mut nonce = [0u8; NONCE_LEN];
+    getrandom::getrandom(&mut salt)
+        .and_then(|_| getrandom::getrandom(&mut nonce))
+        .map_err(|e| LogidError::InternalError(format!("生成随机数失败: {}", e)))?;
+
+    let cipher = cipher(passphrase, &salt)?;
+    let ciphertext = cipher
+        .encrypt(XNonce::from_slice(&nonce), plaintext.as_bytes())
+        .map_err(|_| LogidError::InternalError("加密失败".to_string()))?;
+
+    let mut payload = Vec::with_capacity(SALT_LEN + NONCE_LEN + ciphertext.len());
+    payload.extend_from_slice(&salt);
+    payload.extend_from_slice(&nonce);
+    payload.extend_from_slice(&ciphertext);
+    Ok(format!("{}{}", ENCRYPTED_PREFIX, STANDARD.encode(payload)))
+}
+
+/// 用口令解密 `enc:v1:<base64>` 形式的值
+pub fn decrypt_secret(value: &str, passphrase: &str) -> Result<String, LogidError> {
+    let invalid = || LogidError::CredentialDecryptionFailed("加密值格式无效".to_string());
+    let payload = value
+        .strip_prefix(ENCRYPTED_PREFIX)
+        .and_then(|encoded| STANDARD.decode(encoded.trim()).ok())
+        .filter(|payload| payload.len() > SALT_LEN + NONCE_LEN)
+        .ok_or_else(invalid)?;
+    let (salt, rest) = payload.split_at(SALT_LEN);
+    let (nonce, ciphertext) = rest.split_at(NONCE_LEN);
+
+    let plaintext = cipher(passphrase, salt)?
+        .decrypt(XNonce::from_slice(nonce), ciphertext)
+        .map_err(|_| LogidError::CredentialDecryptionFailed("口令错误或加密值已损坏".to_string()))?;
+    String::from_utf8(plaintext).map_err(|_| invalid())
+}
+
+/// 由口令和盐派生密钥
+fn cipher(passphrase: &str, salt: &[u8]) -> Result<XChaCha20Poly1305, LogidError> {
+    let mut key = [0u8; 32];
+    Argon2::default()
+        .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+        .map_err(|e| LogidError::InternalError(format!("派生密钥失败: {}", e)))?;
+    Ok(XChaCha20Poly1305::new(&key.into()))
+}
+
+/// 获取解密凭据的口令
+///
+/// 依次使用密钥文件、`LOGID_PASSPHRASE` 和交互输入；`confirm` 为 true 时交互输入需要重复确认。
+/// 非交互环境下没有可用的口令时返回错误。
+pub fn passphrase(confirm: bool) -> Result<String, LogidError> {
+    if let Some(path) = key_file_path() {
+        let content = std::fs::read_to_string(&path).map_err(|e| {
+            LogidError::MissingCredentials(format!("无法读取密钥文件 {}: {}", path.display(), e))
+        })?;
+        let key = content.trim_end_matches(['\r', '\n']);
+        if key.is_empty() {
+            return Err(LogidError::MissingCredentials(format!("密钥文件为空: {}", path.display())));
+        }
+        return Ok(key.to_string());
+    }
+    if let Some(passphrase) = std::env::var(PASSPHRASE_ENV_VAR).ok().filter(|p| !p.is_empty()) {
+        return Ok(passphrase);
+    }
+    if let Some(passphrase) = PROMPTED_PASSPHRASE.read().unwrap_or_else(|e| e.into_inner()).clone() {
+        return Ok(passphrase);
+    }
+
+    if !std::io::stdin().is_terminal() {
+        return Err(LogidError::MissingCredentials(format!(
+            "CAS_SESSION 已加密，请通过 --key-file、{} 或 {} 提供口令",
+            KEY_FILE_ENV_VAR, PASSPHRASE_ENV_VAR
+        )));
+    }
+    let read = |prompt: &str| {
+        rpassword::prompt_password(prompt)
+            .map_err(|e| LogidError::InternalError(format!("读取口令失败: {}", e)))
+    };
+    let passphrase = read("🔐 凭据口令: ")?;
+    if passphrase.is_empty() {
+        return Err(LogidError::MissingCredentials("口令不能为空".to_string()));
+    }
+    if confirm && read("🔐 再次输入口令: ")? != passphrase {
+        return Err(LogidError::MissingCredentials("两次输入的口令不一致".to_string()));
+    }
+    *PROMPTED_PASSPHRASE.write().unwrap_or_else(|e| e.into_inner()) = Some(passphrase.clone());
+    Ok(passphrase)
+}
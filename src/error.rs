@@ -4,6 +4,22 @@
 
 use thiserror::Error;
 
+/// 进程退出码，便于脚本区分失败原因
+pub mod exit_code {
+    /// 成功且查询到消息
+    pub const SUCCESS: u8 = 0;
+    /// 其他错误
+    pub const FAILURE: u8 = 1;
+    /// 查询成功但没有找到任何消息
+    pub const NO_MESSAGES: u8 = 3;
+    /// 认证失败或缺少认证凭据
+    pub const AUTH: u8 = 4;
+    /// 网络请求失败或日志服务返回错误
+    pub const NETWORK: u8 = 5;
+    /// 区域、环境变量或过滤规则配置错误
+    pub const CONFIG: u8 = 6;
+}
+
 /// 应用主错误类型
 #[derive(Error, Debug)]
 pub enum LogidError {
@@ -16,9 +32,18 @@ pub enum LogidError {
     #[error("认证失败: {0}")]
     AuthenticationFailed(String),
 
+    #[error("{region} 区域的 CAS_SESSION 已过期或无效")]
+    SessionExpired {
+        /// 会话失效的区域
+        region: String,
+    },
+
     #[error("缺少认证凭据: {0}")]
     MissingCredentials(String),
 
+    #[error("解密 CAS_SESSION 失败: {0}")]
+    CredentialDecryptionFailed(String),
+
     #[error("日志查询失败 [区域: {0}]: {1}")]
     QueryFailed(String, #[source] anyhow::Error),
 
@@ -35,15 +60,124 @@ pub enum LogidError {
     EnvError(#[from] dotenvy::Error),
 
     #[error("环境配置文件未找到: {0}")]
-    #[allow(dead_code)]
     EnvFileNotFound(String),
 
     #[error("IO 错误: {0}")]
     IoError(#[from] std::io::Error),
 
+    #[error("配置档不存在: {0}")]
+    ProfileNotFound(String),
+
     #[error("过滤配置文件格式错误: {0}")]
     FilterConfigError(String),
 
+    #[error("配置文件错误 {}: {message}", config_location(.path, *.line, .key.as_deref()))]
+    ConfigError {
+        /// 出错的配置文件
+        path: String,
+        /// 出错的行号（从 1 开始），无法确定时为 `None`
+        line: Option<usize>,
+        /// 出错的键（点分路径），无法确定时为 `None`
+        key: Option<String>,
+        /// 错误原因
+        message: String,
+    },
+
+    #[error("过滤表达式错误: {0}")]
+    InvalidFilterExpression(String),
+
+    #[error("时间范围错误: {0}")]
+    InvalidTimeRange(String),
+
+    #[error("无效的可用区: {0}")]
+    InvalidZone(String),
+
+    #[error("区域配置无效: {0}")]
+    InvalidRegionConfig(String),
+
+    #[error("无效的 logid: {0}")]
+    InvalidLogid(String),
+
     #[error("内部错误: {0}")]
     InternalError(String),
-}
\ No newline at end of file
+}
+
+impl LogidError {
+    /// 该错误对应的进程退出码，取值见 [`exit_code`]
+    pub fn exit_code(&self) -> u8 {
+        match self {
+            Self::AuthenticationFailed(_)
+            | Self::SessionExpired { .. }
+            | Self::MissingCredentials(_)
+            | Self::CredentialDecryptionFailed(_) => exit_code::AUTH,
+            Self::NetworkError(_) | Self::QueryFailed(..) => exit_code::NETWORK,
+            Self::UnsupportedRegion(_)
+            | Self::RegionNotConfigured(_)
+            | Self::EnvError(_)
+            | Self::EnvFileNotFound(_)
+            | Self::ProfileNotFound(_)
+            | Self::FilterConfigError(_)
+            | Self::ConfigError { .. }
+            | Self::InvalidZone(_)
+            | Self::InvalidRegionConfig(_) => exit_code::CONFIG,
+            Self::JsonParseError(_)
+            | Self::RegexError(_)
+            | Self::IoError(_)
+            | Self::InvalidFilterExpression(_)
+            | Self::InvalidTimeRange(_)
+            | Self::InvalidLogid(_)
+            | Self::InternalError(_) => exit_code::FAILURE,
+        }
+    }
+
+    /// 稳定的错误代码，供 `--json-errors` 等机器可读的输出使用
+    pub fn error_code(&self) -> &'static str {
+        match self {
+            Self::UnsupportedRegion(_) => "unsupported_region",
+            Self::RegionNotConfigured(_) => "region_not_configured",
+            Self::AuthenticationFailed(_) => "authentication_failed",
+            Self::SessionExpired { .. } => "session_expired",
+            Self::MissingCredentials(_) => "missing_credentials",
+            Self::CredentialDecryptionFailed(_) => "credential_decryption_failed",
+            Self::QueryFailed(..) => "query_failed",
+            Self::NetworkError(_) => "network_error",
+            Self::JsonParseError(_) => "json_parse_error",
+            Self::RegexError(_) => "regex_error",
+            Self::EnvError(_) => "env_error",
+            Self::EnvFileNotFound(_) => "env_file_not_found",
+            Self::IoError(_) => "io_error",
+            Self::ProfileNotFound(_) => "profile_not_found",
+            Self::FilterConfigError(_) => "filter_config_error",
+            Self::ConfigError { .. } => "config_error",
+            Self::InvalidFilterExpression(_) => "invalid_filter_expression",
+            Self::InvalidTimeRange(_) => "invalid_time_range",
+            Self::InvalidZone(_) => "invalid_zone",
+            Self::InvalidRegionConfig(_) => "invalid_region_config",
+            Self::InvalidLogid(_) => "invalid_logid",
+            Self::InternalError(_) => "internal_error",
+        }
+    }
+
+    /// 错误涉及的区域，无法确定时返回 `None`
+    pub fn region(&self) -> Option<&str> {
+        match self {
+            Self::UnsupportedRegion(region)
+            | Self::RegionNotConfigured(region)
+            | Self::SessionExpired { region }
+            | Self::QueryFailed(region, _) => Some(region),
+            _ => None,
+        }
+    }
+}
+
+/// 配置错误的位置描述，如 `config.toml 第 3 行 (regions.us.auth_url)`
+fn config_location(path: &str, line: Option<usize>, key: Option<&str>) -> String {
+    let mut location = path.to_string();
+    if let Some(line) = line {
+        location.push_str(&format!(" 第 {} 行", line));
+    }
+    if let Some(key) = key {
+        location.push_str(&format!(" ({})", key));
+    }
+    location
+}
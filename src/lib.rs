@@ -15,9 +15,24 @@
 // 公共宏定义 - 必须在所有模块声明之前
 // ============================================================================
 
+/// 日志开关状态：未调用 `init_logging` 时回退到 ENABLE_LOGGING 环境变量
+static LOGGING_STATE: std::sync::atomic::AtomicU8 = std::sync::atomic::AtomicU8::new(LOGGING_UNSET);
+const LOGGING_UNSET: u8 = 0;
+const LOGGING_ENABLED: u8 = 1;
+const LOGGING_DISABLED: u8 = 2;
+
 /// 检查日志是否启用
 #[doc(hidden)]
 pub fn __is_logging_enabled() -> bool {
+    match LOGGING_STATE.load(std::sync::atomic::Ordering::Relaxed) {
+        LOGGING_ENABLED => true,
+        LOGGING_DISABLED => false,
+        _ => env_logging_enabled(),
+    }
+}
+
+/// ENABLE_LOGGING 环境变量是否启用日志
+fn env_logging_enabled() -> bool {
     std::env::var("ENABLE_LOGGING")
         .map(|v| {
             let v = v.to_lowercase();
@@ -26,7 +41,36 @@ pub fn __is_logging_enabled() -> bool {
         .unwrap_or(false)
 }
 
-/// 条件日志宏，只在 ENABLE_LOGGING 环境变量启用时输出
+/// 未指定日志级别时的默认值：设置了 ENABLE_LOGGING 时为 info，否则只输出错误
+pub fn default_log_level() -> tracing::level_filters::LevelFilter {
+    if env_logging_enabled() {
+        tracing::level_filters::LevelFilter::INFO
+    } else {
+        tracing::level_filters::LevelFilter::ERROR
+    }
+}
+
+/// 初始化诊断日志，输出到标准错误
+///
+/// 级别为 info 及以上时启用 `conditional_info!`；`LevelFilter::OFF` 关闭所有日志。
+/// 已经设置过全局日志订阅者时只更新 `conditional_info!` 的开关。
+pub fn init_logging(level: tracing::level_filters::LevelFilter) {
+    let state = if level >= tracing::level_filters::LevelFilter::INFO {
+        LOGGING_ENABLED
+    } else {
+        LOGGING_DISABLED
+    };
+    LOGGING_STATE.store(state, std::sync::atomic::Ordering::Relaxed);
+
+    let _ = tracing_subscriber::fmt()
+        .with_max_level(level)
+        .with_writer(std::io::stderr)
+        .with_ansi(false)
+        .compact()
+        .try_init();
+}
+
+/// 条件日志宏，只在启用日志（`-v` 或 ENABLE_LOGGING 环境变量）时输出
 #[macro_export]
 macro_rules! conditional_info {
     ($($arg:tt)*) => {
@@ -40,25 +84,44 @@ macro_rules! conditional_info {
 // 模块声明
 // ============================================================================
 
+pub mod analysis;
 pub mod auth;
 pub mod config;
 pub mod error;
 pub mod log_query;
 pub mod output;
+pub mod query_filter;
+pub mod retry;
+pub mod store;
+#[cfg(feature = "telemetry")]
+pub mod telemetry;
+#[cfg(feature = "tui")]
+pub mod tui;
+
+#[cfg(test)]
+mod test_support;
 
 // 重新导出主要的公共类型和函数
-pub use auth::{AuthManager, MultiRegionAuthManager};
+pub use analysis::{ResultStats, StatsOptions};
+pub use auth::{AuthManager, AuthProvider, MultiRegionAuthManager, PreflightReport};
 pub use config::{
-    create_message_filters, get_default_filters, get_region_config, EnvManager, FilterConfig,
-    JwtInfo, Region, RegionConfig,
+    create_message_filters, create_message_filters_lenient, get_default_filters,
+    get_region_config, resolve_regions, CompiledFilters, ConfigFile, CredentialExpiry, EnvManager,
+    FilterConfig, FilterDiagnostic, JwtInfo, KeyFilter, Region, RegionConfig,
 };
 pub use error::LogidError;
 pub use log_query::{
     DetailedLogResult, ExtractedLogMessage, ExtractedValue, LogGroup, LogMeta, LogQueryClient,
-    LogQueryRequest, LogQueryResponse, MultiRegionLogQuery,
+    LogQueryRequest, LogQueryResponse, MultiLogResult, MultiRegionLogQuery, ProgressEvent,
 };
 pub use output::{
-    print_json_output, write_to_file, OutputConfig, OutputFormatter,
+    print_json_output, write_text_to_file, write_to_file, OutputConfig, OutputFormat,
+    OutputFormatter, OutputPipelineBuilder,
+};
+pub use query_filter::{GrepFilter, WhereFilter};
+pub use retry::RetryPolicy;
+pub use store::{
+    Annotation, AnnotationStore, HistoryEntry, HistoryStore, QueueEntry, QueueStatus, QueueStore,
 };
 
 /// 库版本信息
@@ -0,0 +1,101 @@
+//! 批量日志查询模块
+
+use crate::error::LogidError;
+use crate::log_query::client::LogQueryClient;
+use crate::log_query::types::DetailedLogResult;
+use futures::stream::{self, Stream, StreamExt};
+use std::sync::Arc;
+
+/// 默认的批量查询并发数
+pub const DEFAULT_BATCH_CONCURRENCY: usize = 4;
+
+/// 单个 logid 的批量查询结果
+#[derive(Debug)]
+pub struct BatchItemResult {
+    /// 日志 ID
+    pub logid: String,
+    /// 查询结果
+    pub result: Result<DetailedLogResult, LogidError>,
+}
+
+/// 批量日志查询器
+///
+/// 使用同一个查询客户端并发查询多个 logid，并发数受 `concurrency` 限制。
+#[derive(Debug, Clone)]
+pub struct BatchQuery {
+    /// 共享的查询客户端
+    client: Arc<LogQueryClient>,
+    /// 最大并发数
+    concurrency: usize,
+}
+
+impl BatchQuery {
+    /// 创建批量查询器
+    pub fn new(client: Arc<LogQueryClient>, concurrency: usize) -> Self {
+        Self {
+            client,
+            concurrency: concurrency.max(1),
+        }
+    }
+
+    /// 以流的形式返回查询结果，结果顺序与输入顺序一致
+    pub fn stream<'a>(
+        &'a self,
+        logids: Vec<String>,
+        psm_list: &'a [String],
+    ) -> impl Stream<Item = BatchItemResult> + 'a {
+        stream::iter(logids)
+            .map(move |logid| {
+                let client = Arc::clone(&self.client);
+                async move {
+                    let result = client.get_log_details(&logid, psm_list).await;
+                    BatchItemResult { logid, result }
+                }
+            })
+            .buffered(self.concurrency)
+    }
+
+    /// 查询全部 logid 并收集结果
+    pub async fn run(&self, logids: Vec<String>, psm_list: &[String]) -> Vec<BatchItemResult> {
+        self.stream(logids, psm_list).collect().await
+    }
+}
+
+/// logid 的长度范围
+const LOGID_LEN_RANGE: std::ops::RangeInclusive<usize> = 6..=128;
+
+/// 规范化从剪贴板等处粘贴的 logid
+///
+/// 去掉首尾空白和包裹的引号，要求只包含字母、数字、`-` 和 `_`，长度在 6 到 128 之间。
+pub fn normalize_logid(value: &str) -> Result<String, LogidError> {
+    let logid = value
+        .trim()
+        .trim_matches(|c| c == '"' || c == '\'' || c == '`')
+        .trim();
+    let valid = LOGID_LEN_RANGE.contains(&logid.len())
+        && logid
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if valid {
+        return Ok(logid.to_string());
+    }
+
+    // 只展示开头部分，避免把剪贴板中的大段内容打印出来
+    let preview: String = logid.chars().take(40).collect();
+    let ellipsis = if logid.chars().count() > 40 { "…" } else { "" };
+    Err(LogidError::InvalidLogid(format!("{}{}", preview, ellipsis)))
+}
+
+/// 从文本中解析 logid 列表
+///
+/// 每行一个 logid，同一行中以空白分隔的多个 logid 也会拆开，
+/// 便于直接接收 grep/awk 的输出；忽略空行和 `#` 开头的注释行。
+pub fn parse_logid_list(content: &str) -> Vec<String> {
+    content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .flat_map(str::split_whitespace)
+        .map(str::to_string)
+        .collect()
+}
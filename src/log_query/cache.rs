@@ -0,0 +1,159 @@
+//! 查询结果缓存模块
+//!
+//! 以 (区域, logid, PSM 列表) 为键的内存 LRU 缓存，条目在较短的 TTL 后过期。
+//! 批量查询用它合并重复的 logid：同一个键的并发查询依次执行，后到的直接使用缓存结果。
+
+use crate::log_query::types::DetailedLogResult;
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// 默认缓存容量
+pub const DEFAULT_CACHE_CAPACITY: usize = 256;
+
+/// 默认缓存有效期
+pub const DEFAULT_CACHE_TTL: Duration = Duration::from_secs(30);
+
+/// 缓存键
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CacheKey {
+    region: String,
+    logid: String,
+    psm_list: Vec<String>,
+}
+
+impl CacheKey {
+    /// 创建缓存键，PSM 列表顺序不影响结果
+    pub fn new(region: &str, logid: &str, psm_list: &[String]) -> Self {
+        let mut psm_list = psm_list.to_vec();
+        psm_list.sort();
+        psm_list.dedup();
+        Self {
+            region: region.to_string(),
+            logid: logid.to_string(),
+            psm_list,
+        }
+    }
+}
+
+struct CacheEntry {
+    result: DetailedLogResult,
+    inserted_at: Instant,
+}
+
+struct CacheState {
+    entries: HashMap<CacheKey, CacheEntry>,
+    /// 访问顺序，队首为最久未使用
+    order: VecDeque<CacheKey>,
+}
+
+/// 查询结果 LRU 缓存
+pub struct ResultCache {
+    capacity: usize,
+    ttl: Duration,
+    state: Mutex<CacheState>,
+    /// 正在查询的键，用于合并同一个键的并发查询
+    in_flight: Mutex<HashMap<CacheKey, Arc<tokio::sync::Mutex<()>>>>,
+}
+
+impl std::fmt::Debug for ResultCache {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ResultCache")
+            .field("capacity", &self.capacity)
+            .field("ttl", &self.ttl)
+            .field("len", &self.len())
+            .finish()
+    }
+}
+
+impl Default for ResultCache {
+    fn default() -> Self {
+        Self::new(DEFAULT_CACHE_CAPACITY, DEFAULT_CACHE_TTL)
+    }
+}
+
+impl ResultCache {
+    /// 创建缓存
+    pub fn new(capacity: usize, ttl: Duration) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            ttl,
+            state: Mutex::new(CacheState {
+                entries: HashMap::new(),
+                order: VecDeque::new(),
+            }),
+            in_flight: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// 获取键的查询锁
+    ///
+    /// 持有锁期间同一个键的其他查询会等待，拿到锁后应先检查缓存，避免重复请求日志服务。
+    pub async fn lock(&self, key: &CacheKey) -> tokio::sync::OwnedMutexGuard<()> {
+        let lock = {
+            let mut in_flight = self.in_flight.lock().unwrap_or_else(|e| e.into_inner());
+            // 清理已经没有查询持有的锁
+            in_flight.retain(|_, lock| Arc::strong_count(lock) > 1);
+            Arc::clone(in_flight.entry(key.clone()).or_default())
+        };
+        lock.lock_owned().await
+    }
+
+    /// 获取未过期的缓存结果，并将其标记为最近使用
+    pub fn get(&self, key: &CacheKey) -> Option<DetailedLogResult> {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+
+        let expired = match state.entries.get(key) {
+            Some(entry) => entry.inserted_at.elapsed() > self.ttl,
+            None => return None,
+        };
+
+        if expired {
+            state.entries.remove(key);
+            state.order.retain(|k| k != key);
+            return None;
+        }
+
+        state.order.retain(|k| k != key);
+        state.order.push_back(key.clone());
+        state.entries.get(key).map(|entry| entry.result.clone())
+    }
+
+    /// 写入查询结果，超出容量时淘汰最久未使用的条目
+    pub fn insert(&self, key: CacheKey, result: DetailedLogResult) {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+
+        state.order.retain(|k| k != &key);
+        state.order.push_back(key.clone());
+        state.entries.insert(
+            key,
+            CacheEntry {
+                result,
+                inserted_at: Instant::now(),
+            },
+        );
+
+        while state.entries.len() > self.capacity {
+            match state.order.pop_front() {
+                Some(oldest) => {
+                    state.entries.remove(&oldest);
+                }
+                None => break,
+            }
+        }
+    }
+
+    /// 当前缓存条目数（包括尚未清理的过期条目）
+    pub fn len(&self) -> usize {
+        self.state
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .entries
+            .len()
+    }
+
+    /// 缓存是否为空
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
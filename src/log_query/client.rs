@@ -1,64 +1,162 @@
 //! 日志查询客户端模块
 
-use crate::conditional_info;
-use crate::auth::AuthManager;
-use crate::config::{create_message_filters, RegionConfig};
+use crate::auth::{AuthManager, AuthProvider, DEFAULT_REQUEST_TIMEOUT};
+use crate::config::{
+    apply_filters, apply_region_config_proxy, create_message_filters_lenient, get_region_config, load_key_filter,
+    measure_message_filters, CompiledFilters, EnvTarget, FilterMode, KeyFilter, RegionConfig,
+};
 use crate::error::LogidError;
+use crate::log_query::cache::{CacheKey, ResultCache};
+use crate::log_query::dry_run::{DryRunRequest, JWT_HEADER};
+use crate::log_query::partial::extract_partial_items;
+use crate::log_query::progress::{ProgressEvent, ProgressSender};
 use crate::log_query::types::*;
+use crate::retry::RetryPolicy;
 use regex::Regex;
+use futures::future::join_all;
+use std::collections::HashSet;
+use std::sync::Arc;
 use std::time::Instant;
 use tracing::{error, warn};
 
-/// 从环境变量获取代理地址
-fn get_proxy_from_env() -> Option<reqwest::Proxy> {
-    // 优先使用 HTTPS_PROXY
-    if let Ok(proxy) = std::env::var("HTTPS_PROXY") {
-        if !proxy.is_empty() {
-            if let Ok(p) = reqwest::Proxy::https(&proxy) {
-                return Some(p);
+/// 合并各可用区的查询结果
+///
+/// 部分可用区失败时将失败原因记录为警告，全部失败时返回第一个错误。
+/// 多个可用区返回的相同日志项目只保留一次。
+fn merge_zone_responses(
+    zones: &[String],
+    responses: Vec<Result<LogQueryResponse, LogidError>>,
+) -> Result<LogQueryResponse, LogidError> {
+    let mut merged: Option<LogQueryResponse> = None;
+    let mut failures = Vec::new();
+    let mut first_error = None;
+
+    for (zone, response) in zones.iter().zip(responses) {
+        let response = match response {
+            Ok(response) => response,
+            Err(e) => {
+                failures.push(format!("可用区 {} 查询失败: {}", zone, e));
+                first_error.get_or_insert(e);
+                continue;
             }
+        };
+        let Some(merged) = merged.as_mut() else {
+            merged = Some(response);
+            continue;
+        };
+
+        merged.truncated |= response.truncated;
+        merged.bytes_downloaded += response.bytes_downloaded;
+        merged.warnings.extend(response.warnings);
+        match (merged.data.as_mut(), response.data) {
+            (Some(data), Some(other)) => data.items.extend(other.items),
+            (None, other) => merged.data = other,
+            (Some(_), None) => {}
         }
     }
-    // 其次使用 HTTP_PROXY
-    if let Ok(proxy) = std::env::var("HTTP_PROXY") {
-        if !proxy.is_empty() {
-            if let Ok(p) = reqwest::Proxy::http(&proxy) {
-                return Some(p);
-            }
-        }
+
+    let Some(mut merged) = merged else {
+        return Err(first_error.expect("至少有一个可用区"));
+    };
+    if let Some(data) = merged.data.as_mut() {
+        let mut seen = HashSet::new();
+        data.items.retain(|item| seen.insert(item.id.clone()));
     }
-    None
+    for failure in &failures {
+        warn!("{}", failure);
+    }
+    merged.warnings.extend(failures);
+    Ok(merged)
 }
 
+/// 默认的 User-Agent
+const DEFAULT_USER_AGENT: &str = "Mozilla/5.0 (Macintosh; Intel Mac OS X 10_15_7) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/140.0.0.0 Safari/537.36 Edg/140.0.0.0";
+
+/// 日志时间可能使用的键名
+const TIME_KEYS: &[&str] = &["_time", "_ts", "timestamp"];
+
+/// 默认的响应体大小上限（64 MB）
+pub const DEFAULT_MAX_RESPONSE_BYTES: usize = 64 * 1024 * 1024;
+
+/// 默认的扫描时间范围（分钟）
+pub const DEFAULT_SCAN_SPAN_MINUTES: i32 = 10;
+
+/// 结果为空时依次尝试的扫描时间范围（分钟），只使用比当前范围更大的值
+pub const AUTO_WIDEN_SCAN_SPANS: &[i32] = &[30, 60];
+
 /// 日志查询客户端
 ///
 /// 提供基于 JWT 认证的多区域日志查询功能，支持美区和国际化区域的并发查询。
 /// 该结构体封装了日志服务的 API 调用，提供统一的日志查询接口。
 #[derive(Debug)]
 pub struct LogQueryClient {
-    /// 认证管理器
-    auth_manager: AuthManager,
+    /// 认证提供者
+    auth: Arc<dyn AuthProvider>,
     /// 区域配置
     region_config: RegionConfig,
     /// 消息过滤器列表
     message_filters: Vec<Regex>,
+    /// 消息过滤模式
+    filter_mode: FilterMode,
     /// HTTP 客户端
     client: reqwest::Client,
+    /// 响应体大小上限（字节），超出后截断并部分解析
+    max_response_bytes: usize,
+    /// 进度事件发送端
+    progress: Option<ProgressSender>,
+    /// 是否跳过消息过滤和空白清理，输出原始消息
+    raw: bool,
+    /// 代码位置相关字段的键名
+    code_location_keys: CodeLocationKeys,
+    /// 按键名筛选提取的 kv 字段
+    key_filter: KeyFilter,
+    /// 查询结果缓存
+    cache: Option<Arc<ResultCache>>,
+    /// 是否在结果中附带统计信息
+    stats: bool,
+    /// 扫描时间范围（分钟）
+    scan_span_in_min: i32,
+    /// 绝对查询时间范围
+    time_range: Option<TimeRange>,
+    /// 保留的日志级别（已规范化），为空时不过滤
+    levels: Vec<String>,
+    /// 结果为空时是否自动扩大扫描范围重试
+    auto_widen: bool,
+    /// 是否按可用区分别并发查询
+    per_zone: bool,
+    /// 请求超时时间
+    timeout: std::time::Duration,
+    /// 瞬时失败的重试策略
+    retry_policy: RetryPolicy,
 }
 
 impl LogQueryClient {
     /// 创建新的日志查询客户端
+    ///
+    /// `region_config` 可以来自 [`get_region_config`]，也可以由 [`RegionConfig::builder`]
+    /// 完全自定义；客户端的端点、代理、超时等只取自该配置。
     pub async fn new(
         auth_manager: AuthManager,
         region_config: RegionConfig,
     ) -> Result<Self, LogidError> {
-        // 创建消息过滤器
-        let message_filters = create_message_filters(None)?;
+        Self::from_auth_provider(Arc::new(auth_manager), region_config).await
+    }
+
+    /// 使用自定义认证提供者创建日志查询客户端
+    ///
+    /// 适用于不使用 CAS_SESSION 的场景，例如直接提供令牌的 `EnvTokenProvider`、`FileTokenProvider`。
+    pub async fn from_auth_provider(
+        auth: Arc<dyn AuthProvider>,
+        region_config: RegionConfig,
+    ) -> Result<Self, LogidError> {
+        // 创建消息过滤器，跳过无效规则而不是中断查询
+        let compiled_filters = create_message_filters_lenient(None)?;
+        let key_filter = load_key_filter(None)?;
 
         // 配置 HTTP 客户端
-        let mut client_builder = reqwest::Client::builder()
-            .timeout(std::time::Duration::from_secs(30))
-            .user_agent("Mozilla/5.0 (Macintosh; Intel Mac OS X 10_15_7) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/140.0.0.0 Safari/537.36 Edg/140.0.0.0")
+        let client_builder = reqwest::Client::builder()
+            .timeout(DEFAULT_REQUEST_TIMEOUT)
+            .user_agent(DEFAULT_USER_AGENT)
             .default_headers({
                 let mut headers = reqwest::header::HeaderMap::new();
                 headers.insert(
@@ -76,78 +174,394 @@ impl LogQueryClient {
                 headers
             });
 
-        // 添加代理配置
-        if let Some(proxy) = get_proxy_from_env() {
-            client_builder = client_builder.proxy(proxy);
-        }
-
-        let client = client_builder
+        // 添加代理配置，区域代理取自传入的配置
+        let client = apply_region_config_proxy(client_builder, &region_config)
             .build()
             .map_err(|e| LogidError::InternalError(format!("创建 HTTP 客户端失败: {}", e)))?;
 
         conditional_info!(
-            "创建日志查询客户端: region={}, url={}",
-            auth_manager.region_str(),
+            "创建日志查询客户端: region={}, auth={}, url={}",
+            region_config.region.as_str(),
+            auth.name(),
             region_config.log_service_url
         );
 
-        Ok(Self {
-            auth_manager,
+        let max_response_bytes = region_config
+            .max_response_size_mb
+            .map_or(DEFAULT_MAX_RESPONSE_BYTES, |mb| mb.saturating_mul(1024 * 1024));
+        let scan_span_in_min = region_config.scan_span.unwrap_or(DEFAULT_SCAN_SPAN_MINUTES);
+        let request_timeout = region_config.request_timeout;
+
+        let client = Self {
+            auth,
             region_config,
-            message_filters,
+            message_filters: compiled_filters.filters,
+            filter_mode: compiled_filters.mode,
             client,
+            max_response_bytes,
+            progress: None,
+            raw: false,
+            code_location_keys: CodeLocationKeys::default(),
+            key_filter,
+            cache: None,
+            stats: false,
+            scan_span_in_min,
+            time_range: None,
+            levels: Vec::new(),
+            auto_widen: false,
+            per_zone: false,
+            timeout: DEFAULT_REQUEST_TIMEOUT,
+            retry_policy: RetryPolicy::default(),
+        };
+        Ok(match request_timeout {
+            Some(timeout) => client.with_timeout(timeout),
+            None => client,
         })
     }
 
+    /// 根据区域名称创建日志查询客户端
+    ///
+    /// 解析区域配置并创建对应的认证管理器，区域未配置时返回 `RegionNotConfigured`。
+    pub async fn for_region(region: &str) -> Result<Self, LogidError> {
+        Self::for_region_env(region, EnvTarget::Prod).await
+    }
+
+    /// 根据区域名称和部署环境创建日志查询客户端
+    ///
+    /// 区域未配置该环境的端点时返回 `RegionNotConfigured`。
+    pub async fn for_region_env(region: &str, env: EnvTarget) -> Result<Self, LogidError> {
+        let region_config = get_region_config(region)
+            .ok_or_else(|| LogidError::UnsupportedRegion(region.to_string()))?
+            .for_environment(env)?;
+
+        if !region_config.is_configured() {
+            return Err(LogidError::RegionNotConfigured(region.to_string()));
+        }
+
+        let auth_manager = AuthManager::new(region)?;
+        Self::new(auth_manager, region_config).await
+    }
+
+    /// 设置响应体大小上限（字节）
+    ///
+    /// 默认使用区域配置中的 `max_response_size_mb`，未配置时为 64 MB。
+    /// 响应超过该大小时停止接收，只提取已完整接收的日志项目，并在结果中标记 `truncated`。
+    pub fn with_max_response_size(mut self, max_bytes: usize) -> Self {
+        self.max_response_bytes = max_bytes;
+        self
+    }
+
+    /// 设置请求超时时间，同时作用于认证请求和日志查询请求
+    ///
+    /// 默认 30 秒，经过较慢的代理时可以适当调大。
+    pub fn with_timeout(mut self, timeout: std::time::Duration) -> Self {
+        self.timeout = timeout;
+        if let Some(auth_manager) = self.auth.auth_manager() {
+            self.auth = Arc::new(auth_manager.clone().with_timeout(timeout));
+        }
+        self
+    }
+
+    /// 设置超时、连接失败、5xx 和 429 时的重试策略，同时作用于认证请求和日志查询请求
+    pub fn with_retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = retry_policy;
+        if let Some(auth_manager) = self.auth.auth_manager() {
+            self.auth = Arc::new(auth_manager.clone().with_retry_policy(retry_policy));
+        }
+        self
+    }
+
+    /// 扫描时间范围（分钟）
+    pub fn scan_span(&self) -> i32 {
+        self.scan_span_in_min
+    }
+
+    /// 设置扫描时间范围（分钟）
+    ///
+    /// 默认使用区域配置中的 `scan_span`，未配置时为 10 分钟；日志延迟写入时可以扩大到 30 或 60 分钟。
+    pub fn with_scan_span(mut self, minutes: i32) -> Self {
+        self.scan_span_in_min = minutes;
+        self
+    }
+
+    /// 设置结果为空时是否自动扩大扫描范围重试
+    ///
+    /// 启用后查询没有返回任何日志项目时，依次使用 `AUTO_WIDEN_SCAN_SPANS` 中更大的
+    /// 扫描范围重试，尝试过的范围记录在 `DetailedLogResult::scan_widening` 中。
+    /// 设置了绝对查询时间范围时不生效。
+    pub fn with_auto_widen(mut self, auto_widen: bool) -> Self {
+        self.auto_widen = auto_widen;
+        self
+    }
+
+    /// 设置是否按可用区分别并发查询
+    ///
+    /// 区域配置了多个可用区时，为每个可用区单独发送请求并合并结果，
+    /// 避免单个慢可用区拖慢整个查询。部分可用区失败时记录警告并返回其余结果。
+    pub fn with_per_zone(mut self, per_zone: bool) -> Self {
+        self.per_zone = per_zone;
+        self
+    }
+
+    /// 只查询区域中的指定可用区
+    ///
+    /// 可用区必须属于区域配置的 `zones`，否则返回 `InvalidZone`；与 `with_per_zone` 一起使用时
+    /// 只为所选可用区分别发送请求。
+    pub fn with_zones(mut self, zones: &[String]) -> Result<Self, LogidError> {
+        self.region_config = self.region_config.select_zones(zones)?;
+        Ok(self)
+    }
+
+    /// 设置绝对查询时间范围
+    ///
+    /// 用于定位特定故障时间窗口，而不是从当前时间往前扫描。
+    pub fn with_time_range(mut self, range: Option<TimeRange>) -> Self {
+        self.time_range = range;
+        self
+    }
+
+    /// 设置保留的日志级别
+    ///
+    /// 级别不在列表中（或没有级别）的消息在提取时被丢弃，丢弃数量记录在
+    /// `DetailedLogResult::level_filtered` 中。列表为空时不过滤。
+    pub fn with_levels(mut self, levels: &[String]) -> Self {
+        self.levels = levels.iter().map(|level| normalize_level(level)).collect();
+        self
+    }
+
+    /// 设置是否跳过消息过滤
+    ///
+    /// 启用后消息内容不经过任何过滤规则和空白清理，便于确认过滤规则是否删除了需要的内容。
+    pub fn with_raw(mut self, raw: bool) -> Self {
+        self.raw = raw;
+        self
+    }
+
+    /// 设置代码位置相关字段的键名
+    ///
+    /// 除 `_location` 外，匹配这些键的值会被组合到消息的 `code_location` 中。
+    pub fn with_code_location_keys(mut self, keys: CodeLocationKeys) -> Self {
+        self.code_location_keys = keys;
+        self
+    }
+
+    /// 设置消息过滤规则及过滤模式
+    ///
+    /// 默认使用 [`create_message_filters_lenient`] 加载的规则。
+    pub fn with_message_filters(mut self, filters: CompiledFilters) -> Self {
+        self.message_filters = filters.filters;
+        self.filter_mode = filters.mode;
+        self
+    }
+
+    /// 重新加载消息过滤规则和键名筛选配置
+    ///
+    /// 供监视模式在过滤规则文件变化后调用；加载失败时保留原有规则。
+    pub fn reload_message_filters(&mut self) -> Result<(), LogidError> {
+        let compiled = create_message_filters_lenient(None)?;
+        self.key_filter = load_key_filter(None)?;
+        self.message_filters = compiled.filters;
+        self.filter_mode = compiled.mode;
+        Ok(())
+    }
+
+    /// 设置按键名筛选 kv 字段的规则
+    ///
+    /// 默认使用过滤规则文件中的 `keep_keys` 和 `drop_keys`。匹配的键在提取时整体保留或丢弃，
+    /// 保留的值与 `_msg` 一样经过消息过滤规则。
+    pub fn with_key_filter(mut self, key_filter: KeyFilter) -> Self {
+        self.key_filter = key_filter;
+        self
+    }
+
+    /// 设置查询结果缓存
+    ///
+    /// 缓存命中时 `get_log_details` 直接返回缓存的结果，不再访问日志服务；
+    /// 同一个 logid 的并发查询只有第一个会访问日志服务。
+    pub fn with_result_cache(mut self, cache: Arc<ResultCache>) -> Self {
+        self.cache = Some(cache);
+        self
+    }
+
+    /// 设置是否在结果中附带统计信息
+    ///
+    /// 启用后 `DetailedLogResult::stats` 记录下载字节数、提取的消息数、
+    /// 各过滤规则移除的内容以及解析警告。
+    pub fn with_stats(mut self, stats: bool) -> Self {
+        self.stats = stats;
+        self
+    }
+
+    /// 设置进度事件发送端
+    ///
+    /// 查询过程中会发送 `ProgressEvent`，接收端关闭后事件被静默丢弃。
+    pub fn with_progress(mut self, sender: ProgressSender) -> Self {
+        self.progress = Some(sender);
+        self
+    }
+
+    /// 发送进度事件
+    fn emit(&self, event: ProgressEvent) {
+        if let Some(sender) = &self.progress {
+            let _ = sender.send(event);
+        }
+    }
+
     /// 根据 logid 查询日志
     pub async fn query_logs(
         &self,
         logid: &str,
         psm_list: &[String],
+    ) -> Result<LogQueryResponse, LogidError> {
+        self.query_logs_with_span(logid, psm_list, self.scan_span_in_min).await
+    }
+
+    /// 使用指定的扫描时间范围查询日志
+    async fn query_logs_with_span(
+        &self,
+        logid: &str,
+        psm_list: &[String],
+        scan_span_in_min: i32,
+    ) -> Result<LogQueryResponse, LogidError> {
+        let zones = &self.region_config.zones;
+        if !self.per_zone || zones.len() < 2 {
+            return self
+                .query_vregion(logid, psm_list, scan_span_in_min, &self.region_config.vregion)
+                .await;
+        }
+
+        conditional_info!("按可用区并发查询: logid={}, zones={:?}", logid, zones);
+        let responses = join_all(
+            zones
+                .iter()
+                .map(|zone| self.query_vregion(logid, psm_list, scan_span_in_min, zone)),
+        )
+        .await;
+        merge_zone_responses(zones, responses)
+    }
+
+    /// 生成查询请求预览，不发送请求
+    ///
+    /// 与实际查询使用相同的地址、请求头和请求体；启用按可用区查询时每个可用区一个请求。
+    /// 仍会获取 JWT 令牌以验证凭据，输出中的令牌已脱敏。
+    pub async fn dry_run(
+        &self,
+        logid: &str,
+        psm_list: &[String],
+    ) -> Result<Vec<DryRunRequest>, LogidError> {
+        if !self.region_config.is_configured() {
+            return Err(LogidError::RegionNotConfigured(
+                self.region_config.region.as_str().to_string(),
+            ));
+        }
+
+        let jwt_token = self.jwt_token().await?;
+        let zones = &self.region_config.zones;
+        let vregions: Vec<&String> = if self.per_zone && zones.len() >= 2 {
+            zones.iter().collect()
+        } else {
+            vec![&self.region_config.vregion]
+        };
+        Ok(vregions
+            .into_iter()
+            .map(|vregion| {
+                DryRunRequest::new(
+                    self.region_config.region.as_str(),
+                    &self.region_config.log_service_url,
+                    self.request_headers(&jwt_token),
+                    self.request_body(logid, psm_list, self.scan_span_in_min, vregion),
+                )
+            })
+            .collect())
+    }
+
+    /// 获取 JWT 令牌
+    ///
+    /// 网络错误和会话过期保持原样，便于调用方区分认证失败、会话过期和网络不可用
+    async fn jwt_token(&self) -> Result<String, LogidError> {
+        self.auth.get_token(false).await.map_err(|e| match e {
+            LogidError::NetworkError(_) | LogidError::SessionExpired { .. } => e,
+            e => LogidError::AuthenticationFailed(format!(
+                "获取 {} 区域 JWT 令牌失败: {}",
+                self.region_config.region.as_str(),
+                e
+            )),
+        })
+    }
+
+    /// 查询请求体，未指定 PSM 时使用区域的默认 PSM
+    fn request_body(
+        &self,
+        logid: &str,
+        psm_list: &[String],
+        scan_span_in_min: i32,
+        vregion: &str,
+    ) -> LogQueryRequest {
+        LogQueryRequest::new(
+            logid.to_string(),
+            self.region_config.effective_psms(psm_list).to_vec(),
+            scan_span_in_min,
+            vregion.to_string(),
+        )
+        .with_time_range(self.time_range.as_ref())
+    }
+
+    /// 查询请求头，包括区域配置中的附加请求头
+    fn request_headers(&self, jwt_token: &str) -> Vec<(String, String)> {
+        let profile = &self.region_config.headers;
+        let mut headers = vec![
+            (JWT_HEADER.to_string(), jwt_token.to_string()),
+            ("accept".to_string(), "application/json, text/plain, */*".to_string()),
+            ("Content-Type".to_string(), "application/json".to_string()),
+            (
+                "User-Agent".to_string(),
+                profile.user_agent.as_deref().unwrap_or(DEFAULT_USER_AGENT).to_string(),
+            ),
+        ];
+        headers.extend(
+            profile
+                .extra_headers()
+                .into_iter()
+                .map(|(name, value)| (name.to_string(), value.to_string())),
+        );
+        headers
+    }
+
+    /// 向指定虚拟区域发送查询请求
+    async fn query_vregion(
+        &self,
+        logid: &str,
+        psm_list: &[String],
+        scan_span_in_min: i32,
+        vregion: &str,
     ) -> Result<LogQueryResponse, LogidError> {
         // 检查区域是否配置
         if !self.region_config.is_configured() {
             return Err(LogidError::RegionNotConfigured(
-                self.auth_manager.region_str().to_string(),
+                self.region_config.region.as_str().to_string(),
             ));
         }
 
         let start_time = Instant::now();
         conditional_info!(
-            "开始查询日志: logid={}, region={}, psm_list={:?}",
+            "开始查询日志: logid={}, region={}, psm_list={:?}, scan_span={}min",
             logid,
-            self.auth_manager.region_str(),
-            psm_list
+            self.region_config.region.as_str(),
+            self.region_config.effective_psms(psm_list),
+            scan_span_in_min
         );
 
-        // 获取 JWT 令牌
-        let jwt_token = self.auth_manager.get_jwt_token(false).await.map_err(|e| {
-            LogidError::AuthenticationFailed(format!(
-                "获取 {} 区域 JWT 令牌失败: {}",
-                self.auth_manager.region_str(),
-                e
-            ))
-        })?;
+        let jwt_token = self.jwt_token().await?;
+        let request_body = self.request_body(logid, psm_list, scan_span_in_min, vregion);
 
-        // 准备请求体
-        let request_body = LogQueryRequest::new(
-            logid.to_string(),
-            psm_list.to_vec(),
-            10, // 固定 10 分钟扫描范围
-            self.region_config.vregion.clone(),
-        );
+        // 发送 HTTP POST 请求到日志服务 API，附加区域的请求头配置
+        let mut request = self.client.post(&self.region_config.log_service_url);
+        for (name, value) in self.request_headers(&jwt_token) {
+            request = request.header(name, value);
+        }
 
-        // 发送 HTTP POST 请求到日志服务 API
-        let response = self
-            .client
-            .post(&self.region_config.log_service_url)
-            .header("X-Jwt-Token", jwt_token.as_str())
-            .header("accept", "application/json, text/plain, */*")
-            .header("Content-Type", "application/json")
-            .header("User-Agent", "Mozilla/5.0 (Macintosh; Intel Mac OS X 10_15_7) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/140.0.0.0 Safari/537.36 Edg/140.0.0.0")
-            .json(&request_body)
-            .send()
-            .await?;
+        let request = request.json(&request_body).timeout(self.timeout);
+        let response = self.retry_policy.send("日志查询", request).await?;
 
         let elapsed = start_time.elapsed();
         conditional_info!(
@@ -166,15 +580,81 @@ impl LogQueryClient {
                 error_text
             );
             return Err(LogidError::QueryFailed(
-                self.auth_manager.region_str().to_string(),
+                self.region_config.region.as_str().to_string(),
                 anyhow::anyhow!("HTTP {}: {}", status, error_text),
             ));
         }
 
-        // 解析响应数据
-        let response_data: serde_json::Value = response.json().await.map_err(|e| {
-            LogidError::NetworkError(e)
-        })?;
+        // 按上限读取响应体
+        let (body, truncated) = self.read_body_limited(response).await?;
+        self.emit(ProgressEvent::PageFetched {
+            region: self.region_config.region.as_str().to_string(),
+            logid: logid.to_string(),
+            n: 1,
+        });
+
+        let result = if truncated {
+            let warning = format!(
+                "响应体超过 {} 字节上限，已截断并进行部分解析",
+                self.max_response_bytes
+            );
+            warn!("{}", warning);
+            LogQueryResponse {
+                data: Some(LogData {
+                    items: extract_partial_items(&body),
+                    meta: None,
+                    tag_infos: None,
+                }),
+                meta: None,
+                tag_infos: None,
+                timestamp: chrono::Utc::now().to_rfc3339(),
+                region: self.region_config.region.as_str().to_string(),
+                region_display_name: self.region_config.region.display_name().to_string(),
+                truncated: true,
+                bytes_downloaded: body.len(),
+                warnings: vec![warning],
+            }
+        } else {
+            self.parse_response_body(&body)?
+        };
+
+        let items_count = result.data.as_ref().map(|data| data.items.len()).unwrap_or(0);
+        conditional_info!(
+            "日志查询完成: region={}, logid={}, items_found={}, elapsed={:?}",
+            self.region_config.region.as_str(),
+            logid,
+            items_count,
+            elapsed
+        );
+
+        Ok(result)
+    }
+
+    /// 读取响应体，超过大小上限时停止读取
+    ///
+    /// 返回已读取的字节以及是否发生截断。
+    async fn read_body_limited(
+        &self,
+        mut response: reqwest::Response,
+    ) -> Result<(Vec<u8>, bool), LogidError> {
+        let mut body = Vec::new();
+
+        while let Some(chunk) = response.chunk().await? {
+            if body.len() + chunk.len() > self.max_response_bytes {
+                let remaining = self.max_response_bytes - body.len();
+                body.extend_from_slice(&chunk[..remaining]);
+                return Ok((body, true));
+            }
+            body.extend_from_slice(&chunk);
+        }
+
+        Ok((body, false))
+    }
+
+    /// 解析完整的响应体
+    fn parse_response_body(&self, body: &[u8]) -> Result<LogQueryResponse, LogidError> {
+        let response_data: serde_json::Value = serde_json::from_slice(body)?;
+        let mut warnings = Vec::new();
 
         // 尝试解析不同的响应格式
         let data = if let Some(outer_data) = response_data.get("data") {
@@ -188,35 +668,38 @@ impl LogQueryClient {
         } else if response_data.get("items").is_some() {
             response_data.clone()
         } else {
-            warn!("响应中未找到预期的 data 或 items 字段，返回空数据");
+            let warning = "响应中未找到预期的 data 或 items 字段，返回空数据";
+            warn!("{}", warning);
+            warnings.push(warning.to_string());
             serde_json::json!({"items": []})
         };
 
         let meta = response_data.get("meta").cloned();
-        let tag_infos = response_data.get("tag_infos").cloned();
+        let tag_infos = response_data
+            .get("tag_infos")
+            .cloned()
+            .and_then(|v| match serde_json::from_value(v) {
+                Ok(tag_infos) => Some(tag_infos),
+                Err(e) => {
+                    warnings.push(format!("tag_infos 解析失败，已忽略: {}", e));
+                    None
+                }
+            });
 
-        let result = LogQueryResponse {
+        Ok(LogQueryResponse {
             data: Some(serde_json::from_value(data.clone()).map_err(|e| {
                 error!("解析日志数据失败: {}, 原始数据: {}", e, serde_json::to_string(&data).unwrap_or_default());
                 LogidError::JsonParseError(e)
             })?),
             meta,
-            tag_infos: tag_infos.and_then(|v| serde_json::from_value(v).ok()),
+            tag_infos,
             timestamp: chrono::Utc::now().to_rfc3339(),
-            region: self.auth_manager.region_str().to_string(),
-            region_display_name: self.auth_manager.region().display_name().to_string(),
-        };
-
-        let items_count = result.data.as_ref().map(|data| data.items.len()).unwrap_or(0);
-        conditional_info!(
-            "日志查询完成: region={}, logid={}, items_found={}, elapsed={:?}",
-            self.auth_manager.region_str(),
-            logid,
-            items_count,
-            elapsed
-        );
-
-        Ok(result)
+            region: self.region_config.region.as_str().to_string(),
+            region_display_name: self.region_config.region.display_name().to_string(),
+            truncated: false,
+            bytes_downloaded: body.len(),
+            warnings,
+        })
     }
 
     /// 获取详细的日志信息
@@ -225,18 +708,70 @@ impl LogQueryClient {
         logid: &str,
         psm_list: &[String],
     ) -> Result<DetailedLogResult, LogidError> {
-        let response = self.query_logs(logid, psm_list).await?;
+        let region = self.region_config.region.as_str().to_string();
+        let cache_key = CacheKey::new(&region, logid, self.region_config.effective_psms(psm_list));
+        // 同一个 logid 的并发查询排队执行，后到的直接使用前一次的结果
+        let _guard = match self.cache.as_ref() {
+            Some(cache) => Some(cache.lock(&cache_key).await),
+            None => None,
+        };
+        if let Some(cached) = self.cache.as_ref().and_then(|cache| cache.get(&cache_key)) {
+            conditional_info!("使用缓存的查询结果: logid={}", logid);
+            return Ok(cached);
+        }
+
+        self.emit(ProgressEvent::RegionStarted {
+            region: region.clone(),
+            logid: logid.to_string(),
+        });
+
+        let result = self.fetch_log_details(logid, psm_list).await;
+        match &result {
+            Ok(log_details) => {
+                // 被截断的结果不完整，不写入缓存
+                if let Some(cache) = self.cache.as_ref().filter(|_| !log_details.truncated) {
+                    cache.insert(cache_key, log_details.clone());
+                }
+                self.emit(ProgressEvent::MessagesExtracted {
+                    region,
+                    logid: logid.to_string(),
+                    count: log_details.messages.len(),
+                })
+            }
+            Err(e) => self.emit(ProgressEvent::RegionFailed {
+                region,
+                logid: logid.to_string(),
+                error: e.to_string(),
+            }),
+        }
+        result
+    }
+
+    /// 查询并提取日志详情
+    async fn fetch_log_details(
+        &self,
+        logid: &str,
+        psm_list: &[String],
+    ) -> Result<DetailedLogResult, LogidError> {
+        let (response, scan_widening) = self.query_logs_widening(logid, psm_list).await?;
 
         let data = response.data.as_ref().ok_or_else(|| {
             LogidError::QueryFailed(
-                self.auth_manager.region_str().to_string(),
+                self.region_config.region.as_str().to_string(),
                 anyhow::anyhow!("响应中没有数据内容"),
             )
         })?;
 
-        let messages = self.extract_log_messages(data);
+        let (messages, level_filtered) = self.extract_messages(data);
         let meta = data.meta.clone();
         let tag_infos = response.tag_infos.clone();
+        let stats = self.stats.then(|| ResultStats {
+            bytes_downloaded: response.bytes_downloaded,
+            items: data.items.len(),
+            messages_extracted: messages.len(),
+            filter_hits: self.filter_hits(&messages),
+            parse_warnings: response.warnings.clone(),
+        });
 
         Ok(DetailedLogResult {
             logid: logid.to_string(),
@@ -249,74 +784,202 @@ impl LogQueryClient {
             timestamp: response.timestamp,
             region: response.region,
             region_display_name: response.region_display_name,
+            truncated: response.truncated,
+            credential_expiry: None,
+            stats,
+            level_filtered,
+            scan_widening,
+            context: None,
         })
     }
 
+    /// 查询日志，启用自动扩大扫描范围时在结果为空后依次使用更大的范围重试
+    async fn query_logs_widening(
+        &self,
+        logid: &str,
+        psm_list: &[String],
+    ) -> Result<(LogQueryResponse, Option<ScanWidening>), LogidError> {
+        let mut span = self.scan_span_in_min;
+        let mut response = self.query_logs_with_span(logid, psm_list, span).await?;
+        if !self.auto_widen || self.time_range.is_some() {
+            return Ok((response, None));
+        }
+
+        let mut attempted_spans = vec![span];
+        for &next in AUTO_WIDEN_SCAN_SPANS.iter().filter(|&&next| next > self.scan_span_in_min) {
+            if response.data.as_ref().is_some_and(|data| !data.items.is_empty()) {
+                break;
+            }
+            conditional_info!("查询结果为空，扩大扫描范围: {}min -> {}min", span, next);
+            span = next;
+            attempted_spans.push(span);
+            response = self.query_logs_with_span(logid, psm_list, span).await?;
+        }
+
+        let scan_widening = (attempted_spans.len() > 1).then_some(ScanWidening {
+            attempted_spans,
+            final_span: span,
+        });
+        Ok((response, scan_widening))
+    }
+
     /// 从 API 响应中提取日志消息
     pub fn extract_log_messages(&self, data: &LogData) -> Vec<ExtractedLogMessage> {
+        self.extract_messages(data).0
+    }
+
+    /// 提取日志消息，同时返回因级别不匹配被丢弃的消息数
+    pub(crate) fn extract_messages(&self, data: &LogData) -> (Vec<ExtractedLogMessage>, usize) {
         let mut messages = Vec::new();
+        let mut level_filtered = 0;
 
         for item in &data.items {
             for value in &item.value {
                 let mut extracted_values = Vec::new();
                 let mut location = None;
+                let mut time = None;
+                let mut code_location = CodeLocation::default();
                 let level = value.level.clone();
+                let keys = &self.code_location_keys;
 
                 for kv in &value.kv_list {
-                    if kv.key == "_msg" {
+                    if self.key_filter.extracts(&kv.key) {
                         let filtered_value = self.filter_message_content(&kv.value);
-                        extracted_values.push(ExtractedValue {
-                            key: kv.key.clone(),
-                            value: filtered_value,
-                            original_value: kv.value.clone(),
-                            type_field: kv.type_field.clone(),
-                            highlight: kv.highlight.unwrap_or(false),
-                        });
-                    } else if kv.key == "_location" {
+                        // 保留模式下没有任何行匹配的值整体丢弃
+                        let dropped = !self.raw
+                            && self.filter_mode == FilterMode::Keep
+                            && filtered_value.is_empty();
+                        if !dropped {
+                            extracted_values.push(ExtractedValue {
+                                key: kv.key.clone(),
+                                value: filtered_value,
+                                original_value: kv.value.clone(),
+                                type_field: kv.type_field.clone(),
+                                highlight: kv.highlight.unwrap_or(false),
+                            });
+                        }
+                    }
+                    if kv.key == "_location" {
                         location = Some(kv.value.clone());
+                    } else if time.is_none() && TIME_KEYS.contains(&kv.key.as_str()) {
+                        time = Some(kv.value.clone());
+                    } else if keys.file.contains(&kv.key) {
+                        code_location.file = Some(kv.value.clone());
+                    } else if keys.line.contains(&kv.key) {
+                        code_location.line = kv.value.trim().parse().ok();
+                    } else if keys.function.contains(&kv.key) {
+                        code_location.function = Some(kv.value.clone());
                     }
                 }
 
-                if !extracted_values.is_empty() {
-                    messages.push(ExtractedLogMessage {
-                        id: format!("{}-{}", item.id, value.id),
-                        group: item.group.clone(),
-                        values: extracted_values,
-                        location,
-                        level,
-                    });
+                // 缺少独立的文件或行号字段时，从 `_location` 中补全
+                if let Some(parsed) = location.as_deref().map(CodeLocation::parse) {
+                    if code_location.file.is_none() {
+                        code_location.file = parsed.file;
+                        code_location.line = code_location.line.or(parsed.line);
+                    }
                 }
+
+                if extracted_values.is_empty() {
+                    continue;
+                }
+                if !self.level_matches(level.as_deref()) {
+                    level_filtered += 1;
+                    continue;
+                }
+
+                messages.push(ExtractedLogMessage {
+                    id: format!("{}-{}", item.id, value.id),
+                    group: item.group.clone(),
+                    values: extracted_values,
+                    location,
+                    code_location: Some(code_location).filter(|c| !c.is_empty()),
+                    level,
+                    time,
+                    region: None,
+                    annotations: Vec::new(),
+                });
             }
         }
 
-        conditional_info!("提取了 {} 条日志消息", messages.len());
-        messages
+        conditional_info!(
+            "提取了 {} 条日志消息，按级别过滤 {} 条",
+            messages.len(),
+            level_filtered
+        );
+        (messages, level_filtered)
     }
 
-    /// 过滤消息内容中的冗余字段
-    fn filter_message_content(&self, message: &str) -> String {
-        let mut filtered = message.to_string();
+    /// 检查日志级别是否在保留列表中
+    fn level_matches(&self, level: Option<&str>) -> bool {
+        if self.levels.is_empty() {
+            return true;
+        }
+        level.is_some_and(|level| self.levels.contains(&normalize_level(level)))
+    }
 
-        for regex in &self.message_filters {
-            filtered = regex.replace_all(&filtered, "").to_string();
+    /// 统计各过滤规则在这批消息中的命中情况
+    fn filter_hits(&self, messages: &[ExtractedLogMessage]) -> Vec<FilterHit> {
+        if self.raw {
+            return Vec::new();
         }
 
-        // 清理多余空格和换行符
-        filtered = regex::Regex::new(r"[ \t]{2,}")
-            .map(|re| re.replace_all(&filtered, " ").to_string())
-            .unwrap_or(filtered.clone());
+        let mut hits: Vec<FilterHit> = self
+            .message_filters
+            .iter()
+            .map(|regex| FilterHit {
+                pattern: regex.as_str().to_string(),
+                messages: 0,
+                bytes_removed: 0,
+            })
+            .collect();
+
+        for value in messages.iter().flat_map(|message| &message.values) {
+            if self.filter_mode == FilterMode::Keep {
+                // 保留模式只统计命中的消息数，不计算移除的字节数
+                for (hit, regex) in hits.iter_mut().zip(&self.message_filters) {
+                    if regex.is_match(&value.original_value) {
+                        hit.messages += 1;
+                    }
+                }
+                continue;
+            }
+            let removed = measure_message_filters(&self.message_filters, &value.original_value);
+            for (hit, bytes) in hits.iter_mut().zip(removed) {
+                if bytes > 0 {
+                    hit.messages += 1;
+                    hit.bytes_removed += bytes;
+                }
+            }
+        }
 
-        filtered = regex::Regex::new(r"\n\s*\n\s*\n")
-            .map(|re| re.replace_all(&filtered, "\n\n").to_string())
-            .unwrap_or(filtered);
+        hits.retain(|hit| hit.messages > 0);
+        hits
+    }
+
+    /// 过滤消息内容中的冗余字段
+    fn filter_message_content(&self, message: &str) -> String {
+        if self.raw {
+            return message.to_string();
+        }
 
-        filtered.trim().to_string()
+        apply_filters(&self.message_filters, self.filter_mode, message)
     }
 
     /// 获取区域信息
     #[allow(dead_code)]
     pub fn region(&self) -> &str {
-        self.auth_manager.region_str()
+        self.region_config.region.as_str()
+    }
+
+    /// 获取认证提供者
+    pub fn auth_provider(&self) -> &Arc<dyn AuthProvider> {
+        &self.auth
+    }
+
+    /// 获取基于 CAS_SESSION 的认证管理器，使用其他认证提供者时返回 `None`
+    pub fn auth_manager(&self) -> Option<&AuthManager> {
+        self.auth.auth_manager()
     }
 
     /// 获取区域配置
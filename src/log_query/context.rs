@@ -0,0 +1,172 @@
+//! 查询上下文模块
+//!
+//! 记录一次查询解析后的全部参数，随结果一起输出，便于之后用 `logid rerun` 原样重新执行。
+
+use crate::config::{create_message_filters_lenient, EnvTarget, FilterMode};
+use crate::error::LogidError;
+use crate::log_query::types::TimeRange;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+
+/// 当前查询上下文格式版本
+const CONTEXT_VERSION: u32 = 1;
+
+/// 查询上下文
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct QueryContext {
+    /// 上下文格式版本
+    pub version: u32,
+    /// 生成该上下文的 logid 版本
+    pub tool_version: String,
+    /// 查询时间
+    pub created_at: String,
+    /// 日志 ID 列表
+    pub logids: Vec<String>,
+    /// 解析后的查询区域
+    pub regions: Vec<String>,
+    /// 部署环境
+    #[serde(default)]
+    pub env_target: EnvTarget,
+    /// 各区域查询的可用区
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub zones: BTreeMap<String, Vec<String>>,
+    /// 扫描时间范围（分钟）
+    pub scan_span_in_min: i32,
+    /// 绝对查询时间范围
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub time_range: Option<TimeRange>,
+    /// PSM 服务列表（已合并 --psm-file）
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub psm_list: Vec<String>,
+    /// 保留的日志级别
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub levels: Vec<String>,
+    /// `--where` 过滤表达式
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub where_expr: Option<String>,
+    /// `--grep` 正则表达式
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub grep: Option<String>,
+    /// 是否反转 `--grep`
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub invert: bool,
+    /// 是否跳过消息过滤
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub raw: bool,
+    /// 结果为空时是否自动扩大扫描范围
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub auto_widen: bool,
+    /// 是否按可用区分别查询
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub per_zone: bool,
+    /// `--zone` 选择的可用区
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub selected_zones: Vec<String>,
+    /// `--fields` 保留的消息字段
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub fields: Vec<String>,
+    /// 是否附带统计信息
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub stats: bool,
+    /// 响应体大小上限（MB）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_response_size: Option<usize>,
+    /// 单个 HTTP 请求的超时时间（秒），旧版本记录的上下文中没有该字段
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout_secs: Option<u64>,
+    /// 最大重试次数，旧版本记录的上下文中没有该字段
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retries: Option<u32>,
+    /// 追踪上下游 logid 的层数
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub follow_depth: usize,
+    /// 追踪时最多查询的 logid 数量
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub follow_max: Option<usize>,
+    /// 查询时生效的消息过滤规则的哈希
+    pub filters_hash: String,
+}
+
+fn is_false(value: &bool) -> bool {
+    !*value
+}
+
+fn is_zero(value: &usize) -> bool {
+    *value == 0
+}
+
+impl QueryContext {
+    /// 创建查询上下文，其余参数使用默认值，过滤规则哈希取当前生效的规则
+    pub fn new(logids: Vec<String>, regions: Vec<String>) -> Self {
+        Self {
+            version: CONTEXT_VERSION,
+            tool_version: env!("CARGO_PKG_VERSION").to_string(),
+            created_at: chrono::Utc::now().to_rfc3339(),
+            logids,
+            regions,
+            env_target: EnvTarget::Prod,
+            zones: BTreeMap::new(),
+            scan_span_in_min: crate::log_query::DEFAULT_SCAN_SPAN_MINUTES,
+            time_range: None,
+            psm_list: Vec::new(),
+            levels: Vec::new(),
+            where_expr: None,
+            grep: None,
+            invert: false,
+            raw: false,
+            auto_widen: false,
+            per_zone: false,
+            selected_zones: Vec::new(),
+            fields: Vec::new(),
+            stats: false,
+            max_response_size: None,
+            timeout_secs: None,
+            retries: None,
+            follow_depth: 0,
+            follow_max: None,
+            filters_hash: message_filters_hash(),
+        }
+    }
+
+    /// 读取上下文文件
+    ///
+    /// 既可以是单独的上下文，也可以是带有 `context` 字段的查询结果
+    /// （多个 logid 的合并结果取第一个结果的上下文）。
+    pub fn load(path: &str) -> Result<Self, LogidError> {
+        let content = std::fs::read_to_string(path)?;
+        let value: serde_json::Value = serde_json::from_str(&content)?;
+
+        let context = value
+            .get("context")
+            .or_else(|| {
+                value
+                    .get("results")
+                    .and_then(|results| results.as_object())
+                    .and_then(|results| results.values().find_map(|result| result.get("context")))
+            })
+            .unwrap_or(&value);
+        Ok(serde_json::from_value(context.clone())?)
+    }
+
+    /// 当前生效的过滤规则是否与查询时不同
+    pub fn filters_changed(&self) -> bool {
+        self.filters_hash != message_filters_hash()
+    }
+}
+
+/// 当前生效的消息过滤规则的哈希（SHA256 前 16 位）
+///
+/// 规则文件无法读取时按空规则计算；保留模式的规则集与同样规则的删除模式哈希不同。
+pub fn message_filters_hash() -> String {
+    let compiled = create_message_filters_lenient(None).unwrap_or_default();
+    let mut hasher = Sha256::new();
+    if compiled.mode == FilterMode::Keep {
+        hasher.update(b"mode=keep\n");
+    }
+    for pattern in &compiled.filters {
+        hasher.update(pattern.as_str().as_bytes());
+        hasher.update(b"\n");
+    }
+    format!("{:x}", hasher.finalize())[..16].to_string()
+}
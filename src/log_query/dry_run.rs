@@ -0,0 +1,95 @@
+//! 查询请求预览模块
+//!
+//! `--dry-run` 时不发送查询，只输出解析后的请求地址、请求头（JWT 脱敏）和请求体，
+//! 便于排查查询结果为空的原因，或用 curl 复现请求。
+
+use crate::log_query::LogQueryRequest;
+use serde::Serialize;
+
+/// 携带 JWT 令牌的请求头
+pub(crate) const JWT_HEADER: &str = "X-Jwt-Token";
+
+/// 一个未发送的日志查询请求
+#[derive(Debug, Clone, Serialize)]
+pub struct DryRunRequest {
+    /// 区域名称
+    pub region: String,
+    /// 请求方法
+    pub method: String,
+    /// 日志服务地址
+    pub url: String,
+    /// 请求头，JWT 令牌已脱敏
+    pub headers: Vec<(String, String)>,
+    /// 请求体
+    pub body: LogQueryRequest,
+}
+
+impl DryRunRequest {
+    /// 创建请求预览，`X-Jwt-Token` 请求头的值会被脱敏
+    pub fn new(
+        region: impl Into<String>,
+        url: impl Into<String>,
+        headers: Vec<(String, String)>,
+        body: LogQueryRequest,
+    ) -> Self {
+        let headers = headers
+            .into_iter()
+            .map(|(name, value)| {
+                if name.eq_ignore_ascii_case(JWT_HEADER) {
+                    (name, mask_token(&value))
+                } else {
+                    (name, value)
+                }
+            })
+            .collect();
+        Self {
+            region: region.into(),
+            method: "POST".to_string(),
+            url: url.into(),
+            headers,
+            body,
+        }
+    }
+
+    /// 序列化后的请求体
+    pub fn body_json(&self) -> String {
+        serde_json::to_string(&self.body).unwrap_or_default()
+    }
+
+    /// 格式化为便于阅读的文本
+    pub fn format_text(&self) -> String {
+        let mut lines = vec![format!("# 区域: {}", self.region), format!("{} {}", self.method, self.url)];
+        lines.extend(self.headers.iter().map(|(name, value)| format!("{}: {}", name, value)));
+        lines.push(String::new());
+        lines.push(serde_json::to_string_pretty(&self.body).unwrap_or_default());
+        lines.join("\n")
+    }
+
+    /// 生成等价的 curl 命令，JWT 令牌需要替换为真实值
+    pub fn to_curl(&self) -> String {
+        let mut parts = vec![format!("curl -X {} {}", self.method, shell_quote(&self.url))];
+        parts.extend(
+            self.headers
+                .iter()
+                .map(|(name, value)| format!("  -H {}", shell_quote(&format!("{}: {}", name, value)))),
+        );
+        parts.push(format!("  --data {}", shell_quote(&self.body_json())));
+        parts.join(" \\\n")
+    }
+}
+
+/// 脱敏令牌：保留首尾各 4 个字符，过短时全部替换为 `*`
+pub fn mask_token(token: &str) -> String {
+    let chars: Vec<char> = token.chars().collect();
+    if chars.len() <= 8 {
+        return "*".repeat(chars.len());
+    }
+    let head: String = chars[..4].iter().collect();
+    let tail: String = chars[chars.len() - 4..].iter().collect();
+    format!("{}****{}", head, tail)
+}
+
+/// 用单引号包裹 shell 参数
+fn shell_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', r"'\''"))
+}
@@ -0,0 +1,195 @@
+//! 下游 logid 递归追踪模块
+//!
+//! 日志消息中经常带有上下游请求的 logid。从消息中提取这些 logid 并按层级继续查询，
+//! 得到以起始 logid 为根的调用链树。
+
+use crate::error::LogidError;
+use crate::log_query::DetailedLogResult;
+use futures::future::join_all;
+use regex::Regex;
+use serde::Serialize;
+use std::collections::HashSet;
+use std::future::Future;
+use std::sync::OnceLock;
+
+/// 默认最多查询的 logid 数量（包括起始 logid）
+pub const DEFAULT_FOLLOW_MAX_LOGIDS: usize = 50;
+
+/// 从消息中提取 logid 的键
+const MESSAGE_KEY: &str = "_msg";
+
+/// logid 形态的片段：UUID、以 14 位时间开头的十六进制串，以及 `logid=...` 形式的取值
+fn logid_patterns() -> &'static [Regex] {
+    static PATTERNS: OnceLock<Vec<Regex>> = OnceLock::new();
+    PATTERNS.get_or_init(|| {
+        [
+            r"\b[0-9a-fA-F]{8}-[0-9a-fA-F]{4}-[0-9a-fA-F]{4}-[0-9a-fA-F]{4}-[0-9a-fA-F]{12}\b",
+            r"\b20\d{12}[0-9a-fA-F]{12,24}\b",
+            r#"(?i)\b(?:x[-_]tt[-_])?log_?id["']?\s*[:=]\s*["']?([A-Za-z0-9_-]{16,64})"#,
+        ]
+        .iter()
+        .map(|pattern| Regex::new(pattern).expect("内置 logid 正则有效"))
+        .collect()
+    })
+}
+
+/// 从文本中提取 logid 形态的片段，按出现顺序去重
+pub fn extract_logids(text: &str) -> Vec<String> {
+    let mut found: Vec<(usize, String)> = Vec::new();
+    for pattern in logid_patterns() {
+        for captures in pattern.captures_iter(text) {
+            let matched = captures.get(1).or_else(|| captures.get(0)).expect("总有整体匹配");
+            if !found.iter().any(|(_, logid)| logid == matched.as_str()) {
+                found.push((matched.start(), matched.as_str().to_string()));
+            }
+        }
+    }
+    found.sort_by_key(|(start, _)| *start);
+    found.into_iter().map(|(_, logid)| logid).collect()
+}
+
+/// 从查询结果的 `_msg` 中提取其他 logid（不包括结果自身的 logid）
+///
+/// 使用过滤前的原始值，消息过滤规则通常会删除其中的 logid。
+pub fn referenced_logids(result: &DetailedLogResult) -> Vec<String> {
+    let mut logids: Vec<String> = Vec::new();
+    let values = result
+        .messages
+        .iter()
+        .flat_map(|message| &message.values)
+        .filter(|value| value.key == MESSAGE_KEY);
+    for value in values {
+        for logid in extract_logids(&value.original_value) {
+            if logid != result.logid && !logids.contains(&logid) {
+                logids.push(logid);
+            }
+        }
+    }
+    logids
+}
+
+/// 调用链树的一个节点
+#[derive(Debug, Serialize)]
+pub struct FollowNode {
+    /// 日志 ID
+    pub logid: String,
+    /// 所在层级，起始 logid 为 0
+    pub depth: usize,
+    /// 查询结果
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub result: Option<DetailedLogResult>,
+    /// 查询失败时的错误信息
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// 消息中引用、且在更浅层级未出现过的 logid
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub children: Vec<FollowNode>,
+}
+
+impl FollowNode {
+    /// 树中全部节点的消息总数
+    pub fn message_count(&self) -> usize {
+        self.result.as_ref().map_or(0, |result| result.messages.len())
+            + self.children.iter().map(FollowNode::message_count).sum::<usize>()
+    }
+
+    /// 以缩进的树形文本描述调用链
+    pub fn format_tree(&self) -> String {
+        let mut lines = Vec::new();
+        self.push_tree_lines(&mut lines);
+        lines.join("\n")
+    }
+
+    fn push_tree_lines(&self, lines: &mut Vec<String>) {
+        let status = match (&self.result, &self.error) {
+            (Some(result), _) => format!("{} 条消息", result.messages.len()),
+            (None, Some(error)) => format!("查询失败: {}", error),
+            (None, None) => "未查询".to_string(),
+        };
+        lines.push(format!("{}{} ({})", "  ".repeat(self.depth), self.logid, status));
+        for child in &self.children {
+            child.push_tree_lines(lines);
+        }
+    }
+}
+
+/// 查询起始 logid，并按层级递归查询消息中引用的 logid
+///
+/// 同一层级的 logid 并发查询；已经出现过的 logid 不会重复查询，避免调用链成环。
+/// 查询的 logid 总数达到 `max_logids` 后不再展开新的节点。
+pub async fn follow_logids<F, Fut>(
+    root: &str,
+    max_depth: usize,
+    max_logids: usize,
+    query: F,
+) -> FollowNode
+where
+    F: Fn(String) -> Fut,
+    Fut: Future<Output = Result<DetailedLogResult, LogidError>>,
+{
+    // 按查询顺序平铺的节点：(logid, 父节点下标, 查询结果)
+    type PendingNode = (String, Option<usize>, Option<Result<DetailedLogResult, LogidError>>);
+    let mut nodes: Vec<PendingNode> = vec![(root.to_string(), None, None)];
+    let mut visited: HashSet<String> = HashSet::from([root.to_string()]);
+    let mut frontier = vec![0];
+
+    for depth in 0..=max_depth {
+        let outcomes = join_all(frontier.iter().map(|&index| query(nodes[index].0.clone()))).await;
+        for (&index, outcome) in frontier.iter().zip(outcomes) {
+            nodes[index].2 = Some(outcome);
+        }
+        if depth == max_depth {
+            break;
+        }
+
+        let mut next = Vec::new();
+        for &index in &frontier {
+            let Some(Ok(result)) = &nodes[index].2 else {
+                continue;
+            };
+            for logid in referenced_logids(result) {
+                if visited.len() >= max_logids {
+                    break;
+                }
+                if visited.insert(logid.clone()) {
+                    next.push(nodes.len());
+                    nodes.push((logid, Some(index), None));
+                }
+            }
+        }
+        if next.is_empty() {
+            break;
+        }
+        frontier = next;
+    }
+
+    let mut built: Vec<Option<FollowNode>> = Vec::with_capacity(nodes.len());
+    let parents: Vec<Option<usize>> = nodes.iter().map(|(_, parent, _)| *parent).collect();
+    for (logid, parent, outcome) in nodes {
+        let depth = parent.map_or(0, |p| built[p].as_ref().map_or(0, |n: &FollowNode| n.depth) + 1);
+        let (result, error) = match outcome {
+            Some(Ok(result)) => (Some(result), None),
+            Some(Err(e)) => (None, Some(e.to_string())),
+            None => (None, None),
+        };
+        built.push(Some(FollowNode {
+            logid,
+            depth,
+            result,
+            error,
+            children: Vec::new(),
+        }));
+    }
+
+    // 子节点总是排在父节点之后，倒序挂到父节点上即可保持原有顺序
+    for index in (1..built.len()).rev() {
+        let node = built[index].take().expect("每个节点只挂载一次");
+        let parent = parents[index].expect("非根节点都有父节点");
+        built[parent]
+            .as_mut()
+            .expect("父节点尚未挂载")
+            .children
+            .insert(0, node);
+    }
+    built[0].take().expect("根节点存在")
+}
@@ -3,13 +3,38 @@
 //! 处理多区域的日志查询功能，通过 logid 进行日志搜索。
 //! 支持并发区域查询和智能区域检测，提供统一的日志查询接口。
 
+mod batch;
+mod cache;
 mod client;
+mod context;
+mod dry_run;
+mod follow;
 mod multi_region;
+mod partial;
+mod progress;
+mod psm;
+mod time;
 mod types;
+mod watch;
 
-pub use client::LogQueryClient;
+pub use batch::{
+    normalize_logid, parse_logid_list, BatchItemResult, BatchQuery, DEFAULT_BATCH_CONCURRENCY,
+};
+pub use cache::{CacheKey, ResultCache, DEFAULT_CACHE_CAPACITY, DEFAULT_CACHE_TTL};
+pub use client::{
+    LogQueryClient, AUTO_WIDEN_SCAN_SPANS, DEFAULT_MAX_RESPONSE_BYTES, DEFAULT_SCAN_SPAN_MINUTES,
+};
+pub use context::{message_filters_hash, QueryContext};
+pub use dry_run::{mask_token, DryRunRequest};
+pub use follow::{
+    extract_logids, follow_logids, referenced_logids, FollowNode, DEFAULT_FOLLOW_MAX_LOGIDS,
+};
 pub use multi_region::MultiRegionLogQuery;
+pub use psm::{load_psm_file, merge_psm_lists, parse_psm_list};
+pub use progress::{progress_channel, ProgressEvent, ProgressReceiver, ProgressSender};
+pub use time::{parse_interval, parse_message_time, parse_time_bound, resolve_time_range};
 pub use types::*;
+pub use watch::SeenMessages;
 
 #[cfg(test)]
 mod tests {
@@ -31,12 +56,1001 @@ mod tests {
         assert_eq!(request.vregion, "test_vregion");
     }
 
+    #[test]
+    fn test_parse_message_time_formats() {
+        let expected = chrono::DateTime::parse_from_rfc3339("2024-01-01T12:00:00Z")
+            .unwrap()
+            .with_timezone(&chrono::Utc);
+
+        assert_eq!(parse_message_time("2024-01-01T12:00:00Z"), Some(expected));
+        assert_eq!(parse_message_time("2024-01-01 12:00:00"), Some(expected));
+        assert_eq!(parse_message_time("1704110400"), Some(expected));
+        assert_eq!(parse_message_time("1704110400000"), Some(expected));
+        assert_eq!(parse_message_time("not a time"), None);
+    }
+
+    #[test]
+    fn test_resolve_time_range() {
+        let now = chrono::DateTime::parse_from_rfc3339("2024-01-01T12:00:00Z")
+            .unwrap()
+            .with_timezone(&chrono::Utc);
+        let ts = now.timestamp();
+
+        assert_eq!(parse_time_bound("-2h", now).unwrap().timestamp(), ts - 7200);
+        assert_eq!(parse_time_bound("now", now).unwrap(), now);
+        assert!(parse_time_bound("-2x", now).is_err());
+        assert!(resolve_time_range(None, None, 10, now).unwrap().is_none());
+
+        let range = resolve_time_range(Some("-30m"), None, 10, now).unwrap().unwrap();
+        assert_eq!((range.start, range.end), (Some(ts - 1800), Some(ts)));
+
+        let range = resolve_time_range(None, Some("2024-01-01T11:00:00Z"), 10, now).unwrap().unwrap();
+        assert_eq!((range.start, range.end), (Some(ts - 4200), Some(ts - 3600)));
+
+        assert!(resolve_time_range(Some("-1h"), Some("-2h"), 10, now).is_err());
+
+        let request = LogQueryRequest::new("id".to_string(), Vec::new(), 10, String::new())
+            .with_time_range(Some(&range));
+        let body = serde_json::to_value(&request).unwrap();
+        assert_eq!(body["start_time"], ts - 4200);
+        assert_eq!(body["end_time"], ts - 3600);
+    }
+
+    #[test]
+    fn test_time_span_rejects_non_ascii_unit() {
+        let now = chrono::Utc::now();
+        // 多字节单位不能导致按字节切分时 panic
+        assert!(parse_time_bound("-5分", now).is_err());
+        assert!(parse_time_bound("-é", now).is_err());
+        assert!(parse_interval("30秒").is_err());
+        assert!(parse_interval("s").is_err());
+    }
+
+    #[test]
+    fn test_parse_logid_list() {
+        let content = "logid-1\n\n# comment\n  logid-2  \nlogid-3\n";
+        assert_eq!(parse_logid_list(content), vec!["logid-1", "logid-2", "logid-3"]);
+        assert_eq!(parse_logid_list("logid-1 logid-2\tlogid-3\n"), vec!["logid-1", "logid-2", "logid-3"]);
+    }
+
+    #[test]
+    fn test_normalize_logid_from_clipboard() {
+        assert_eq!(normalize_logid("  \"0217040000abc-def_01\"\n").unwrap(), "0217040000abc-def_01");
+        assert!(normalize_logid("").is_err());
+        assert!(normalize_logid("two logids").is_err());
+        let error = normalize_logid(&"x".repeat(200)).unwrap_err().to_string();
+        assert!(error.ends_with('…') && error.len() < 100);
+    }
+
+    #[test]
+    fn test_psm_file_merged_with_flags() {
+        let content = "# 支付链路\npay.gateway\n\n  pay.core  # 核心服务\npay.api\n";
+        let file = parse_psm_list(content);
+        assert_eq!(file, vec!["pay.gateway", "pay.core", "pay.api"]);
+
+        let flags = vec!["pay.api".to_string(), "user.profile".to_string()];
+        assert_eq!(
+            merge_psm_lists(&flags, &file),
+            vec!["pay.api", "user.profile", "pay.gateway", "pay.core"]
+        );
+    }
+
+    #[test]
+    fn test_extract_partial_items_from_truncated_body() {
+        let body = br#"{"data":{"items":[
+            {"id":"1","group":{"psm":"a.b.c"},"value":[{"id":"v1","kv_list":[{"key":"_msg","value":"hello"}]}]},
+            {"id":"2","group":{"psm":"a.b.c"},"value":[{"id":"v2","kv_list":[{"key":"_msg","value":"wor"#;
+
+        let items = partial::extract_partial_items(body);
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0].id, "1");
+        assert_eq!(items[0].value[0].kv_list[0].value, "hello");
+    }
+
+    #[test]
+    fn test_log_group_keeps_unknown_fields() {
+        let group: LogGroup = serde_json::from_str(
+            r#"{"psm":"a.b.c","cluster":"default","stage":"canary","container":{"name":"main"}}"#,
+        )
+        .unwrap();
+
+        assert_eq!(group.psm.as_deref(), Some("a.b.c"));
+        assert_eq!(group.extra["cluster"], "default");
+        assert_eq!(group.extra["container"]["name"], "main");
+
+        let value = serde_json::to_value(&group).unwrap();
+        assert_eq!(value["stage"], "canary");
+        assert_eq!(value["container"]["name"], "main");
+    }
+
+    #[test]
+    fn test_extract_partial_items_without_items_array() {
+        assert!(partial::extract_partial_items(br#"{"data":{"meta":"#).is_empty());
+    }
+
     #[test]
     fn test_message_filtering() {
-        let _filters = vec![Regex::new("test_filter").unwrap()];
+        let _filters = [Regex::new("test_filter").unwrap()];
 
         // 这里需要创建 LogQueryClient 实例来测试过滤功能
         // 由于构造函数需要异步，在单元测试中比较复杂
         // 可以考虑重构为同步测试或者使用异步测试框架
     }
+
+    #[tokio::test]
+    async fn test_progress_events_for_successful_query() {
+        use crate::test_support::{http_response, mock_client, MockServer};
+        use std::time::Duration;
+
+        let server = MockServer::start(Duration::ZERO, |_, head| {
+            if head.starts_with("GET") {
+                http_response(200, &[("x-jwt-token", "token")], "")
+            } else {
+                http_response(
+                    200,
+                    &[("content-type", "application/json")],
+                    r#"{"data":{"items":[{"id":"1","group":{"psm":"a.b.c"},"value":[{"id":"v1","kv_list":[{"key":"_msg","value":"hello"}]}]}]}}"#,
+                )
+            }
+        })
+        .await;
+
+        let (sender, mut receiver) = progress_channel();
+        let client = mock_client(&server).await.with_progress(sender);
+
+        let result = client.get_log_details("logid-1", &[]).await.unwrap();
+        assert_eq!(result.messages.len(), 1);
+        drop(client);
+
+        let mut events = Vec::new();
+        while let Some(event) = receiver.recv().await {
+            events.push(event);
+        }
+
+        let (region, logid) = ("us".to_string(), "logid-1".to_string());
+        assert_eq!(
+            events,
+            vec![
+                ProgressEvent::RegionStarted { region: region.clone(), logid: logid.clone() },
+                ProgressEvent::PageFetched { region: region.clone(), logid: logid.clone(), n: 1 },
+                ProgressEvent::MessagesExtracted { region, logid, count: 1 },
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_raw_client_skips_message_cleanup() {
+        use crate::auth::AuthManager;
+        use crate::config::{Region, RegionConfig};
+
+        let data: LogData = serde_json::from_str(
+            r#"{"items":[{"id":"1","group":{},"value":[{"id":"v1","kv_list":[{"key":"_msg","value":"  a    b  "}]}]}]}"#,
+        )
+        .unwrap();
+
+        let new_client = || async {
+            let auth = AuthManager::from_session(Region::Us, "session").unwrap();
+            let config = RegionConfig::new(Region::Us, "http://127.0.0.1:0".to_string(), String::new(), Vec::new());
+            LogQueryClient::new(auth, config).await.unwrap()
+        };
+
+        let filtered = new_client().await.extract_log_messages(&data);
+        assert_eq!(filtered[0].values[0].value, "a b");
+
+        let raw = new_client().await.with_raw(true).extract_log_messages(&data);
+        assert_eq!(raw[0].values[0].value, "  a    b  ");
+    }
+
+    #[tokio::test]
+    async fn test_level_filter_counts_suppressed_messages() {
+        use crate::auth::AuthManager;
+        use crate::config::{Region, RegionConfig};
+
+        let data: LogData = serde_json::from_str(
+            r#"{"items":[{"id":"1","group":{},"value":[
+                {"id":"v1","level":"Error","kv_list":[{"key":"_msg","value":"a"}]},
+                {"id":"v2","level":"WARNING","kv_list":[{"key":"_msg","value":"b"}]},
+                {"id":"v3","level":"Info","kv_list":[{"key":"_msg","value":"c"}]},
+                {"id":"v4","kv_list":[{"key":"_msg","value":"d"}]}
+            ]}]}"#,
+        )
+        .unwrap();
+
+        let auth = AuthManager::from_session(Region::Us, "session").unwrap();
+        let config = RegionConfig::new(Region::Us, "http://127.0.0.1:0".to_string(), String::new(), Vec::new());
+        let client = LogQueryClient::new(auth, config)
+            .await
+            .unwrap()
+            .with_levels(&["error".to_string(), "warn".to_string()]);
+
+        let (messages, level_filtered) = client.extract_messages(&data);
+        let ids: Vec<_> = messages.iter().map(|m| m.id.as_str()).collect();
+        assert_eq!(ids, vec!["1-v1", "1-v2"]);
+        assert_eq!(level_filtered, 2);
+    }
+
+    #[tokio::test]
+    async fn test_extract_code_location() {
+        use crate::auth::AuthManager;
+        use crate::config::{Region, RegionConfig};
+
+        let data: LogData = serde_json::from_str(
+            r#"{"items":[{"id":"1","group":{},"value":[
+                {"id":"v1","kv_list":[{"key":"_msg","value":"a"},{"key":"_file","value":"handler.go"},{"key":"_line","value":"42"},{"key":"_func","value":"Handle"}]},
+                {"id":"v2","kv_list":[{"key":"_msg","value":"b"},{"key":"_location","value":"src/main.rs:7"}]},
+                {"id":"v3","kv_list":[{"key":"_msg","value":"c"}]}
+            ]}]}"#,
+        )
+        .unwrap();
+
+        let auth = AuthManager::from_session(Region::Us, "session").unwrap();
+        let config = RegionConfig::new(Region::Us, "http://127.0.0.1:0".to_string(), String::new(), Vec::new());
+        let client = LogQueryClient::new(auth, config).await.unwrap();
+        let messages = client.extract_log_messages(&data);
+
+        assert_eq!(
+            messages[0].code_location,
+            Some(CodeLocation {
+                file: Some("handler.go".to_string()),
+                line: Some(42),
+                function: Some("Handle".to_string()),
+            })
+        );
+        assert_eq!(messages[1].code_location, Some(CodeLocation::parse("src/main.rs:7")));
+        assert_eq!(messages[1].code_location.as_ref().unwrap().line, Some(7));
+        assert_eq!(messages[2].code_location, None);
+    }
+
+    #[tokio::test]
+    async fn test_key_filter_keeps_and_drops_kv_pairs() {
+        use crate::auth::AuthManager;
+        use crate::config::{KeyFilter, Region, RegionConfig};
+
+        let data: LogData = serde_json::from_str(
+            r#"{"items":[{"id":"1","group":{},"value":[
+                {"id":"v1","kv_list":[{"key":"_msg","value":"a"},{"key":"status","value":"500"},{"key":"_compliance_tag","value":"x"},{"key":"_location","value":"a.go:1"}]},
+                {"id":"v2","kv_list":[{"key":"_compliance_nlp_log","value":"y"}]}
+            ]}]}"#,
+        )
+        .unwrap();
+
+        let auth = AuthManager::from_session(Region::Us, "session").unwrap();
+        let config = RegionConfig::new(Region::Us, "http://127.0.0.1:0".to_string(), String::new(), Vec::new());
+        let client = LogQueryClient::new(auth, config).await.unwrap().with_key_filter(KeyFilter {
+            keep_keys: vec!["*".to_string()],
+            drop_keys: vec!["_compliance_*".to_string(), "_location".to_string()],
+        });
+        let messages = client.extract_log_messages(&data);
+
+        // 被丢弃的键不出现在 values 中，但 `_location` 仍用于代码位置；只有被丢弃键的消息整条跳过
+        assert_eq!(messages.len(), 1);
+        let keys: Vec<&str> = messages[0].values.iter().map(|v| v.key.as_str()).collect();
+        assert_eq!(keys, vec!["_msg", "status"]);
+        assert_eq!(messages[0].location.as_deref(), Some("a.go:1"));
+    }
+
+    #[tokio::test]
+    async fn test_keep_mode_keeps_only_matching_lines() {
+        use crate::auth::AuthManager;
+        use crate::config::{compile_filters_lenient, CompiledFilters, FilterMode, Region, RegionConfig};
+
+        let data: LogData = serde_json::from_str(
+            r#"{"items":[{"id":"1","group":{},"value":[
+                {"id":"v1","kv_list":[{"key":"_msg","value":"start\nrequest failed: status=500\nretrying"}]},
+                {"id":"v2","kv_list":[{"key":"_msg","value":"all good"}]}
+            ]}]}"#,
+        )
+        .unwrap();
+
+        let auth = AuthManager::from_session(Region::Us, "session").unwrap();
+        let config = RegionConfig::new(Region::Us, "http://127.0.0.1:0".to_string(), String::new(), Vec::new());
+        let filters = CompiledFilters {
+            mode: FilterMode::Keep,
+            ..compile_filters_lenient(&["error|panic|status=".to_string()])
+        };
+        let client = LogQueryClient::new(auth, config).await.unwrap().with_message_filters(filters);
+
+        let messages = client.extract_log_messages(&data);
+        assert_eq!(messages.len(), 1);
+        assert_eq!(messages[0].values[0].value, "request failed: status=500");
+
+        // --raw 时保留完整消息
+        let messages = client.with_raw(true).extract_log_messages(&data);
+        assert_eq!(messages.len(), 2);
+    }
+
+    fn detailed_result(logid: &str, messages: Vec<ExtractedLogMessage>) -> DetailedLogResult {
+        DetailedLogResult {
+            logid: logid.to_string(),
+            messages,
+            meta: None,
+            tag_infos: None,
+            total_items: 0,
+            scan_time_range: None,
+            level_list: None,
+            timestamp: chrono::Utc::now().to_rfc3339(),
+            region: "us".to_string(),
+            region_display_name: "美区".to_string(),
+            truncated: false,
+            credential_expiry: None,
+            stats: None,
+            level_filtered: 0,
+            scan_widening: None,
+            context: None,
+        }
+    }
+
+    #[test]
+    fn test_result_cache_lru_and_ttl() {
+        use std::time::Duration;
+
+        let cache = ResultCache::new(2, Duration::from_secs(60));
+        let key = |logid: &str| CacheKey::new("us", logid, &[]);
+
+        cache.insert(key("a"), detailed_result("a", Vec::new()));
+        cache.insert(key("b"), detailed_result("b", Vec::new()));
+        assert!(cache.get(&key("a")).is_some());
+
+        // b 最久未使用，插入 c 时被淘汰
+        cache.insert(key("c"), detailed_result("c", Vec::new()));
+        assert!(cache.get(&key("b")).is_none());
+        assert!(cache.get(&key("a")).is_some());
+        assert_eq!(cache.len(), 2);
+
+        let expired = ResultCache::new(2, Duration::ZERO);
+        expired.insert(key("a"), detailed_result("a", Vec::new()));
+        std::thread::sleep(Duration::from_millis(5));
+        assert!(expired.get(&key("a")).is_none());
+        assert!(expired.is_empty());
+
+        // PSM 顺序不影响缓存键
+        assert_eq!(
+            CacheKey::new("us", "a", &["x".to_string(), "y".to_string()]),
+            CacheKey::new("us", "a", &["y".to_string(), "x".to_string()])
+        );
+    }
+
+    #[test]
+    fn test_watch_emits_only_new_messages() {
+        let message = |id: &str| ExtractedLogMessage {
+            id: id.to_string(),
+            ..ExtractedLogMessage::default()
+        };
+        let ids = |messages: Vec<&ExtractedLogMessage>| -> Vec<String> {
+            messages.into_iter().map(|m| m.id.clone()).collect()
+        };
+
+        let mut seen = SeenMessages::new();
+        let first = detailed_result("a", vec![message("1"), message("2")]);
+        assert_eq!(ids(seen.take_new(&first)), vec!["1", "2"]);
+
+        let second = detailed_result("a", vec![message("1"), message("2"), message("3")]);
+        assert_eq!(ids(seen.take_new(&second)), vec!["3"]);
+
+        // 不同 logid 的相同消息 ID 互不影响
+        let other = detailed_result("b", vec![message("1")]);
+        assert_eq!(ids(seen.take_new(&other)), vec!["1"]);
+        assert_eq!(seen.len(), 4);
+
+        assert_eq!(parse_interval("30s").unwrap(), std::time::Duration::from_secs(30));
+        assert_eq!(parse_interval("2m").unwrap(), std::time::Duration::from_secs(120));
+        assert_eq!(parse_interval("45").unwrap(), std::time::Duration::from_secs(45));
+        assert!(parse_interval("0s").is_err());
+        assert!(parse_interval("soon").is_err());
+    }
+
+    #[test]
+    fn test_multi_region_result_merges_and_dedups() {
+        use crate::error::LogidError;
+
+        let message = |id: &str| ExtractedLogMessage {
+            id: id.to_string(),
+            ..ExtractedLogMessage::default()
+        };
+        let region_result = |region: &str, ids: &[&str]| {
+            let mut result = detailed_result("a", ids.iter().map(|id| message(id)).collect());
+            result.region = region.to_string();
+            result
+        };
+
+        let merged = MultiRegionResult::from_region_results(
+            "a",
+            vec![
+                ("eu".to_string(), Ok(region_result("eu", &["1", "2"]))),
+                ("i18n".to_string(), Err(LogidError::MissingCredentials("i18n".to_string()))),
+                ("us".to_string(), Ok(region_result("us", &["2", "3"]))),
+            ],
+        );
+        assert_eq!(merged.duplicates_removed, 1);
+        assert_eq!(
+            merged.regions.iter().map(|s| (s.region.as_str(), s.messages, s.is_ok())).collect::<Vec<_>>(),
+            vec![("eu", 2, true), ("i18n", 0, false), ("us", 2, true)]
+        );
+        assert_eq!(merged.failed_regions().count(), 1);
+        let status = serde_json::to_value(&merged).unwrap();
+        assert_eq!(status["regions"][1]["error"]["error_code"], "missing_credentials");
+        assert!(status["regions"][0].get("error").is_none());
+
+        let result = merged.into_result().unwrap();
+        assert_eq!(result.region, "eu,us");
+        let messages: Vec<(&str, Option<&str>)> =
+            result.messages.iter().map(|m| (m.id.as_str(), m.region.as_deref())).collect();
+        assert_eq!(messages, vec![("1", Some("eu")), ("2", Some("eu")), ("3", Some("us"))]);
+
+        // 消息 ID 相同但内容不同的不是同一条日志
+        let mut other = region_result("us", &["2"]);
+        other.messages[0].time = Some("2024-01-01T12:00:00Z".to_string());
+        let merged = MultiRegionResult::from_region_results(
+            "a",
+            vec![("eu".to_string(), Ok(region_result("eu", &["2"]))), ("us".to_string(), Ok(other))],
+        );
+        assert_eq!((merged.duplicates_removed, merged.result.messages.len()), (0, 2));
+
+        // 所有区域都失败时返回错误
+        let failed = MultiRegionResult::from_region_results(
+            "a",
+            vec![("us".to_string(), Err(LogidError::MissingCredentials("us".to_string())))],
+        );
+        assert!(!failed.has_success());
+        assert_eq!(failed.into_result().unwrap_err().error_code(), "missing_credentials");
+    }
+
+    #[tokio::test]
+    async fn test_client_result_cache_skips_repeat_requests() {
+        use crate::test_support::{http_response, mock_client, MockServer};
+        use std::sync::Arc;
+        use std::time::Duration;
+
+        let server = MockServer::start(Duration::from_millis(50), |_, head| {
+            if head.starts_with("GET") {
+                http_response(200, &[("x-jwt-token", "token")], "")
+            } else {
+                http_response(200, &[("content-type", "application/json")], r#"{"data":{"items":[]}}"#)
+            }
+        })
+        .await;
+
+        let client = mock_client(&server).await.with_result_cache(Arc::new(ResultCache::default()));
+
+        client.get_log_details("logid-1", &[]).await.unwrap();
+        client.get_log_details("logid-1", &[]).await.unwrap();
+        // 一次认证请求 + 一次查询请求
+        assert_eq!(server.request_count(), 2);
+
+        client.get_log_details("logid-2", &[]).await.unwrap();
+        assert_eq!(server.request_count(), 3);
+
+        // 并发查询同一个 logid 时只有一个请求到达日志服务
+        let (first, second) = tokio::join!(
+            client.get_log_details("logid-3", &[]),
+            client.get_log_details("logid-3", &[])
+        );
+        assert!(first.is_ok() && second.is_ok());
+        assert_eq!(server.request_count(), 4);
+    }
+
+    #[tokio::test]
+    async fn test_dry_run_builds_request_without_querying() {
+        use crate::test_support::{http_response, mock_client, MockServer};
+        use std::time::Duration;
+
+        let server = MockServer::start(Duration::ZERO, |_, head| {
+            assert!(head.starts_with("GET"), "dry-run 不应发送查询请求");
+            http_response(200, &[("x-jwt-token", "jwt-token-0123456789")], "")
+        })
+        .await;
+
+        let client = mock_client(&server).await.with_scan_span(30);
+
+        let requests = client.dry_run("logid-1", &["pay.api".to_string()]).await.unwrap();
+        assert_eq!(requests.len(), 1);
+        let request = &requests[0];
+        assert_eq!(request.url, server.url("/query"));
+        assert_eq!(request.body.vregion, "US-TTP");
+        assert_eq!(request.body.scan_span_in_min, 30);
+        assert!(request
+            .headers
+            .contains(&("X-Jwt-Token".to_string(), "jwt-****6789".to_string())));
+        assert!(!request.to_curl().contains("jwt-token-0123456789"));
+        assert!(request.to_curl().contains(r#"--data '{"logid":"logid-1","psm_list":["pay.api"]"#));
+        // 只有一次认证请求
+        assert_eq!(server.request_count(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_region_default_psms_apply_without_psm_flag() {
+        use crate::auth::EnvTokenProvider;
+        use crate::config::{Region, RegionConfig};
+        use std::sync::Arc;
+
+        std::env::set_var("LOGID_TEST_DEFAULT_PSMS_TOKEN", "jwt-token");
+        let provider = Arc::new(EnvTokenProvider::new("LOGID_TEST_DEFAULT_PSMS_TOKEN"));
+        let config = RegionConfig::new(Region::Us, "http://127.0.0.1:9/query".to_string(), "US-TTP".to_string(), Vec::new())
+            .with_default_psms(vec!["a.b.c".to_string(), "d.e.f".to_string()]);
+        let client = LogQueryClient::from_auth_provider(provider, config).await.unwrap();
+
+        let requests = client.dry_run("logid-1", &[]).await.unwrap();
+        assert_eq!(requests[0].body.psm_list, vec!["a.b.c", "d.e.f"]);
+        // 显式指定的 PSM 优先，不与区域默认值合并
+        let requests = client.dry_run("logid-1", &["pay.api".to_string()]).await.unwrap();
+        assert_eq!(requests[0].body.psm_list, vec!["pay.api"]);
+        std::env::remove_var("LOGID_TEST_DEFAULT_PSMS_TOKEN");
+    }
+
+    #[tokio::test]
+    async fn test_region_config_sets_default_scan_span_and_response_limit() {
+        use crate::auth::EnvTokenProvider;
+        use crate::config::{Region, RegionConfig};
+        use std::sync::Arc;
+
+        std::env::set_var("LOGID_TEST_SCAN_SPAN_TOKEN", "jwt-token");
+        let provider = Arc::new(EnvTokenProvider::new("LOGID_TEST_SCAN_SPAN_TOKEN"));
+        let config = RegionConfig::new(Region::Us, "http://127.0.0.1:9/query".to_string(), "US-TTP".to_string(), Vec::new())
+            .with_scan_span(Some(30))
+            .with_max_response_size_mb(Some(1));
+        let client = LogQueryClient::from_auth_provider(provider, config).await.unwrap();
+        assert_eq!(client.scan_span(), 30);
+        let requests = client.dry_run("logid-1", &[]).await.unwrap();
+        assert_eq!(requests[0].body.scan_span_in_min, 30);
+
+        // 命令行参数仍然可以覆盖区域配置
+        let client = client.with_scan_span(45);
+        let requests = client.dry_run("logid-1", &[]).await.unwrap();
+        assert_eq!(requests[0].body.scan_span_in_min, 45);
+        std::env::remove_var("LOGID_TEST_SCAN_SPAN_TOKEN");
+    }
+
+    #[tokio::test]
+    async fn test_region_config_builder() {
+        use crate::auth::EnvTokenProvider;
+        use crate::config::{Region, RegionConfig};
+        use std::sync::Arc;
+
+        let error = RegionConfig::builder(Region::Us).zones(["US-East"]).build().unwrap_err();
+        assert_eq!(error.error_code(), "invalid_region_config");
+        let error = RegionConfig::builder(Region::Us)
+            .log_service_url("http://127.0.0.1:9/query")
+            .build()
+            .unwrap_err();
+        assert!(error.to_string().contains("zones"), "{}", error);
+
+        let config = RegionConfig::builder(Region::Us)
+            .log_service_url("http://127.0.0.1:9/query")
+            .zones(["US-East", "US-West"])
+            .scan_span(20)
+            .proxy("direct")
+            .build()
+            .unwrap();
+        assert_eq!(config.vregion, "US-East,US-West");
+
+        std::env::set_var("LOGID_TEST_BUILDER_TOKEN", "jwt-token");
+        let provider = Arc::new(EnvTokenProvider::new("LOGID_TEST_BUILDER_TOKEN"));
+        let client = LogQueryClient::from_auth_provider(provider, config).await.unwrap();
+        let requests = client.dry_run("logid-1", &[]).await.unwrap();
+        assert_eq!(requests[0].url, "http://127.0.0.1:9/query");
+        assert_eq!(requests[0].body.vregion, "US-East,US-West");
+        assert_eq!(requests[0].body.scan_span_in_min, 20);
+        std::env::remove_var("LOGID_TEST_BUILDER_TOKEN");
+    }
+
+    #[tokio::test]
+    async fn test_client_uses_custom_auth_provider() {
+        use crate::auth::{AuthProvider, FileTokenProvider};
+        use crate::config::{Region, RegionConfig};
+        use std::sync::Arc;
+
+        let dir = tempfile::tempdir().unwrap();
+        let token_path = dir.path().join("token");
+        let provider: Arc<dyn AuthProvider> = Arc::new(FileTokenProvider::new(&token_path));
+        let config = RegionConfig::new(
+            Region::Us,
+            "http://127.0.0.1:9/query".to_string(),
+            "US-TTP".to_string(),
+            Vec::new(),
+        );
+        let client = LogQueryClient::from_auth_provider(provider, config).await.unwrap();
+        assert!(client.auth_manager().is_none());
+        assert!(client.dry_run("logid-1", &[]).await.is_err());
+
+        // 每次查询重新读取令牌文件
+        std::fs::write(&token_path, "file-token-0123456789\n").unwrap();
+        let requests = client.dry_run("logid-1", &[]).await.unwrap();
+        assert!(requests[0]
+            .headers
+            .contains(&("X-Jwt-Token".to_string(), "file****6789".to_string())));
+    }
+
+    #[tokio::test]
+    async fn test_client_applies_region_header_profile() {
+        use crate::config::HeaderProfile;
+        use crate::test_support::{http_response, mock_auth, mock_region_config, MockServer};
+        use std::time::Duration;
+
+        let server = MockServer::start(Duration::ZERO, |_, head| {
+            let head = head.to_lowercase();
+            if head.starts_with("get") {
+                http_response(200, &[("x-jwt-token", "token")], "")
+            } else if head.contains("user-agent: logid-test")
+                && head.contains("origin: https://o.example.com")
+                && head.contains("x-marker: 1")
+            {
+                http_response(200, &[("content-type", "application/json")], r#"{"data":{"items":[]}}"#)
+            } else {
+                http_response(400, &[], "missing headers")
+            }
+        })
+        .await;
+
+        let headers = HeaderProfile {
+            user_agent: Some("logid-test".to_string()),
+            origin: Some("https://o.example.com".to_string()),
+            custom: [("X-Marker".to_string(), "1".to_string())].into(),
+            ..HeaderProfile::default()
+        };
+        let config = mock_region_config(&server).with_headers(headers);
+        let client = LogQueryClient::new(mock_auth(&server), config).await.unwrap();
+
+        assert!(client.get_log_details("logid-1", &[]).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_stats_block_reports_filter_hits_and_warnings() {
+        use crate::test_support::{http_response, mock_client, MockServer};
+        use std::time::Duration;
+
+        const BODY: &str = r#"{"data":{"items":[{"id":"1","group":{},"value":[
+            {"id":"v1","kv_list":[{"key":"_msg","value":"\"LogID\": \"abc\" ok"}]},
+            {"id":"v2","kv_list":[{"key":"_msg","value":"plain"}]}
+        ]}]},"tag_infos":"bad"}"#;
+
+        let server = MockServer::start(Duration::ZERO, |_, head| {
+            if head.starts_with("GET") {
+                http_response(200, &[("x-jwt-token", "token")], "")
+            } else {
+                http_response(200, &[("content-type", "application/json")], BODY)
+            }
+        })
+        .await;
+
+        let client = mock_client(&server).await.with_stats(true);
+
+        let result = client.get_log_details("logid-1", &[]).await.unwrap();
+        let stats = result.stats.clone().unwrap();
+        assert_eq!(stats.bytes_downloaded, BODY.len());
+        assert_eq!(stats.items, 1);
+        assert_eq!(stats.messages_extracted, 2);
+        assert_eq!(stats.filter_hits.len(), 1);
+        assert!(stats.filter_hits[0].pattern.contains("LogID"));
+        assert_eq!(stats.filter_hits[0].messages, 1);
+        assert_eq!(stats.filter_hits[0].bytes_removed, r#""LogID": "abc""#.len());
+        assert_eq!(stats.parse_warnings.len(), 1);
+
+        // 合并多个区域时统计信息累加
+        let merged = DetailedLogResult::merge_regions("logid-1", vec![result.clone(), result]);
+        let merged_stats = merged.stats.unwrap();
+        assert_eq!(merged_stats.messages_extracted, 4);
+        assert_eq!(merged_stats.filter_hits[0].bytes_removed, 2 * r#""LogID": "abc""#.len());
+    }
+
+    #[tokio::test]
+    async fn test_scan_span_is_sent_in_request_body() {
+        use crate::test_support::{http_response, mock_client, MockServer};
+        use std::time::Duration;
+
+        let server = MockServer::start(Duration::ZERO, |_, request| {
+            if request.starts_with("GET") {
+                http_response(200, &[("x-jwt-token", "token")], "")
+            } else if request.contains(r#""scan_span_in_min":30"#) {
+                http_response(200, &[("content-type", "application/json")], r#"{"data":{"items":[]}}"#)
+            } else {
+                http_response(400, &[], "unexpected scan span")
+            }
+        })
+        .await;
+
+        let client = mock_client(&server).await;
+        assert!(client.query_logs("logid-1", &[]).await.is_err());
+
+        let client = client.with_scan_span(30);
+        assert!(client.query_logs("logid-1", &[]).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_auto_widen_retries_empty_results() {
+        use crate::test_support::{http_response, mock_client, MockServer};
+        use std::time::Duration;
+
+        let server = MockServer::start(Duration::ZERO, |_, request| {
+            let headers = [("content-type", "application/json")];
+            if request.starts_with("GET") {
+                http_response(200, &[("x-jwt-token", "token")], "")
+            } else if request.contains(r#""scan_span_in_min":60"#) {
+                http_response(200, &headers, r#"{"data":{"items":[{"id":"1","group":{},"value":[{"id":"v1","kv_list":[{"key":"_msg","value":"late"}]}]}]}}"#)
+            } else {
+                http_response(200, &headers, r#"{"data":{"items":[]}}"#)
+            }
+        })
+        .await;
+
+        let client = mock_client(&server).await;
+
+        // 未启用时只查询一次
+        let result = client.get_log_details("logid-1", &[]).await.unwrap();
+        assert!(result.messages.is_empty());
+        assert!(result.scan_widening.is_none());
+
+        let client = client.with_auto_widen(true);
+        let result = client.get_log_details("logid-1", &[]).await.unwrap();
+        assert_eq!(result.messages.len(), 1);
+        assert_eq!(
+            result.scan_widening,
+            Some(ScanWidening { attempted_spans: vec![10, 30, 60], final_span: 60 })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_per_zone_queries_are_merged() {
+        use crate::config::{Region, RegionConfig};
+        use crate::test_support::{http_response, mock_auth, MockServer};
+        use std::time::Duration;
+
+        let item = |id: &str| {
+            format!(
+                r#"{{"id":"{}","group":{{}},"value":[{{"id":"v{}","kv_list":[{{"key":"_msg","value":"m"}}]}}]}}"#,
+                id, id
+            )
+        };
+        let zone_a = format!(r#"{{"data":{{"items":[{},{}]}}}}"#, item("1"), item("shared"));
+        let zone_b = format!(r#"{{"data":{{"items":[{},{}]}}}}"#, item("2"), item("shared"));
+        let server = MockServer::start(Duration::ZERO, move |_, request| {
+            let headers = [("content-type", "application/json")];
+            if request.starts_with("GET") {
+                http_response(200, &[("x-jwt-token", "token")], "")
+            } else if request.contains(r#""vregion":"US-A""#) {
+                http_response(200, &headers, &zone_a)
+            } else if request.contains(r#""vregion":"US-B""#) {
+                http_response(200, &headers, &zone_b)
+            } else {
+                http_response(500, &[], "zone unavailable")
+            }
+        })
+        .await;
+
+        let auth = mock_auth(&server);
+        let zones = vec!["US-A".to_string(), "US-B".to_string(), "US-C".to_string()];
+        let config = RegionConfig::new(Region::Us, server.url("/query"), zones.join(","), zones);
+        let client = LogQueryClient::new(auth, config).await.unwrap();
+
+        // 未启用时所有可用区拼接在一个请求中
+        assert!(client.query_logs("logid-1", &[]).await.is_err());
+
+        let client = client.with_per_zone(true);
+        let response = client.query_logs("logid-1", &[]).await.unwrap();
+        let ids: Vec<&str> = response.data.as_ref().unwrap().items.iter().map(|i| i.id.as_str()).collect();
+        assert_eq!(ids, vec!["1", "shared", "2"]);
+        assert_eq!(response.warnings.len(), 1);
+        assert!(response.warnings[0].contains("US-C"));
+    }
+
+    #[tokio::test]
+    async fn test_zone_selection() {
+        use crate::config::{Region, RegionConfig};
+        use crate::test_support::{http_response, mock_auth, MockServer};
+        use std::time::Duration;
+
+        let server = MockServer::start(Duration::ZERO, |_, request| {
+            if request.starts_with("GET") {
+                http_response(200, &[("x-jwt-token", "token")], "")
+            } else if request.contains(r#""vregion":"US-B""#) {
+                let body = r#"{"data":{"items":[{"id":"1","group":{},"value":[]}]}}"#;
+                http_response(200, &[("content-type", "application/json")], body)
+            } else {
+                http_response(500, &[], "unexpected vregion")
+            }
+        })
+        .await;
+
+        let zones = vec!["US-A".to_string(), "US-B".to_string()];
+        let config = RegionConfig::new(Region::Us, server.url("/query"), zones.join(","), zones);
+        let client = LogQueryClient::new(mock_auth(&server), config).await.unwrap();
+
+        // --zone 只查询所选的可用区，名称不区分大小写，不属于该区域的可用区报错
+        assert!(client.region_config().select_zones(&["US-X".to_string()]).is_err());
+        let client = client.with_zones(&["us-b".to_string()]).unwrap();
+        assert_eq!(client.region_config().vregion, "US-B");
+        let response = client.query_logs("logid-1", &[]).await.unwrap();
+        assert_eq!(response.data.unwrap().items.len(), 1);
+        assert!(response.warnings.is_empty());
+        assert!(matches!(
+            client.with_zones(&["US-A".to_string()]),
+            Err(crate::error::LogidError::InvalidZone(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_request_timeout_is_configurable() {
+        use crate::config::{Region, RegionConfig};
+        use crate::test_support::{http_response, mock_auth, mock_client, MockServer};
+        use std::time::{Duration, Instant};
+
+        let server = MockServer::start(Duration::from_millis(300), |_, head| {
+            if head.starts_with("GET") {
+                http_response(200, &[("x-jwt-token", "token")], "")
+            } else {
+                http_response(200, &[("content-type", "application/json")], r#"{"data":{"items":[]}}"#)
+            }
+        })
+        .await;
+
+        let server = &server;
+        let client = |timeout: Duration| async move {
+            mock_client(server).await.with_timeout(timeout)
+        };
+
+        let started = Instant::now();
+        let short = client(Duration::from_millis(50)).await;
+        assert!(short.get_log_details("logid-1", &[]).await.is_err());
+        assert!(started.elapsed() < Duration::from_millis(300));
+
+        let long = client(Duration::from_secs(5)).await;
+        assert!(long.get_log_details("logid-1", &[]).await.is_ok());
+
+        // 区域配置中的超时时间作为客户端的默认值
+        let auth = mock_auth(server);
+        let config = RegionConfig::builder(Region::Us)
+            .log_service_url(server.url("/query"))
+            .vregion("US-TTP")
+            .timeout(Duration::from_millis(50))
+            .build()
+            .unwrap();
+        let started = Instant::now();
+        let client = LogQueryClient::new(auth, config).await.unwrap();
+        assert!(client.get_log_details("logid-1", &[]).await.is_err());
+        assert!(started.elapsed() < Duration::from_millis(300));
+    }
+
+    #[tokio::test]
+    async fn test_transient_failures_are_retried() {
+        use crate::retry::RetryPolicy;
+        use crate::test_support::{http_response, mock_client, MockServer};
+        use std::time::Duration;
+
+        // 认证失败一次，查询连续两次 503
+        let server = MockServer::start(Duration::ZERO, |n, head| match n {
+            1 => http_response(502, &[], "bad gateway"),
+            2 => http_response(200, &[("x-jwt-token", "token")], ""),
+            5 if head.starts_with("POST") => {
+                http_response(200, &[("content-type", "application/json")], r#"{"data":{"items":[]}}"#)
+            }
+            _ => http_response(503, &[], "unavailable"),
+        })
+        .await;
+
+        let policy = RetryPolicy {
+            max_retries: 2,
+            base_delay: Duration::from_millis(1),
+            max_delay: Duration::from_millis(5),
+        };
+        let client = mock_client(&server).await.with_retry_policy(policy);
+
+        assert!(client.get_log_details("logid-1", &[]).await.is_ok());
+        assert_eq!(server.request_count(), 5);
+
+        // 不重试时直接返回错误
+        let client = client.with_retry_policy(RetryPolicy { max_retries: 0, ..policy });
+        assert!(client.query_logs("logid-2", &[]).await.is_err());
+        assert_eq!(server.request_count(), 6);
+        assert!(policy.delay(10) <= policy.max_delay && policy.delay(10) >= policy.max_delay / 2);
+    }
+
+    #[tokio::test]
+    async fn test_errors_map_to_exit_codes() {
+        use crate::auth::AuthManager;
+        use crate::config::{Region, RegionConfig};
+        use crate::error::{exit_code, LogidError};
+        use crate::test_support::{http_response, mock_client, MockServer};
+        use std::time::Duration;
+
+        // 认证服务不可达时是网络错误，而不是认证失败
+        let closed = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/jwt", closed.local_addr().unwrap());
+        drop(closed);
+        let auth = AuthManager::with_credentials(Region::Us, "session".to_string(), url).unwrap();
+        let config = RegionConfig::new(Region::Us, "http://127.0.0.1:9/query".to_string(), "US-TTP".to_string(), Vec::new());
+        let client = LogQueryClient::new(auth, config).await.unwrap();
+        let error = client.query_logs("logid-1", &[]).await.unwrap_err();
+        assert_eq!(error.exit_code(), exit_code::NETWORK);
+
+        let server = MockServer::start(Duration::ZERO, |_, _| http_response(401, &[], "expired")).await;
+        let client = mock_client(&server).await;
+        let error = client.query_logs("logid-1", &[]).await.unwrap_err();
+        assert_eq!(error.exit_code(), exit_code::AUTH);
+
+        // 认证服务返回 401 表示会话过期，查询客户端不再包装成通用的认证失败
+        assert_eq!(error.error_code(), "session_expired");
+
+        let error = LogidError::RegionNotConfigured("cn".to_string());
+        assert_eq!(error.exit_code(), exit_code::CONFIG);
+        assert_eq!((error.error_code(), error.region()), ("region_not_configured", Some("cn")));
+    }
+
+    #[test]
+    fn test_query_context_loads_from_results() {
+        let context = QueryContext {
+            psm_list: vec!["pay.api".to_string()],
+            time_range: Some(TimeRange { start: Some(1704100000), end: Some(1704103600) }),
+            where_expr: Some(r#"level == "ERROR""#.to_string()),
+            per_zone: true,
+            ..QueryContext::new(vec!["logid-1".to_string()], vec!["us".to_string()])
+        };
+        assert!(!context.filters_changed());
+
+        let dir = tempfile::tempdir().unwrap();
+        let write = |name: &str, value: serde_json::Value| {
+            let path = dir.path().join(name);
+            std::fs::write(&path, value.to_string()).unwrap();
+            path.to_string_lossy().into_owned()
+        };
+
+        let mut result = detailed_result("logid-1", Vec::new());
+        result.context = Some(context.clone());
+        let single = write("single.json", serde_json::to_value(&result).unwrap());
+        let multi = write(
+            "multi.json",
+            serde_json::json!({"region": "us", "results": {"logid-1": serde_json::to_value(&result).unwrap()}}),
+        );
+        let bare = write("context.json", serde_json::to_value(&context).unwrap());
+
+        for path in [single, multi, bare] {
+            assert_eq!(QueryContext::load(&path).unwrap(), context);
+        }
+
+        // 默认值不输出，保持上下文简洁
+        let value = serde_json::to_value(&context).unwrap();
+        assert!(value.get("raw").is_none() && value.get("levels").is_none());
+        assert_eq!(value["per_zone"], true);
+    }
+
+    #[tokio::test]
+    async fn test_follow_referenced_logids() {
+        let text = "call downstream logid=20240101120000010112345678ABCDEF ok, \
+                    trace 550e8400-e29b-41d4-a716-446655440000 and X-Tt-Logid: \"abcdefabcdef0123456789\"";
+        assert_eq!(
+            extract_logids(text),
+            vec![
+                "20240101120000010112345678ABCDEF",
+                "550e8400-e29b-41d4-a716-446655440000",
+                "abcdefabcdef0123456789",
+            ]
+        );
+        assert!(extract_logids("status=500 took 302ms").is_empty());
+
+        let message = |text: &str| crate::test_support::message("a.b.c", "INFO", text);
+        // root -> child-a, child-b；child-a 又引用 root（成环）和 grandchild
+        let query = |logid: String| async move {
+            let text = match logid.as_str() {
+                "root-logid-000000001" => "logid=child-a-logid-00000001 logid=child-b-logid-00000001",
+                "child-a-logid-00000001" => "logid=root-logid-000000001 logid=grandchild-logid-00001",
+                "child-b-logid-00000001" => return Err(crate::error::LogidError::InternalError("失败".to_string())),
+                _ => "leaf",
+            };
+            Ok(detailed_result(&logid, vec![message(text)]))
+        };
+
+        let tree = follow_logids("root-logid-000000001", 1, DEFAULT_FOLLOW_MAX_LOGIDS, query).await;
+        let children: Vec<&str> = tree.children.iter().map(|c| c.logid.as_str()).collect();
+        assert_eq!(children, vec!["child-a-logid-00000001", "child-b-logid-00000001"]);
+        assert!(tree.children[0].children.is_empty());
+        assert!(tree.children[1].error.is_some());
+        assert_eq!(tree.message_count(), 2);
+
+        let tree = follow_logids("root-logid-000000001", 3, DEFAULT_FOLLOW_MAX_LOGIDS, query).await;
+        let grandchild = &tree.children[0].children[0];
+        assert_eq!((grandchild.logid.as_str(), grandchild.depth), ("grandchild-logid-00001", 2));
+        assert!(tree.format_tree().contains("    grandchild-logid-00001 (1 条消息)"));
+
+        // 达到数量上限后不再展开
+        let tree = follow_logids("root-logid-000000001", 3, 2, query).await;
+        assert_eq!(tree.children.len(), 1);
+    }
 }
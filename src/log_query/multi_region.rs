@@ -1,51 +1,192 @@
 //! 多区域日志查询模块
 
-use crate::conditional_info;
 use crate::auth::MultiRegionAuthManager;
+use crate::config::EnvTarget;
 use crate::error::LogidError;
 use crate::log_query::client::LogQueryClient;
+use crate::log_query::progress::ProgressSender;
 use crate::log_query::types::*;
+use crate::retry::RetryPolicy;
+use futures::future::join_all;
 use std::collections::HashMap;
 
 /// 多区域日志查询器
 ///
 /// 管理多个区域的日志查询客户端，提供统一的查询接口。
+/// 缺少凭据的区域不会阻止其他区域查询，这些区域的查询结果为认证错误。
 #[derive(Debug)]
-#[allow(dead_code)]
 pub struct MultiRegionLogQuery {
     /// 多区域认证管理器
-    #[allow(dead_code)]
     auth_manager: MultiRegionAuthManager,
     /// 查询客户端映射
     clients: HashMap<String, LogQueryClient>,
+    /// 认证不可用的区域
+    unavailable: Vec<String>,
 }
 
-#[allow(dead_code)]
 impl MultiRegionLogQuery {
     /// 创建新的多区域日志查询器
     pub async fn new(regions: &[&str]) -> Result<Self, LogidError> {
+        Self::for_environment(regions, EnvTarget::Prod).await
+    }
+
+    /// 创建使用指定部署环境端点的多区域日志查询器
+    ///
+    /// 缺少凭据的区域会被记为不可用，只有所有区域都不可用时才返回错误。
+    pub async fn for_environment(regions: &[&str], env: EnvTarget) -> Result<Self, LogidError> {
         let auth_manager = MultiRegionAuthManager::new(regions)?;
         let mut clients = HashMap::new();
+        let mut unavailable = Vec::new();
+        let mut first_error = None;
 
         for region in regions {
             let region_config = crate::config::get_region_config(region)
-                .ok_or_else(|| LogidError::UnsupportedRegion(region.to_string()))?;
+                .ok_or_else(|| LogidError::UnsupportedRegion(region.to_string()))?
+                .for_environment(env)?;
+            if !region_config.is_configured() {
+                return Err(LogidError::RegionNotConfigured(region.to_string()));
+            }
 
-            let auth = auth_manager.get_manager(region).ok_or_else(|| {
-                LogidError::AuthenticationFailed(format!("未找到 {} 区域的认证管理器", region))
-            })?;
+            let auth = match auth_manager.manager(region) {
+                Ok(auth) => auth,
+                Err(e) => {
+                    conditional_info!("{} 区域认证不可用，跳过: {}", region, e);
+                    unavailable.push(region.to_string());
+                    first_error.get_or_insert(e);
+                    continue;
+                }
+            };
 
             let client = LogQueryClient::new(auth.as_ref().clone(), region_config).await?;
             clients.insert(region.to_string(), client);
         }
 
-        conditional_info!("多区域日志查询器初始化完成，共 {} 个区域", clients.len());
+        if clients.is_empty() {
+            if let Some(e) = first_error {
+                return Err(e);
+            }
+        }
+
+        conditional_info!(
+            "多区域日志查询器初始化完成，共 {} 个区域，{} 个区域不可用",
+            clients.len(),
+            unavailable.len()
+        );
         Ok(Self {
             auth_manager,
             clients,
+            unavailable,
         })
     }
 
+    /// 对所有区域客户端应用同一个设置
+    fn map_clients(mut self, f: impl Fn(LogQueryClient) -> LogQueryClient) -> Self {
+        self.clients = self
+            .clients
+            .into_iter()
+            .map(|(region, client)| (region, f(client)))
+            .collect();
+        self
+    }
+
+    /// 设置所有区域客户端的响应体大小上限（字节）
+    pub fn with_max_response_size(self, max_bytes: usize) -> Self {
+        self.map_clients(|client| client.with_max_response_size(max_bytes))
+    }
+
+    /// 设置所有区域客户端的请求超时时间
+    pub fn with_timeout(self, timeout: std::time::Duration) -> Self {
+        self.map_clients(|client| client.with_timeout(timeout))
+    }
+
+    /// 设置所有区域客户端的重试策略
+    pub fn with_retry_policy(self, retry_policy: RetryPolicy) -> Self {
+        self.map_clients(|client| client.with_retry_policy(retry_policy))
+    }
+
+    /// 设置所有区域客户端的扫描时间范围（分钟）
+    pub fn with_scan_span(self, minutes: i32) -> Self {
+        self.map_clients(|client| client.with_scan_span(minutes))
+    }
+
+    /// 设置所有区域客户端在结果为空时是否自动扩大扫描范围重试
+    pub fn with_auto_widen(self, auto_widen: bool) -> Self {
+        self.map_clients(|client| client.with_auto_widen(auto_widen))
+    }
+
+    /// 设置所有区域客户端是否按可用区分别并发查询
+    pub fn with_per_zone(self, per_zone: bool) -> Self {
+        self.map_clients(|client| client.with_per_zone(per_zone))
+    }
+
+    /// 只查询指定的可用区
+    ///
+    /// 每个区域只查询属于它的可用区，不包含任何所选可用区的区域不再查询；
+    /// 可用区不属于任何区域时返回 `InvalidZone`。
+    pub fn with_zones(mut self, zones: &[String]) -> Result<Self, LogidError> {
+        if zones.is_empty() {
+            return Ok(self);
+        }
+        if let Some(unknown) = zones.iter().find(|zone| {
+            self.clients
+                .values()
+                .all(|client| client.region_config().owned_zones(std::slice::from_ref(zone)).is_empty())
+        }) {
+            let mut regions: Vec<&str> = self.clients.keys().map(String::as_str).collect();
+            regions.sort_unstable();
+            return Err(LogidError::InvalidZone(format!(
+                "{} 不属于任何查询的区域（{}）",
+                unknown,
+                regions.join(", ")
+            )));
+        }
+
+        let mut clients = HashMap::new();
+        for (region, client) in self.clients {
+            let owned = client.region_config().owned_zones(zones);
+            if owned.is_empty() {
+                conditional_info!("区域 {} 不包含所选的可用区，跳过", region);
+                continue;
+            }
+            clients.insert(region, client.with_zones(&owned)?);
+        }
+        self.clients = clients;
+        Ok(self)
+    }
+
+    /// 设置所有区域客户端的绝对查询时间范围
+    pub fn with_time_range(self, range: Option<TimeRange>) -> Self {
+        self.map_clients(|client| client.with_time_range(range.clone()))
+    }
+
+    /// 设置所有区域客户端保留的日志级别
+    pub fn with_levels(self, levels: &[String]) -> Self {
+        self.map_clients(|client| client.with_levels(levels))
+    }
+
+    /// 设置所有区域客户端是否跳过消息过滤
+    pub fn with_raw(self, raw: bool) -> Self {
+        self.map_clients(|client| client.with_raw(raw))
+    }
+
+    /// 设置所有区域客户端是否在结果中附带统计信息
+    pub fn with_stats(self, stats: bool) -> Self {
+        self.map_clients(|client| client.with_stats(stats))
+    }
+
+    /// 为所有区域客户端设置进度事件发送端
+    pub fn with_progress(self, sender: ProgressSender) -> Self {
+        self.map_clients(|client| client.with_progress(sender.clone()))
+    }
+
+    /// 重新加载所有区域客户端的消息过滤规则
+    pub fn reload_message_filters(&mut self) -> Result<(), LogidError> {
+        for client in self.clients.values_mut() {
+            client.reload_message_filters()?;
+        }
+        Ok(())
+    }
+
     /// 获取指定区域的查询客户端
     pub fn get_client(&self, region: &str) -> Option<&LogQueryClient> {
         self.clients.get(region)
@@ -58,10 +199,7 @@ impl MultiRegionLogQuery {
         logid: &str,
         psm_list: &[String],
     ) -> Result<LogQueryResponse, LogidError> {
-        let client = self.clients.get(region).ok_or_else(|| {
-            LogidError::UnsupportedRegion(format!("未找到 {} 区域的查询客户端", region))
-        })?;
-
+        let client = self.client(region)?;
         client.query_logs(logid, psm_list).await
     }
 
@@ -72,15 +210,60 @@ impl MultiRegionLogQuery {
         logid: &str,
         psm_list: &[String],
     ) -> Result<DetailedLogResult, LogidError> {
-        let client = self.clients.get(region).ok_or_else(|| {
-            LogidError::UnsupportedRegion(format!("未找到 {} 区域的查询客户端", region))
-        })?;
-
+        let client = self.client(region)?;
         client.get_log_details(logid, psm_list).await
     }
 
-    /// 获取所有已管理的区域列表
+    /// 并发查询所有区域的详细日志信息
+    ///
+    /// 返回每个区域各自的查询结果，区域顺序与 `managed_regions` 一致。
+    pub async fn get_log_details_all(
+        &self,
+        logid: &str,
+        psm_list: &[String],
+    ) -> Vec<(String, Result<DetailedLogResult, LogidError>)> {
+        let regions = self.managed_regions();
+        let results = join_all(regions.iter().map(|region| {
+            self.get_log_details_region(region, logid, psm_list)
+        }))
+        .await;
+
+        regions.into_iter().zip(results).collect()
+    }
+
+    /// 并发查询所有区域并合并结果
+    ///
+    /// 各区域的客户端同时发起查询，单个区域失败（包括认证不可用）只记录在该区域的状态中；
+    /// 成功区域的消息按区域标记后合并，同一条日志从多个区域返回时只保留一条。
+    pub async fn query_all(&self, logid: &str, psm_list: &[String]) -> MultiRegionResult {
+        MultiRegionResult::from_region_results(logid, self.get_log_details_all(logid, psm_list).await)
+    }
+
+    /// 获取所有已管理的区域列表，包括认证不可用的区域
     pub fn managed_regions(&self) -> Vec<String> {
-        self.clients.keys().cloned().collect()
+        let mut regions: Vec<String> = self.clients.keys().chain(&self.unavailable).cloned().collect();
+        regions.sort();
+        regions
+    }
+
+    /// 获取认证不可用的区域列表
+    pub fn unavailable_regions(&self) -> &[String] {
+        &self.unavailable
+    }
+
+    /// 移除指定区域的查询客户端，之后的查询不再包含该区域
+    pub fn skip_region(&mut self, region: &str) {
+        self.clients.remove(region);
+    }
+
+    /// 获取区域的查询客户端，认证不可用时返回该区域的认证错误
+    fn client(&self, region: &str) -> Result<&LogQueryClient, LogidError> {
+        if let Some(client) = self.clients.get(region) {
+            return Ok(client);
+        }
+        if self.unavailable.iter().any(|r| r == region) {
+            self.auth_manager.manager(region)?;
+        }
+        Err(LogidError::UnsupportedRegion(format!("未找到 {} 区域的查询客户端", region)))
     }
 }
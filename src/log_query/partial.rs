@@ -0,0 +1,56 @@
+//! 截断响应的部分解析模块
+//!
+//! 当响应体超过大小上限被截断时，完整的 JSON 已无法解析。
+//! 这里以流式方式逐个解析 `items` 数组中的元素，尽可能提取已完整接收的日志项目。
+
+use crate::log_query::types::LogItem;
+
+/// 从被截断的响应体中提取完整的日志项目
+///
+/// 定位第一个 `"items"` 数组后逐个反序列化元素，遇到不完整的元素时停止。
+pub(crate) fn extract_partial_items(body: &[u8]) -> Vec<LogItem> {
+    let mut items = Vec::new();
+
+    let Some(mut pos) = find_items_array_start(body) else {
+        return items;
+    };
+
+    loop {
+        // 跳过元素之间的空白和逗号
+        while pos < body.len() && (body[pos].is_ascii_whitespace() || body[pos] == b',') {
+            pos += 1;
+        }
+        if pos >= body.len() || body[pos] == b']' {
+            break;
+        }
+
+        let mut stream = serde_json::Deserializer::from_slice(&body[pos..]).into_iter::<LogItem>();
+        match stream.next() {
+            Some(Ok(item)) => {
+                items.push(item);
+                pos += stream.byte_offset();
+            }
+            _ => break,
+        }
+    }
+
+    items
+}
+
+/// 查找 `"items"` 数组的起始位置（`[` 之后的偏移）
+fn find_items_array_start(body: &[u8]) -> Option<usize> {
+    const KEY: &[u8] = b"\"items\"";
+
+    let key_pos = body.windows(KEY.len()).position(|w| w == KEY)?;
+    let mut pos = key_pos + KEY.len();
+
+    while pos < body.len() && (body[pos].is_ascii_whitespace() || body[pos] == b':') {
+        pos += 1;
+    }
+
+    if pos < body.len() && body[pos] == b'[' {
+        Some(pos + 1)
+    } else {
+        None
+    }
+}
@@ -0,0 +1,44 @@
+//! 查询进度事件模块
+//!
+//! 查询过程中通过可选的 `tokio::sync::mpsc` 通道发送结构化事件，
+//! 便于 GUI、TUI 等嵌入方展示实时状态，而无需解析 tracing 日志。
+
+use serde::Serialize;
+use tokio::sync::mpsc;
+
+/// 查询进度事件
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum ProgressEvent {
+    /// 开始在某个区域查询
+    RegionStarted { region: String, logid: String },
+    /// 已接收一页响应数据（`n` 从 1 开始）
+    PageFetched {
+        region: String,
+        logid: String,
+        n: usize,
+    },
+    /// 已从响应中提取日志消息
+    MessagesExtracted {
+        region: String,
+        logid: String,
+        count: usize,
+    },
+    /// 区域查询失败
+    RegionFailed {
+        region: String,
+        logid: String,
+        error: String,
+    },
+}
+
+/// 进度事件发送端
+pub type ProgressSender = mpsc::UnboundedSender<ProgressEvent>;
+
+/// 进度事件接收端
+pub type ProgressReceiver = mpsc::UnboundedReceiver<ProgressEvent>;
+
+/// 创建进度事件通道
+pub fn progress_channel() -> (ProgressSender, ProgressReceiver) {
+    mpsc::unbounded_channel()
+}
@@ -0,0 +1,37 @@
+//! PSM 列表文件解析
+
+use crate::error::LogidError;
+
+/// 从文本中解析 PSM 列表
+///
+/// 每行一个 PSM，忽略空行和 `#` 开头的注释，行尾的 `# 注释` 也会被去掉。
+pub fn parse_psm_list(content: &str) -> Vec<String> {
+    content
+        .lines()
+        .map(|line| line.split('#').next().unwrap_or_default().trim())
+        .filter(|line| !line.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// 读取 PSM 列表文件
+pub fn load_psm_file(path: &str) -> Result<Vec<String>, LogidError> {
+    let content = std::fs::read_to_string(path).map_err(|e| {
+        LogidError::IoError(std::io::Error::new(
+            e.kind(),
+            format!("读取 PSM 文件 {} 失败: {}", path, e),
+        ))
+    })?;
+    Ok(parse_psm_list(&content))
+}
+
+/// 合并命令行 `--psm` 参数和 PSM 文件中的列表，按首次出现的顺序去重
+pub fn merge_psm_lists(flags: &[String], file: &[String]) -> Vec<String> {
+    let mut merged: Vec<String> = Vec::with_capacity(flags.len() + file.len());
+    for psm in flags.iter().chain(file) {
+        if !merged.contains(psm) {
+            merged.push(psm.clone());
+        }
+    }
+    merged
+}
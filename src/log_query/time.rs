@@ -0,0 +1,120 @@
+//! 日志时间解析模块
+
+use crate::error::LogidError;
+use crate::log_query::types::TimeRange;
+use chrono::{DateTime, Duration, NaiveDateTime, SecondsFormat, TimeZone, Utc};
+
+/// 解析日志消息中的时间
+///
+/// 支持 RFC3339、`YYYY-MM-DD HH:MM:SS[.fff]`（按 UTC 处理）以及
+/// 秒/毫秒/微秒/纳秒精度的 Unix 时间戳。
+pub fn parse_message_time(value: &str) -> Option<DateTime<Utc>> {
+    let value = value.trim();
+
+    if let Ok(number) = value.parse::<i64>() {
+        // 根据位数判断时间戳精度
+        let dt = match value.trim_start_matches('-').len() {
+            0..=10 => Utc.timestamp_opt(number, 0).single(),
+            11..=13 => Utc.timestamp_millis_opt(number).single(),
+            14..=16 => Some(Utc.timestamp_nanos(number.saturating_mul(1000))),
+            _ => Some(Utc.timestamp_nanos(number)),
+        };
+        return dt;
+    }
+
+    if let Ok(dt) = DateTime::parse_from_rfc3339(value) {
+        return Some(dt.with_timezone(&Utc));
+    }
+
+    ["%Y-%m-%d %H:%M:%S%.f", "%Y-%m-%d %H:%M:%S", "%Y-%m-%dT%H:%M:%S%.f"]
+        .iter()
+        .find_map(|fmt| NaiveDateTime::parse_from_str(value, fmt).ok())
+        .map(|naive| Utc.from_utc_datetime(&naive))
+}
+
+/// 解析查询时间范围的边界
+///
+/// 除 [`parse_message_time`] 支持的格式外，还支持 `now` 以及相对当前时间的
+/// `-30s`、`-15m`、`-2h`、`-1d`。
+pub fn parse_time_bound(value: &str, now: DateTime<Utc>) -> Result<DateTime<Utc>, LogidError> {
+    let value = value.trim();
+    if value.eq_ignore_ascii_case("now") {
+        return Ok(now);
+    }
+
+    if let Some(offset) = value.strip_prefix('-').and_then(parse_span) {
+        return Ok(now - offset);
+    }
+
+    parse_message_time(value).ok_or_else(|| {
+        LogidError::InvalidTimeRange(format!(
+            "无法解析时间 '{}'，支持 RFC3339、Unix 时间戳或 -30m/-2h/-1d 等相对时间",
+            value
+        ))
+    })
+}
+
+/// 解析 `30s`、`15m`、`2h`、`1d` 形式的时长
+fn parse_span(value: &str) -> Option<Duration> {
+    // 按字符而不是字节取单位，避免多字节字符导致切分 panic
+    let (unit_start, unit) = value.char_indices().last()?;
+    let amount = value[..unit_start].parse::<i64>().ok()?;
+    match unit {
+        's' => Duration::try_seconds(amount),
+        'm' => Duration::try_minutes(amount),
+        'h' => Duration::try_hours(amount),
+        'd' => Duration::try_days(amount),
+        _ => None,
+    }
+}
+
+/// 解析轮询间隔，支持 `30s`、`5m`、`1h`，不带单位时按秒计算
+pub fn parse_interval(value: &str) -> Result<std::time::Duration, LogidError> {
+    let value = value.trim();
+    let span = match value.parse::<i64>() {
+        Ok(seconds) => Duration::try_seconds(seconds),
+        Err(_) => parse_span(value),
+    };
+    span.and_then(|span| span.to_std().ok())
+        .filter(|interval| !interval.is_zero())
+        .ok_or_else(|| {
+            LogidError::InvalidTimeRange(format!(
+                "无法解析间隔 '{}'，支持 30s、5m、1h 或秒数",
+                value
+            ))
+        })
+}
+
+/// 根据 `--start`/`--end` 参数计算查询时间范围
+///
+/// 两者都未指定时返回 `None`；只指定开始时间时结束时间为当前时间，
+/// 只指定结束时间时开始时间为结束时间前 `default_span_min` 分钟。
+pub fn resolve_time_range(
+    start: Option<&str>,
+    end: Option<&str>,
+    default_span_min: i32,
+    now: DateTime<Utc>,
+) -> Result<Option<TimeRange>, LogidError> {
+    if start.is_none() && end.is_none() {
+        return Ok(None);
+    }
+
+    let end = end.map(|v| parse_time_bound(v, now)).transpose()?.unwrap_or(now);
+    let start = match start {
+        Some(value) => parse_time_bound(value, now)?,
+        None => end - Duration::minutes(i64::from(default_span_min)),
+    };
+
+    if start >= end {
+        return Err(LogidError::InvalidTimeRange(format!(
+            "开始时间 {} 不早于结束时间 {}",
+            start.to_rfc3339_opts(SecondsFormat::Secs, true),
+            end.to_rfc3339_opts(SecondsFormat::Secs, true)
+        )));
+    }
+
+    Ok(Some(TimeRange {
+        start: Some(start.timestamp()),
+        end: Some(end.timestamp()),
+    }))
+}
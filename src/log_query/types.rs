@@ -1,7 +1,10 @@
 //! 日志查询数据类型模块
 
+use crate::config::CredentialExpiry;
+use crate::error::LogidError;
+use crate::log_query::context::QueryContext;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap, HashSet};
 
 /// 日志查询请求体
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -16,6 +19,12 @@ pub struct LogQueryRequest {
     pub scan_span_in_min: i32,
     /// 虚拟区域
     pub vregion: String,
+    /// 查询开始时间（Unix 秒）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub start_time: Option<i64>,
+    /// 查询结束时间（Unix 秒）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub end_time: Option<i64>,
 }
 
 impl LogQueryRequest {
@@ -31,8 +40,17 @@ impl LogQueryRequest {
             psm_list,
             scan_span_in_min,
             vregion,
+            start_time: None,
+            end_time: None,
         }
     }
+
+    /// 设置绝对查询时间范围
+    pub fn with_time_range(mut self, range: Option<&TimeRange>) -> Self {
+        self.start_time = range.and_then(|r| r.start);
+        self.end_time = range.and_then(|r| r.end);
+        self
+    }
 }
 
 /// 日志查询响应数据
@@ -52,6 +70,15 @@ pub struct LogQueryResponse {
     /// 区域显示名称
     #[serde(rename = "region_display_name")]
     pub region_display_name: String,
+    /// 响应体是否因超过大小上限而被截断
+    #[serde(default)]
+    pub truncated: bool,
+    /// 下载的响应体字节数
+    #[serde(default)]
+    pub bytes_downloaded: usize,
+    /// 解析过程中的警告
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<String>,
 }
 
 /// 日志数据
@@ -78,7 +105,7 @@ pub struct LogItem {
 }
 
 /// 日志分组信息
-#[derive(Debug, Clone, Deserialize, Serialize)]
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
 pub struct LogGroup {
     /// PSM 服务名
     pub psm: Option<String>,
@@ -94,6 +121,9 @@ pub struct LogGroup {
     pub vregion: Option<String>,
     /// IDC
     pub idc: Option<String>,
+    /// 其他未显式建模的分组字段（如 cluster、stage、container），原样保留
+    #[serde(flatten, default, skip_serializing_if = "HashMap::is_empty")]
+    pub extra: HashMap<String, serde_json::Value>,
 }
 
 /// 日志值
@@ -137,7 +167,7 @@ pub struct LogMeta {
 }
 
 /// 时间范围
-#[derive(Debug, Clone, Deserialize, Serialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
 pub struct TimeRange {
     /// 开始时间戳
     pub start: Option<i64>,
@@ -146,23 +176,104 @@ pub struct TimeRange {
 }
 
 /// 提取的日志消息
-#[derive(Debug, Clone, Serialize)]
+///
+/// 从保存的结果读取时，缺少的字段（如经过 `--fields` 投影的输出）取默认值。
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
 pub struct ExtractedLogMessage {
     /// 项目 ID
+    #[serde(default)]
     pub id: String,
     /// 分组信息
+    #[serde(default)]
     pub group: LogGroup,
     /// 提取的值列表（过滤后的）
+    #[serde(default)]
     pub values: Vec<ExtractedValue>,
     /// 日志代码位置
     #[serde(rename = "location")]
     pub location: Option<String>,
+    /// 结构化的代码位置（由 `_location`、`_file`、`_line`、`_func` 等字段组合而来）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub code_location: Option<CodeLocation>,
     /// 日志级别
     pub level: Option<String>,
+    /// 日志时间
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub time: Option<String>,
+    /// 消息所属区域（多区域查询时设置）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub region: Option<String>,
+    /// 用户批注
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub annotations: Vec<String>,
+}
+
+/// 结构化的代码位置
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub struct CodeLocation {
+    /// 源文件
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub file: Option<String>,
+    /// 行号
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub line: Option<u32>,
+    /// 函数名
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub function: Option<String>,
+}
+
+impl CodeLocation {
+    /// 解析 `path/to/file.go:123` 形式的位置字符串
+    pub fn parse(location: &str) -> Self {
+        let location = location.trim();
+        match location.rsplit_once(':') {
+            Some((file, line)) if !file.is_empty() => match line.trim().parse() {
+                Ok(line) => Self {
+                    file: Some(file.to_string()),
+                    line: Some(line),
+                    function: None,
+                },
+                Err(_) => Self {
+                    file: Some(location.to_string()),
+                    ..Self::default()
+                },
+            },
+            _ => Self {
+                file: Some(location.to_string()).filter(|f| !f.is_empty()),
+                ..Self::default()
+            },
+        }
+    }
+
+    /// 是否没有任何位置信息
+    pub fn is_empty(&self) -> bool {
+        self.file.is_none() && self.line.is_none() && self.function.is_none()
+    }
+}
+
+/// 代码位置相关字段的键名配置
+#[derive(Debug, Clone)]
+pub struct CodeLocationKeys {
+    /// 源文件键名
+    pub file: Vec<String>,
+    /// 行号键名
+    pub line: Vec<String>,
+    /// 函数名键名
+    pub function: Vec<String>,
+}
+
+impl Default for CodeLocationKeys {
+    fn default() -> Self {
+        Self {
+            file: vec!["_file".to_string()],
+            line: vec!["_line".to_string()],
+            function: vec!["_func".to_string(), "_function".to_string()],
+        }
+    }
 }
 
 /// 提取的值
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct ExtractedValue {
     /// 键名
     pub key: String,
@@ -177,7 +288,9 @@ pub struct ExtractedValue {
 }
 
 /// 详细的日志查询结果
-#[derive(Debug, Clone, Serialize)]
+///
+/// 可以从 `logid query` 保存的 JSON 中读回，输出时省略的字段取默认值。
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct DetailedLogResult {
     /// 日志 ID
     pub logid: String,
@@ -189,7 +302,7 @@ pub struct DetailedLogResult {
     #[serde(rename = "tag_infos")]
     pub tag_infos: Option<Vec<serde_json::Value>>,
     /// 消息总数
-    #[serde(rename = "total_items")]
+    #[serde(rename = "total_items", default)]
     pub total_items: usize,
     /// 扫描时间范围
     #[serde(rename = "scan_time_range")]
@@ -198,10 +311,296 @@ pub struct DetailedLogResult {
     #[serde(rename = "level_list")]
     pub level_list: Option<Vec<String>>,
     /// 查询时间戳
+    #[serde(default)]
     pub timestamp: String,
     /// 区域信息
+    #[serde(default)]
     pub region: String,
     /// 区域显示名称
-    #[serde(rename = "region_display_name")]
+    #[serde(rename = "region_display_name", default)]
     pub region_display_name: String,
+    /// 结果是否因响应体过大而被截断（仅包含部分日志）
+    #[serde(default)]
+    pub truncated: bool,
+    /// 认证凭据过期信息
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub credential_expiry: Option<CredentialExpiry>,
+    /// 结果统计信息（需要显式开启）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stats: Option<ResultStats>,
+    /// 因日志级别不匹配而被过滤掉的消息数
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub level_filtered: usize,
+    /// 空结果时自动扩大扫描范围的记录
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scan_widening: Option<ScanWidening>,
+    /// 查询上下文，用于 `logid rerun` 重新执行
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub context: Option<QueryContext>,
+}
+
+/// 空结果时自动扩大扫描范围的记录
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct ScanWidening {
+    /// 依次尝试的扫描时间范围（分钟）
+    pub attempted_spans: Vec<i32>,
+    /// 最终结果使用的扫描时间范围（分钟）
+    pub final_span: i32,
+}
+
+fn is_zero(value: &usize) -> bool {
+    *value == 0
+}
+
+/// 规范化日志级别名称，用于级别过滤时的比较
+///
+/// 忽略大小写，并将 `WARNING` 视为 `WARN`。
+pub fn normalize_level(level: &str) -> String {
+    match level.trim().to_uppercase().as_str() {
+        "WARNING" => "WARN".to_string(),
+        other => other.to_string(),
+    }
+}
+
+/// 查询结果的统计信息
+///
+/// 用于解释结果为什么看起来“很小”：下载了多少数据、提取了多少消息、过滤规则移除了多少内容。
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub struct ResultStats {
+    /// 下载的响应体字节数
+    pub bytes_downloaded: usize,
+    /// 日志项数量
+    pub items: usize,
+    /// 提取的消息数量
+    pub messages_extracted: usize,
+    /// 各过滤规则的命中情况（仅包含有命中的规则）
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub filter_hits: Vec<FilterHit>,
+    /// 解析警告
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub parse_warnings: Vec<String>,
+}
+
+/// 单条过滤规则的命中统计
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct FilterHit {
+    /// 过滤规则
+    pub pattern: String,
+    /// 被该规则修改的消息数量
+    pub messages: usize,
+    /// 该规则移除的字节数
+    pub bytes_removed: usize,
+}
+
+impl ResultStats {
+    /// 累加另一个结果的统计信息，过滤规则按规则文本合并
+    pub fn merge(&mut self, other: ResultStats) {
+        self.bytes_downloaded += other.bytes_downloaded;
+        self.items += other.items;
+        self.messages_extracted += other.messages_extracted;
+        self.parse_warnings.extend(other.parse_warnings);
+        for hit in other.filter_hits {
+            match self.filter_hits.iter_mut().find(|h| h.pattern == hit.pattern) {
+                Some(existing) => {
+                    existing.messages += hit.messages;
+                    existing.bytes_removed += hit.bytes_removed;
+                }
+                None => self.filter_hits.push(hit),
+            }
+        }
+    }
+}
+
+impl DetailedLogResult {
+    /// 合并同一 logid 在多个区域的查询结果
+    ///
+    /// 每条消息都会标记所属区域，`region` 为逗号分隔的区域列表。
+    pub fn merge_regions(logid: &str, results: Vec<DetailedLogResult>) -> Self {
+        let regions: Vec<String> = results.iter().map(|r| r.region.clone()).collect();
+        let display_names: Vec<String> = results
+            .iter()
+            .map(|r| r.region_display_name.clone())
+            .collect();
+
+        let mut merged = DetailedLogResult {
+            logid: logid.to_string(),
+            messages: Vec::new(),
+            meta: None,
+            tag_infos: None,
+            total_items: 0,
+            scan_time_range: None,
+            level_list: None,
+            timestamp: chrono::Utc::now().to_rfc3339(),
+            region: regions.join(","),
+            region_display_name: display_names.join(","),
+            truncated: false,
+            credential_expiry: None,
+            stats: None,
+            level_filtered: 0,
+            scan_widening: None,
+            context: None,
+        };
+
+        for result in results {
+            merged.total_items += result.total_items;
+            merged.truncated |= result.truncated;
+            merged.level_filtered += result.level_filtered;
+            if let Some(stats) = result.stats {
+                merged.stats.get_or_insert_with(ResultStats::default).merge(stats);
+            }
+            merged.messages.extend(result.messages.into_iter().map(|mut message| {
+                message.region.get_or_insert_with(|| result.region.clone());
+                message
+            }));
+        }
+
+        merged
+    }
+
+    /// 去除重复的消息，保留首次出现的一条，返回去除的数量
+    ///
+    /// 相邻区域的可用区可能覆盖同一批日志，同一条日志项会从多个区域返回。
+    /// 消息 ID 只是响应内的序号组合，不能单独标识日志，因此按 ID、分组、时间和值一起判断，
+    /// 不考虑消息所属区域。
+    pub fn dedup_messages(&mut self) -> usize {
+        let mut seen = HashSet::new();
+        let before = self.messages.len();
+        self.messages.retain(|message| {
+            // 先转换为 Value，使分组中额外字段的顺序固定
+            let key = serde_json::to_value((&message.id, &message.group, &message.time, &message.values))
+                .map(|value| value.to_string())
+                .unwrap_or_default();
+            seen.insert(key)
+        });
+        before - self.messages.len()
+    }
+}
+
+/// 多个 logid 的合并查询结果
+#[derive(Debug, Clone, Serialize)]
+pub struct MultiLogResult {
+    /// 查询区域
+    pub region: String,
+    /// 按 logid 索引的查询结果
+    pub results: BTreeMap<String, DetailedLogResult>,
+    /// 按 logid 索引的查询失败原因
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub errors: BTreeMap<String, String>,
+    /// 查询时间戳
+    pub timestamp: String,
+}
+
+impl MultiLogResult {
+    /// 创建空的合并结果
+    pub fn new(region: &str) -> Self {
+        Self {
+            region: region.to_string(),
+            results: BTreeMap::new(),
+            errors: BTreeMap::new(),
+            timestamp: chrono::Utc::now().to_rfc3339(),
+        }
+    }
+
+    /// 所有结果中的消息总数
+    pub fn total_messages(&self) -> usize {
+        self.results.values().map(|r| r.messages.len()).sum()
+    }
+}
+
+/// 多区域查询中单个区域的查询状态
+#[derive(Debug, Serialize)]
+pub struct RegionQueryStatus {
+    /// 区域
+    pub region: String,
+    /// 该区域返回的消息数（去重前）
+    pub messages: usize,
+    /// 查询失败的原因，成功时为 `None`；序列化为 `error_code` 和 `message`
+    #[serde(serialize_with = "serialize_error", skip_serializing_if = "Option::is_none")]
+    pub error: Option<LogidError>,
+}
+
+fn serialize_error<S: serde::Serializer>(error: &Option<LogidError>, serializer: S) -> Result<S::Ok, S::Error> {
+    error
+        .as_ref()
+        .map(|e| serde_json::json!({ "error_code": e.error_code(), "message": e.to_string() }))
+        .serialize(serializer)
+}
+
+impl RegionQueryStatus {
+    /// 该区域是否查询成功
+    pub fn is_ok(&self) -> bool {
+        self.error.is_none()
+    }
+}
+
+/// 同一 logid 在多个区域的合并查询结果
+///
+/// 由 [`MultiRegionLogQuery::query_all`](crate::log_query::MultiRegionLogQuery::query_all) 返回，
+/// 单个区域失败不影响其他区域的结果。
+#[derive(Debug, Serialize)]
+pub struct MultiRegionResult {
+    /// 成功区域合并、去重后的结果
+    pub result: DetailedLogResult,
+    /// 每个区域的查询状态，顺序与查询的区域一致
+    pub regions: Vec<RegionQueryStatus>,
+    /// 跨区域去重时移除的消息数
+    pub duplicates_removed: usize,
+}
+
+impl MultiRegionResult {
+    /// 由各区域的查询结果合并
+    pub fn from_region_results(
+        logid: &str,
+        results: Vec<(String, Result<DetailedLogResult, LogidError>)>,
+    ) -> Self {
+        let mut succeeded = Vec::new();
+        let mut regions = Vec::new();
+        for (region, result) in results {
+            match result {
+                Ok(result) => {
+                    regions.push(RegionQueryStatus {
+                        region,
+                        messages: result.messages.len(),
+                        error: None,
+                    });
+                    succeeded.push(result);
+                }
+                Err(e) => regions.push(RegionQueryStatus {
+                    region,
+                    messages: 0,
+                    error: Some(e),
+                }),
+            }
+        }
+
+        let mut result = DetailedLogResult::merge_regions(logid, succeeded);
+        let duplicates_removed = result.dedup_messages();
+        if duplicates_removed > 0 {
+            conditional_info!("跨区域去除了 {} 条重复消息", duplicates_removed);
+        }
+        Self {
+            result,
+            regions,
+            duplicates_removed,
+        }
+    }
+
+    /// 查询失败的区域
+    pub fn failed_regions(&self) -> impl Iterator<Item = &RegionQueryStatus> {
+        self.regions.iter().filter(|status| !status.is_ok())
+    }
+
+    /// 是否有区域查询成功（没有查询任何区域时也视为成功）
+    pub fn has_success(&self) -> bool {
+        self.regions.is_empty() || self.regions.iter().any(RegionQueryStatus::is_ok)
+    }
+
+    /// 转换为合并结果，所有区域都失败时返回最后一个区域的错误
+    pub fn into_result(self) -> Result<DetailedLogResult, LogidError> {
+        if self.has_success() {
+            return Ok(self.result);
+        }
+        let error = self.regions.into_iter().rev().find_map(|status| status.error);
+        Err(error.unwrap_or_else(|| LogidError::InternalError("没有区域查询结果".to_string())))
+    }
 }
@@ -0,0 +1,44 @@
+//! 监视模式的消息去重
+
+use crate::log_query::types::{DetailedLogResult, ExtractedLogMessage};
+use std::collections::HashSet;
+
+/// 监视模式下已输出过的消息
+///
+/// 以 logid、区域和消息 ID 作为键，重复查询时只返回新出现的消息。
+#[derive(Debug, Default)]
+pub struct SeenMessages {
+    seen: HashSet<(String, Option<String>, String)>,
+}
+
+impl SeenMessages {
+    /// 创建空记录
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 返回结果中尚未输出过的消息，并将其标记为已输出
+    pub fn take_new<'a>(&mut self, result: &'a DetailedLogResult) -> Vec<&'a ExtractedLogMessage> {
+        result
+            .messages
+            .iter()
+            .filter(|message| {
+                self.seen.insert((
+                    result.logid.clone(),
+                    message.region.clone(),
+                    message.id.clone(),
+                ))
+            })
+            .collect()
+    }
+
+    /// 已输出的消息数
+    pub fn len(&self) -> usize {
+        self.seen.len()
+    }
+
+    /// 是否还没有输出过消息
+    pub fn is_empty(&self) -> bool {
+        self.seen.is_empty()
+    }
+}
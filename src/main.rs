@@ -4,14 +4,19 @@
 //! 支持多区域（us/i18n/cn）查询、PSM 过滤，输出 JSON 格式。
 
 use anyhow::Result;
-use clap::{Parser, Subcommand};
+use clap::{Args, CommandFactory, FromArgMatches, Parser, Subcommand};
+use commands::completions::RegionValueParser;
+use futures::future::join_all;
 use tracing::error;
 
 // 使用库中的模块
 use logid::{
-    auth, config, error::LogidError, log_query, output,
+    config,
+    error::{exit_code, LogidError},
+    log_query, output, query_filter, store,
     conditional_info,
 };
+use std::process::ExitCode;
 
 mod commands;
 
@@ -20,26 +25,293 @@ mod commands;
 #[command(about = "字节跳动 logid 查询工具", long_about = None)]
 #[command(version)]
 struct Cli {
+    /// 输出诊断日志（-v 为 info，-vv 为 debug，-vvv 为 trace）
+    #[arg(short, long, action = clap::ArgAction::Count, global = true)]
+    verbose: u8,
+    /// 不输出任何诊断日志
+    #[arg(short, long, global = true, conflicts_with = "verbose")]
+    quiet: bool,
+    /// 失败时在标准输出打印 JSON 错误对象（error_code、message、region、hint），而不是文字说明
+    #[arg(long, global = true)]
+    json_errors: bool,
+    /// 使用 ~/.config/logid/profiles.json 中的命名配置档（也可通过 LOGID_PROFILE 设置）
+    #[arg(long, global = true, value_name = "NAME")]
+    profile: Option<String>,
+    /// 认证和查询请求使用的 HTTP(S) 代理，优先于配置档、LOGID_PROXY 和 HTTPS_PROXY/HTTP_PROXY
+    #[arg(
+        long,
+        global = true,
+        value_name = "URL",
+        value_parser = |v: &str| reqwest::Proxy::all(v).map(|_| v.to_string()).map_err(|e| e.to_string())
+    )]
+    proxy: Option<String>,
+    /// 不使用任何代理，忽略配置档和环境变量中的代理设置
+    #[arg(long, global = true, conflicts_with = "proxy")]
+    no_proxy: bool,
+    /// 消息过滤规则文件，优先于 LOGID_FILTERS 和 ~/.config/logid/message_filters.json
+    #[arg(long, global = true, value_name = "FILE")]
+    filter_config: Option<std::path::PathBuf>,
+    /// 使用的消息过滤规则集（如 strict、none），优先于 config.toml 中的 filter_profile
+    #[arg(long, global = true, value_name = "NAME")]
+    filter_profile: Option<String>,
+    /// 使用指定的 .env 文件，优先于可执行文件目录和 ~/.config/logid 中的 .env
+    #[arg(long, global = true, value_name = "FILE")]
+    env_file: Option<std::path::PathBuf>,
+    /// 解密 CAS_SESSION 的密钥文件（文件内容即口令），优先于 LOGID_KEY_FILE 和 LOGID_PASSPHRASE
+    #[arg(long, global = true, value_name = "FILE")]
+    key_file: Option<std::path::PathBuf>,
     #[command(subcommand)]
     command: Commands,
 }
 
+/// 根据 -v/-q 计算日志级别，都未指定时沿用 ENABLE_LOGGING 环境变量
+fn log_level(verbose: u8, quiet: bool) -> tracing::level_filters::LevelFilter {
+    use tracing::level_filters::LevelFilter;
+    match (quiet, verbose) {
+        (true, _) => LevelFilter::OFF,
+        (false, 0) => logid::default_log_level(),
+        (false, 1) => LevelFilter::INFO,
+        (false, 2) => LevelFilter::DEBUG,
+        (false, _) => LevelFilter::TRACE,
+    }
+}
+
 #[derive(Subcommand)]
 enum Commands {
     #[command(
         about = "查询日志",
-        long_about = "通过 logid 查询字节跳动内部日志服务\n\n示例:\n  logid query '550e8400-e29b-41d4-a716-446655440000' --region us\n  logid query 'logid123' --region i18n --psm service.psm\n  logid query 'logid456' --region us --psm psm1 --psm psm2\n\n参数说明:\n  - logid: 要查询的日志 ID，通常是 UUID 格式\n  - region: 查询区域 (cn/i18n/us)\n  - psm: 过滤的 PSM 服务名称，可多次指定\n\n区域说明:\n  * us: 美区 (https://logservice-tx.tiktok-us.org)\n  * i18n: 国际化区域 (https://logservice-sg.tiktok-row.org)\n  * cn: 中国区 (需要特殊配置)\n\n认证说明:\n  需要在环境变量中配置对应区域的 CAS_SESSION:\n  - CAS_SESSION_US: 美区认证凭据\n  - CAS_SESSION_I18n: 国际化区域认证凭据\n  - CAS_SESSION_CN: 中国区认证凭据"
+        long_about = "通过 logid 查询字节跳动内部日志服务\n\n示例:\n  logid query '550e8400-e29b-41d4-a716-446655440000' --region us\n  logid query 'logid123' --region i18n --psm service.psm\n  logid query 'logid456' --region us --psm psm1 --psm psm2\n  logid query 'logid789' --region us --format table\n  logid query 'logid789' --region us -o results/logid789.json\n  logid query 'id1' 'id2' 'id3' --region us\n  logid query 'logid123' --region us,i18n\n  grep -o 'logid=[0-9a-f-]*' err.log | cut -d= -f2 | logid query - --region us\n  logid query --from-clipboard --region us\n  logid query 'logid123' --region all\n  logid query 'logid123' --region us --grep 'timeout|deadline'\n  logid query 'logid123' --region us --start -2h --end -1h\n  logid query 'logid123' --region us --watch --interval 30s\n  logid query 'logid123' --region us --where 'level == \"ERROR\" && psm =~ \"pay\"'\n  logid query 'logid123' --region us --follow-depth 2\n\n参数说明:\n  - logid: 要查询的日志 ID，通常是 UUID 格式；指定多个时并发查询，结果按 logid 合并；- 表示从标准输入读取（每行一个或以空白分隔）\n  - from-clipboard: 从系统剪贴板读取 logid，去掉首尾空白和引号并校验格式\n  - region: 查询区域 (cn/i18n/us/eu)；逗号分隔多个区域或 all 时并发查询，消息带有 region 标记\n  - psm: 过滤的 PSM 服务名称，可多次指定\n  - psm-file: 从文件读取 PSM 列表（每行一个，支持 # 注释），与 --psm 合并去重\n  - raw: 跳过消息过滤规则和空白清理（别名 --no-filter），用于确认过滤规则是否删除了需要的内容\n  - where: 布尔过滤表达式，支持 ==、!=、=~、!~、&&、||、! 和括号\n  - grep: 只保留消息内容匹配正则表达式的消息，配合 --invert 只保留不匹配的消息\n  - level: 只保留指定级别的消息，逗号分隔（如 error,warn），被过滤的数量记录在 level_filtered 中\n  - stats: 在结果中附带 stats 统计块，说明下载量、提取数量和过滤规则移除的内容\n  - scan-span: 扫描时间范围（分钟，默认使用区域配置的 scan_span，未配置时为 10），日志延迟写入时可扩大到 30 或 60\n  - auto-widen: 结果为空时依次以 30、60 分钟扫描范围重试，尝试过的范围记录在 scan_widening 中\n  - per-zone: 为每个可用区单独并发查询并合并结果，部分可用区失败时返回其余结果\n  - timeout: 单个 HTTP 请求的超时时间（默认 30s），经过较慢的代理时可调大\n  - retries: 超时、连接失败、5xx 或 429 时按指数退避重试的次数（默认 2，0 表示不重试）\n  - env-target: 部署环境 prod（默认）/boe/ppe（别名 --env），非线上环境的端点在 ~/.config/logid/regions.json 中配置\n  - start/end: 绝对查询时间范围，支持 RFC3339、Unix 时间戳或 -2h 等相对时间；只指定 --start 时结束于当前时间\n  - format: 输出格式，json（默认）或 table\n  - fields: JSON 输出中每条消息只保留指定字段（点路径，如 group.psm、values.value）\n  - output: 结果写入的文件路径，父目录不存在时自动创建\n  - open: 查询完成后输出 Web 控制台链接并在浏览器中打开，链接模板可在 regions.json 的 web_url_template 中覆盖\n  - watch/interval: 按间隔（默认 30s）重复查询，以 NDJSON 只输出新出现的消息，按 Ctrl+C 退出\n  - follow-depth: 从 _msg 中提取上下游 logid 并递归查询的层数，输出以起始 logid 为根的调用链树；follow-max 限制查询的 logid 总数（默认 50）\n\n区域说明:\n  * us: 美区 (https://logservice-tx.tiktok-us.org)\n  * i18n: 国际化区域 (https://logservice-sg.tiktok-row.org)\n  * cn: 中国区 (需要特殊配置)\n\n认证说明:\n  需要在环境变量中配置对应区域的 CAS_SESSION:\n  - CAS_SESSION_US: 美区认证凭据\n  - CAS_SESSION_I18N: 国际化区域认证凭据\n  - CAS_SESSION_CN: 中国区认证凭据"
+    )]
+    Query(Box<QueryArgs>),
+    #[command(
+        about = "批量查询多个 logid",
+        long_about = "从文件或标准输入读取 logid 列表并发查询，每个 logid 输出一行 JSON（NDJSON）\n\n示例:\n  logid batch --file ids.txt --region us\n  cat ids.txt | logid batch --region i18n --concurrency 8\n\n参数说明:\n  - file: logid 列表文件，每行一个，支持 # 注释；省略或为 - 时读取标准输入\n  - region: 查询区域 (cn/i18n/us/eu)\n  - psm: 过滤的 PSM 服务名称，可多次指定\n  - psm-file: 从文件读取 PSM 列表，与 --psm 合并去重\n  - raw: 跳过消息过滤规则和空白清理（别名 --no-filter）\n  - concurrency: 最大并发查询数\n  - timeout: 单个 HTTP 请求的超时时间（默认 30s）\n  - retries: 瞬时失败时的重试次数（默认 2）\n  - env-target: 部署环境 prod（默认）/boe/ppe（别名 --env）\n\n输出说明:\n  查询失败的 logid 输出 {\"logid\": ..., \"error\": ...}，不会中断其余查询"
+    )]
+    Batch {
+        /// logid 列表文件（省略或为 - 时读取标准输入）
+        #[arg(long)]
+        file: Option<String>,
+        /// 查询区域 (cn/i18n/us/eu)
+        #[arg(short, long, value_parser = RegionValueParser)]
+        region: String,
+        /// 过滤的 PSM 服务名称
+        #[arg(short, long)]
+        psm: Vec<String>,
+        /// 从文件读取 PSM 列表（每行一个，支持 # 注释），与 --psm 合并
+        #[arg(long, value_name = "FILE")]
+        psm_file: Option<String>,
+        /// 跳过所有消息过滤规则和空白清理，输出原始消息
+        #[arg(long, visible_alias = "no-filter")]
+        raw: bool,
+        /// 最大并发查询数
+        #[arg(short, long, default_value_t = log_query::DEFAULT_BATCH_CONCURRENCY)]
+        concurrency: usize,
+        #[command(flatten)]
+        request: commands::RequestArgs,
+        /// 部署环境 (prod/boe/ppe)
+        #[arg(long, visible_alias = "env", value_enum, default_value_t = config::EnvTarget::Prod)]
+        env_target: config::EnvTarget,
+    },
+    #[command(
+        about = "将多个 logid 的日志合并为一条时间线",
+        long_about = "查询多个 logid 并按时间顺序交错输出全部消息，每个 logid 使用不同颜色标记，\n适合对比客户端请求与其异步回调链路\n\n示例:\n  logid timeline 'logid-request' 'logid-callback' --region us\n  logid timeline 'id1' 'id2' 'id3' --region i18n --psm service.psm --no-color\n\n参数说明:\n  - logids: 要合并的日志 ID，至少两个；- 表示从标准输入读取\n  - region: 查询区域 (cn/i18n/us/eu)\n  - psm: 过滤的 PSM 服务名称，可多次指定\n  - no-color: 禁用颜色（输出不是终端或设置了 NO_COLOR 时自动禁用）\n  - raw: 跳过消息过滤规则和空白清理（别名 --no-filter）"
+    )]
+    Timeline {
+        /// 要合并的日志 ID，至少两个；`-` 表示从标准输入读取
+        #[arg(required = true)]
+        logids: Vec<String>,
+        /// 查询区域 (cn/i18n/us/eu)
+        #[arg(short, long, value_parser = RegionValueParser)]
+        region: String,
+        /// 过滤的 PSM 服务名称
+        #[arg(short, long)]
+        psm: Vec<String>,
+        /// 禁用颜色输出
+        #[arg(long)]
+        no_color: bool,
+        /// 跳过所有消息过滤规则和空白清理，输出原始消息
+        #[arg(long, visible_alias = "no-filter")]
+        raw: bool,
+    },
+    #[command(
+        about = "将查询结果导出为 CSV 或 Parquet",
+        long_about = "将已保存的查询结果（或一次新的查询）展开为每条消息一行，写入 CSV 或 Parquet，\n便于在表格或数仓中分析\n\n示例:\n  logid export result.json -o result.csv\n  logid batch --file ids.txt --region us > batch.ndjson && logid export batch.ndjson -o batch.csv\n  logid export --logid 'logid123' --region us\n  logid export result.json --format parquet -o result.parquet\n\n参数说明:\n  - input: query 输出的 JSON 结果或 batch 输出的 NDJSON 文件\n  - logid: 不读取文件，直接查询这些 logid 后导出，可多次指定\n  - region: 配合 --logid 使用的查询区域，支持逗号分隔或 all\n  - psm: 配合 --logid 使用的 PSM 过滤，可多次指定\n  - format: csv（默认）或 parquet（需要启用 parquet 特性）\n  - output: 输出文件路径；CSV 未指定时写到标准输出，Parquet 必须指定\n\n列说明:\n  logid, region, timestamp, level, psm, pod, message, annotations（多条批注以 ; 拼接）"
     )]
-    Query {
+    Export {
+        /// 已保存的查询结果文件（JSON 或 NDJSON）
+        #[arg(required_unless_present = "logid", conflicts_with = "logid")]
+        input: Option<String>,
+        /// 直接查询并导出的日志 ID
+        #[arg(long, requires = "region")]
+        logid: Vec<String>,
+        /// 查询区域 (cn/i18n/us/eu)，配合 --logid 使用
+        #[arg(short, long, value_parser = RegionValueParser)]
+        region: Option<String>,
+        /// 过滤的 PSM 服务名称，配合 --logid 使用
+        #[arg(short, long)]
+        psm: Vec<String>,
+        /// 导出格式
+        #[arg(short, long, value_enum, default_value_t = output::ExportFormat::Csv)]
+        format: output::ExportFormat,
+        /// 将结果写入文件
+        #[arg(short, long, value_name = "PATH")]
+        output: Option<String>,
+    },
+    #[command(
+        about = "对比两个查询结果",
+        long_about = "对比两个已保存的查询结果（或两个 logid），按 PSM 分组列出只在一侧出现的消息和内容变化的消息，\n适合对比同一接口失败和成功的两次请求\n\n示例:\n  logid diff failed.json ok.json\n  logid diff 'logid-failed' 'logid-ok' --region us\n  logid diff 'logid-failed' 'logid-ok' --region us --psm pay.api --format table\n\n参数说明:\n  - a/b: 已保存的查询结果文件（JSON 或 NDJSON）；指定 --region 时为 logid\n  - region: 按 logid 查询两侧结果时使用的区域\n  - psm: 按 logid 查询时过滤的 PSM 服务名称，可多次指定\n  - format: json（默认）输出结构化差异，table 输出 -/+/~ 标记的文本\n  - output: 将差异写入文件\n\n匹配规则:\n  同一 PSM 内有代码位置的消息按位置配对，位置相同但内容不同记为变化；\n  没有代码位置的消息按内容配对"
+    )]
+    Diff {
+        /// A 侧：结果文件，或指定 --region 时的 logid
+        a: String,
+        /// B 侧：结果文件，或指定 --region 时的 logid
+        b: String,
+        /// 查询区域 (cn/i18n/us/eu)，指定时 a 和 b 作为 logid 查询
+        #[arg(short, long, value_parser = RegionValueParser)]
+        region: Option<String>,
+        /// 过滤的 PSM 服务名称，配合 --region 使用
+        #[arg(short, long, requires = "region")]
+        psm: Vec<String>,
+        /// 输出格式
+        #[arg(short, long, value_enum, default_value_t = output::OutputFormat::Json)]
+        format: output::OutputFormat,
+        /// 将差异写入文件
+        #[arg(short, long, value_name = "PATH")]
+        output: Option<String>,
+    },
+    #[command(
+        about = "输出查询结果的汇总统计",
+        long_about = "查询 logid 并输出汇总统计：按级别、PSM、Pod 的消息数量，时间分布直方图，以及重复出现的错误\n\n示例:\n  logid stats 'logid123' --region us\n  logid stats 'id1' 'id2' --region i18n --psm pay.api --format table\n  logid stats 'logid123' --region us --bucket 1m --top 20\n\n参数说明:\n  - logids: 要统计的日志 ID，指定多个时合并统计；- 表示从标准输入读取\n  - region: 查询区域 (cn/i18n/us/eu)，支持逗号分隔或 all\n  - psm: 过滤的 PSM 服务名称，可多次指定\n  - bucket: 时间直方图的分桶宽度，如 10s、1m；默认根据时间跨度自动选择\n  - top: 输出的高频错误数量（默认 10）\n  - format: json（默认）或 table\n\n说明:\n  高频错误只统计 ERROR/FATAL/CRITICAL/PANIC 级别的消息，数字替换为 <n> 后归类"
+    )]
+    Stats {
+        /// 要统计的日志 ID；`-` 表示从标准输入读取
+        #[arg(required = true)]
+        logids: Vec<String>,
+        /// 查询区域 (cn/i18n/us/eu)
+        #[arg(short, long, value_parser = RegionValueParser)]
+        region: String,
+        /// 过滤的 PSM 服务名称
+        #[arg(short, long)]
+        psm: Vec<String>,
+        /// 时间直方图的分桶宽度，如 10s、1m（不带单位时按秒计算）
+        #[arg(
+            long,
+            value_name = "DURATION",
+            value_parser = |v: &str| log_query::parse_interval(v).map_err(|e| e.to_string())
+        )]
+        bucket: Option<std::time::Duration>,
+        /// 输出的高频错误数量
+        #[arg(long, default_value_t = 10)]
+        top: usize,
+        /// 输出格式
+        #[arg(short, long, value_enum, default_value_t = output::OutputFormat::Json)]
+        format: output::OutputFormat,
+    },
+    #[cfg(feature = "tui")]
+    #[command(
+        about = "在终端界面中浏览查询结果",
+        long_about = "查询单个 logid 并在交互式界面中浏览：左侧按 PSM/Pod 分组列出消息，右侧显示完整消息及过滤前的原始值\n\n示例:\n  logid tui 'logid123' --region us\n  logid tui 'logid123' --region i18n --psm service.psm\n\n按键:\n  j/k 或方向键  移动\n  Tab          切换 PSM/Pod 分组\n  l            切换级别过滤\n  /            搜索消息内容（同时匹配原始值）\n  y            复制选中消息的原始值（OSC 52）\n  q/Esc        退出"
+    )]
+    Tui {
         /// 要查询的日志 ID
         logid: String,
-        /// 查询区域 (cn/i18n/us)
-        #[arg(short, long)]
+        /// 查询区域 (cn/i18n/us/eu)
+        #[arg(short, long, value_parser = RegionValueParser)]
         region: String,
         /// 过滤的 PSM 服务名称
         #[arg(short, long)]
         psm: Vec<String>,
     },
+    #[command(
+        about = "管理待查询队列",
+        long_about = "将 logid 加入本地队列，在网络或认证可用时再批量执行，适合 VPN 不稳定的场景\n\n示例:\n  logid queue add 'logid123' 'logid456' --region us\n  logid queue run\n  logid queue run --rounds 10 --interval 60\n  logid queue list\n  logid queue clear\n\n说明:\n  - 队列保存在 ~/.config/logid/queue.json\n  - 查询结果写入 ~/.config/logid/queue_results/<编号>-<logid>.json\n  - 失败的条目会在后续轮次中重试，超过最大尝试次数后标记为失败"
+    )]
+    Queue {
+        #[command(subcommand)]
+        command: QueueCommands,
+    },
+    #[command(
+        about = "为查询结果中的消息添加批注",
+        long_about = "为查询结果中的消息添加批注，批注保存在 ~/.config/logid/annotations.jsonl，\n之后查询同一 logid 时会附带在对应消息的 annotations 字段中\n\n示例:\n  logid annotate 'logid123' 'item1-value2' '根因：下游超时'\n  logid annotate 'logid123'\n\n参数说明:\n  - query_id: 查询标识，即查询时使用的 logid\n  - message_id: 消息 ID，即输出中 messages[].id\n  - note: 批注内容；省略消息 ID 和批注时列出该查询的全部批注"
+    )]
+    Annotate {
+        /// 查询标识（logid）
+        query_id: String,
+        /// 消息 ID
+        message_id: Option<String>,
+        /// 批注内容
+        note: Option<String>,
+    },
+    #[command(
+        about = "检查和管理认证凭据",
+        long_about = "检查和管理认证凭据\n\n示例:\n  logid auth check\n  logid auth refresh\n  logid auth refresh --region us\n\n说明:\n  - check: 对每个已配置的区域尝试获取 JWT 令牌，输出 CAS 变量、认证 URL、令牌状态和过期时间\n  - refresh: 强制刷新令牌并以 JSON 输出令牌元数据，便于脚本使用"
+    )]
+    Auth {
+        #[command(subcommand)]
+        command: AuthCommands,
+    },
+    #[command(
+        about = "管理消息过滤规则",
+        long_about = "管理消息过滤规则，规则保存在 ~/.config/logid/message_filters.json\n\n示例:\n  logid filters list\n  logid filters add '\"trace_id\":\\s*\"[^\"]*\"'\n  logid filters remove 3\n  logid filters test '{\"LogID\": \"abc\", \"msg\": \"hello\"}'\n\n说明:\n  - 用户规则文件不存在时，add/remove 以当前生效的规则为起点创建该文件\n  - 规则加载顺序: 用户规则文件 > reference/message_filters.json > 内置默认规则"
+    )]
+    Filters {
+        #[command(subcommand)]
+        command: FiltersCommands,
+    },
+    #[command(
+        about = "管理和检查配置",
+        long_about = "管理和检查配置\n\n示例:\n  logid config init\n  logid config show\n  logid config validate"
+    )]
+    Config {
+        #[command(subcommand)]
+        command: ConfigCommands,
+    },
+    #[command(
+        about = "列出支持的区域及其配置状态",
+        long_about = "列出支持的区域及其配置状态\n\n示例:\n  logid regions\n\n输出说明:\n  - 端点: 日志服务 URL、vregion 和 zones 是否已配置\n  - 凭据: 是否找到区域的 CAS_SESSION（或通用 CAS_SESSION）\n  - 状态: 端点和凭据都可用时才能查询该区域"
+    )]
+    Regions,
+    #[command(
+        about = "根据查询上下文重新执行查询",
+        long_about = "读取查询结果中记录的 context（或单独保存的上下文文件），使用相同的区域、时间范围、PSM 和过滤条件重新查询\n\n示例:\n  logid query 'logid123' --region us -o result.json\n  logid rerun result.json\n  logid rerun result.json --format table\n\n说明:\n  - 文件可以是 query 输出的 JSON 结果（含 context 字段）或单独的上下文 JSON\n  - 当前的消息过滤规则或可用区配置与查询时不同时会给出提示"
+    )]
+    Rerun {
+        /// 查询结果或上下文文件
+        context: String,
+        /// 输出格式
+        #[arg(short, long, value_enum, default_value_t = output::OutputFormat::Json)]
+        format: output::OutputFormat,
+        /// 将结果写入文件
+        #[arg(short, long, value_name = "PATH")]
+        output: Option<String>,
+    },
+    #[command(
+        about = "查看和重新执行查询历史",
+        long_about = "每次 query 的参数和结果数量都会记录在 ~/.config/logid/history.jsonl 中\n\n示例:\n  logid history list\n  logid history list --limit 50\n  logid history search pay.api\n  logid history rerun 12\n  logid history rerun 12 --format table\n\n说明:\n  - 编号为记录在历史文件中的顺序，list 和 search 的输出中以 #编号 显示\n  - search 按 logid、区域或 PSM 匹配关键字（不区分大小写）\n  - rerun 使用记录的完整查询上下文重新查询，与 logid rerun 相同"
+    )]
+    History {
+        #[command(subcommand)]
+        command: HistoryCommands,
+    },
+    #[command(
+        about = "生成 shell 补全脚本",
+        long_about = "生成 shell 补全脚本，输出到标准输出\n\n示例:\n  logid completions bash > ~/.local/share/bash-completion/completions/logid\n  logid completions zsh > ~/.zfunc/_logid\n  logid completions fish > ~/.config/fish/completions/logid.fish\n  logid completions powershell >> $PROFILE\n\n说明:\n  补全覆盖子命令、--region 区域名称和 --format 输出格式"
+    )]
+    Completions {
+        /// 目标 shell (bash/zsh/fish/powershell/elvish)
+        #[arg(value_enum)]
+        shell: clap_complete::Shell,
+    },
+    #[command(
+        hide = true,
+        about = "生成 man 手册页",
+        long_about = "根据命令行定义生成 roff 格式的 man 手册页，包含各子命令的说明和示例，供打包时随二进制安装\n\n示例:\n  logid man > logid.1\n  logid man --dir target/man\n\n参数说明:\n  - dir: 为主命令和每个子命令分别生成 logid.1、logid-query.1 等文件；省略时将主命令的页面输出到标准输出"
+    )]
+    Man {
+        /// 输出目录，为每个子命令分别生成手册页
+        #[arg(long, value_name = "DIR")]
+        dir: Option<String>,
+    },
     #[command(
         about = "更新 logid 到最新版本",
         long_about = "更新 logid 到最新版本\n\n示例:\n  logid update\n  logid update --check\n  logid update --force\n\n参数说明:\n  - check: 仅检查是否有新版本，不执行更新\n  - force: 强制更新，即使当前已是最新版本\n\n更新流程:\n  1. 从 GitHub 获取最新版本信息\n  2. 比较当前版本与最新版本\n  3. 下载对应平台的二进制文件\n  4. 验证文件完整性（SHA256）\n  5. 备份当前版本并替换文件\n\n注意事项:\n  - 需要网络连接\n  - 需要文件写入权限\n  - 更新前会自动备份当前版本\n  - 支持 Linux/macOS/Windows 平台"
@@ -54,140 +326,1541 @@ enum Commands {
     },
 }
 
-#[tokio::main]
-async fn main() -> Result<()> {
-    // 检查是否启用日志，默认关闭
-    let logging_enabled = std::env::var("ENABLE_LOGGING")
-        .unwrap_or_else(|_| "false".to_string())
-        .to_lowercase();
+#[derive(Args)]
+struct QueryArgs {
+    /// 要查询的日志 ID，可指定多个；`-` 表示从标准输入读取
+    #[arg(required_unless_present = "from_clipboard")]
+    logids: Vec<String>,
+    /// 从系统剪贴板读取要查询的 logid（去掉首尾空白并校验格式）
+    #[arg(long, conflicts_with = "logids")]
+    from_clipboard: bool,
+    /// 查询区域 (cn/i18n/us/eu)，可用逗号分隔多个区域，或使用 all 查询所有已配置区域
+    #[arg(short, long, value_parser = RegionValueParser)]
+    region: String,
+    /// 过滤的 PSM 服务名称
+    #[arg(short, long)]
+    psm: Vec<String>,
+    /// 从文件读取 PSM 列表（每行一个，支持 # 注释），与 --psm 合并
+    #[arg(long, value_name = "FILE")]
+    psm_file: Option<String>,
+    /// 响应体大小上限（MB），超出后截断并只解析已接收的部分；默认使用区域配置，未配置时为 64
+    #[arg(long, value_name = "MB")]
+    max_response_size: Option<usize>,
+    /// CAS_SESSION 在该时长（小时）内过期时发出提醒
+    #[arg(long, value_name = "HOURS", default_value_t = 12)]
+    expiry_warning: u64,
+    /// 跳过所有消息过滤规则和空白清理，输出原始消息
+    #[arg(long, visible_alias = "no-filter")]
+    raw: bool,
+    /// 对提取后的消息进行二次筛选，如 'level == "ERROR" && psm =~ "pay"'
+    #[arg(long = "where", value_name = "EXPR")]
+    where_expr: Option<String>,
+    /// 只保留消息内容匹配该正则表达式的消息
+    #[arg(long, value_name = "REGEX")]
+    grep: Option<String>,
+    /// 与 --grep 一起使用，只保留不匹配的消息
+    #[arg(long, requires = "grep")]
+    invert: bool,
+    /// 只保留指定级别的消息，逗号分隔，如 error,warn
+    #[arg(long, value_name = "LEVELS", value_delimiter = ',')]
+    level: Vec<String>,
+    /// 在结果中附带统计信息（下载字节数、提取消息数、各过滤规则移除的内容、解析警告）
+    #[arg(long)]
+    stats: bool,
+    /// 扫描时间范围（分钟），日志延迟写入时可扩大到 30 或 60；默认使用区域配置，未配置时为 10
+    #[arg(
+        long,
+        value_name = "MINUTES",
+        value_parser = clap::value_parser!(i32).range(1..=1440)
+    )]
+    scan_span: Option<i32>,
+    /// 为区域的每个可用区单独并发查询并合并结果，避免慢可用区拖慢整个查询
+    #[arg(long)]
+    per_zone: bool,
+    /// 只查询指定的可用区，逗号分隔，如 US-TTP2；必须属于查询区域的 zones
+    #[arg(long, value_name = "ZONES", value_delimiter = ',')]
+    zone: Vec<String>,
+    #[command(flatten)]
+    request: commands::RequestArgs,
+    /// 按间隔重复查询，只以 NDJSON 输出新出现的消息，按 Ctrl+C 退出
+    #[arg(long, conflicts_with = "output")]
+    watch: bool,
+    /// 监视模式的查询间隔，如 30s、5m（不带单位时按秒计算）
+    #[arg(
+        long,
+        value_name = "DURATION",
+        default_value = "30s",
+        requires = "watch",
+        value_parser = |v: &str| log_query::parse_interval(v).map_err(|e| e.to_string())
+    )]
+    interval: std::time::Duration,
+    /// 结果为空时自动扩大扫描范围重试（依次 30、60 分钟），尝试过的范围记录在 scan_widening 中
+    #[arg(long, conflicts_with_all = ["start", "end"])]
+    auto_widen: bool,
+    /// 部署环境，boe/ppe 使用 regions.json 中配置的对应端点（别名 --env）
+    #[arg(long, visible_alias = "env", value_enum, default_value_t = config::EnvTarget::Prod)]
+    env_target: config::EnvTarget,
+    /// 查询开始时间（RFC3339、Unix 时间戳或 -2h 等相对时间）
+    #[arg(long, value_name = "TIME", allow_hyphen_values = true)]
+    start: Option<String>,
+    /// 查询结束时间（格式同 --start，默认当前时间）
+    #[arg(long, value_name = "TIME", allow_hyphen_values = true)]
+    end: Option<String>,
+    /// 输出格式 (json/table)
+    #[arg(short, long, value_enum, default_value_t = output::OutputFormat::Json)]
+    format: output::OutputFormat,
+    /// JSON 输出中每条消息只保留这些字段，逗号分隔的点路径，如 logid,level,group.psm,values.value
+    #[arg(long, value_name = "FIELDS", value_delimiter = ',')]
+    fields: Vec<String>,
+    /// 将结果写入指定文件而不是标准输出
+    #[arg(short, long, value_name = "PATH")]
+    output: Option<String>,
+    /// 查询完成后输出 Web 控制台链接并在浏览器中打开
+    #[arg(long, conflicts_with = "watch")]
+    open: bool,
+    /// 从消息中提取上下游 logid 并递归查询的层数，0 表示不追踪
+    #[arg(long, value_name = "N", default_value_t = 0, conflicts_with = "watch")]
+    follow_depth: usize,
+    /// 递归追踪时最多查询的 logid 数量（包括起始 logid）
+    #[arg(long, value_name = "N", default_value_t = log_query::DEFAULT_FOLLOW_MAX_LOGIDS)]
+    follow_max: usize,
+    /// 只输出将要发送的请求（地址、请求头和请求体，JWT 已脱敏），不实际查询
+    #[arg(long, conflicts_with_all = ["watch", "follow_depth", "open"])]
+    dry_run: bool,
+}
 
-    let should_log = matches!(logging_enabled.as_str(), "true" | "on" | "1" | "yes");
+#[derive(Subcommand)]
+enum AuthCommands {
+    #[command(
+        about = "检查各区域的认证凭据是否可用",
+        long_about = "检查各区域的认证凭据是否可用\n\n对每个已配置的区域调用 JWT 认证接口并输出状态表，\n用于在查询前排查“认证失败”等问题。任一区域失败时退出码非零。"
+    )]
+    Check,
+    #[command(
+        about = "强制刷新令牌并输出令牌元数据",
+        long_about = "强制刷新令牌并以 JSON 输出令牌元数据（过期时间、剩余秒数、CAS_SESSION 过期时间）\n\n不指定区域时刷新所有已配置的区域。任一区域失败时退出码非零。"
+    )]
+    Refresh {
+        /// 刷新的区域 (cn/i18n/us/eu)，可用逗号分隔多个区域；默认所有已配置区域
+        #[arg(short, long, value_parser = RegionValueParser)]
+        region: Option<String>,
+    },
+    #[command(
+        about = "登录并保存区域的 CAS_SESSION",
+        long_about = "登录并保存区域的 CAS_SESSION\n\n在浏览器中打开区域的 SSO 页面，登录后粘贴 CAS_SESSION（或整段 Cookie 请求头），\n验证能获取 JWT 令牌后写入 ~/.config/logid/.env 中对应的 CAS_SESSION_<REGION>。\n\n示例:\n  logid auth login --region us\n  logid auth login --region i18n --no-browser\n  logid auth login --region us --session \"$COOKIE\""
+    )]
+    Login {
+        /// 登录的区域，一次只能登录一个区域（不支持 all）
+        #[arg(short, long, value_parser = RegionValueParser)]
+        region: String,
+        /// 直接使用给定的 CAS_SESSION（或 Cookie 请求头），不打开浏览器也不提示输入
+        #[arg(long, value_name = "COOKIE")]
+        session: Option<String>,
+        /// 不自动打开浏览器，只输出登录地址
+        #[arg(long)]
+        no_browser: bool,
+    },
+    #[command(
+        about = "加密 .env 中保存的 CAS_SESSION",
+        long_about = "加密 .env 中保存的 CAS_SESSION\n\n把 .env 文件（--env-file 或 ~/.config/logid/.env）中明文的 CAS_SESSION 变量替换为\nenc:v1:... 形式的密文，查询时用口令解密。口令来自 --key-file、LOGID_KEY_FILE、\nLOGID_PASSPHRASE 或交互输入。加密后 auth login 写入的新凭据也会自动加密。\n\n示例:\n  logid auth encrypt\n  logid auth encrypt --key-file ~/.config/logid/key"
+    )]
+    Encrypt,
+}
 
-    if should_log {
-        tracing_subscriber::fmt::init();
-    } else {
-        tracing_subscriber::fmt()
-            .with_max_level(tracing::Level::ERROR)
-            .with_ansi(false)
-            .compact()
-            .init();
+#[derive(Subcommand)]
+enum FiltersCommands {
+    #[command(about = "列出当前生效的过滤规则和可用的过滤规则集")]
+    List,
+    #[command(about = "添加过滤规则")]
+    Add {
+        /// 正则表达式
+        pattern: String,
+    },
+    #[command(about = "移除过滤规则")]
+    Remove {
+        /// 规则文本或 list 输出中的序号
+        pattern: String,
+    },
+    #[command(about = "测试样例文本会被过滤掉哪些内容")]
+    Test {
+        /// 样例消息
+        sample: String,
+    },
+    #[command(
+        about = "编译所有已配置的过滤规则，报告无效规则及出错位置",
+        long_about = "编译所有已配置的过滤规则，报告无效规则及出错位置\n\n检查当前生效的规则、config.toml 和过滤规则文件中的所有过滤规则集；存在无效规则时以退出码 6 结束\n\n示例:\n  logid filters validate\n  logid filters validate --sample '{\"LogID\": \"abc\", \"msg\": \"hello\"}'"
+    )]
+    Validate {
+        /// 用当前生效的规则过滤该样例，输出过滤前后的内容
+        #[arg(long, value_name = "MESSAGE")]
+        sample: Option<String>,
+    },
+}
+
+#[derive(Subcommand)]
+enum ConfigCommands {
+    #[command(
+        about = "交互式创建 ~/.config/logid/.env",
+        long_about = "交互式创建 ~/.config/logid/.env\n\n依次询问各区域的 CAS_SESSION、代理地址和日志开关，\n写入前会访问 JWT 认证接口验证每个凭据。"
+    )]
+    Init,
+    #[command(
+        about = "显示最终生效的配置",
+        long_about = "显示最终生效的配置\n\n包括加载的 .env 文件、CAS_SESSION 变量（已遮盖）、代理设置、\n当前生效的消息过滤规则及其来源，以及各区域的日志服务和认证端点。"
+    )]
+    Show,
+    #[command(
+        about = "检查配置是否有效",
+        long_about = "检查配置是否有效\n\n编译所有消息过滤规则并列出无效的正则表达式，\n查询时这些规则会被跳过，其余规则照常生效。"
+    )]
+    Validate,
+    #[command(
+        about = "读取 config.toml 中的配置项",
+        long_about = "读取 ~/.config/logid/config.toml 中的配置项，字符串直接输出内容，其他类型输出 TOML 写法
+
+示例:
+  logid config get region
+  logid config get defaults.region
+  logid config get filters.profile
+  logid config get regions.us.auth_url
+
+说明:
+  - 键为点分路径；defaults.<键> 等同于顶层的 <键>，filters.profile 等同于 filter_profile
+  - 配置项未设置时返回错误（退出码 6）"
+    )]
+    Get {
+        /// 点分路径形式的键，如 region、proxy.https
+        key: String,
+    },
+    #[command(
+        about = "修改 config.toml 中的配置项",
+        long_about = "修改 ~/.config/logid/config.toml 中的配置项，保留文件中的注释和格式
+
+示例:
+  logid config set defaults.region us
+  logid config set retries 3
+  logid config set psm '[\"pay.api\", \"pay.gateway\"]'
+  logid config set proxy.https http://127.0.0.1:7890
+
+说明:
+  - 值按 TOML 解析（数字、布尔值、数组），无法解析时作为字符串
+  - 写入前按 config validate 的规则校验，校验失败时不修改文件
+  - 先写入临时文件再替换，中断时不会留下损坏的配置"
+    )]
+    Set {
+        /// 点分路径形式的键，如 region、proxy.https
+        key: String,
+        /// 新的值
+        value: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum HistoryCommands {
+    #[command(about = "列出最近的查询")]
+    List {
+        /// 显示的最大条数
+        #[arg(short = 'n', long, default_value_t = 20)]
+        limit: usize,
+    },
+    #[command(about = "按 logid、区域或 PSM 搜索查询历史")]
+    Search {
+        /// 搜索关键字
+        keyword: String,
+    },
+    #[command(about = "重新执行指定编号的查询")]
+    Rerun {
+        /// 历史记录编号
+        number: usize,
+        /// 输出格式
+        #[arg(short, long, value_enum, default_value_t = output::OutputFormat::Json)]
+        format: output::OutputFormat,
+        /// 将结果写入文件
+        #[arg(short, long, value_name = "PATH")]
+        output: Option<String>,
+    },
+}
+
+#[derive(Subcommand)]
+enum QueueCommands {
+    #[command(about = "将 logid 加入队列")]
+    Add {
+        /// 要查询的日志 ID，可指定多个
+        #[arg(required = true)]
+        logids: Vec<String>,
+        /// 查询区域 (cn/i18n/us/eu)
+        #[arg(short, long, value_parser = RegionValueParser)]
+        region: String,
+        /// 过滤的 PSM 服务名称
+        #[arg(short, long)]
+        psm: Vec<String>,
+    },
+    #[command(about = "执行队列中等待的查询")]
+    Run {
+        /// 单个条目的最大尝试次数（只计入日志服务返回的失败）
+        #[arg(long, default_value_t = 5)]
+        max_attempts: u32,
+        /// 最多执行的轮数
+        #[arg(long, default_value_t = 1)]
+        rounds: u32,
+        /// 两轮之间的等待时间（秒）
+        #[arg(long, default_value_t = 30)]
+        interval: u64,
+    },
+    #[command(about = "列出队列条目")]
+    List,
+    #[command(about = "移除已完成的条目")]
+    Clear,
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    migrate_config();
+    let cli = match parse_cli() {
+        Ok(cli) => cli,
+        Err(e) => {
+            // 参数尚未解析完成，直接检查是否要求 JSON 格式的错误
+            if std::env::args_os().any(|arg| arg == "--json-errors") {
+                print_json_error(&e);
+            } else {
+                print_error(&e);
+            }
+            return ExitCode::from(error_exit_code(&e));
+        }
+    };
+    logid::init_logging(log_level(cli.verbose, cli.quiet));
+    if let Some(profile) = &cli.profile {
+        conditional_info!("使用配置档: {}", profile);
     }
+    config::set_proxy_setting(config::ProxySetting::from_args(cli.proxy.as_deref(), cli.no_proxy));
+    config::set_filter_config_path(cli.filter_config.clone());
+    config::set_filter_profile(cli.filter_profile.clone());
+    config::set_env_file_path(cli.env_file.clone());
+    config::set_key_file_path(cli.key_file.clone());
 
-    let cli = Cli::parse();
+    #[cfg(feature = "telemetry")]
+    let telemetry = init_telemetry();
 
+    let json_errors = cli.json_errors;
     match run_command(cli.command).await {
-        Ok(_) => Ok(()),
+        Ok(code) => code,
         Err(e) => {
             error!("执行失败: {}", e);
-            print_error(&e);
-            Err(e)
+            if json_errors {
+                print_json_error(&e);
+            } else {
+                print_error(&e);
+            }
+            #[cfg(feature = "telemetry")]
+            if let (Some(telemetry), Some(logid_error)) = (&telemetry, e.downcast_ref::<LogidError>()) {
+                telemetry.report_error(logid_error).await;
+            }
+            ExitCode::from(error_exit_code(&e))
+        }
+    }
+}
+
+/// 把用户配置目录迁移到当前格式版本，在标准错误输出修改过的文件和备份
+///
+/// 在解析命令行参数之前执行，保证之后读取的都是新格式的配置。
+fn migrate_config() {
+    let Some(dir) = config::user_config_dir().filter(|dir| dir.is_dir()) else {
+        return;
+    };
+    match config::migrate_config_dir(&dir) {
+        Ok(Some(report)) => {
+            for migration in &report.applied {
+                eprintln!("🔧 配置已迁移到版本 {}: {}", migration.version, migration.description);
+                for (file, backup) in migration.changed_files.iter().zip(&migration.backups) {
+                    eprintln!("   {}（原文件备份为 {}）", file.display(), backup.display());
+                }
+            }
+        }
+        Ok(None) => {}
+        Err(e) => eprintln!("⚠️  迁移配置目录 {} 失败: {}", dir.display(), e),
+    }
+}
+
+/// 解析命令行参数
+///
+/// `~/.config/logid/config.toml` 和配置档（`--profile` 或 `LOGID_PROFILE`）中的区域、PSM、
+/// 输出格式等作为对应参数的默认值，优先级为：命令行参数 > 配置档 > config.toml。
+/// config.toml 无效时给出警告并忽略其中的默认值，保证 `--help` 和 `config` 子命令仍然可用。
+fn parse_cli() -> Result<Cli> {
+    let argv: Vec<std::ffi::OsString> = std::env::args_os().collect();
+    let command = Cli::command();
+    let config_file = if subcommand_from_args(&command, &argv).as_deref() == Some("config") {
+        // config 子命令自己读取并报告配置文件的错误（如 config validate）
+        config::ConfigFile::default()
+    } else {
+        config::ConfigFile::load_default().unwrap_or_else(|e| {
+            eprintln!("⚠️  已忽略 config.toml 中的命令默认值: {}", e);
+            config::ConfigFile::default()
+        })
+    };
+    let mut command = apply_command_defaults(command, &config_file.command_defaults());
+
+    let profile_name = config::requested_profile(profile_from_args(&argv).as_deref());
+    if let Some(name) = &profile_name {
+        let profile = config::activate_profile(name)?;
+        command = apply_command_defaults(command, &config::CommandDefaults::from(&profile));
+    }
+
+    let matches = command.get_matches_from(argv);
+    let mut cli = Cli::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
+    cli.profile = profile_name;
+    if let Some(path) = cli.filter_config.as_ref().filter(|path| !path.is_file()) {
+        return Err(LogidError::FilterConfigError(format!(
+            "过滤规则文件不存在: {}",
+            path.display()
+        ))
+        .into());
+    }
+    if let Some(path) = cli.env_file.as_ref().filter(|path| !path.is_file()) {
+        return Err(LogidError::EnvFileNotFound(path.display().to_string()).into());
+    }
+    if let Some(path) = cli.key_file.as_ref().filter(|path| !path.is_file()) {
+        return Err(LogidError::MissingCredentials(format!("密钥文件不存在: {}", path.display())).into());
+    }
+    Ok(cli)
+}
+
+/// 在完整解析之前从参数中找出子命令名称
+///
+/// 跳过全局选项及其取值（如 `--env-file FILE`），返回第一个位置参数。
+fn subcommand_from_args(command: &clap::Command, argv: &[std::ffi::OsString]) -> Option<String> {
+    let takes_value = |name: &str| {
+        command
+            .get_arguments()
+            .any(|arg| arg.get_long() == Some(name) && arg.get_action().takes_values())
+    };
+    let mut args = argv.iter().skip(1).map(|arg| arg.to_string_lossy());
+    while let Some(arg) = args.next() {
+        if arg == "--" {
+            break;
+        }
+        if let Some(name) = arg.strip_prefix("--") {
+            if !name.contains('=') && takes_value(name) {
+                args.next();
+            }
+            continue;
+        }
+        if !arg.starts_with('-') {
+            return Some(arg.into_owned());
         }
     }
+    None
 }
 
-async fn run_command(command: Commands) -> Result<()> {
-    match command {
-        Commands::Query { logid, region, psm } => {
-            conditional_info!("开始查询日志: logid={}, region={}, psm_list={:?}", logid, region, psm);
-            run_query(&logid, &region, &psm).await
+/// 在完整解析之前从参数中找出 `--profile NAME` 或 `--profile=NAME`
+fn profile_from_args(argv: &[std::ffi::OsString]) -> Option<String> {
+    let mut args = argv.iter().skip(1).map(|arg| arg.to_string_lossy());
+    while let Some(arg) = args.next() {
+        if arg == "--" {
+            break;
+        }
+        if arg == "--profile" {
+            return args.next().map(|value| value.into_owned());
         }
+        if let Some(value) = arg.strip_prefix("--profile=") {
+            return Some(value.to_string());
+        }
+    }
+    None
+}
+
+/// 将 config.toml 或配置档中的默认值应用到子命令上
+///
+/// 区域和 PSM 只修改 `--region` 为必填的子命令（query、batch、timeline 等），
+/// `--format` 的默认值只在给定格式是该子命令的可选值时应用，
+/// `--timeout` 和 `--retries` 应用到所有带有这两个参数的子命令。
+fn apply_command_defaults(command: clap::Command, defaults: &config::CommandDefaults) -> clap::Command {
+    let names: Vec<String> = command
+        .get_subcommands()
+        .map(|sub| sub.get_name().to_string())
+        .collect();
+    let mut command = names.into_iter().fold(command, |command, name| {
+        command.mut_subcommand(name, |sub| apply_command_defaults(sub, defaults))
+    });
+
+    let argument = |command: &clap::Command, id: &str| {
+        command
+            .get_arguments()
+            .find(|arg| arg.get_id() == id)
+            .cloned()
+    };
+    if argument(&command, "region").is_some_and(|arg| arg.is_required_set()) {
+        if let Some(region) = &defaults.region {
+            command = command.mut_arg("region", |arg| arg.default_value(region.clone()).required(false));
+        }
+        if !defaults.psm.is_empty() && argument(&command, "psm").is_some() {
+            command = command.mut_arg("psm", |arg| arg.default_values(defaults.psm.clone()));
+        }
+    }
+    if let (Some(format), Some(arg)) = (&defaults.format, argument(&command, "format")) {
+        if arg.get_possible_values().iter().any(|value| value.matches(format, true)) {
+            command = command.mut_arg("format", |arg| arg.default_value(format.to_lowercase()));
+        }
+    }
+    if let (Some(timeout), Some(_)) = (&defaults.timeout, argument(&command, "timeout")) {
+        command = command.mut_arg("timeout", |arg| arg.default_value(timeout.clone()));
+    }
+    if let (Some(retries), Some(_)) = (defaults.retries, argument(&command, "retries")) {
+        command = command.mut_arg("retries", |arg| arg.default_value(retries.to_string()));
+    }
+    command
+}
+
+/// 错误对应的退出码，非 `LogidError` 的错误统一返回 1
+fn error_exit_code(error: &anyhow::Error) -> u8 {
+    if let Some(logid_error) = error.downcast_ref::<LogidError>() {
+        return logid_error.exit_code();
+    }
+    if error.downcast_ref::<reqwest::Error>().is_some() {
+        return exit_code::NETWORK;
+    }
+    exit_code::FAILURE
+}
+
+/// 查询成功时的退出码：没有找到任何消息时返回 3
+fn messages_exit_code(message_count: usize) -> ExitCode {
+    if message_count == 0 {
+        ExitCode::from(exit_code::NO_MESSAGES)
+    } else {
+        ExitCode::SUCCESS
+    }
+}
+
+/// 启用错误上报（需要 telemetry 特性并配置 LOGID_TELEMETRY_URL）
+#[cfg(feature = "telemetry")]
+fn init_telemetry() -> Option<logid::telemetry::Telemetry> {
+    let config = logid::telemetry::TelemetryConfig::from_env(&config::EnvManager::new().ok()?)?;
+    let mut telemetry = logid::telemetry::Telemetry::new(config);
+    // 只记录子命令名称，不记录 logid 等参数
+    if let Some(command) = std::env::args().nth(1).filter(|arg| {
+        Cli::command().get_subcommands().any(|sub| sub.get_name() == arg)
+    }) {
+        telemetry = telemetry.with_command(command);
+    }
+    telemetry.install_panic_hook();
+    Some(telemetry)
+}
+
+async fn run_command(command: Commands) -> Result<ExitCode> {
+    let result = match command {
+        Commands::Query(mut args) => {
+            args.logids = if args.from_clipboard {
+                vec![commands::read_clipboard_logid()?]
+            } else {
+                commands::expand_stdin_logids(std::mem::take(&mut args.logids))?
+            };
+            conditional_info!(
+                "开始查询日志: logids={:?}, region={}, psm_list={:?}",
+                args.logids,
+                args.region,
+                args.psm
+            );
+            if args.dry_run {
+                return run_dry_run(&args).await.map(|()| ExitCode::SUCCESS);
+            }
+            return run_query(&args).await.map(messages_exit_code);
+        }
+        Commands::Batch {
+            file,
+            region,
+            psm,
+            psm_file,
+            raw,
+            concurrency,
+            request,
+            env_target,
+        } => {
+            let psm = resolve_psm_list(&psm, psm_file.as_deref())?;
+            commands::batch::batch_command(
+                file.as_deref(),
+                &region,
+                &psm,
+                raw,
+                concurrency,
+                &request,
+                env_target,
+            )
+            .await
+        }
+        Commands::Timeline {
+            logids,
+            region,
+            psm,
+            no_color,
+            raw,
+        } => {
+            let logids = commands::expand_stdin_logids(logids)?;
+            if logids.len() < 2 {
+                return Err(anyhow::anyhow!("timeline 至少需要两个 logid"));
+            }
+            commands::timeline::timeline_command(&logids, &region, &psm, no_color, raw).await
+        }
+        Commands::Export {
+            input,
+            logid,
+            region,
+            psm,
+            format,
+            output,
+        } => {
+            commands::export::export_command(input, logid, region, &psm, format, output.as_deref())
+                .await
+        }
+        Commands::Diff {
+            a,
+            b,
+            region,
+            psm,
+            format,
+            output,
+        } => {
+            commands::diff::diff_command(a, b, region.as_deref(), &psm, format, output.as_deref())
+                .await
+        }
+        Commands::Stats {
+            logids,
+            region,
+            psm,
+            bucket,
+            top,
+            format,
+        } => {
+            let logids = commands::expand_stdin_logids(logids)?;
+            commands::stats::stats_command(logids, &region, &psm, bucket, top, format).await
+        }
+        #[cfg(feature = "tui")]
+        Commands::Tui { logid, region, psm } => {
+            commands::tui::tui_command(&logid, &region, &psm).await
+        }
+        Commands::Queue { command } => match command {
+            QueueCommands::Add { logids, region, psm } => {
+                commands::queue::add_command(&logids, &region, &psm)
+            }
+            QueueCommands::Run {
+                max_attempts,
+                rounds,
+                interval,
+            } => commands::queue::run_command(max_attempts, rounds, interval).await,
+            QueueCommands::List => commands::queue::list_command(),
+            QueueCommands::Clear => commands::queue::clear_command(),
+        },
+        Commands::Annotate {
+            query_id,
+            message_id,
+            note,
+        } => commands::annotate::annotate_command(
+            &query_id,
+            message_id.as_deref(),
+            note.as_deref(),
+        ),
+        Commands::Auth { command } => match command {
+            AuthCommands::Check => commands::auth::check_command().await,
+            AuthCommands::Refresh { region } => {
+                commands::auth::refresh_command(region.as_deref()).await
+            }
+            AuthCommands::Login {
+                region,
+                session,
+                no_browser,
+            } => commands::auth::login_command(&region, session, no_browser).await,
+            AuthCommands::Encrypt => commands::auth::encrypt_command(),
+        },
+        Commands::Filters { command } => match command {
+            FiltersCommands::List => commands::filters::list_command(),
+            FiltersCommands::Add { pattern } => commands::filters::add_command(&pattern),
+            FiltersCommands::Remove { pattern } => commands::filters::remove_command(&pattern),
+            FiltersCommands::Test { sample } => commands::filters::test_command(&sample),
+            FiltersCommands::Validate { sample } => {
+                commands::filters::validate_command(sample.as_deref())
+            }
+        },
+        Commands::Config { command } => match command {
+            ConfigCommands::Init => commands::config::init_command().await,
+            ConfigCommands::Show => commands::config::show_command(),
+            ConfigCommands::Validate => commands::config::validate_command(),
+            ConfigCommands::Get { key } => commands::config::get_command(&key),
+            ConfigCommands::Set { key, value } => commands::config::set_command(&key, &value),
+        },
+        Commands::Regions => commands::regions::regions_command(),
+        Commands::Rerun {
+            context,
+            format,
+            output,
+        } => {
+            let context = log_query::QueryContext::load(&context)?;
+            warn_if_context_changed(&context);
+            return run_query(&query_args_from_context(&context, format, output)?)
+                .await
+                .map(messages_exit_code);
+        }
+        Commands::History { command } => match command {
+            HistoryCommands::List { limit } => commands::history::list_command(limit),
+            HistoryCommands::Search { keyword } => commands::history::search_command(&keyword),
+            HistoryCommands::Rerun {
+                number,
+                format,
+                output,
+            } => {
+                let entry = store::HistoryStore::open_default()?.get(number)?;
+                warn_if_context_changed(&entry.context);
+                return run_query(&query_args_from_context(&entry.context, format, output)?)
+                    .await
+                    .map(messages_exit_code);
+            }
+        },
+        Commands::Completions { shell } => {
+            commands::completions::completions_command(shell, Cli::command())
+        }
+        Commands::Man { dir } => commands::man::man_command(Cli::command(), dir.as_deref()),
         Commands::Update { check, force } => {
             commands::update::update_command(check, force).await
         }
+    };
+    result.map(|_| ExitCode::SUCCESS)
+}
+
+/// 查询后端：单区域客户端或多区域查询器
+enum QueryBackend {
+    Single(Box<log_query::LogQueryClient>),
+    Regions(log_query::MultiRegionLogQuery),
+}
+
+impl QueryBackend {
+    /// 根据区域参数创建查询后端
+    async fn new(args: &QueryArgs, time_range: Option<log_query::TimeRange>) -> Result<Self> {
+        let regions = config::resolve_regions(&args.region)?;
+        let max_response_bytes = args.max_response_size.map(|mb| mb.saturating_mul(1024 * 1024));
+
+        if let [region] = regions.as_slice() {
+            let mut client = log_query::LogQueryClient::for_region_env(region, args.env_target).await?;
+            // 未指定时沿用区域配置中的默认值
+            if let Some(max_bytes) = max_response_bytes {
+                client = client.with_max_response_size(max_bytes);
+            }
+            if let Some(span) = args.scan_span {
+                client = client.with_scan_span(span);
+            }
+            let client = args
+                .request
+                .apply(client)
+                .with_zones(&args.zone)?
+                .with_auto_widen(args.auto_widen)
+                .with_per_zone(args.per_zone)
+                .with_time_range(time_range)
+                .with_raw(args.raw)
+                .with_levels(&args.level)
+                .with_stats(args.stats);
+            return Ok(Self::Single(Box::new(client)));
+        }
+
+        if regions.is_empty() {
+            return Err(LogidError::RegionNotConfigured(args.region.clone()).into());
+        }
+
+        let region_refs: Vec<&str> = regions.iter().map(String::as_str).collect();
+        let mut query = log_query::MultiRegionLogQuery::for_environment(&region_refs, args.env_target).await?;
+        if let Some(max_bytes) = max_response_bytes {
+            query = query.with_max_response_size(max_bytes);
+        }
+        if let Some(span) = args.scan_span {
+            query = query.with_scan_span(span);
+        }
+        let query = args
+            .request
+            .apply_multi(query)
+            .with_zones(&args.zone)?
+            .with_auto_widen(args.auto_widen)
+            .with_per_zone(args.per_zone)
+            .with_time_range(time_range)
+            .with_raw(args.raw)
+            .with_levels(&args.level)
+            .with_stats(args.stats);
+        Ok(Self::Regions(query))
+    }
+
+    /// 查询单个 logid，多区域时并发查询并合并结果
+    async fn get_log_details(
+        &self,
+        logid: &str,
+        psm_list: &[String],
+    ) -> Result<log_query::DetailedLogResult> {
+        match self {
+            Self::Single(client) => Ok(client.get_log_details(logid, psm_list).await?),
+            Self::Regions(query) => {
+                let merged = query.query_all(logid, psm_list).await;
+                for status in merged.failed_regions() {
+                    if let Some(e) = &status.error {
+                        eprintln!("⚠️  区域 {} 查询 {} 失败: {}", status.region, logid, e);
+                    }
+                }
+                Ok(merged.into_result()?)
+            }
+        }
+    }
+
+    /// 生成各区域的查询请求预览，不发送查询
+    async fn dry_run(&self, logid: &str, psm_list: &[String]) -> Result<Vec<log_query::DryRunRequest>> {
+        let clients: Vec<&log_query::LogQueryClient> = match self {
+            Self::Single(client) => vec![client.as_ref()],
+            Self::Regions(query) => query
+                .managed_regions()
+                .iter()
+                .filter_map(|region| query.get_client(region))
+                .collect(),
+        };
+        let mut requests = Vec::new();
+        for client in clients {
+            requests.extend(client.dry_run(logid, psm_list).await?);
+        }
+        Ok(requests)
+    }
+
+    /// 重新加载各区域客户端的消息过滤规则
+    fn reload_message_filters(&mut self) -> Result<(), LogidError> {
+        match self {
+            Self::Single(client) => client.reload_message_filters(),
+            Self::Regions(query) => query.reload_message_filters(),
+        }
+    }
+
+    /// 查询涉及的各区域认证管理器
+    fn auth_managers(&self) -> Vec<&logid::AuthManager> {
+        match self {
+            Self::Single(client) => client.auth_manager().into_iter().collect(),
+            Self::Regions(query) => query
+                .managed_regions()
+                .iter()
+                .filter_map(|region| query.get_client(region))
+                .filter_map(|client| client.auth_manager())
+                .collect(),
+        }
+    }
+
+    /// 查询前验证所有区域的凭据
+    ///
+    /// 多区域查询时跳过凭据不可用的区域并给出提示，只有所有区域都不可用时才报错。
+    async fn preflight(&mut self) -> Result<()> {
+        let report = logid::auth::preflight_check(&self.auth_managers()).await;
+        match self {
+            Self::Regions(query) if report.failures.len() < report.checked.len() => {
+                for failure in &report.failures {
+                    eprintln!("⚠️  区域 {} 的凭据不可用，已跳过: {}", failure.region, failure.error);
+                    query.skip_region(&failure.region);
+                }
+                Ok(())
+            }
+            _ => Ok(report.into_result()?),
+        }
+    }
+
+    /// 获取各区域即将过期的凭据信息
+    async fn expiring_credentials(&self, warning_window: std::time::Duration) -> Vec<config::CredentialExpiry> {
+        let mut expiring = Vec::new();
+        for manager in self.auth_managers() {
+            if let Some(expiry) = manager.credential_expiry(warning_window).await {
+                if expiry.expiring_soon {
+                    eprintln!(
+                        "⚠️  {} 区域的 CAS_SESSION 将在 {} 后过期（{}），请及时更新 {}",
+                        expiry.region,
+                        expiry.remaining_display(),
+                        expiry.expires_at.to_rfc3339(),
+                        manager.region().cas_session_env_var()
+                    );
+                }
+                expiring.push(expiry);
+            }
+        }
+        expiring
     }
 }
 
-/// 执行日志查询的主要逻辑
-async fn run_query(
-    logid: &str,
-    region: &str,
-    psm_list: &[String],
-) -> Result<()> {
-    // 检查区域配置
-    let region_config = config::get_region_config(region)
-        .ok_or_else(|| LogidError::UnsupportedRegion(region.to_string()))?;
+/// 输出每个 logid 将要发送的查询请求，不实际查询
+///
+/// JSON 格式输出 `DryRunRequest` 数组，table 格式输出请求文本和等价的 curl 命令。
+async fn run_dry_run(args: &QueryArgs) -> Result<()> {
+    let psm_list = resolve_psm_list(&args.psm, args.psm_file.as_deref())?;
+    let time_range = log_query::resolve_time_range(
+        args.start.as_deref(),
+        args.end.as_deref(),
+        effective_scan_span(args),
+        chrono::Utc::now(),
+    )?;
+    let backend = QueryBackend::new(args, time_range).await?;
+
+    let mut requests = Vec::new();
+    for logid in &args.logids {
+        requests.extend(backend.dry_run(logid, &psm_list).await?);
+    }
 
-    // 如果是 cn 区域且未配置，显示友好错误
-    if region == "cn" && !region_config.is_configured() {
-        return Err(LogidError::RegionNotConfigured(region.to_string()).into());
+    let text = match args.format {
+        output::OutputFormat::Json => serde_json::to_string_pretty(&requests)?,
+        output::OutputFormat::Table => requests
+            .iter()
+            .map(|request| format!("{}\n\n{}", request.format_text(), request.to_curl()))
+            .collect::<Vec<_>>()
+            .join("\n\n"),
+    };
+    match &args.output {
+        Some(path) => {
+            output::write_text_to_file(&text, path)?;
+            eprintln!("✅ 请求已写入 {}", path);
+        }
+        None => println!("{}", text),
     }
+    Ok(())
+}
+
+/// 执行查询并输出结果，返回输出的消息总数
+async fn run_query(args: &QueryArgs) -> Result<usize> {
+    // 先解析过滤表达式，避免语法错误时仍发起查询
+    let where_filter = args
+        .where_expr
+        .as_deref()
+        .map(query_filter::WhereFilter::parse)
+        .transpose()?;
+    let grep_filter = args
+        .grep
+        .as_deref()
+        .map(|pattern| query_filter::GrepFilter::new(pattern, args.invert))
+        .transpose()?;
+    let psm_list = resolve_psm_list(&args.psm, args.psm_file.as_deref())?;
+    // 在发起查询前读取 config.toml 的输出偏好，配置损坏时使用默认值，不影响查询和输出
+    let preferences = config::ConfigFile::load_default_lenient().output;
+    let time_range = log_query::resolve_time_range(
+        args.start.as_deref(),
+        args.end.as_deref(),
+        effective_scan_span(args),
+        chrono::Utc::now(),
+    )?;
 
-    // 创建认证管理器
-    let auth_manager = auth::AuthManager::new(region)?;
+    let context = build_query_context(args, &psm_list, time_range.clone())?;
 
+    // 根据区域配置创建认证管理器和查询客户端
     conditional_info!("创建日志查询客户端...");
-    let log_client = log_query::LogQueryClient::new(auth_manager, region_config).await?;
+    let mut backend = QueryBackend::new(args, time_range).await?;
+
+    // 查询多个 logid 时先验证凭据，避免查询到一半才发现会话过期
+    if args.logids.len() > 1 {
+        backend.preflight().await?;
+    }
+
+    if args.watch {
+        return run_watch(args, &mut backend, &psm_list, where_filter.as_ref(), grep_filter.as_ref()).await;
+    }
+
+    if args.follow_depth > 0 {
+        return run_follow(args, &backend, &psm_list, where_filter.as_ref(), grep_filter.as_ref()).await;
+    }
 
     conditional_info!("开始查询日志...");
-    let query_response = log_client.query_logs(logid, psm_list).await?;
+    let results = join_all(
+        args.logids
+            .iter()
+            .map(|logid| query_log_details(&backend, logid, &psm_list)),
+    )
+    .await
+    .into_iter()
+    .map(|result| {
+        result.map(|mut log_details| {
+            log_details.context = Some(context.clone());
+            if let Some(filter) = &where_filter {
+                filter.apply(&mut log_details);
+            }
+            if let Some(filter) = &grep_filter {
+                filter.apply(&mut log_details);
+            }
+            log_details
+        })
+    })
+    .collect::<Vec<_>>();
 
-    conditional_info!("提取日志消息...");
-    let data = query_response.data.as_ref().ok_or_else(|| {
-        anyhow::anyhow!("响应中没有数据内容")
-    })?;
+    record_history(&context, &results);
 
-    // 使用 LogQueryClient 的 extract_log_messages 方法提取消息
-    let extracted_messages = log_client.extract_log_messages(data);
+    // 单区域查询时在结果中附带凭据过期信息
+    let credential_expiry = backend
+        .expiring_credentials(std::time::Duration::from_secs(args.expiry_warning * 3600))
+        .await
+        .into_iter()
+        .next()
+        .filter(|_| matches!(backend, QueryBackend::Single(_)));
 
     conditional_info!("格式化输出结果...");
-    let output_config = output::OutputConfig::new();
-    let formatter = output::OutputFormatter::new(output_config);
-
-    // 创建 DetailedLogResult 结构
-    let data_items = data.items.len();
-    let log_details = log_query::DetailedLogResult {
-        logid: logid.to_string(),
-        region: region.to_string(),
-        messages: extracted_messages,
-        scan_time_range: None,
-        meta: query_response.data.and_then(|d| d.meta),
-        tag_infos: query_response.tag_infos,
-        total_items: data_items,
-        level_list: None,
-        timestamp: query_response.timestamp,
-        region_display_name: query_response.region_display_name,
+    // config.toml 的输出偏好作为起点，命令行参数优先
+    let mut output_config = preferences.apply(output::OutputConfig::new()).with_format(args.format);
+    if !args.fields.is_empty() {
+        output_config = output_config.with_fields(args.fields.clone());
+    }
+    let sink = match &args.output {
+        Some(path) => output::OutputSink::File(path.into()),
+        None => output::OutputSink::Stdout,
     };
+    let pipeline = output::OutputPipelineBuilder::new()
+        .config(output_config)
+        .sink(sink)
+        .build();
 
-    let formatted = formatter.format_log_result(&log_details)?;
-    println!("{}", formatted);
+    let message_count = if args.logids.len() == 1 {
+        let mut log_details = results.into_iter().next().expect("至少有一个 logid")?;
+        log_details.credential_expiry = credential_expiry;
+        warn_if_truncated(&log_details, args.max_response_size);
+        pipeline.emit(&log_details)?;
+        log_details.messages.len()
+    } else {
+        let mut multi = log_query::MultiLogResult::new(&args.region);
+        let mut first_error = None;
+        for (logid, result) in args.logids.iter().zip(results) {
+            match result {
+                Ok(mut log_details) => {
+                    log_details.credential_expiry = credential_expiry.clone();
+                    warn_if_truncated(&log_details, args.max_response_size);
+                    multi.results.insert(logid.clone(), log_details);
+                }
+                Err(e) => {
+                    eprintln!("⚠️  查询 {} 失败: {}", logid, e);
+                    multi.errors.insert(logid.clone(), e.to_string());
+                    first_error.get_or_insert(e);
+                }
+            }
+        }
+        // 所有 logid 都失败时返回第一个错误，退出码与单个 logid 查询一致
+        if multi.results.is_empty() {
+            if let Some(e) = first_error {
+                return Err(e);
+            }
+        }
+        pipeline.emit_multi(&multi)?;
+        multi.results.values().map(|log_details| log_details.messages.len()).sum()
+    };
 
-    Ok(())
+    if let Some(path) = &args.output {
+        eprintln!("✅ 结果已写入 {}", path);
+    }
+
+    if args.open {
+        open_web_urls(args)?;
+    }
+
+    Ok(message_count)
 }
 
-/// 打印友好的错误信息
-fn print_error(error: &anyhow::Error) {
-    if let Some(logid_error) = error.downcast_ref::<LogidError>() {
-        match logid_error {
-            LogidError::UnsupportedRegion(region) => {
-                eprintln!("不支持的区域: {}", region);
-                eprintln!("支持的区域: cn, i18n, us");
+/// 递归追踪消息中引用的 logid，以调用链树输出
+///
+/// JSON 格式输出每个起始 logid 的 `FollowNode` 树（多个起始 logid 时为数组），
+/// table 格式输出缩进的调用链概要。
+async fn run_follow(
+    args: &QueryArgs,
+    backend: &QueryBackend,
+    psm_list: &[String],
+    where_filter: Option<&query_filter::WhereFilter>,
+    grep_filter: Option<&query_filter::GrepFilter>,
+) -> Result<usize> {
+    let query = |logid: String| async move {
+        let mut log_details = query_log_details(backend, &logid, psm_list)
+            .await
+            .map_err(|e| match e.downcast::<LogidError>() {
+                Ok(e) => e,
+                Err(e) => LogidError::InternalError(e.to_string()),
+            })?;
+        if let Some(filter) = where_filter {
+            filter.apply(&mut log_details);
+        }
+        if let Some(filter) = grep_filter {
+            filter.apply(&mut log_details);
+        }
+        Ok(log_details)
+    };
+
+    let trees = join_all(
+        args.logids
+            .iter()
+            .map(|logid| log_query::follow_logids(logid, args.follow_depth, args.follow_max, query)),
+    )
+    .await;
+
+    let text = match args.format {
+        output::OutputFormat::Json if trees.len() == 1 => serde_json::to_string_pretty(&trees[0])?,
+        output::OutputFormat::Json => serde_json::to_string_pretty(&trees)?,
+        output::OutputFormat::Table => trees
+            .iter()
+            .map(log_query::FollowNode::format_tree)
+            .collect::<Vec<_>>()
+            .join("\n\n"),
+    };
+    match &args.output {
+        Some(path) => {
+            output::write_text_to_file(&text, path)?;
+            eprintln!("✅ 结果已写入 {}", path);
+        }
+        None => println!("{}", text),
+    }
+
+    Ok(trees.iter().map(log_query::FollowNode::message_count).sum())
+}
+
+/// 输出每个 logid 在各区域 Web 控制台中的链接，并尝试在浏览器中打开
+fn open_web_urls(args: &QueryArgs) -> Result<()> {
+    for region in config::resolve_regions(&args.region)? {
+        let Some(region_config) = config::get_region_config(&region)
+            .and_then(|config| config.for_environment(args.env_target).ok())
+        else {
+            continue;
+        };
+        for logid in &args.logids {
+            let Some(url) = region_config.web_url(logid) else {
+                eprintln!("⚠️  区域 {} 未配置 Web 控制台链接（regions.json 中的 web_url_template）", region);
+                break;
+            };
+            eprintln!("🔗 {}", url);
+            if let Err(e) = commands::open_in_browser(&url) {
+                eprintln!("⚠️  {}", e);
             }
-            LogidError::RegionNotConfigured(region) => {
-                eprintln!("区域 {} 尚未配置日志服务", region);
-                eprintln!("请联系相关团队获取配置信息");
+        }
+    }
+    Ok(())
+}
+
+/// 监视模式：按间隔重复查询，以 NDJSON 输出新出现的消息，返回输出的消息总数
+///
+/// 过滤规则文件变化时在下一轮查询前重新加载，无需重启。
+async fn run_watch(
+    args: &QueryArgs,
+    backend: &mut QueryBackend,
+    psm_list: &[String],
+    where_filter: Option<&query_filter::WhereFilter>,
+    grep_filter: Option<&query_filter::GrepFilter>,
+) -> Result<usize> {
+    use std::io::Write;
+
+    let projection = output::FieldProjection::new(&args.fields);
+    let mut seen = log_query::SeenMessages::new();
+    let mut filter_watcher = config::FilterFileWatcher::new();
+    let mut ticker = tokio::time::interval(args.interval);
+    ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+    // 长时间运行时在后台提前刷新各区域的令牌，轮询查询不会等待认证请求
+    let _pre_refresher = logid::auth::TokenPreRefresher::spawn(
+        backend.auth_managers().into_iter().cloned(),
+        logid::auth::DEFAULT_PRE_REFRESH_LEAD,
+    );
+
+    eprintln!("👀 每 {:?} 查询一次，按 Ctrl+C 退出", args.interval);
+    loop {
+        tokio::select! {
+            _ = ticker.tick() => {}
+            _ = tokio::signal::ctrl_c() => break,
+        }
+
+        if filter_watcher.changed() {
+            match backend.reload_message_filters() {
+                Ok(()) => eprintln!("🔄 过滤规则已变化，已重新加载"),
+                Err(e) => eprintln!("⚠️  重新加载过滤规则失败，继续使用原有规则: {}", e),
             }
-            LogidError::MissingCredentials(var) => {
-                eprintln!("缺少认证凭据: {}", var);
-                eprintln!("请在环境变量或 .env 文件中设置相应的 CAS_SESSION");
-                eprintln!("例如: export CAS_SESSION_US=your_session_cookie");
+        }
+
+        let results = join_all(
+            args.logids
+                .iter()
+                .map(|logid| query_log_details(backend, logid, psm_list)),
+        )
+        .await;
+
+        let mut stdout = std::io::stdout().lock();
+        for (logid, result) in args.logids.iter().zip(results) {
+            let mut log_details = match result {
+                Ok(log_details) => log_details,
+                Err(e) => {
+                    // 单次失败不退出，下一轮继续查询
+                    eprintln!("⚠️  查询 {} 失败: {}", logid, e);
+                    continue;
+                }
+            };
+            if let Some(filter) = where_filter {
+                filter.apply(&mut log_details);
             }
-            LogidError::AuthenticationFailed(msg) => {
-                eprintln!("认证失败: {}", msg);
-                eprintln!("请检查 CAS_SESSION 是否有效或网络连接是否正常");
+            if let Some(filter) = grep_filter {
+                filter.apply(&mut log_details);
             }
-            LogidError::NetworkError(e) => {
-                eprintln!("网络请求失败: {}", e);
-                eprintln!("请检查网络连接和防火墙设置");
+
+            for message in seen.take_new(&log_details) {
+                let mut value = serde_json::to_value(message)?;
+                value["logid"] = serde_json::json!(log_details.logid);
+                if !projection.is_empty() {
+                    value = projection.apply(&value);
+                }
+                writeln!(stdout, "{}", value)?;
             }
-            LogidError::QueryFailed(region, source) => {
-                eprintln!("区域 {} 查询失败: {}", region, source);
-                eprintln!("请检查日志 ID 是否正确或稍后重试");
+        }
+        stdout.flush()?;
+    }
+
+    eprintln!("共输出 {} 条消息", seen.len());
+    Ok(seen.len())
+}
+
+/// 将查询记录到本地历史，写入失败不影响查询结果
+fn record_history(
+    context: &log_query::QueryContext,
+    results: &[Result<log_query::DetailedLogResult>],
+) {
+    let result_count = results
+        .iter()
+        .flatten()
+        .map(|log_details| log_details.messages.len())
+        .sum();
+    let failed = results.iter().filter(|result| result.is_err()).count();
+    let entry = store::HistoryEntry::new(context, result_count, failed);
+    if let Err(e) = store::HistoryStore::open_default().and_then(|history| history.record(&entry)) {
+        conditional_info!("记录查询历史失败: {}", e);
+    }
+}
+
+/// 查询使用的扫描时间范围：`--scan-span`，其次第一个区域配置中的 `scan_span`，最后是内置默认值
+fn effective_scan_span(args: &QueryArgs) -> i32 {
+    args.scan_span
+        .or_else(|| {
+            let regions = config::resolve_regions(&args.region).ok()?;
+            config::get_region_config(regions.first()?)?.scan_span
+        })
+        .unwrap_or(log_query::DEFAULT_SCAN_SPAN_MINUTES)
+}
+
+/// 记录本次查询解析后的全部参数
+fn build_query_context(
+    args: &QueryArgs,
+    psm_list: &[String],
+    time_range: Option<log_query::TimeRange>,
+) -> Result<log_query::QueryContext> {
+    let regions = config::resolve_regions(&args.region)?;
+    let zones = regions
+        .iter()
+        .filter_map(|region| {
+            let config = config::get_region_config(region)?.for_environment(args.env_target).ok()?;
+            // 指定了 --zone 时只记录该区域实际查询的可用区
+            let config = config.select_zones(&config.owned_zones(&args.zone)).ok()?;
+            Some((region.clone(), config.zones))
+        })
+        .filter(|(_, zones)| !zones.is_empty())
+        .collect();
+
+    Ok(log_query::QueryContext {
+        env_target: args.env_target,
+        zones,
+        scan_span_in_min: effective_scan_span(args),
+        time_range,
+        psm_list: psm_list.to_vec(),
+        levels: args.level.clone(),
+        where_expr: args.where_expr.clone(),
+        grep: args.grep.clone(),
+        invert: args.invert,
+        raw: args.raw,
+        auto_widen: args.auto_widen,
+        per_zone: args.per_zone,
+        selected_zones: args.zone.clone(),
+        fields: args.fields.clone(),
+        stats: args.stats,
+        max_response_size: args.max_response_size,
+        timeout_secs: Some(args.request.timeout.as_secs()),
+        retries: Some(args.request.retries),
+        follow_depth: args.follow_depth,
+        follow_max: (args.follow_depth > 0).then_some(args.follow_max),
+        ..log_query::QueryContext::new(args.logids.clone(), regions)
+    })
+}
+
+/// 根据查询上下文构造查询参数，未记录的参数使用默认值
+fn query_args_from_context(
+    context: &log_query::QueryContext,
+    format: output::OutputFormat,
+    output: Option<String>,
+) -> Result<QueryArgs> {
+    let mut argv = vec!["logid".to_string(), "query".to_string()];
+    argv.extend(context.logids.iter().cloned());
+    argv.extend(["--region".to_string(), context.regions.join(",")]);
+    argv.extend(["--env-target".to_string(), context.env_target.to_string()]);
+    argv.extend(["--scan-span".to_string(), context.scan_span_in_min.to_string()]);
+    for psm in &context.psm_list {
+        argv.extend(["--psm".to_string(), psm.clone()]);
+    }
+    if !context.levels.is_empty() {
+        argv.extend(["--level".to_string(), context.levels.join(",")]);
+    }
+    if !context.selected_zones.is_empty() {
+        argv.extend(["--zone".to_string(), context.selected_zones.join(",")]);
+    }
+    if let Some(range) = &context.time_range {
+        let bounds = [("--start", range.start), ("--end", range.end)];
+        for (flag, timestamp) in bounds {
+            if let Some(time) = timestamp.and_then(|ts| chrono::DateTime::from_timestamp(ts, 0)) {
+                argv.extend([flag.to_string(), time.to_rfc3339()]);
             }
-            _ => {
-                eprintln!("发生错误: {}", error);
+        }
+    }
+    if let Some(expr) = &context.where_expr {
+        argv.extend(["--where".to_string(), expr.clone()]);
+    }
+    if let Some(pattern) = &context.grep {
+        argv.extend(["--grep".to_string(), pattern.clone()]);
+    }
+    if !context.fields.is_empty() {
+        argv.extend(["--fields".to_string(), context.fields.join(",")]);
+    }
+    let values = [
+        ("--max-response-size", context.max_response_size.map(|mb| mb.to_string())),
+        ("--timeout", context.timeout_secs.map(|secs| format!("{}s", secs))),
+        ("--retries", context.retries.map(|retries| retries.to_string())),
+        ("--follow-depth", Some(context.follow_depth).filter(|depth| *depth > 0).map(|d| d.to_string())),
+        ("--follow-max", context.follow_max.map(|max| max.to_string())),
+    ];
+    for (flag, value) in values {
+        if let Some(value) = value {
+            argv.extend([flag.to_string(), value]);
+        }
+    }
+    let flags = [
+        ("--invert", context.invert),
+        ("--raw", context.raw),
+        ("--auto-widen", context.auto_widen),
+        ("--per-zone", context.per_zone),
+        ("--stats", context.stats),
+    ];
+    argv.extend(flags.iter().filter(|(_, on)| *on).map(|(flag, _)| flag.to_string()));
+
+    match Cli::try_parse_from(argv)?.command {
+        Commands::Query(mut args) => {
+            args.format = format;
+            args.output = output;
+            Ok(*args)
+        }
+        _ => unreachable!("上下文总是生成 query 参数"),
+    }
+}
+
+/// 使用默认参数查询多个 logid，返回每个 logid 各自的查询结果
+async fn query_fresh_results(
+    logids: Vec<String>,
+    region: &str,
+    psm_list: &[String],
+) -> Result<Vec<(String, Result<log_query::DetailedLogResult>)>> {
+    let context = log_query::QueryContext {
+        psm_list: psm_list.to_vec(),
+        ..log_query::QueryContext::new(logids, config::resolve_regions(region)?)
+    };
+    let args = query_args_from_context(&context, output::OutputFormat::Json, None)?;
+    let backend = QueryBackend::new(&args, None).await?;
+
+    let results = join_all(
+        args.logids
+            .iter()
+            .map(|logid| query_log_details(&backend, logid, psm_list)),
+    )
+    .await;
+    Ok(args.logids.into_iter().zip(results).collect())
+}
+
+/// 当前的过滤规则或可用区配置与查询时不同时给出提示
+fn warn_if_context_changed(context: &log_query::QueryContext) {
+    if context.tool_version != env!("CARGO_PKG_VERSION") {
+        eprintln!("ℹ️  上下文由 logid {} 生成，当前版本 {}", context.tool_version, env!("CARGO_PKG_VERSION"));
+    }
+    if context.filters_changed() {
+        eprintln!("⚠️  消息过滤规则已变化，结果可能与原始查询不同");
+    }
+    for (region, zones) in &context.zones {
+        let current = config::get_region_config(region)
+            .and_then(|config| config.for_environment(context.env_target).ok())
+            .map(|config| config.zones)
+            .unwrap_or_default();
+        if &current != zones {
+            eprintln!("⚠️  区域 {} 的可用区配置已变化: {:?} → {:?}", region, zones, current);
+        }
+    }
+}
+
+/// 合并 --psm 参数和 --psm-file 文件中的 PSM 列表
+fn resolve_psm_list(flags: &[String], psm_file: Option<&str>) -> Result<Vec<String>> {
+    let Some(path) = psm_file else {
+        return Ok(flags.to_vec());
+    };
+    let psm_list = log_query::merge_psm_lists(flags, &log_query::load_psm_file(path)?);
+    conditional_info!("从 {} 读取 PSM 列表，合并后共 {} 个", path, psm_list.len());
+    Ok(psm_list)
+}
+
+/// 查询单个 logid 并附加用户批注
+async fn query_log_details(
+    backend: &QueryBackend,
+    logid: &str,
+    psm_list: &[String],
+) -> Result<log_query::DetailedLogResult> {
+    let mut log_details = backend.get_log_details(logid, psm_list).await?;
+
+    // 附加用户批注
+    match store::AnnotationStore::open_default()
+        .and_then(|annotations| annotations.apply(logid, &mut log_details.messages))
+    {
+        Ok(applied) if applied > 0 => conditional_info!("已附加 {} 条批注", applied),
+        Ok(_) => {}
+        Err(e) => conditional_info!("读取批注失败: {}", e),
+    }
+
+    Ok(log_details)
+}
+
+/// 结果被截断时提示用户
+fn warn_if_truncated(log_details: &log_query::DetailedLogResult, max_response_size_mb: Option<usize>) {
+    if !log_details.truncated {
+        return;
+    }
+    match max_response_size_mb {
+        Some(max_mb) => eprintln!(
+            "⚠️  {} 的响应体超过 {} MB 上限，结果仅包含部分日志",
+            log_details.logid, max_mb
+        ),
+        None => eprintln!(
+            "⚠️  {} 的响应体超过大小上限，结果仅包含部分日志（可用 --max-response-size 调大）",
+            log_details.logid
+        ),
+    }
+}
+
+/// 错误的描述和处理建议
+///
+/// 终端输出和 `--json-errors` 共用同一份建议，保持两种输出一致。
+fn error_hint(error: &LogidError) -> Option<String> {
+    let hint = match error {
+        LogidError::UnsupportedRegion(_) => {
+            let regions: Vec<&str> = config::Region::all().into_iter().map(|r| r.as_str()).collect();
+            let aliases: Vec<String> = config::region_aliases()
+                .iter()
+                .map(|(alias, region)| format!("{}={}", alias, region.as_str()))
+                .collect();
+            if aliases.is_empty() {
+                format!("支持的区域: {}", regions.join(", "))
+            } else {
+                format!("支持的区域: {}\n区域别名: {}", regions.join(", "), aliases.join(", "))
             }
         }
+        LogidError::RegionNotConfigured(_) => {
+            "请联系相关团队获取配置信息\n已有日志服务端点时可设置 LOGID_LOG_SERVICE_URL_<REGION> 和 LOGID_VREGION_<REGION>"
+                .to_string()
+        }
+        LogidError::MissingCredentials(_) => {
+            "请在环境变量或 .env 文件中设置相应的 CAS_SESSION\n例如: export CAS_SESSION_US=your_session_cookie"
+                .to_string()
+        }
+        LogidError::AuthenticationFailed(_) => "请检查 CAS_SESSION 是否有效或网络连接是否正常".to_string(),
+        LogidError::SessionExpired { region } => match config::Region::from_str(region) {
+            Some(r) => format!(
+                "请运行 logid auth login --region {} 重新登录，或更新 {} 后重试",
+                region,
+                r.cas_session_env_var()
+            ),
+            None => format!("请运行 logid auth login --region {} 重新登录", region),
+        },
+        LogidError::NetworkError(_) => "请检查网络连接和防火墙设置".to_string(),
+        LogidError::InvalidFilterExpression(_) => {
+            "示例: --where 'level == \"ERROR\" && psm =~ \"pay\"'".to_string()
+        }
+        LogidError::InvalidTimeRange(_) => {
+            "示例: --start 2024-05-01T10:00:00Z --end 2024-05-01T10:30:00Z 或 --start -2h".to_string()
+        }
+        LogidError::ProfileNotFound(_) => format!(
+            "请在 {} 的 profiles 中定义该配置档，或检查 --profile/LOGID_PROFILE",
+            config::user_profiles_path()?.display()
+        ),
+        LogidError::InvalidLogid(_) => {
+            "logid 只能包含字母、数字、- 和 _，请确认复制的内容是否完整".to_string()
+        }
+        LogidError::QueryFailed(..) => "请检查日志 ID 是否正确或稍后重试".to_string(),
+        _ => return None,
+    };
+    Some(hint)
+}
+
+/// 打印友好的错误信息
+fn print_error(error: &anyhow::Error) {
+    if let Some(logid_error) = error.downcast_ref::<LogidError>() {
+        let headline = match logid_error {
+            LogidError::RegionNotConfigured(region) => format!("区域 {} 尚未配置日志服务", region),
+            LogidError::QueryFailed(region, source) => format!("区域 {} 查询失败: {}", region, source),
+            LogidError::UnsupportedRegion(_)
+            | LogidError::MissingCredentials(_)
+            | LogidError::AuthenticationFailed(_)
+            | LogidError::SessionExpired { .. }
+            | LogidError::NetworkError(_)
+            | LogidError::InvalidFilterExpression(_)
+            | LogidError::InvalidTimeRange(_)
+            | LogidError::ProfileNotFound(_)
+            | LogidError::InvalidLogid(_) => logid_error.to_string(),
+            _ => format!("发生错误: {}", error),
+        };
+        eprintln!("{}", headline);
+        if let Some(hint) = error_hint(logid_error) {
+            eprintln!("{}", hint);
+        }
     } else {
         eprintln!("未知错误: {}", error);
     }
 }
+
+/// 以 JSON 对象输出错误到标准输出，供脚本和编辑器插件解析
+///
+/// 字段: error_code、message、region（可能为 null）、hint（可能为 null）。
+fn print_json_error(error: &anyhow::Error) {
+    let logid_error = error.downcast_ref::<LogidError>();
+    let error_code = match logid_error {
+        Some(logid_error) => logid_error.error_code(),
+        None if error.downcast_ref::<reqwest::Error>().is_some() => "network_error",
+        None => "error",
+    };
+    let value = serde_json::json!({
+        "error_code": error_code,
+        "message": error.to_string(),
+        "region": logid_error.and_then(LogidError::region),
+        "hint": logid_error.and_then(error_hint),
+    });
+    println!("{}", value);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_query_args_round_trip_through_context() {
+        let cli = Cli::try_parse_from([
+            "logid", "query", "logid-1", "--region", "us", "--psm", "pay.api", "--level", "error",
+            "--fields", "logid,level", "--stats", "--max-response-size", "8", "--timeout", "2m",
+            "--retries", "5", "--follow-depth", "2", "--follow-max", "10", "--per-zone",
+        ])
+        .unwrap();
+        let Commands::Query(args) = cli.command else { unreachable!() };
+
+        let context = build_query_context(&args, &args.psm, None).unwrap();
+        let json = serde_json::to_string(&context).unwrap();
+        let context: log_query::QueryContext = serde_json::from_str(&json).unwrap();
+        let rerun = query_args_from_context(&context, output::OutputFormat::Table, None).unwrap();
+
+        assert_eq!(rerun.logids, args.logids);
+        assert_eq!(rerun.psm, args.psm);
+        assert_eq!(rerun.level, args.level);
+        assert_eq!(rerun.fields, args.fields);
+        assert!(rerun.stats && rerun.per_zone);
+        assert_eq!(rerun.max_response_size, Some(8));
+        assert_eq!(rerun.request.timeout, std::time::Duration::from_secs(120));
+        assert_eq!(rerun.request.retries, 5);
+        assert_eq!((rerun.follow_depth, rerun.follow_max), (2, 10));
+        assert_eq!(rerun.format, output::OutputFormat::Table);
+
+        // 旧版本的上下文没有这些字段，重新执行时使用默认值
+        let legacy = log_query::QueryContext::new(vec!["logid-1".to_string()], vec!["us".to_string()]);
+        let rerun = query_args_from_context(&legacy, output::OutputFormat::Json, None).unwrap();
+        assert!(rerun.fields.is_empty() && !rerun.stats);
+        assert_eq!(rerun.request.retries, logid::retry::DEFAULT_RETRIES);
+        assert_eq!(rerun.follow_depth, 0);
+    }
+}
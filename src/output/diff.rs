@@ -0,0 +1,206 @@
+//! 查询结果对比模块
+//!
+//! 比较两个查询结果（如同一接口失败和成功的两次请求），按 PSM 分组列出
+//! 只在一侧出现的消息和内容发生变化的消息。
+
+use super::export::saved_log_results;
+use crate::error::LogidError;
+use crate::log_query::DetailedLogResult;
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// 没有 PSM 的消息归入的分组名
+const UNKNOWN_PSM: &str = "-";
+
+/// 参与对比的一条消息
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DiffMessage {
+    /// PSM 服务名称
+    pub psm: Option<String>,
+    /// 日志代码位置，用于匹配两侧的同一条日志
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub location: Option<String>,
+    /// 日志级别
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub level: Option<String>,
+    /// 消息时间
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub time: Option<String>,
+    /// 消息内容，多个值按行拼接
+    pub message: String,
+}
+
+impl DiffMessage {
+    /// 从查询结果中提取参与对比的消息
+    pub fn from_result(result: &DetailedLogResult) -> Vec<Self> {
+        result
+            .messages
+            .iter()
+            .map(|message| Self {
+                psm: message.group.psm.clone(),
+                location: message.location.clone(),
+                level: message.level.clone(),
+                time: message.time.clone(),
+                message: message
+                    .values
+                    .iter()
+                    .map(|value| value.value.as_str())
+                    .collect::<Vec<_>>()
+                    .join("\n"),
+            })
+            .collect()
+    }
+
+    /// 匹配键：有代码位置时按位置匹配，否则按内容匹配
+    fn key(&self) -> &str {
+        self.location.as_deref().unwrap_or(&self.message)
+    }
+}
+
+/// 从已保存的查询结果中读取参与对比的消息
+///
+/// 文件中包含多个 logid 的结果时，全部消息合并为一侧参与对比。
+pub fn diff_messages_from_json(content: &str) -> Result<Vec<DiffMessage>, LogidError> {
+    Ok(saved_log_results(content)?
+        .iter()
+        .flat_map(DiffMessage::from_result)
+        .collect())
+}
+
+/// 同一位置的日志在两侧内容不同
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ChangedMessage {
+    /// A 侧的消息
+    pub a: DiffMessage,
+    /// B 侧的消息
+    pub b: DiffMessage,
+}
+
+/// 单个 PSM 下的差异
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct PsmDiff {
+    /// PSM 服务名称，没有 PSM 的消息为 `-`
+    pub psm: String,
+    /// 只在 A 中出现的消息
+    pub only_in_a: Vec<DiffMessage>,
+    /// 只在 B 中出现的消息
+    pub only_in_b: Vec<DiffMessage>,
+    /// 内容发生变化的消息
+    pub changed: Vec<ChangedMessage>,
+    /// 两侧相同的消息数量
+    pub unchanged: usize,
+}
+
+impl PsmDiff {
+    /// 是否没有差异
+    pub fn is_empty(&self) -> bool {
+        self.only_in_a.is_empty() && self.only_in_b.is_empty() && self.changed.is_empty()
+    }
+}
+
+/// 两个查询结果的差异
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ResultDiff {
+    /// A 侧标识（文件路径或 logid）
+    pub a: String,
+    /// B 侧标识（文件路径或 logid）
+    pub b: String,
+    /// 按 PSM 名称排序的差异，只包含有差异的 PSM
+    pub psms: Vec<PsmDiff>,
+}
+
+impl ResultDiff {
+    /// 对比两侧的消息
+    ///
+    /// 同一 PSM 内按匹配键（代码位置或内容）配对，同一键出现多次时按出现顺序依次配对；
+    /// 配对后内容不同记为变化，未配对的消息记为只在一侧出现。
+    pub fn compare(
+        a: impl Into<String>,
+        a_messages: &[DiffMessage],
+        b: impl Into<String>,
+        b_messages: &[DiffMessage],
+    ) -> Self {
+        type Sides<'a> = (Vec<&'a DiffMessage>, Vec<&'a DiffMessage>);
+        let mut groups: BTreeMap<&str, BTreeMap<&str, Sides>> = BTreeMap::new();
+        for message in a_messages {
+            let psm = message.psm.as_deref().unwrap_or(UNKNOWN_PSM);
+            groups.entry(psm).or_default().entry(message.key()).or_default().0.push(message);
+        }
+        for message in b_messages {
+            let psm = message.psm.as_deref().unwrap_or(UNKNOWN_PSM);
+            groups.entry(psm).or_default().entry(message.key()).or_default().1.push(message);
+        }
+
+        let psms = groups
+            .into_iter()
+            .map(|(psm, keys)| {
+                let mut diff = PsmDiff {
+                    psm: psm.to_string(),
+                    ..PsmDiff::default()
+                };
+                for (left, right) in keys.into_values() {
+                    for index in 0..left.len().max(right.len()) {
+                        match (left.get(index), right.get(index)) {
+                            (Some(a), Some(b)) if a.message == b.message => diff.unchanged += 1,
+                            (Some(a), Some(b)) => diff.changed.push(ChangedMessage {
+                                a: (*a).clone(),
+                                b: (*b).clone(),
+                            }),
+                            (Some(a), None) => diff.only_in_a.push((*a).clone()),
+                            (None, Some(b)) => diff.only_in_b.push((*b).clone()),
+                            (None, None) => unreachable!(),
+                        }
+                    }
+                }
+                diff
+            })
+            .filter(|diff| !diff.is_empty())
+            .collect();
+
+        Self {
+            a: a.into(),
+            b: b.into(),
+            psms,
+        }
+    }
+
+    /// 两侧是否没有差异
+    pub fn is_empty(&self) -> bool {
+        self.psms.is_empty()
+    }
+
+    /// 格式化为便于阅读的文本
+    ///
+    /// `-` 表示只在 A 中出现，`+` 表示只在 B 中出现，`~` 表示内容变化。
+    pub fn format_text(&self) -> String {
+        let mut lines = vec![format!("--- A: {}", self.a), format!("+++ B: {}", self.b)];
+        if self.is_empty() {
+            lines.push("两侧消息相同".to_string());
+        }
+
+        let describe = |message: &DiffMessage| {
+            let location = message.location.as_deref().map(|l| format!(" ({})", l)).unwrap_or_default();
+            let level = message.level.as_deref().unwrap_or("-");
+            format!("[{}]{} {}", level, location, message.message.replace('\n', " ⏎ "))
+        };
+
+        for diff in &self.psms {
+            lines.push(String::new());
+            lines.push(format!(
+                "== {} (只在 A: {}, 只在 B: {}, 变化: {}, 相同: {})",
+                diff.psm,
+                diff.only_in_a.len(),
+                diff.only_in_b.len(),
+                diff.changed.len(),
+                diff.unchanged
+            ));
+            lines.extend(diff.only_in_a.iter().map(|m| format!("- {}", describe(m))));
+            lines.extend(diff.only_in_b.iter().map(|m| format!("+ {}", describe(m))));
+            for change in &diff.changed {
+                lines.push(format!("~ A {}", describe(&change.a)));
+                lines.push(format!("  B {}", describe(&change.b)));
+            }
+        }
+        lines.join("\n")
+    }
+}
@@ -0,0 +1,195 @@
+//! 结果导出模块
+//!
+//! 将查询结果展开为每条消息一行（logid、区域、时间、级别、PSM、Pod、消息内容、批注），
+//! 写入 CSV 或 Parquet（需要 `parquet` 特性），便于在表格或数仓中分析。
+
+use crate::error::LogidError;
+use crate::log_query::DetailedLogResult;
+use serde::Serialize;
+use serde_json::Value;
+use std::io::Write;
+
+/// 导出格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum ExportFormat {
+    /// 逗号分隔值，带表头
+    #[default]
+    Csv,
+    /// Apache Parquet（需要 parquet 特性）
+    Parquet,
+}
+
+/// 导出的一行（对应一条消息）
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct ExportRow {
+    /// 日志 ID
+    pub logid: String,
+    /// 区域
+    pub region: String,
+    /// 消息时间
+    pub timestamp: Option<String>,
+    /// 日志级别
+    pub level: Option<String>,
+    /// PSM 服务名称
+    pub psm: Option<String>,
+    /// Pod 名称
+    pub pod: Option<String>,
+    /// 消息内容，多个值按行拼接
+    pub message: String,
+    /// 用户批注，多条批注以 `; ` 拼接
+    pub annotations: Option<String>,
+}
+
+/// 表头，与 `ExportRow` 的字段顺序一致
+const COLUMNS: [&str; 8] = ["logid", "region", "timestamp", "level", "psm", "pod", "message", "annotations"];
+
+impl ExportRow {
+    /// 展开查询结果中的全部消息
+    pub fn from_result(result: &DetailedLogResult) -> Vec<Self> {
+        result
+            .messages
+            .iter()
+            .map(|message| Self {
+                logid: result.logid.clone(),
+                region: message.region.clone().unwrap_or_else(|| result.region.clone()),
+                timestamp: message.time.clone(),
+                level: message.level.clone(),
+                psm: message.group.psm.clone(),
+                pod: message.group.pod_name.clone(),
+                message: message
+                    .values
+                    .iter()
+                    .map(|value| value.value.as_str())
+                    .collect::<Vec<_>>()
+                    .join("\n"),
+                annotations: (!message.annotations.is_empty()).then(|| message.annotations.join("; ")),
+            })
+            .collect()
+    }
+
+    fn fields(&self) -> [&str; 8] {
+        [
+            &self.logid,
+            &self.region,
+            self.timestamp.as_deref().unwrap_or_default(),
+            self.level.as_deref().unwrap_or_default(),
+            self.psm.as_deref().unwrap_or_default(),
+            self.pod.as_deref().unwrap_or_default(),
+            &self.message,
+            self.annotations.as_deref().unwrap_or_default(),
+        ]
+    }
+}
+
+/// 从已保存的查询结果中读取导出行
+///
+/// 支持 `logid query` 输出的单个结果、多个 logid 的合并结果（`results`），
+/// 以及 `logid batch` 输出的 NDJSON（查询失败的行会被跳过）。
+pub fn rows_from_json(content: &str) -> Result<Vec<ExportRow>, LogidError> {
+    Ok(saved_log_results(content)?
+        .iter()
+        .flat_map(ExportRow::from_result)
+        .collect())
+}
+
+/// 读取已保存的查询结果，跳过查询失败的条目
+pub(super) fn saved_log_results(content: &str) -> Result<Vec<DetailedLogResult>, LogidError> {
+    saved_results(content)?
+        .into_iter()
+        .filter(|result| result.get("error").is_none())
+        .map(|result| serde_json::from_value(result).map_err(LogidError::JsonParseError))
+        .collect()
+}
+
+/// 将已保存的查询结果拆分为单个 logid 的结果对象
+fn saved_results(content: &str) -> Result<Vec<Value>, LogidError> {
+    let documents: Vec<Value> = match serde_json::from_str::<Value>(content) {
+        Ok(value) => vec![value],
+        Err(e) => {
+            let lines: Result<Vec<Value>, _> = content
+                .lines()
+                .filter(|line| !line.trim().is_empty())
+                .map(serde_json::from_str)
+                .collect();
+            // 按行也无法解析时返回整体解析的错误，位置信息更准确
+            lines.map_err(|_| LogidError::JsonParseError(e))?
+        }
+    };
+
+    let mut results = Vec::new();
+    for document in documents {
+        match document.get("results").and_then(Value::as_object) {
+            Some(merged) => results.extend(merged.values().cloned()),
+            None => results.push(document),
+        }
+    }
+    Ok(results)
+}
+
+/// 以 CSV 格式写入导出行
+pub fn write_csv<W: Write>(rows: &[ExportRow], writer: W) -> Result<(), LogidError> {
+    let mut csv = csv::Writer::from_writer(writer);
+    csv.write_record(COLUMNS).map_err(std::io::Error::from)?;
+    for row in rows {
+        csv.write_record(row.fields()).map_err(std::io::Error::from)?;
+    }
+    csv.flush()?;
+    Ok(())
+}
+
+/// 以 Parquet 格式写入导出行，所有列均为 UTF-8 字符串
+#[cfg(feature = "parquet")]
+pub fn write_parquet<W: Write + Send>(rows: &[ExportRow], writer: W) -> Result<(), LogidError> {
+    use arrow_array::{ArrayRef, RecordBatch, StringArray};
+    use arrow_schema::{DataType, Field, Schema};
+    use std::sync::Arc;
+
+    let parquet_error = |e: &dyn std::fmt::Display| LogidError::InternalError(format!("写入 Parquet 失败: {}", e));
+
+    let schema = Arc::new(Schema::new(
+        COLUMNS
+            .iter()
+            .enumerate()
+            .map(|(index, name)| Field::new(*name, DataType::Utf8, !matches!(index, 0 | 1 | 6)))
+            .collect::<Vec<_>>(),
+    ));
+    let columns: Vec<ArrayRef> = (0..COLUMNS.len())
+        .map(|index| {
+            let values = rows.iter().map(|row| {
+                let value = row.fields()[index];
+                (matches!(index, 0 | 1 | 6) || !value.is_empty()).then_some(value)
+            });
+            Arc::new(values.collect::<StringArray>()) as ArrayRef
+        })
+        .collect();
+    let batch = RecordBatch::try_new(schema.clone(), columns).map_err(|e| parquet_error(&e))?;
+
+    let mut parquet = parquet::arrow::ArrowWriter::try_new(writer, schema, None).map_err(|e| parquet_error(&e))?;
+    parquet.write(&batch).map_err(|e| parquet_error(&e))?;
+    parquet.close().map_err(|e| parquet_error(&e))?;
+    Ok(())
+}
+
+/// 按格式写入导出行
+///
+/// 未指定路径时 CSV 写到标准输出；Parquet 是二进制格式，必须指定输出文件。
+pub fn write_export(rows: &[ExportRow], format: ExportFormat, path: Option<&str>) -> Result<(), LogidError> {
+    if let Some(path) = path {
+        super::utils::create_parent_dirs(path)?;
+    }
+
+    match (format, path) {
+        (ExportFormat::Csv, Some(path)) => write_csv(rows, std::fs::File::create(path)?),
+        (ExportFormat::Csv, None) => write_csv(rows, std::io::stdout().lock()),
+        #[cfg(feature = "parquet")]
+        (ExportFormat::Parquet, Some(path)) => write_parquet(rows, std::fs::File::create(path)?),
+        #[cfg(feature = "parquet")]
+        (ExportFormat::Parquet, None) => Err(LogidError::InternalError(
+            "Parquet 导出需要使用 --output 指定文件".to_string(),
+        )),
+        #[cfg(not(feature = "parquet"))]
+        (ExportFormat::Parquet, _) => Err(LogidError::InternalError(
+            "当前构建未启用 parquet 特性，无法导出 Parquet".to_string(),
+        )),
+    }
+}
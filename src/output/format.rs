@@ -1,5 +1,42 @@
 //! 输出格式配置模块
 
+use serde::Deserialize;
+use std::io::IsTerminal;
+
+/// 输出格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum OutputFormat {
+    /// JSON 格式（默认）
+    #[default]
+    Json,
+    /// 适合终端阅读的表格格式
+    Table,
+}
+
+/// 颜色输出模式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum ColorMode {
+    /// 标准输出是终端且未设置 `NO_COLOR` 时使用颜色（默认）
+    #[default]
+    Auto,
+    /// 总是使用颜色
+    Always,
+    /// 不使用颜色
+    Never,
+}
+
+impl ColorMode {
+    /// 当前环境下是否输出颜色
+    pub fn enabled(self) -> bool {
+        match self {
+            Self::Always => true,
+            Self::Never => false,
+            Self::Auto => std::env::var_os("NO_COLOR").is_none() && std::io::stdout().is_terminal(),
+        }
+    }
+}
+
 /// 输出配置
 #[derive(Debug, Clone)]
 pub struct OutputConfig {
@@ -9,6 +46,12 @@ pub struct OutputConfig {
     pub show_scan_time_range: bool,
     /// 是否显示标签信息
     pub show_tag_infos: bool,
+    /// 输出格式
+    pub format: OutputFormat,
+    /// JSON 输出中每条消息保留的字段路径，为空时输出完整消息
+    pub fields: Vec<String>,
+    /// 颜色输出模式
+    pub color: ColorMode,
 }
 
 impl Default for OutputConfig {
@@ -17,6 +60,9 @@ impl Default for OutputConfig {
             show_metadata: true,
             show_scan_time_range: true,
             show_tag_infos: false,
+            format: OutputFormat::Json,
+            fields: Vec::new(),
+            color: ColorMode::Auto,
         }
     }
 }
@@ -26,4 +72,40 @@ impl OutputConfig {
     pub fn new() -> Self {
         Self::default()
     }
+
+    /// 设置输出格式
+    pub fn with_format(mut self, format: OutputFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// 设置每条消息保留的字段路径，如 `level`、`group.psm`、`values.value`
+    pub fn with_fields(mut self, fields: Vec<String>) -> Self {
+        self.fields = fields;
+        self
+    }
+
+    /// 设置是否输出元数据
+    pub fn with_show_metadata(mut self, show: bool) -> Self {
+        self.show_metadata = show;
+        self
+    }
+
+    /// 设置是否输出扫描时间范围
+    pub fn with_show_scan_time_range(mut self, show: bool) -> Self {
+        self.show_scan_time_range = show;
+        self
+    }
+
+    /// 设置是否输出标签信息
+    pub fn with_show_tag_infos(mut self, show: bool) -> Self {
+        self.show_tag_infos = show;
+        self
+    }
+
+    /// 设置颜色输出模式
+    pub fn with_color(mut self, color: ColorMode) -> Self {
+        self.color = color;
+        self
+    }
 }
@@ -1,9 +1,9 @@
 //! 输出格式化器模块
 
-use crate::conditional_info;
 use crate::error::LogidError;
-use crate::log_query::DetailedLogResult;
-use crate::output::format::OutputConfig;
+use crate::log_query::{BatchItemResult, DetailedLogResult, MultiLogResult};
+use crate::output::format::{OutputConfig, OutputFormat};
+use crate::output::projection::FieldProjection;
 use serde_json::json;
 use std::io::{self, Write};
 use tracing::error;
@@ -11,27 +11,151 @@ use tracing::error;
 /// 输出格式化器
 pub struct OutputFormatter {
     config: OutputConfig,
+    projection: FieldProjection,
 }
 
 impl OutputFormatter {
     /// 创建新的输出格式化器
     pub fn new(config: OutputConfig) -> Self {
-        Self { config }
+        let projection = FieldProjection::new(&config.fields);
+        Self { config, projection }
+    }
+
+    /// 按配置的输出格式渲染日志详情
+    pub fn render(&self, log_details: &DetailedLogResult) -> Result<String, LogidError> {
+        match self.config.format {
+            OutputFormat::Json => self.format_log_result(log_details),
+            OutputFormat::Table => self.format_table(log_details),
+        }
     }
 
     /// 格式化日志详情为 JSON 格式
     pub fn format_log_result(&self, log_details: &DetailedLogResult) -> Result<String, LogidError> {
         conditional_info!("格式化日志结果为 JSON 格式: logid={}", log_details.logid);
 
+        let json_result = self.build_json_value(log_details)?;
+        serde_json::to_string_pretty(&json_result).map_err(LogidError::JsonParseError)
+    }
+
+    /// 按配置的输出格式渲染多个 logid 的合并结果
+    pub fn render_multi(&self, multi: &MultiLogResult) -> Result<String, LogidError> {
+        match self.config.format {
+            OutputFormat::Json => self.format_multi_result(multi),
+            OutputFormat::Table => {
+                let mut sections = Vec::new();
+                for (logid, log_details) in &multi.results {
+                    sections.push(format!("=== {} ===\n{}", logid, self.format_table(log_details)?));
+                }
+                for (logid, error) in &multi.errors {
+                    sections.push(format!("=== {} ===\n查询失败: {}", logid, error));
+                }
+                Ok(sections.join("\n\n"))
+            }
+        }
+    }
+
+    /// 格式化多个 logid 的合并结果为 JSON 格式
+    ///
+    /// `results` 以 logid 为键，查询失败的 logid 记录在 `errors` 中。
+    pub fn format_multi_result(&self, multi: &MultiLogResult) -> Result<String, LogidError> {
+        let mut results = serde_json::Map::new();
+        for (logid, log_details) in &multi.results {
+            results.insert(logid.clone(), self.build_json_value(log_details)?);
+        }
+
+        let mut json_result = json!({
+            "region": multi.region,
+            "total_logids": multi.results.len() + multi.errors.len(),
+            "total_messages": multi.total_messages(),
+            "results": results,
+            "timestamp": multi.timestamp,
+        });
+
+        if !multi.errors.is_empty() {
+            json_result["errors"] = serde_json::to_value(&multi.errors).map_err(LogidError::JsonParseError)?;
+        }
+
+        serde_json::to_string_pretty(&json_result).map_err(LogidError::JsonParseError)
+    }
+
+    /// 格式化批量查询中的单个结果为一行 JSON（NDJSON）
+    ///
+    /// 查询失败时输出 `{"logid": ..., "error": ...}`。
+    pub fn format_ndjson_line(&self, item: &BatchItemResult) -> Result<String, LogidError> {
+        let json_result = match &item.result {
+            Ok(log_details) => self.build_json_value(log_details)?,
+            Err(e) => json!({
+                "logid": item.logid,
+                "error": e.to_string(),
+            }),
+        };
+        serde_json::to_string(&json_result).map_err(LogidError::JsonParseError)
+    }
+
+    /// 构建消息列表的 JSON 值，配置了字段投影时只保留指定字段
+    ///
+    /// 投影时每条消息可以引用所属结果的 `logid`。
+    fn message_values(&self, log_details: &DetailedLogResult) -> Result<serde_json::Value, LogidError> {
+        let messages = serde_json::to_value(&log_details.messages).map_err(LogidError::JsonParseError)?;
+        if self.projection.is_empty() {
+            return Ok(messages);
+        }
+
+        let projected = messages
+            .as_array()
+            .into_iter()
+            .flatten()
+            .map(|message| {
+                let mut message = message.clone();
+                if let Some(fields) = message.as_object_mut() {
+                    fields
+                        .entry("logid")
+                        .or_insert_with(|| json!(log_details.logid));
+                }
+                self.projection.apply(&message)
+            })
+            .collect();
+        Ok(serde_json::Value::Array(projected))
+    }
+
+    /// 构建日志详情的 JSON 值
+    fn build_json_value(&self, log_details: &DetailedLogResult) -> Result<serde_json::Value, LogidError> {
         let mut json_result = json!({
             "logid": log_details.logid,
             "region": log_details.region,
             "region_display_name": log_details.region_display_name,
             "total_items": log_details.total_items,
-            "messages": log_details.messages,
+            "messages": self.message_values(log_details)?,
             "timestamp": log_details.timestamp,
         });
 
+        if log_details.truncated {
+            json_result["truncated"] = json!(true);
+        }
+
+        if log_details.level_filtered > 0 {
+            json_result["level_filtered"] = json!(log_details.level_filtered);
+        }
+
+        if let Some(widening) = &log_details.scan_widening {
+            json_result["scan_widening"] =
+                serde_json::to_value(widening).map_err(LogidError::JsonParseError)?;
+        }
+
+        if let Some(context) = &log_details.context {
+            json_result["context"] = serde_json::to_value(context).map_err(LogidError::JsonParseError)?;
+        }
+
+        if let Some(stats) = &log_details.stats {
+            json_result["stats"] = serde_json::to_value(stats).map_err(LogidError::JsonParseError)?;
+        }
+
+        if let Some(expiry) = &log_details.credential_expiry {
+            json_result["expiring_soon"] = json!(expiry.expiring_soon);
+            json_result["credential_expiry"] =
+                serde_json::to_value(expiry).map_err(LogidError::JsonParseError)?;
+        }
+
         if self.config.show_metadata {
             if let Some(meta) = &log_details.meta {
                 json_result["meta"] = serde_json::to_value(meta).map_err(LogidError::JsonParseError)?;
@@ -52,12 +176,114 @@ impl OutputFormatter {
             }
         }
 
-        serde_json::to_string_pretty(&json_result).map_err(LogidError::JsonParseError)
+        Ok(json_result)
+    }
+
+    /// 格式化日志详情为表格格式
+    ///
+    /// 列依次为时间、级别、PSM、Pod 和消息内容，多行消息会被合并为一行。
+    pub fn format_table(&self, log_details: &DetailedLogResult) -> Result<String, LogidError> {
+        conditional_info!("格式化日志结果为表格格式: logid={}", log_details.logid);
+
+        const HEADERS: [&str; 5] = ["TIME", "LEVEL", "PSM", "POD", "MESSAGE"];
+
+        let rows: Vec<[String; 5]> = log_details
+            .messages
+            .iter()
+            .map(|message| {
+                let text = message
+                    .values
+                    .iter()
+                    .map(|v| v.value.split_whitespace().collect::<Vec<_>>().join(" "))
+                    .collect::<Vec<_>>()
+                    .join(" ");
+                [
+                    message.time.clone().unwrap_or_else(|| "-".to_string()),
+                    message.level.clone().unwrap_or_else(|| "-".to_string()),
+                    message.group.psm.clone().unwrap_or_else(|| "-".to_string()),
+                    message.group.pod_name.clone().unwrap_or_else(|| "-".to_string()),
+                    text,
+                ]
+            })
+            .collect();
+
+        // 计算除消息列外各列的宽度
+        let mut widths = [0usize; 4];
+        for (i, width) in widths.iter_mut().enumerate() {
+            *width = rows
+                .iter()
+                .map(|row| row[i].chars().count())
+                .chain(std::iter::once(HEADERS[i].len()))
+                .max()
+                .unwrap_or(0);
+        }
+
+        let format_row = |cells: [&str; 5]| {
+            let mut line = String::new();
+            for (i, width) in widths.iter().enumerate() {
+                line.push_str(&format!("{:<width$}  ", cells[i], width = width));
+            }
+            line.push_str(cells[4]);
+            line
+        };
+
+        let mut lines = vec![format_row(HEADERS)];
+        lines.push(format_row([
+            &"-".repeat(widths[0]),
+            &"-".repeat(widths[1]),
+            &"-".repeat(widths[2]),
+            &"-".repeat(widths[3]),
+            "-------",
+        ]));
+        for row in &rows {
+            lines.push(format_row([&row[0], &row[1], &row[2], &row[3], &row[4]]));
+        }
+
+        lines.push(String::new());
+        lines.push(format!(
+            "logid: {}  区域: {}  消息数: {}{}{}",
+            log_details.logid,
+            log_details.region_display_name,
+            log_details.messages.len(),
+            if log_details.level_filtered > 0 {
+                format!("（按级别过滤 {} 条）", log_details.level_filtered)
+            } else {
+                String::new()
+            },
+            if log_details.truncated { "（响应已截断）" } else { "" }
+        ));
+
+        if let Some(widening) = &log_details.scan_widening {
+            let spans: Vec<String> = widening
+                .attempted_spans
+                .iter()
+                .map(|span| format!("{}min", span))
+                .collect();
+            lines.push(format!("扫描范围已自动扩大: {}", spans.join(" → ")));
+        }
+
+        if let Some(stats) = &log_details.stats {
+            lines.push(format!(
+                "下载: {} 字节  日志项: {}  提取消息: {}",
+                stats.bytes_downloaded, stats.items, stats.messages_extracted
+            ));
+            for hit in &stats.filter_hits {
+                lines.push(format!(
+                    "过滤: {}  命中 {} 条消息，移除 {} 字节",
+                    hit.pattern, hit.messages, hit.bytes_removed
+                ));
+            }
+            for warning in &stats.parse_warnings {
+                lines.push(format!("警告: {}", warning));
+            }
+        }
+
+        Ok(lines.join("\n"))
     }
 
     /// 打印格式化结果到标准输出
     pub fn print_result(&self, log_details: &DetailedLogResult) -> Result<(), LogidError> {
-        let formatted_output = self.format_log_result(log_details)?;
+        let formatted_output = self.render(log_details)?;
         print!("{}", formatted_output);
         io::stdout().flush().map_err(|e| {
             error!("刷新标准输出失败: {}", e);
@@ -69,7 +295,7 @@ impl OutputFormatter {
     /// 写入格式化结果到指定的写入器
     #[allow(dead_code)]
     pub fn write_result<W: Write>(&self, writer: &mut W, log_details: &DetailedLogResult) -> Result<(), LogidError> {
-        let formatted_output = self.format_log_result(log_details)?;
+        let formatted_output = self.render(log_details)?;
         writer.write_all(formatted_output.as_bytes()).map_err(|e| {
             error!("写入输出失败: {}", e);
             LogidError::IoError(e)
@@ -0,0 +1,102 @@
+//! 导出清单模块
+//!
+//! 导出结果包含多个文件时，在目录中写入 `manifest.json` 描述每个文件及其校验和，
+//! 便于下游自动化程序可靠地读取导出内容。
+
+use crate::error::LogidError;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::path::Path;
+
+/// 清单文件名
+pub const MANIFEST_FILE_NAME: &str = "manifest.json";
+
+/// 当前清单格式版本
+const MANIFEST_VERSION: u32 = 1;
+
+/// 生成导出文件时使用的查询参数
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QueryParameters {
+    /// 日志 ID 列表
+    pub logids: Vec<String>,
+    /// 查询区域
+    pub region: String,
+    /// PSM 服务列表
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub psm_list: Vec<String>,
+}
+
+/// 清单中的单个导出文件
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ManifestArtifact {
+    /// 相对于导出目录的路径
+    pub path: String,
+    /// 文件大小（字节）
+    pub bytes: u64,
+    /// 文件内容的 SHA256（十六进制）
+    pub sha256: String,
+    /// 生成该文件的查询参数（与清单级别的参数不同时记录）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub query: Option<QueryParameters>,
+}
+
+/// 导出清单
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExportManifest {
+    /// 清单格式版本
+    pub version: u32,
+    /// 生成清单的 logid 版本
+    pub tool_version: String,
+    /// 生成时间
+    pub generated_at: String,
+    /// 整个导出共用的查询参数
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub query: Option<QueryParameters>,
+    /// 导出的文件列表
+    pub artifacts: Vec<ManifestArtifact>,
+}
+
+impl ExportManifest {
+    /// 创建空清单
+    pub fn new(query: Option<QueryParameters>) -> Self {
+        Self {
+            version: MANIFEST_VERSION,
+            tool_version: env!("CARGO_PKG_VERSION").to_string(),
+            generated_at: chrono::Utc::now().to_rfc3339(),
+            query,
+            artifacts: Vec::new(),
+        }
+    }
+
+    /// 登记导出目录中的一个文件，计算其大小和校验和
+    ///
+    /// `path` 可以是绝对路径或相对于 `dir` 的路径，清单中统一记录为相对路径。
+    pub fn add_file(
+        &mut self,
+        dir: &Path,
+        path: &Path,
+        query: Option<QueryParameters>,
+    ) -> Result<(), LogidError> {
+        let full_path = if path.is_absolute() { path.to_path_buf() } else { dir.join(path) };
+        let content = std::fs::read(&full_path)?;
+        let relative = full_path.strip_prefix(dir).unwrap_or(&full_path);
+
+        self.artifacts.push(ManifestArtifact {
+            path: relative.to_string_lossy().replace('\\', "/"),
+            bytes: content.len() as u64,
+            sha256: format!("{:x}", Sha256::digest(&content)),
+            query,
+        });
+        Ok(())
+    }
+
+    /// 将清单写入导出目录，返回清单文件路径
+    pub fn write(&self, dir: &Path) -> Result<std::path::PathBuf, LogidError> {
+        std::fs::create_dir_all(dir)?;
+        let path = dir.join(MANIFEST_FILE_NAME);
+        std::fs::write(&path, serde_json::to_string_pretty(self)?)?;
+
+        conditional_info!("导出清单已写入: {} ({} 个文件)", path.display(), self.artifacts.len());
+        Ok(path)
+    }
+}
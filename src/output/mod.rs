@@ -1,14 +1,28 @@
 //! 输出格式化模块
 //!
-//! 提供 JSON 格式输出支持。
+//! 提供 JSON 格式和表格格式输出支持。
 
+mod diff;
+mod export;
 mod format;
 mod formatter;
+mod manifest;
+mod pipeline;
+mod projection;
+mod timeline;
 mod utils;
 
-pub use format::OutputConfig;
+#[cfg(feature = "parquet")]
+pub use export::write_parquet;
+pub use diff::{diff_messages_from_json, ChangedMessage, DiffMessage, PsmDiff, ResultDiff};
+pub use export::{rows_from_json, write_csv, write_export, ExportFormat, ExportRow};
+pub use format::{ColorMode, OutputConfig, OutputFormat};
 pub use formatter::OutputFormatter;
-pub use utils::{print_json_output, write_to_file};
+pub use manifest::{ExportManifest, ManifestArtifact, QueryParameters, MANIFEST_FILE_NAME};
+pub use pipeline::{Compression, OutputPipeline, OutputPipelineBuilder, OutputSink};
+pub use projection::FieldProjection;
+pub use timeline::{build_timeline, format_timeline, TimelineEntry};
+pub use utils::{print_json_output, write_text_to_file, write_to_file};
 
 #[cfg(test)]
 mod tests {
@@ -29,6 +43,7 @@ mod tests {
                         env: Some("production".to_string()),
                         vregion: Some("US-TTP".to_string()),
                         idc: Some("us-east-1".to_string()),
+                        extra: Default::default(),
                     },
                     values: vec![
                         ExtractedValue {
@@ -41,6 +56,10 @@ mod tests {
                     ],
                     level: Some("INFO".to_string()),
                     location: Some("src/main.rs:42".to_string()),
+                    code_location: None,
+                    time: None,
+                    region: None,
+                    annotations: Vec::new(),
                 },
             ],
             meta: None,
@@ -54,6 +73,12 @@ mod tests {
             timestamp: "2024-01-01T12:00:00Z".to_string(),
             region: "us".to_string(),
             region_display_name: "美区".to_string(),
+            truncated: false,
+            credential_expiry: None,
+            stats: None,
+            level_filtered: 0,
+            scan_widening: None,
+            context: None,
         }
     }
 
@@ -81,9 +106,239 @@ mod tests {
         assert!(json_value["messages"].is_array());
     }
 
+    #[test]
+    fn test_formatter_table_output() {
+        let config = OutputConfig::new().with_format(OutputFormat::Table);
+        let formatter = OutputFormatter::new(config);
+        let log_result = create_test_log_result();
+
+        let output = formatter.render(&log_result).unwrap();
+        let lines: Vec<&str> = output.lines().collect();
+
+        assert!(lines[0].starts_with("TIME"));
+        assert!(lines[0].contains("LEVEL") && lines[0].contains("PSM") && lines[0].contains("POD"));
+        assert!(lines[2].contains("INFO"));
+        assert!(lines[2].contains("test.psm"));
+        assert!(lines[2].contains("test-pod-123"));
+        assert!(lines[2].ends_with("这是一条测试消息"));
+    }
+
+    #[test]
+    fn test_build_timeline_interleaves_by_time() {
+        let mut first = create_test_log_result();
+        first.logid = "first".to_string();
+        first.messages[0].time = Some("2024-01-01 12:00:02".to_string());
+
+        let mut second = create_test_log_result();
+        second.logid = "second".to_string();
+        second.messages[0].time = Some("2024-01-01 12:00:01".to_string());
+        let mut untimed = second.messages[0].clone();
+        untimed.time = None;
+        second.messages.push(untimed);
+
+        let results = vec![first, second];
+        let timeline = build_timeline(&results);
+        let order: Vec<&str> = timeline.iter().map(|e| e.logid).collect();
+        assert_eq!(order, vec!["second", "first", "second"]);
+        assert!(timeline[2].time.is_none());
+
+        let output = format_timeline(&results, false);
+        assert!(output.contains("[1] first"));
+        assert!(!output.contains("\x1b["));
+    }
+
+    #[test]
+    fn test_format_multi_result_keyed_by_logid() {
+        use crate::log_query::MultiLogResult;
+
+        let mut multi = MultiLogResult::new("us");
+        multi.results.insert("test_logid_123".to_string(), create_test_log_result());
+        multi.errors.insert("missing".to_string(), "查询失败".to_string());
+
+        let formatter = OutputFormatter::new(OutputConfig::new());
+        let output = formatter.format_multi_result(&multi).unwrap();
+        let json_value: Value = serde_json::from_str(&output).unwrap();
+
+        assert_eq!(json_value["total_logids"], 2);
+        assert_eq!(json_value["total_messages"], 1);
+        assert_eq!(json_value["results"]["test_logid_123"]["logid"], "test_logid_123");
+        assert_eq!(json_value["errors"]["missing"], "查询失败");
+    }
+
+    #[test]
+    fn test_write_to_file_creates_parent_dirs() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("nested/dir/result.json");
+        let log_result = create_test_log_result();
+
+        write_to_file(&log_result, path.to_str().unwrap(), OutputConfig::new()).unwrap();
+
+        let content = std::fs::read_to_string(&path).unwrap();
+        let json_value: Value = serde_json::from_str(&content).unwrap();
+        assert_eq!(json_value["logid"], "test_logid_123");
+    }
+
     #[test]
     fn test_print_json_output() {
         let log_result = create_test_log_result();
         assert!(print_json_output(&log_result).is_ok());
     }
+
+    #[test]
+    fn test_export_manifest_records_checksums() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("a.json"), "hello").unwrap();
+        std::fs::create_dir_all(dir.path().join("psm")).unwrap();
+        std::fs::write(dir.path().join("psm/b.json"), "").unwrap();
+
+        let query = QueryParameters {
+            logids: vec!["logid-1".to_string()],
+            region: "us".to_string(),
+            psm_list: Vec::new(),
+        };
+        let mut manifest = ExportManifest::new(Some(query.clone()));
+        manifest.add_file(dir.path(), std::path::Path::new("a.json"), None).unwrap();
+        manifest
+            .add_file(dir.path(), &dir.path().join("psm/b.json"), Some(query))
+            .unwrap();
+        assert!(manifest.add_file(dir.path(), std::path::Path::new("missing.json"), None).is_err());
+
+        let path = manifest.write(dir.path()).unwrap();
+        let written: ExportManifest = serde_json::from_str(&std::fs::read_to_string(path).unwrap()).unwrap();
+        assert_eq!(written.artifacts.len(), 2);
+        assert_eq!(written.artifacts[0].path, "a.json");
+        assert_eq!(written.artifacts[0].bytes, 5);
+        assert_eq!(
+            written.artifacts[0].sha256,
+            "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824"
+        );
+        assert_eq!(written.artifacts[1].path, "psm/b.json");
+        assert!(written.artifacts[1].query.is_some());
+    }
+
+    #[test]
+    fn test_field_projection() {
+        let formatter = OutputFormatter::new(OutputConfig::new().with_fields(vec![
+            "logid".to_string(),
+            "level".to_string(),
+            "group.psm".to_string(),
+            "values.value".to_string(),
+            "missing.field".to_string(),
+        ]));
+
+        let json: Value = serde_json::from_str(&formatter.format_log_result(&create_test_log_result()).unwrap()).unwrap();
+        let message = &json["messages"][0];
+        assert_eq!(
+            message,
+            &serde_json::json!({
+                "logid": "test_logid_123",
+                "level": "INFO",
+                "group": {"psm": "test.psm"},
+                "values": [{"value": "这是一条测试消息"}],
+            })
+        );
+    }
+
+    #[test]
+    fn test_output_pipeline_redacts_and_compresses() {
+        use flate2::read::GzDecoder;
+        use regex::Regex;
+        use std::io::Read;
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("nested/result.json.gz");
+        let pipeline = OutputPipelineBuilder::new()
+            .redact(Regex::new(r"192\.168\.\d+\.\d+").unwrap(), "<ip>")
+            .redact_with(|text| text.replace("test-pod-123", "<pod>"))
+            .compression(Compression::Gzip)
+            .sink(OutputSink::File(path.clone()))
+            .build();
+
+        pipeline.emit(&create_test_log_result()).unwrap();
+
+        let mut text = String::new();
+        GzDecoder::new(std::fs::File::open(&path).unwrap())
+            .read_to_string(&mut text)
+            .unwrap();
+        assert!(text.contains("<ip>") && text.contains("<pod>"));
+        assert!(!text.contains("192.168.1.100") && !text.contains("test-pod-123"));
+
+        let json: Value = serde_json::from_str(&text).unwrap();
+        assert_eq!(json["logid"], "test_logid_123");
+    }
+
+    #[test]
+    fn test_export_rows_and_csv() {
+        let mut log_result = create_test_log_result();
+        log_result.messages[0].values[0].value = "超时, 重试\n第二行".to_string();
+        log_result.messages[0].annotations = vec!["根因".to_string(), "已修复".to_string()];
+        let rows = ExportRow::from_result(&log_result);
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0].psm.as_deref(), Some("test.psm"));
+        assert_eq!(rows[0].annotations.as_deref(), Some("根因; 已修复"));
+        assert_eq!(rows[0].region, "us");
+
+        // 单个结果、合并结果和 NDJSON 都能读取
+        let single = serde_json::to_string(&log_result).unwrap();
+        assert_eq!(rows_from_json(&single).unwrap(), rows);
+        let multi = serde_json::json!({ "results": { "test_logid_123": log_result } }).to_string();
+        assert_eq!(rows_from_json(&multi).unwrap(), rows);
+        let ndjson = format!("{}\n{{\"logid\":\"bad\",\"error\":\"失败\"}}\n{}\n", single, single);
+        assert_eq!(rows_from_json(&ndjson).unwrap().len(), 2);
+        assert!(rows_from_json("not json").is_err());
+
+        let mut csv = Vec::new();
+        write_csv(&rows, &mut csv).unwrap();
+        let csv = String::from_utf8(csv).unwrap();
+        assert!(csv.starts_with("logid,region,timestamp,level,psm,pod,message,annotations\n"));
+        assert!(csv.contains("test_logid_123,us,,INFO,test.psm,test-pod-123,\"超时, 重试\n第二行\",根因; 已修复\n"));
+
+        #[cfg(feature = "parquet")]
+        {
+            let mut parquet = Vec::new();
+            write_parquet(&rows, &mut parquet).unwrap();
+            assert!(parquet.starts_with(b"PAR1"));
+        }
+    }
+
+    #[test]
+    fn test_result_diff_groups_by_psm() {
+        let message = |psm: &str, location: Option<&str>, text: &str| DiffMessage {
+            psm: Some(psm.to_string()),
+            location: location.map(str::to_string),
+            level: Some("INFO".to_string()),
+            time: None,
+            message: text.to_string(),
+        };
+        let a = vec![
+            message("pay.api", Some("handler.go:10"), "start"),
+            message("pay.api", Some("handler.go:20"), "status=500"),
+            message("pay.api", None, "retrying"),
+            message("risk.api", Some("check.go:5"), "ok"),
+        ];
+        let b = vec![
+            message("pay.api", Some("handler.go:10"), "start"),
+            message("pay.api", Some("handler.go:20"), "status=200"),
+            message("risk.api", Some("check.go:5"), "ok"),
+            message("risk.api", Some("check.go:9"), "cache hit"),
+        ];
+
+        let diff = ResultDiff::compare("failed.json", &a, "ok.json", &b);
+        assert_eq!(diff.psms.len(), 2);
+        let pay = &diff.psms[0];
+        assert_eq!(pay.psm, "pay.api");
+        assert_eq!(pay.unchanged, 1);
+        assert_eq!(pay.only_in_a, vec![a[2].clone()]);
+        assert_eq!(pay.changed.len(), 1);
+        assert_eq!(pay.changed[0].b.message, "status=200");
+        assert_eq!(diff.psms[1].only_in_b, vec![b[3].clone()]);
+        assert!(diff.format_text().contains("~ A [INFO] (handler.go:20) status=500"));
+
+        // 从保存的结果读取时与直接提取的消息一致
+        let log_result = create_test_log_result();
+        let saved = serde_json::to_string(&log_result).unwrap();
+        let messages = diff_messages_from_json(&saved).unwrap();
+        assert_eq!(messages, DiffMessage::from_result(&log_result));
+        assert!(ResultDiff::compare("a", &messages, "b", &messages).is_empty());
+    }
 }
@@ -0,0 +1,206 @@
+//! 输出管道模块
+//!
+//! 将格式化、脱敏、压缩和输出目标组合为一条管道，嵌入方可以构建与命令行相同的输出行为：
+//!
+//! ```no_run
+//! use logid::output::{Compression, OutputConfig, OutputPipelineBuilder, OutputSink};
+//! use regex::Regex;
+//!
+//! # fn run(result: &logid::log_query::DetailedLogResult) -> Result<(), logid::error::LogidError> {
+//! let pipeline = OutputPipelineBuilder::new()
+//!     .config(OutputConfig::new())
+//!     .redact(Regex::new(r"\d{11}").unwrap(), "<phone>")
+//!     .compression(Compression::Gzip)
+//!     .sink(OutputSink::File("result.json.gz".into()))
+//!     .build();
+//! pipeline.emit(result)?;
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::error::LogidError;
+use crate::log_query::{DetailedLogResult, MultiLogResult};
+use crate::output::format::OutputConfig;
+use crate::output::formatter::OutputFormatter;
+use crate::output::utils::create_parent_dirs;
+use regex::Regex;
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+/// 脱敏函数
+type RedactFn = Box<dyn Fn(&str) -> String + Send + Sync>;
+
+/// 压缩方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Compression {
+    /// 不压缩
+    #[default]
+    None,
+    /// gzip 压缩
+    Gzip,
+}
+
+/// 输出目标
+pub enum OutputSink {
+    /// 标准输出，未压缩时在末尾追加换行
+    Stdout,
+    /// 文件，父目录不存在时自动创建
+    File(PathBuf),
+    /// 任意写入器
+    Writer(Mutex<Box<dyn Write + Send>>),
+}
+
+impl OutputSink {
+    /// 使用任意写入器作为输出目标
+    pub fn writer(writer: impl Write + Send + 'static) -> Self {
+        Self::Writer(Mutex::new(Box::new(writer)))
+    }
+
+    fn write(&self, content: &[u8], newline: bool) -> Result<(), LogidError> {
+        match self {
+            Self::Stdout => {
+                let mut stdout = std::io::stdout().lock();
+                stdout.write_all(content)?;
+                if newline {
+                    stdout.write_all(b"\n")?;
+                }
+                stdout.flush()?;
+            }
+            Self::File(path) => {
+                create_parent_dirs(&path.to_string_lossy())?;
+                std::fs::write(path, content)?;
+                conditional_info!("输出已写入文件: {}", path.display());
+            }
+            Self::Writer(writer) => {
+                let mut writer = writer
+                    .lock()
+                    .map_err(|_| LogidError::InternalError("输出写入器锁已损坏".to_string()))?;
+                writer.write_all(content)?;
+                writer.flush()?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// 输出管道：格式化 → 脱敏 → 压缩 → 输出目标
+pub struct OutputPipeline {
+    formatter: OutputFormatter,
+    redactors: Vec<RedactFn>,
+    compression: Compression,
+    sinks: Vec<OutputSink>,
+}
+
+impl OutputPipeline {
+    /// 渲染单个结果，返回经过脱敏和压缩后的字节
+    pub fn render(&self, log_details: &DetailedLogResult) -> Result<Vec<u8>, LogidError> {
+        let text = self.formatter.render(log_details)?;
+        self.finish(text)
+    }
+
+    /// 渲染多个 logid 的合并结果，返回经过脱敏和压缩后的字节
+    pub fn render_multi(&self, multi: &MultiLogResult) -> Result<Vec<u8>, LogidError> {
+        let text = self.formatter.render_multi(multi)?;
+        self.finish(text)
+    }
+
+    /// 渲染单个结果并写入所有输出目标
+    pub fn emit(&self, log_details: &DetailedLogResult) -> Result<(), LogidError> {
+        let content = self.render(log_details)?;
+        self.write(&content)
+    }
+
+    /// 渲染合并结果并写入所有输出目标
+    pub fn emit_multi(&self, multi: &MultiLogResult) -> Result<(), LogidError> {
+        let content = self.render_multi(multi)?;
+        self.write(&content)
+    }
+
+    /// 对格式化文本依次脱敏并压缩
+    fn finish(&self, text: String) -> Result<Vec<u8>, LogidError> {
+        let text = self
+            .redactors
+            .iter()
+            .fold(text, |text, redact| redact(&text));
+
+        match self.compression {
+            Compression::None => Ok(text.into_bytes()),
+            Compression::Gzip => {
+                let mut encoder =
+                    flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+                encoder.write_all(text.as_bytes())?;
+                Ok(encoder.finish()?)
+            }
+        }
+    }
+
+    fn write(&self, content: &[u8]) -> Result<(), LogidError> {
+        let newline = self.compression == Compression::None;
+        for sink in &self.sinks {
+            sink.write(content, newline)?;
+        }
+        Ok(())
+    }
+}
+
+/// 输出管道构建器
+///
+/// 未指定输出目标时输出到标准输出。
+#[derive(Default)]
+pub struct OutputPipelineBuilder {
+    config: OutputConfig,
+    redactors: Vec<RedactFn>,
+    compression: Compression,
+    sinks: Vec<OutputSink>,
+}
+
+impl OutputPipelineBuilder {
+    /// 创建构建器
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 设置格式化配置
+    pub fn config(mut self, config: OutputConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// 将匹配正则表达式的内容替换为 `replacement`
+    pub fn redact(self, pattern: Regex, replacement: &str) -> Self {
+        let replacement = replacement.to_string();
+        self.redact_with(move |text| pattern.replace_all(text, replacement.as_str()).into_owned())
+    }
+
+    /// 添加自定义脱敏函数，按添加顺序执行
+    pub fn redact_with(mut self, redact: impl Fn(&str) -> String + Send + Sync + 'static) -> Self {
+        self.redactors.push(Box::new(redact));
+        self
+    }
+
+    /// 设置压缩方式
+    pub fn compression(mut self, compression: Compression) -> Self {
+        self.compression = compression;
+        self
+    }
+
+    /// 添加输出目标
+    pub fn sink(mut self, sink: OutputSink) -> Self {
+        self.sinks.push(sink);
+        self
+    }
+
+    /// 构建输出管道
+    pub fn build(mut self) -> OutputPipeline {
+        if self.sinks.is_empty() {
+            self.sinks.push(OutputSink::Stdout);
+        }
+        OutputPipeline {
+            formatter: OutputFormatter::new(self.config),
+            redactors: self.redactors,
+            compression: self.compression,
+            sinks: self.sinks,
+        }
+    }
+}
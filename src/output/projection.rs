@@ -0,0 +1,74 @@
+//! 字段投影模块
+//!
+//! 按点分隔的路径（如 `group.psm`、`values.value`）从 JSON 值中挑选字段，
+//! 保留原有的嵌套结构；路径经过数组时对每个元素分别投影。
+
+use serde_json::{Map, Value};
+
+/// 字段投影
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FieldProjection {
+    paths: Vec<Vec<String>>,
+}
+
+impl FieldProjection {
+    /// 从字段路径列表创建投影，忽略空路径
+    pub fn new<S: AsRef<str>>(fields: &[S]) -> Self {
+        let paths = fields
+            .iter()
+            .map(|field| field.as_ref().trim())
+            .filter(|field| !field.is_empty())
+            .map(|field| field.split('.').map(str::to_string).collect())
+            .collect();
+        Self { paths }
+    }
+
+    /// 是否没有任何字段（不做投影）
+    pub fn is_empty(&self) -> bool {
+        self.paths.is_empty()
+    }
+
+    /// 投影单个值，不存在的字段被忽略
+    pub fn apply(&self, value: &Value) -> Value {
+        let mut projected = Value::Object(Map::new());
+        for path in &self.paths {
+            let segments: Vec<&str> = path.iter().map(String::as_str).collect();
+            project_path(value, &segments, &mut projected);
+        }
+        projected
+    }
+}
+
+/// 将 `source` 中 `path` 指向的内容合并到 `target`
+fn project_path(source: &Value, path: &[&str], target: &mut Value) {
+    let Some((key, rest)) = path.split_first() else {
+        *target = source.clone();
+        return;
+    };
+
+    match source {
+        Value::Object(fields) => {
+            let Some(child) = fields.get(*key) else {
+                return;
+            };
+            if !target.is_object() {
+                *target = Value::Object(Map::new());
+            }
+            if let Value::Object(target_fields) = target {
+                let entry = target_fields.entry(key.to_string()).or_insert(Value::Null);
+                project_path(child, rest, entry);
+            }
+        }
+        Value::Array(items) => {
+            if !matches!(target, Value::Array(existing) if existing.len() == items.len()) {
+                *target = Value::Array(vec![Value::Null; items.len()]);
+            }
+            if let Value::Array(target_items) = target {
+                for (item, target_item) in items.iter().zip(target_items.iter_mut()) {
+                    project_path(item, path, target_item);
+                }
+            }
+        }
+        _ => {}
+    }
+}
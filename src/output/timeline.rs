@@ -0,0 +1,91 @@
+//! 多 logid 时间线输出模块
+
+use crate::log_query::{parse_message_time, DetailedLogResult, ExtractedLogMessage};
+use chrono::{DateTime, Utc};
+
+/// 时间线中各 logid 使用的 ANSI 颜色
+const COLORS: &[&str] = &["36", "33", "35", "32", "34", "31"];
+
+/// 时间线条目
+#[derive(Debug, Clone)]
+pub struct TimelineEntry<'a> {
+    /// 所属 logid 在输入中的序号
+    pub source: usize,
+    /// 所属 logid
+    pub logid: &'a str,
+    /// 解析后的时间
+    pub time: Option<DateTime<Utc>>,
+    /// 日志消息
+    pub message: &'a ExtractedLogMessage,
+}
+
+/// 将多个查询结果的消息合并为按时间排序的时间线
+///
+/// 无法解析时间的消息排在最后，并保持各自原有的顺序。
+pub fn build_timeline(results: &[DetailedLogResult]) -> Vec<TimelineEntry<'_>> {
+    let mut entries: Vec<TimelineEntry> = results
+        .iter()
+        .enumerate()
+        .flat_map(|(source, result)| {
+            result.messages.iter().map(move |message| TimelineEntry {
+                source,
+                logid: &result.logid,
+                time: message.time.as_deref().and_then(parse_message_time),
+                message,
+            })
+        })
+        .collect();
+
+    // sort_by_key 是稳定排序，时间相同的消息保持原有顺序
+    entries.sort_by_key(|entry| (entry.time.is_none(), entry.time));
+    entries
+}
+
+/// 格式化时间线
+///
+/// 每个 logid 使用不同的颜色标记，`color` 为 false 时输出纯文本。
+pub fn format_timeline(results: &[DetailedLogResult], color: bool) -> String {
+    let paint = |source: usize, text: &str| {
+        if color {
+            format!("\x1b[{}m{}\x1b[0m", COLORS[source % COLORS.len()], text)
+        } else {
+            text.to_string()
+        }
+    };
+
+    let mut lines = Vec::new();
+    for (source, result) in results.iter().enumerate() {
+        lines.push(format!(
+            "{} {} ({} 条消息)",
+            paint(source, &format!("[{}]", source + 1)),
+            result.logid,
+            result.messages.len()
+        ));
+    }
+    lines.push(String::new());
+
+    for entry in build_timeline(results) {
+        let time = entry
+            .time
+            .map(|t| t.format("%Y-%m-%d %H:%M:%S%.3f").to_string())
+            .unwrap_or_else(|| "-".repeat(23));
+        let text = entry
+            .message
+            .values
+            .iter()
+            .map(|v| v.value.split_whitespace().collect::<Vec<_>>().join(" "))
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        lines.push(format!(
+            "{}  {} {:<5} {} | {}",
+            time,
+            paint(entry.source, &format!("[{}]", entry.source + 1)),
+            entry.message.level.as_deref().unwrap_or("-"),
+            entry.message.group.psm.as_deref().unwrap_or("-"),
+            text
+        ));
+    }
+
+    lines.join("\n")
+}
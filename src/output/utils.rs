@@ -1,6 +1,5 @@
 //! 输出便捷函数模块
 
-use crate::conditional_info;
 use crate::error::LogidError;
 use crate::log_query::DetailedLogResult;
 use crate::output::format::OutputConfig;
@@ -15,12 +14,15 @@ pub fn print_json_output(log_details: &DetailedLogResult) -> Result<(), LogidErr
 }
 
 /// 便捷函数：输出到文件
-#[allow(dead_code)]
+///
+/// 父目录不存在时会自动创建。
 pub fn write_to_file(
     log_details: &DetailedLogResult,
     file_path: &str,
     config: OutputConfig,
 ) -> Result<(), LogidError> {
+    create_parent_dirs(file_path)?;
+
     let mut file = std::fs::File::create(file_path).map_err(LogidError::IoError)?;
 
     let formatter = OutputFormatter::new(config);
@@ -29,3 +31,24 @@ pub fn write_to_file(
     conditional_info!("日志结果已写入文件: {}", file_path);
     Ok(())
 }
+
+/// 便捷函数：将已格式化的文本写入文件
+///
+/// 父目录不存在时会自动创建。
+pub fn write_text_to_file(content: &str, file_path: &str) -> Result<(), LogidError> {
+    create_parent_dirs(file_path)?;
+    std::fs::write(file_path, content).map_err(LogidError::IoError)?;
+
+    conditional_info!("输出已写入文件: {}", file_path);
+    Ok(())
+}
+
+/// 创建文件的父目录
+pub(super) fn create_parent_dirs(file_path: &str) -> Result<(), LogidError> {
+    if let Some(parent) = std::path::Path::new(file_path).parent() {
+        if !parent.as_os_str().is_empty() {
+            std::fs::create_dir_all(parent).map_err(LogidError::IoError)?;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,100 @@
+//! 过滤表达式求值模块
+
+use crate::log_query::ExtractedLogMessage;
+use regex::Regex;
+
+/// 过滤表达式语法树
+#[derive(Debug, Clone)]
+pub enum Expr {
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+    Not(Box<Expr>),
+    Compare(Comparison),
+}
+
+/// 单个字段比较
+#[derive(Debug, Clone)]
+pub struct Comparison {
+    /// 字段名
+    pub field: String,
+    /// 匹配方式
+    pub matcher: Matcher,
+    /// 是否取反（`!=`、`!~`）
+    pub negated: bool,
+}
+
+/// 字段值的匹配方式
+#[derive(Debug, Clone)]
+pub enum Matcher {
+    /// 完全相等（`==`）
+    Equals(String),
+    /// 正则匹配（`=~`）
+    Regex(Regex),
+}
+
+impl Expr {
+    /// 对单条日志消息求值
+    pub fn evaluate(&self, message: &ExtractedLogMessage) -> bool {
+        match self {
+            Expr::And(left, right) => left.evaluate(message) && right.evaluate(message),
+            Expr::Or(left, right) => left.evaluate(message) || right.evaluate(message),
+            Expr::Not(inner) => !inner.evaluate(message),
+            Expr::Compare(comparison) => comparison.evaluate(message),
+        }
+    }
+}
+
+impl Comparison {
+    /// 字段不存在时视为不匹配（取反运算则视为匹配）
+    fn evaluate(&self, message: &ExtractedLogMessage) -> bool {
+        let matched = field_value(message, &self.field).is_some_and(|value| match &self.matcher {
+            Matcher::Equals(expected) => value == *expected,
+            Matcher::Regex(regex) => regex.is_match(&value),
+        });
+        matched != self.negated
+    }
+}
+
+/// 获取消息中指定字段的值
+///
+/// 依次查找内置字段、消息中的键值（如 `_msg`）和分组的扩展字段。
+fn field_value(message: &ExtractedLogMessage, field: &str) -> Option<String> {
+    let group = &message.group;
+    let builtin = match field {
+        "id" => Some(message.id.clone()),
+        "level" => message.level.clone(),
+        "location" => message.location.clone(),
+        "time" => message.time.clone(),
+        "region" => message.region.clone(),
+        "psm" => group.psm.clone(),
+        "pod" | "pod_name" => group.pod_name.clone(),
+        "ip" | "ipv4" => group.ipv4.clone(),
+        "env" => group.env.clone(),
+        "vregion" => group.vregion.clone(),
+        "idc" => group.idc.clone(),
+        "message" | "msg" => Some(
+            message
+                .values
+                .iter()
+                .map(|v| v.value.as_str())
+                .collect::<Vec<_>>()
+                .join(" "),
+        ),
+        _ => None,
+    };
+
+    builtin
+        .or_else(|| {
+            message
+                .values
+                .iter()
+                .find(|v| v.key == field)
+                .map(|v| v.value.clone())
+        })
+        .or_else(|| {
+            group.extra.get(field).map(|value| match value {
+                serde_json::Value::String(s) => s.clone(),
+                other => other.to_string(),
+            })
+        })
+}
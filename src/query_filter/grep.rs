@@ -0,0 +1,36 @@
+//! 按消息内容的正则匹配过滤
+
+use crate::error::LogidError;
+use crate::log_query::{DetailedLogResult, ExtractedLogMessage};
+use regex::Regex;
+
+/// `--grep` 过滤条件
+///
+/// 对消息的 `ExtractedValue::value`（过滤规则处理后的内容）进行正则匹配，
+/// 任一值匹配即视为消息匹配；`invert` 为真时只保留不匹配的消息。
+#[derive(Debug, Clone)]
+pub struct GrepFilter {
+    regex: Regex,
+    invert: bool,
+}
+
+impl GrepFilter {
+    /// 编译正则表达式
+    pub fn new(pattern: &str, invert: bool) -> Result<Self, LogidError> {
+        Ok(Self {
+            regex: Regex::new(pattern)?,
+            invert,
+        })
+    }
+
+    /// 判断消息是否应被保留
+    pub fn matches(&self, message: &ExtractedLogMessage) -> bool {
+        let found = message.values.iter().any(|value| self.regex.is_match(&value.value));
+        found != self.invert
+    }
+
+    /// 仅保留满足条件的消息
+    pub fn apply(&self, log_details: &mut DetailedLogResult) {
+        log_details.messages.retain(|message| self.matches(message));
+    }
+}
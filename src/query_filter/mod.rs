@@ -0,0 +1,118 @@
+//! 查询结果后置过滤模块
+//!
+//! 提供简单的布尔表达式，对提取后的日志消息进行二次筛选，例如：
+//!
+//! ```text
+//! level == "ERROR" && psm =~ "pay"
+//! !(pod =~ "canary") || msg =~ "timeout"
+//! ```
+//!
+//! 可用字段：`id`、`level`、`location`、`time`、`region`、`psm`、`pod`、`ip`、
+//! `env`、`vregion`、`idc`、`message`，以及消息中的任意键（如 `_msg`）和分组扩展字段。
+//!
+//! 只需要按消息内容查找时，可以使用更简单的 [`GrepFilter`]。
+
+mod expr;
+mod grep;
+mod parser;
+
+pub use expr::{Comparison, Expr, Matcher};
+pub use grep::GrepFilter;
+pub use parser::parse;
+
+use crate::error::LogidError;
+use crate::log_query::{DetailedLogResult, ExtractedLogMessage};
+
+/// 已解析的 `--where` 过滤条件
+#[derive(Debug, Clone)]
+pub struct WhereFilter {
+    expr: Expr,
+}
+
+impl WhereFilter {
+    /// 解析过滤表达式
+    pub fn parse(input: &str) -> Result<Self, LogidError> {
+        Ok(Self { expr: parse(input)? })
+    }
+
+    /// 判断消息是否满足过滤条件
+    pub fn matches(&self, message: &ExtractedLogMessage) -> bool {
+        self.expr.evaluate(message)
+    }
+
+    /// 仅保留满足过滤条件的消息
+    pub fn apply(&self, log_details: &mut DetailedLogResult) {
+        log_details.messages.retain(|message| self.matches(message));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message(level: &str, psm: &str, msg: &str) -> ExtractedLogMessage {
+        let mut message = crate::test_support::message(psm, level, msg);
+        message.id = "1".to_string();
+        message.group.extra = [("cluster".to_string(), serde_json::json!("default"))].into();
+        message
+    }
+
+    #[test]
+    fn test_where_filter_boolean_logic() {
+        let filter = WhereFilter::parse(r#"level == "ERROR" && psm =~ "pay""#).unwrap();
+        assert!(filter.matches(&message("ERROR", "payment.api", "boom")));
+        assert!(!filter.matches(&message("INFO", "payment.api", "ok")));
+        assert!(!filter.matches(&message("ERROR", "user.api", "boom")));
+
+        let filter = WhereFilter::parse(r#"!(level == INFO) || _msg =~ "(?i)timeout""#).unwrap();
+        assert!(filter.matches(&message("ERROR", "a", "ok")));
+        assert!(filter.matches(&message("INFO", "a", "Request TIMEOUT")));
+        assert!(!filter.matches(&message("INFO", "a", "ok")));
+    }
+
+    #[test]
+    fn test_where_filter_missing_and_extra_fields() {
+        let msg = message("ERROR", "a", "x");
+        assert!(WhereFilter::parse(r#"cluster == "default""#).unwrap().matches(&msg));
+        assert!(!WhereFilter::parse(r#"stage == "canary""#).unwrap().matches(&msg));
+        assert!(WhereFilter::parse(r#"stage != "canary""#).unwrap().matches(&msg));
+    }
+
+    #[test]
+    fn test_where_filter_rejects_invalid_expressions() {
+        for input in [
+            "level ==",
+            r#"level = "ERROR""#,
+            r#"(level == "ERROR""#,
+            r#"psm =~ "(""#,
+            r#"level == "ERROR" extra"#,
+            "",
+        ] {
+            assert!(
+                matches!(WhereFilter::parse(input), Err(LogidError::InvalidFilterExpression(_))),
+                "应拒绝: {}",
+                input
+            );
+        }
+    }
+
+    #[test]
+    fn test_grep_filter_and_invert() {
+        let mut result = crate::log_query::DetailedLogResult::merge_regions("logid", Vec::new());
+        result.messages = vec![
+            message("ERROR", "a", "request timeout after 3s"),
+            message("INFO", "a", "ok"),
+        ];
+
+        let mut matched = result.clone();
+        GrepFilter::new(r"time(out)?\b", false).unwrap().apply(&mut matched);
+        assert_eq!(matched.messages.len(), 1);
+        assert_eq!(matched.messages[0].values[0].value, "request timeout after 3s");
+
+        GrepFilter::new("timeout", true).unwrap().apply(&mut result);
+        assert_eq!(result.messages.len(), 1);
+        assert_eq!(result.messages[0].values[0].value, "ok");
+
+        assert!(GrepFilter::new("(unclosed", false).is_err());
+    }
+}
@@ -0,0 +1,228 @@
+//! 过滤表达式解析模块
+//!
+//! 语法（优先级从低到高）：
+//!
+//! ```text
+//! expr       := and ("||" and)*
+//! and        := unary ("&&" unary)*
+//! unary      := "!" unary | primary
+//! primary    := "(" expr ")" | comparison
+//! comparison := field ("==" | "!=" | "=~" | "!~") literal
+//! ```
+//!
+//! 字段名由字母、数字、`_`、`.`、`-` 组成，字面量为双引号字符串或不含空白的裸词。
+
+use crate::error::LogidError;
+use crate::query_filter::expr::{Comparison, Expr, Matcher};
+use regex::Regex;
+
+/// 词法单元
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Ident(String),
+    Str(String),
+    Eq,
+    Ne,
+    Match,
+    NotMatch,
+    And,
+    Or,
+    Not,
+    LParen,
+    RParen,
+}
+
+/// 解析过滤表达式
+pub fn parse(input: &str) -> Result<Expr, LogidError> {
+    let tokens = tokenize(input)?;
+    let mut parser = Parser { tokens, pos: 0 };
+    let expr = parser.parse_or()?;
+
+    if let Some(token) = parser.peek() {
+        return Err(invalid(format!("多余的内容: {:?}", token)));
+    }
+
+    Ok(expr)
+}
+
+fn invalid(message: impl Into<String>) -> LogidError {
+    LogidError::InvalidFilterExpression(message.into())
+}
+
+fn tokenize(input: &str) -> Result<Vec<Token>, LogidError> {
+    let mut tokens = Vec::new();
+    let mut chars = input.chars().peekable();
+
+    while let Some(&c) = chars.peek() {
+        match c {
+            c if c.is_whitespace() => {
+                chars.next();
+            }
+            '(' => {
+                chars.next();
+                tokens.push(Token::LParen);
+            }
+            ')' => {
+                chars.next();
+                tokens.push(Token::RParen);
+            }
+            '&' | '|' => {
+                chars.next();
+                if chars.next() != Some(c) {
+                    return Err(invalid(format!("无效的运算符 '{}'，应为 '{}{}'", c, c, c)));
+                }
+                tokens.push(if c == '&' { Token::And } else { Token::Or });
+            }
+            '=' => {
+                chars.next();
+                match chars.next() {
+                    Some('=') => tokens.push(Token::Eq),
+                    Some('~') => tokens.push(Token::Match),
+                    _ => return Err(invalid("无效的运算符 '='，应为 '==' 或 '=~'")),
+                }
+            }
+            '!' => {
+                chars.next();
+                match chars.peek() {
+                    Some('=') => {
+                        chars.next();
+                        tokens.push(Token::Ne);
+                    }
+                    Some('~') => {
+                        chars.next();
+                        tokens.push(Token::NotMatch);
+                    }
+                    _ => tokens.push(Token::Not),
+                }
+            }
+            '"' => {
+                chars.next();
+                let mut value = String::new();
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => match chars.next() {
+                            Some('n') => value.push('\n'),
+                            Some('t') => value.push('\t'),
+                            Some(escaped) => value.push(escaped),
+                            None => return Err(invalid("字符串未结束")),
+                        },
+                        Some(ch) => value.push(ch),
+                        None => return Err(invalid("字符串未结束")),
+                    }
+                }
+                tokens.push(Token::Str(value));
+            }
+            _ => {
+                let mut word = String::new();
+                while let Some(&ch) = chars.peek() {
+                    if ch.is_alphanumeric() || matches!(ch, '_' | '.' | '-') {
+                        word.push(ch);
+                        chars.next();
+                    } else {
+                        break;
+                    }
+                }
+                if word.is_empty() {
+                    return Err(invalid(format!("无法识别的字符 '{}'", c)));
+                }
+                tokens.push(Token::Ident(word));
+            }
+        }
+    }
+
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    fn parse_or(&mut self) -> Result<Expr, LogidError> {
+        let mut expr = self.parse_and()?;
+        while self.peek() == Some(&Token::Or) {
+            self.next();
+            expr = Expr::Or(Box::new(expr), Box::new(self.parse_and()?));
+        }
+        Ok(expr)
+    }
+
+    fn parse_and(&mut self) -> Result<Expr, LogidError> {
+        let mut expr = self.parse_unary()?;
+        while self.peek() == Some(&Token::And) {
+            self.next();
+            expr = Expr::And(Box::new(expr), Box::new(self.parse_unary()?));
+        }
+        Ok(expr)
+    }
+
+    fn parse_unary(&mut self) -> Result<Expr, LogidError> {
+        if self.peek() == Some(&Token::Not) {
+            self.next();
+            return Ok(Expr::Not(Box::new(self.parse_unary()?)));
+        }
+        self.parse_primary()
+    }
+
+    fn parse_primary(&mut self) -> Result<Expr, LogidError> {
+        match self.next() {
+            Some(Token::LParen) => {
+                let expr = self.parse_or()?;
+                match self.next() {
+                    Some(Token::RParen) => Ok(expr),
+                    _ => Err(invalid("缺少右括号 ')'")),
+                }
+            }
+            Some(Token::Ident(field)) => self.parse_comparison(field),
+            Some(token) => Err(invalid(format!("期望字段名，实际为 {:?}", token))),
+            None => Err(invalid("表达式不完整")),
+        }
+    }
+
+    fn parse_comparison(&mut self, field: String) -> Result<Expr, LogidError> {
+        let op = self
+            .next()
+            .ok_or_else(|| invalid(format!("字段 {} 后缺少比较运算符", field)))?;
+
+        let value = match self.next() {
+            Some(Token::Str(value)) | Some(Token::Ident(value)) => value,
+            _ => return Err(invalid(format!("字段 {} 的比较缺少字面量", field))),
+        };
+
+        let compile = |pattern: &str| {
+            Regex::new(pattern)
+                .map_err(|e| invalid(format!("无效的正则表达式 '{}': {}", pattern, e)))
+        };
+
+        let (matcher, negated) = match op {
+            Token::Eq => (Matcher::Equals(value), false),
+            Token::Ne => (Matcher::Equals(value), true),
+            Token::Match => (Matcher::Regex(compile(&value)?), false),
+            Token::NotMatch => (Matcher::Regex(compile(&value)?), true),
+            token => {
+                return Err(invalid(format!(
+                    "字段 {} 后期望比较运算符，实际为 {:?}",
+                    field, token
+                )))
+            }
+        };
+
+        Ok(Expr::Compare(Comparison {
+            field,
+            matcher,
+            negated,
+        }))
+    }
+}
@@ -0,0 +1,106 @@
+//! 请求重试模块
+//!
+//! 对认证和日志查询请求中的瞬时失败（超时、连接失败、5xx、429）按带随机抖动的指数退避重试。
+
+use reqwest::{RequestBuilder, Response, StatusCode};
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::time::Duration;
+
+/// 命令行默认的重试次数
+pub const DEFAULT_RETRIES: u32 = 2;
+
+/// 重试策略
+///
+/// 第 n 次重试前等待 `base_delay * 2^n`（不超过 `max_delay`），
+/// 实际等待时间在该值的一半到全部之间随机抖动，避免多个请求同时重试。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// 最大重试次数，0 表示不重试
+    pub max_retries: u32,
+    /// 首次重试前的等待时间
+    pub base_delay: Duration,
+    /// 单次等待时间上限
+    pub max_delay: Duration,
+}
+
+impl Default for RetryPolicy {
+    /// 默认不重试，保持库调用方原有的行为
+    fn default() -> Self {
+        Self::new(0)
+    }
+}
+
+impl RetryPolicy {
+    /// 创建指定重试次数的策略，退避从 500ms 开始，最长 8s
+    pub fn new(max_retries: u32) -> Self {
+        Self {
+            max_retries,
+            base_delay: Duration::from_millis(500),
+            max_delay: Duration::from_secs(8),
+        }
+    }
+
+    /// 第 `attempt` 次重试（从 0 开始）前的等待时间
+    pub fn delay(&self, attempt: u32) -> Duration {
+        let exponential = self
+            .base_delay
+            .saturating_mul(2u32.saturating_pow(attempt))
+            .min(self.max_delay);
+        let half = exponential / 2;
+        let jitter_range = (exponential - half).as_millis() as u64;
+        let jitter = if jitter_range == 0 {
+            0
+        } else {
+            random_u64() % (jitter_range + 1)
+        };
+        half + Duration::from_millis(jitter)
+    }
+
+    /// 发送请求，遇到瞬时失败时按策略重试
+    ///
+    /// 重试次数用尽后返回最后一次的响应或错误，非成功状态码仍由调用方处理。
+    /// 请求体无法复制（流式请求体）时只发送一次。
+    pub async fn send(&self, label: &str, request: RequestBuilder) -> Result<Response, reqwest::Error> {
+        let mut attempt = 0;
+        loop {
+            let Some(current) = request.try_clone() else {
+                return request.send().await;
+            };
+            let reason = match current.send().await {
+                Ok(response) if attempt < self.max_retries && is_retryable_status(response.status()) => {
+                    format!("HTTP {}", response.status())
+                }
+                Err(e) if attempt < self.max_retries && is_retryable_error(&e) => e.to_string(),
+                outcome => return outcome,
+            };
+
+            let delay = self.delay(attempt);
+            attempt += 1;
+            conditional_info!(
+                "{} 请求失败（{}），{:?} 后进行第 {}/{} 次重试",
+                label,
+                reason,
+                delay,
+                attempt,
+                self.max_retries
+            );
+            tokio::time::sleep(delay).await;
+        }
+    }
+}
+
+/// 服务端临时不可用或限流时可以重试
+fn is_retryable_status(status: StatusCode) -> bool {
+    status.is_server_error() || status == StatusCode::TOO_MANY_REQUESTS
+}
+
+/// 超时和连接失败可以重试，其余错误（如 URL 无效）重试也不会成功
+fn is_retryable_error(error: &reqwest::Error) -> bool {
+    error.is_timeout() || error.is_connect() || error.is_request()
+}
+
+/// 不引入额外依赖的随机数，只用于退避抖动
+fn random_u64() -> u64 {
+    RandomState::new().build_hasher().finish()
+}
@@ -0,0 +1,120 @@
+//! 消息批注存储模块
+
+use crate::config::user_config_dir;
+use crate::error::LogidError;
+use crate::log_query::ExtractedLogMessage;
+use serde::{Deserialize, Serialize};
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// 批注文件名
+const ANNOTATIONS_FILE_NAME: &str = "annotations.jsonl";
+
+/// 消息批注
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Annotation {
+    /// 查询标识（当前为 logid）
+    pub query_id: String,
+    /// 消息 ID（对应 `ExtractedLogMessage::id`）
+    pub message_id: String,
+    /// 批注内容
+    pub note: String,
+    /// 创建时间
+    pub created_at: String,
+}
+
+impl Annotation {
+    /// 创建新的批注
+    pub fn new(query_id: &str, message_id: &str, note: &str) -> Self {
+        Self {
+            query_id: query_id.to_string(),
+            message_id: message_id.to_string(),
+            note: note.to_string(),
+            created_at: chrono::Utc::now().to_rfc3339(),
+        }
+    }
+}
+
+/// 批注存储
+///
+/// 以 JSON Lines 格式保存在 `~/.config/logid/annotations.jsonl`。
+#[derive(Debug, Clone)]
+pub struct AnnotationStore {
+    path: PathBuf,
+}
+
+impl AnnotationStore {
+    /// 使用指定文件创建批注存储
+    pub fn new(path: PathBuf) -> Self {
+        Self { path }
+    }
+
+    /// 使用用户配置目录下的默认文件创建批注存储
+    pub fn open_default() -> Result<Self, LogidError> {
+        let dir = user_config_dir()
+            .ok_or_else(|| LogidError::InternalError("无法确定用户主目录".to_string()))?;
+        Ok(Self::new(dir.join(ANNOTATIONS_FILE_NAME)))
+    }
+
+    /// 存储文件路径
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// 追加一条批注
+    pub fn add(&self, annotation: &Annotation) -> Result<(), LogidError> {
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        writeln!(file, "{}", serde_json::to_string(annotation)?)?;
+        Ok(())
+    }
+
+    /// 读取全部批注
+    pub fn list(&self) -> Result<Vec<Annotation>, LogidError> {
+        if !self.path.exists() {
+            return Ok(Vec::new());
+        }
+
+        let content = std::fs::read_to_string(&self.path)?;
+        content
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| serde_json::from_str(line).map_err(LogidError::JsonParseError))
+            .collect()
+    }
+
+    /// 读取指定查询的批注
+    pub fn for_query(&self, query_id: &str) -> Result<Vec<Annotation>, LogidError> {
+        Ok(self
+            .list()?
+            .into_iter()
+            .filter(|a| a.query_id == query_id)
+            .collect())
+    }
+
+    /// 将批注附加到对应的消息上，返回附加的批注数量
+    pub fn apply(
+        &self,
+        query_id: &str,
+        messages: &mut [ExtractedLogMessage],
+    ) -> Result<usize, LogidError> {
+        let annotations = self.for_query(query_id)?;
+        let mut applied = 0;
+
+        for annotation in annotations {
+            if let Some(message) = messages.iter_mut().find(|m| m.id == annotation.message_id) {
+                message.annotations.push(annotation.note);
+                applied += 1;
+            }
+        }
+
+        Ok(applied)
+    }
+}
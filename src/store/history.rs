@@ -0,0 +1,137 @@
+//! 查询历史存储模块
+
+use crate::config::user_config_dir;
+use crate::error::LogidError;
+use crate::log_query::QueryContext;
+use serde::{Deserialize, Serialize};
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// 历史文件名
+const HISTORY_FILE_NAME: &str = "history.jsonl";
+
+/// 一次查询的历史记录
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    /// 查询时间
+    pub recorded_at: String,
+    /// 查询的日志 ID
+    pub logids: Vec<String>,
+    /// 查询区域
+    pub regions: Vec<String>,
+    /// PSM 服务列表
+    #[serde(default)]
+    pub psm_list: Vec<String>,
+    /// 返回的消息总数
+    pub result_count: usize,
+    /// 查询失败的 logid 数量
+    #[serde(default)]
+    pub failed: usize,
+    /// 完整的查询上下文，用于重新执行
+    pub context: QueryContext,
+}
+
+impl HistoryEntry {
+    /// 根据查询上下文创建历史记录
+    pub fn new(context: &QueryContext, result_count: usize, failed: usize) -> Self {
+        Self {
+            recorded_at: chrono::Utc::now().to_rfc3339(),
+            logids: context.logids.clone(),
+            regions: context.regions.clone(),
+            psm_list: context.psm_list.clone(),
+            result_count,
+            failed,
+            context: context.clone(),
+        }
+    }
+
+    /// 是否匹配关键字（logid、区域或 PSM 包含该关键字，不区分大小写）
+    pub fn matches(&self, keyword: &str) -> bool {
+        let keyword = keyword.to_lowercase();
+        self.logids
+            .iter()
+            .chain(&self.regions)
+            .chain(&self.psm_list)
+            .any(|value| value.to_lowercase().contains(&keyword))
+    }
+}
+
+/// 查询历史存储
+///
+/// 以 JSON Lines 格式追加保存在 `~/.config/logid/history.jsonl`，
+/// 记录编号为在文件中的行号（从 1 开始）。
+#[derive(Debug, Clone)]
+pub struct HistoryStore {
+    path: PathBuf,
+}
+
+impl HistoryStore {
+    /// 使用指定文件创建历史存储
+    pub fn new(path: PathBuf) -> Self {
+        Self { path }
+    }
+
+    /// 使用用户配置目录下的默认文件创建历史存储
+    pub fn open_default() -> Result<Self, LogidError> {
+        let dir = user_config_dir()
+            .ok_or_else(|| LogidError::InternalError("无法确定用户主目录".to_string()))?;
+        Ok(Self::new(dir.join(HISTORY_FILE_NAME)))
+    }
+
+    /// 存储文件路径
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// 追加一条历史记录
+    pub fn record(&self, entry: &HistoryEntry) -> Result<(), LogidError> {
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        writeln!(file, "{}", serde_json::to_string(entry)?)?;
+        Ok(())
+    }
+
+    /// 读取全部历史记录及其编号，按记录顺序排列
+    pub fn list(&self) -> Result<Vec<(usize, HistoryEntry)>, LogidError> {
+        if !self.path.exists() {
+            return Ok(Vec::new());
+        }
+
+        let content = std::fs::read_to_string(&self.path)?;
+        content
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .map(|(index, line)| {
+                serde_json::from_str(line)
+                    .map(|entry| (index + 1, entry))
+                    .map_err(LogidError::JsonParseError)
+            })
+            .collect()
+    }
+
+    /// 查找匹配关键字的历史记录
+    pub fn search(&self, keyword: &str) -> Result<Vec<(usize, HistoryEntry)>, LogidError> {
+        Ok(self
+            .list()?
+            .into_iter()
+            .filter(|(_, entry)| entry.matches(keyword))
+            .collect())
+    }
+
+    /// 按编号读取历史记录
+    pub fn get(&self, number: usize) -> Result<HistoryEntry, LogidError> {
+        self.list()?
+            .into_iter()
+            .find(|(index, _)| *index == number)
+            .map(|(_, entry)| entry)
+            .ok_or_else(|| LogidError::InternalError(format!("历史记录 #{} 不存在", number)))
+    }
+}
@@ -0,0 +1,133 @@
+//! 本地存储模块
+//!
+//! 管理保存在用户配置目录下的本地数据，例如消息批注、查询历史和待查询队列。
+//! 批注和历史以 JSON Lines 格式追加写入，队列以 JSON 文件整体保存，便于手工查看和迁移。
+
+mod annotation;
+mod history;
+mod queue;
+
+pub use annotation::{Annotation, AnnotationStore};
+pub use history::{HistoryEntry, HistoryStore};
+pub use queue::{QueueEntry, QueueStatus, QueueStore};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::log_query::ExtractedLogMessage;
+
+    fn message(id: &str) -> ExtractedLogMessage {
+        ExtractedLogMessage {
+            id: id.to_string(),
+            ..ExtractedLogMessage::default()
+        }
+    }
+
+    #[test]
+    fn test_annotation_store_roundtrip() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = AnnotationStore::new(dir.path().join("annotations.jsonl"));
+
+        store.add(&Annotation::new("logid-a", "1-1", "root cause")).unwrap();
+        store.add(&Annotation::new("logid-b", "2-1", "unrelated")).unwrap();
+        store.add(&Annotation::new("logid-a", "1-2", "retry here")).unwrap();
+
+        assert_eq!(store.list().unwrap().len(), 3);
+        let notes = store.for_query("logid-a").unwrap();
+        assert_eq!(notes.len(), 2);
+        assert_eq!(notes[0].note, "root cause");
+    }
+
+    #[test]
+    fn test_apply_annotations_to_messages() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = AnnotationStore::new(dir.path().join("annotations.jsonl"));
+        store.add(&Annotation::new("logid-a", "1-1", "root cause")).unwrap();
+
+        let mut messages = vec![message("1-1"), message("1-2")];
+        let applied = store.apply("logid-a", &mut messages).unwrap();
+
+        assert_eq!(applied, 1);
+        assert_eq!(messages[0].annotations, vec!["root cause".to_string()]);
+        assert!(messages[1].annotations.is_empty());
+    }
+
+    #[test]
+    fn test_queue_enqueue_and_clear() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = QueueStore::new(dir.path().join("queue.json"));
+
+        let first = store.enqueue("logid-a", "us", &[]).unwrap();
+        let second = store.enqueue("logid-b", "i18n", &["a.b.c".to_string()]).unwrap();
+        assert_eq!(first.id, 1);
+        assert_eq!(second.id, 2);
+
+        let mut entries = store.load().unwrap();
+        assert_eq!(entries.len(), 2);
+        assert!(entries.iter().all(|e| e.status == QueueStatus::Pending));
+
+        entries[0].status = QueueStatus::Done;
+        store.save(&entries).unwrap();
+
+        assert_eq!(store.clear_done().unwrap(), 1);
+        let entries = store.load().unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].logid, "logid-b");
+        assert_eq!(store.results_dir(), dir.path().join("queue_results"));
+    }
+
+    #[test]
+    fn test_queue_counts_only_attempts_that_reached_service() {
+        use crate::error::LogidError;
+
+        let dir = tempfile::tempdir().unwrap();
+        let store = QueueStore::new(dir.path().join("queue.json"));
+        let mut entry = store.enqueue("logid-a", "us", &[]).unwrap();
+
+        // 认证不可用时请求没有到达日志服务，条目保持等待
+        for _ in 0..3 {
+            entry.record_failure(&LogidError::MissingCredentials("us".to_string()), 2);
+        }
+        assert_eq!((entry.attempts, entry.status), (0, QueueStatus::Pending));
+        assert!(entry.last_error.as_deref().unwrap().contains("缺少认证凭据"));
+
+        let service_error = || LogidError::QueryFailed("us".to_string(), anyhow::anyhow!("HTTP 500"));
+        entry.record_failure(&service_error(), 2);
+        assert_eq!((entry.attempts, entry.status), (1, QueueStatus::Pending));
+        entry.record_failure(&service_error(), 2);
+        assert_eq!((entry.attempts, entry.status), (2, QueueStatus::Failed));
+    }
+
+    #[test]
+    fn test_history_store_search_and_get() {
+        use crate::log_query::QueryContext;
+
+        let dir = tempfile::tempdir().unwrap();
+        let store = HistoryStore::new(dir.path().join("history.jsonl"));
+
+        let first = QueryContext {
+            psm_list: vec!["pay.api".to_string()],
+            ..QueryContext::new(vec!["logid-a".to_string()], vec!["us".to_string()])
+        };
+        let second = QueryContext::new(vec!["logid-b".to_string()], vec!["i18n".to_string()]);
+        store.record(&HistoryEntry::new(&first, 12, 0)).unwrap();
+        store.record(&HistoryEntry::new(&second, 0, 1)).unwrap();
+
+        assert_eq!(store.list().unwrap().len(), 2);
+        let found = store.search("PAY").unwrap();
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].0, 1);
+        assert_eq!(found[0].1.result_count, 12);
+
+        let entry = store.get(2).unwrap();
+        assert_eq!(entry.context, second);
+        assert!(store.get(3).is_err());
+    }
+
+    #[test]
+    fn test_missing_store_is_empty() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = AnnotationStore::new(dir.path().join("missing.jsonl"));
+        assert!(store.list().unwrap().is_empty());
+    }
+}
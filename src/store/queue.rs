@@ -0,0 +1,173 @@
+//! 查询队列存储模块
+
+use crate::config::user_config_dir;
+use crate::error::{exit_code, LogidError};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// 队列文件名
+const QUEUE_FILE_NAME: &str = "queue.json";
+/// 队列结果目录名
+const QUEUE_RESULTS_DIR: &str = "queue_results";
+
+/// 队列条目状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum QueueStatus {
+    /// 等待查询
+    Pending,
+    /// 查询成功
+    Done,
+    /// 超过最大重试次数
+    Failed,
+}
+
+/// 查询队列条目
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QueueEntry {
+    /// 条目编号
+    pub id: u64,
+    /// 日志 ID
+    pub logid: String,
+    /// 查询区域
+    pub region: String,
+    /// PSM 服务列表
+    #[serde(default)]
+    pub psm_list: Vec<String>,
+    /// 加入队列的时间
+    pub added_at: String,
+    /// 已尝试次数
+    #[serde(default)]
+    pub attempts: u32,
+    /// 最近一次失败原因
+    #[serde(default)]
+    pub last_error: Option<String>,
+    /// 当前状态
+    pub status: QueueStatus,
+    /// 查询结果文件路径
+    #[serde(default)]
+    pub result_path: Option<String>,
+}
+
+impl QueueEntry {
+    /// 记录一次失败的查询
+    ///
+    /// 只有请求到达了日志服务的失败才计入尝试次数，达到 `max_attempts` 时标记为失败；
+    /// 网络不可用（断网、VPN 未连接）和认证不可用时条目保持等待，留到网络恢复后重试。
+    pub fn record_failure(&mut self, error: &LogidError, max_attempts: u32) {
+        self.last_error = Some(error.to_string());
+        if !reached_service(error) {
+            return;
+        }
+        self.attempts += 1;
+        if self.attempts >= max_attempts {
+            self.status = QueueStatus::Failed;
+        }
+    }
+}
+
+/// 错误是否发生在请求到达日志服务之后
+fn reached_service(error: &LogidError) -> bool {
+    match error {
+        LogidError::QueryFailed(..) => true,
+        error => !matches!(error.exit_code(), exit_code::NETWORK | exit_code::AUTH),
+    }
+}
+
+/// 查询队列存储
+///
+/// 队列保存在 `~/.config/logid/queue.json`，查询结果写入 `queue_results/` 目录。
+#[derive(Debug, Clone)]
+pub struct QueueStore {
+    path: PathBuf,
+}
+
+impl QueueStore {
+    /// 使用指定文件创建队列存储
+    pub fn new(path: PathBuf) -> Self {
+        Self { path }
+    }
+
+    /// 使用用户配置目录下的默认文件创建队列存储
+    pub fn open_default() -> Result<Self, LogidError> {
+        let dir = user_config_dir()
+            .ok_or_else(|| LogidError::InternalError("无法确定用户主目录".to_string()))?;
+        Ok(Self::new(dir.join(QUEUE_FILE_NAME)))
+    }
+
+    /// 队列文件路径
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// 查询结果目录
+    pub fn results_dir(&self) -> PathBuf {
+        self.path
+            .parent()
+            .map(|dir| dir.join(QUEUE_RESULTS_DIR))
+            .unwrap_or_else(|| PathBuf::from(QUEUE_RESULTS_DIR))
+    }
+
+    /// 读取全部队列条目
+    pub fn load(&self) -> Result<Vec<QueueEntry>, LogidError> {
+        if !self.path.exists() {
+            return Ok(Vec::new());
+        }
+        let content = std::fs::read_to_string(&self.path)?;
+        if content.trim().is_empty() {
+            return Ok(Vec::new());
+        }
+        Ok(serde_json::from_str(&content)?)
+    }
+
+    /// 保存全部队列条目（先写临时文件再替换，避免中断时损坏队列）
+    pub fn save(&self, entries: &[QueueEntry]) -> Result<(), LogidError> {
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let tmp_path = self.path.with_extension("json.tmp");
+        std::fs::write(&tmp_path, serde_json::to_string_pretty(entries)?)?;
+        std::fs::rename(&tmp_path, &self.path)?;
+        Ok(())
+    }
+
+    /// 将 logid 加入队列
+    pub fn enqueue(
+        &self,
+        logid: &str,
+        region: &str,
+        psm_list: &[String],
+    ) -> Result<QueueEntry, LogidError> {
+        let mut entries = self.load()?;
+        let id = entries.iter().map(|e| e.id).max().unwrap_or(0) + 1;
+
+        let entry = QueueEntry {
+            id,
+            logid: logid.to_string(),
+            region: region.to_string(),
+            psm_list: psm_list.to_vec(),
+            added_at: chrono::Utc::now().to_rfc3339(),
+            attempts: 0,
+            last_error: None,
+            status: QueueStatus::Pending,
+            result_path: None,
+        };
+
+        entries.push(entry.clone());
+        self.save(&entries)?;
+        Ok(entry)
+    }
+
+    /// 移除已完成的条目，返回移除的数量
+    pub fn clear_done(&self) -> Result<usize, LogidError> {
+        let entries = self.load()?;
+        let before = entries.len();
+        let remaining: Vec<QueueEntry> = entries
+            .into_iter()
+            .filter(|e| e.status != QueueStatus::Done)
+            .collect();
+        let removed = before - remaining.len();
+        self.save(&remaining)?;
+        Ok(removed)
+    }
+}
@@ -0,0 +1,207 @@
+//! 错误上报模块（需启用 `telemetry` 特性）
+//!
+//! 仅在编译时启用 `telemetry` 特性且配置了 `LOGID_TELEMETRY_URL` 时生效，
+//! 捕获 panic 和意外的 `InternalError`，以 JSON 形式 POST 到指定地址。
+//! 上报内容只包含脱敏后的错误信息、版本和平台，不包含 logid、查询参数或凭据。
+
+use crate::config::EnvManager;
+use crate::error::LogidError;
+use regex::Regex;
+use serde::Serialize;
+use std::sync::OnceLock;
+use std::time::Duration;
+
+/// 上报地址环境变量
+pub const TELEMETRY_URL_ENV: &str = "LOGID_TELEMETRY_URL";
+
+/// 上报请求超时时间
+const REPORT_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// 上报配置
+#[derive(Debug, Clone)]
+pub struct TelemetryConfig {
+    /// 上报地址
+    pub endpoint: String,
+}
+
+impl TelemetryConfig {
+    /// 从环境变量读取配置，未设置上报地址时返回 `None`（默认关闭）
+    pub fn from_env(env_manager: &EnvManager) -> Option<Self> {
+        env_manager
+            .get_env(TELEMETRY_URL_ENV)
+            .map(|v| v.trim().to_string())
+            .filter(|v| !v.is_empty())
+            .map(|endpoint| Self { endpoint })
+    }
+}
+
+/// 错误报告
+#[derive(Debug, Clone, Serialize)]
+pub struct ErrorReport {
+    /// 报告类型：panic 或 internal_error
+    pub kind: &'static str,
+    /// 脱敏后的错误信息
+    pub message: String,
+    /// panic 发生的源码位置
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub location: Option<String>,
+    /// 执行的子命令
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub command: Option<String>,
+    /// logid 版本
+    pub version: &'static str,
+    /// 操作系统
+    pub os: &'static str,
+    /// CPU 架构
+    pub arch: &'static str,
+}
+
+impl ErrorReport {
+    fn new(kind: &'static str, message: &str, location: Option<String>, command: Option<String>) -> Self {
+        Self {
+            kind,
+            message: redact(message),
+            location,
+            command,
+            version: env!("CARGO_PKG_VERSION"),
+            os: std::env::consts::OS,
+            arch: std::env::consts::ARCH,
+        }
+    }
+}
+
+/// 错误上报器
+#[derive(Debug, Clone)]
+pub struct Telemetry {
+    config: TelemetryConfig,
+    command: Option<String>,
+}
+
+impl Telemetry {
+    /// 创建上报器
+    pub fn new(config: TelemetryConfig) -> Self {
+        Self { config, command: None }
+    }
+
+    /// 设置当前执行的子命令，随报告一起上报
+    pub fn with_command(mut self, command: impl Into<String>) -> Self {
+        self.command = Some(command.into());
+        self
+    }
+
+    /// 安装 panic 钩子，上报后继续执行原有的钩子
+    pub fn install_panic_hook(&self) {
+        let telemetry = self.clone();
+        let previous = std::panic::take_hook();
+        std::panic::set_hook(Box::new(move |info| {
+            let payload = info.payload();
+            let message = payload
+                .downcast_ref::<&str>()
+                .map(|s| s.to_string())
+                .or_else(|| payload.downcast_ref::<String>().cloned())
+                .unwrap_or_else(|| "未知 panic".to_string());
+            let location = info.location().map(|l| format!("{}:{}", l.file(), l.line()));
+            telemetry.send_blocking(ErrorReport::new("panic", &message, location, telemetry.command.clone()));
+            previous(info);
+        }));
+    }
+
+    /// 上报意外的内部错误，其他类型的错误属于用户环境问题，不上报
+    pub async fn report_error(&self, error: &LogidError) {
+        if let LogidError::InternalError(message) = error {
+            self.send(&ErrorReport::new("internal_error", message, None, self.command.clone()))
+                .await;
+        }
+    }
+
+    /// 发送报告，失败时静默忽略，不影响命令本身的结果
+    pub async fn send(&self, report: &ErrorReport) {
+        let client = match reqwest::Client::builder().timeout(REPORT_TIMEOUT).build() {
+            Ok(client) => client,
+            Err(_) => return,
+        };
+        match client.post(&self.config.endpoint).json(report).send().await {
+            Ok(response) => conditional_info!("错误报告已发送: {}", response.status()),
+            Err(e) => conditional_info!("错误报告发送失败: {}", e),
+        }
+    }
+
+    /// 在独立线程中同步发送报告，用于 panic 钩子等无法 await 的场景
+    fn send_blocking(&self, report: ErrorReport) {
+        let telemetry = self.clone();
+        let handle = std::thread::spawn(move || {
+            if let Ok(runtime) = tokio::runtime::Builder::new_current_thread().enable_all().build() {
+                runtime.block_on(telemetry.send(&report));
+            }
+        });
+        let _ = handle.join();
+    }
+}
+
+/// 脱敏：移除令牌、邮箱、IP 地址、UUID 和用户主目录等可能识别用户或请求的信息
+pub fn redact(text: &str) -> String {
+    static PATTERNS: OnceLock<Vec<(Regex, &'static str)>> = OnceLock::new();
+    let patterns = PATTERNS.get_or_init(|| {
+        [
+            (r"eyJ[A-Za-z0-9_-]+\.[A-Za-z0-9_-]+\.[A-Za-z0-9_-]*", "<jwt>"),
+            (r"[\w.+-]+@[\w-]+(\.[\w-]+)+", "<email>"),
+            (r"[0-9a-fA-F]{8}-[0-9a-fA-F]{4}-[0-9a-fA-F]{4}-[0-9a-fA-F]{4}-[0-9a-fA-F]{12}", "<uuid>"),
+            (r"\b\d{1,3}(\.\d{1,3}){3}\b", "<ip>"),
+            (r"(/home|/Users)/[^/\s]+", "~"),
+            (r"[A-Za-z0-9_-]{24,}", "<redacted>"),
+        ]
+        .into_iter()
+        .map(|(pattern, replacement)| (Regex::new(pattern).expect("内置脱敏规则无效"), replacement))
+        .collect()
+    });
+
+    patterns
+        .iter()
+        .fold(text.to_string(), |text, (regex, replacement)| {
+            regex.replace_all(&text, *replacement).into_owned()
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{http_response, MockServer};
+    use std::sync::{Arc, Mutex};
+
+    #[tokio::test]
+    async fn test_report_internal_error_is_redacted() {
+        let received = Arc::new(Mutex::new(Vec::new()));
+        let sink = received.clone();
+        let server = MockServer::start(Duration::ZERO, move |_, request| {
+            sink.lock().unwrap().push(request.to_string());
+            http_response(200, &[], "{}")
+        })
+        .await;
+        let telemetry = Telemetry::new(TelemetryConfig { endpoint: server.url("/report") })
+            .with_command("query");
+
+        // 用户环境错误不上报
+        telemetry
+            .report_error(&LogidError::MissingCredentials("CAS_SESSION_US".to_string()))
+            .await;
+        assert_eq!(server.request_count(), 0);
+
+        let error = LogidError::InternalError(
+            "读取 /home/alice/.cache/logid 失败: token=eyJhbGciOiJIUzI1NiJ9.eyJzdWIiOiIxIn0.abc \
+             logid=550e8400-e29b-41d4-a716-446655440000 from 10.0.0.1 by alice@example.com"
+                .to_string(),
+        );
+        telemetry.report_error(&error).await;
+
+        assert_eq!(server.request_count(), 1);
+        let request = received.lock().unwrap()[0].clone();
+        let body: serde_json::Value =
+            serde_json::from_str(&request[request.find("\r\n\r\n").unwrap() + 4..]).unwrap();
+        assert_eq!(body["kind"], "internal_error");
+        assert_eq!(body["command"], "query");
+        assert_eq!(
+            body["message"],
+            "读取 ~/.cache/logid 失败: token=<jwt> logid=<uuid> from <ip> by <email>"
+        );
+    }
+}
@@ -0,0 +1,161 @@
+//! 测试辅助模块
+//!
+//! 提供一个最小的本地 HTTP 服务，用于在不访问真实服务的情况下测试认证和查询流程。
+//! 模拟服务在 `/jwt` 提供认证端点、在 `/query` 提供日志查询端点时，可以直接用
+//! [`mock_client`] 创建指向它的美区查询客户端。[`message`] 用于构造测试中的日志消息。
+
+use crate::auth::AuthManager;
+use crate::config::{Region, RegionConfig};
+use crate::log_query::{ExtractedLogMessage, ExtractedValue, LogGroup, LogQueryClient};
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+/// 本地模拟服务
+pub(crate) struct MockServer {
+    /// 监听地址
+    pub addr: SocketAddr,
+    /// 已处理的请求数
+    pub requests: Arc<AtomicUsize>,
+}
+
+impl MockServer {
+    /// 启动模拟服务
+    ///
+    /// `respond` 接收请求序号（从 1 开始）和完整的请求文本（请求头和请求体），返回完整的 HTTP 响应；
+    /// `delay` 为每个响应前的等待时间，用于模拟慢速服务。
+    pub async fn start<F>(delay: Duration, respond: F) -> Self
+    where
+        F: Fn(usize, &str) -> String + Send + Sync + 'static,
+    {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let requests = Arc::new(AtomicUsize::new(0));
+        let respond = Arc::new(respond);
+
+        let counter = requests.clone();
+        tokio::spawn(async move {
+            while let Ok((socket, _)) = listener.accept().await {
+                let counter = counter.clone();
+                let respond = respond.clone();
+                tokio::spawn(async move {
+                    handle_connection(socket, delay, counter, respond.as_ref()).await;
+                });
+            }
+        });
+
+        Self { addr, requests }
+    }
+
+    /// 获取指定路径的 URL
+    pub fn url(&self, path: &str) -> String {
+        format!("http://{}{}", self.addr, path)
+    }
+
+    /// 已处理的请求数
+    pub fn request_count(&self) -> usize {
+        self.requests.load(Ordering::SeqCst)
+    }
+}
+
+/// 使用模拟服务 `/jwt` 端点认证的美区认证管理器
+pub(crate) fn mock_auth(server: &MockServer) -> AuthManager {
+    AuthManager::with_credentials(Region::Us, "session".to_string(), server.url("/jwt")).unwrap()
+}
+
+/// 查询模拟服务 `/query` 端点的美区配置
+pub(crate) fn mock_region_config(server: &MockServer) -> RegionConfig {
+    RegionConfig::new(Region::Us, server.url("/query"), "US-TTP".to_string(), Vec::new())
+}
+
+/// 指向模拟服务的美区查询客户端
+pub(crate) async fn mock_client(server: &MockServer) -> LogQueryClient {
+    LogQueryClient::new(mock_auth(server), mock_region_config(server)).await.unwrap()
+}
+
+/// 构造只包含一个 `_msg` 值的日志消息
+pub(crate) fn message(psm: &str, level: &str, msg: &str) -> ExtractedLogMessage {
+    ExtractedLogMessage {
+        group: LogGroup {
+            psm: Some(psm.to_string()),
+            ..LogGroup::default()
+        },
+        values: vec![ExtractedValue {
+            key: "_msg".to_string(),
+            value: msg.to_string(),
+            original_value: msg.to_string(),
+            type_field: None,
+            highlight: false,
+        }],
+        level: Some(level.to_string()),
+        ..ExtractedLogMessage::default()
+    }
+}
+
+/// 构造 HTTP 响应
+pub(crate) fn http_response(status: u16, headers: &[(&str, &str)], body: &str) -> String {
+    let mut response = format!("HTTP/1.1 {} OK\r\n", status);
+    for (name, value) in headers {
+        response.push_str(&format!("{}: {}\r\n", name, value));
+    }
+    response.push_str(&format!(
+        "content-length: {}\r\nconnection: close\r\n\r\n{}",
+        body.len(),
+        body
+    ));
+    response
+}
+
+async fn handle_connection<F>(mut socket: TcpStream, delay: Duration, counter: Arc<AtomicUsize>, respond: &F)
+where
+    F: Fn(usize, &str) -> String,
+{
+    let Some(request) = read_request(&mut socket).await else {
+        return;
+    };
+
+    let n = counter.fetch_add(1, Ordering::SeqCst) + 1;
+    tokio::time::sleep(delay).await;
+    let _ = socket.write_all(respond(n, &request).as_bytes()).await;
+}
+
+/// 读取完整请求，返回请求头和请求体文本
+async fn read_request(socket: &mut TcpStream) -> Option<String> {
+    let mut buf = Vec::new();
+    let mut chunk = [0u8; 4096];
+
+    let header_end = loop {
+        let read = socket.read(&mut chunk).await.ok()?;
+        if read == 0 {
+            return None;
+        }
+        buf.extend_from_slice(&chunk[..read]);
+        if let Some(pos) = buf.windows(4).position(|w| w == b"\r\n\r\n") {
+            break pos + 4;
+        }
+    };
+
+    let head = String::from_utf8_lossy(&buf[..header_end]).to_string();
+    let content_length = head
+        .lines()
+        .find_map(|line| {
+            let (name, value) = line.split_once(':')?;
+            name.eq_ignore_ascii_case("content-length")
+                .then(|| value.trim().parse::<usize>().ok())
+                .flatten()
+        })
+        .unwrap_or(0);
+
+    while buf.len() < header_end + content_length {
+        let read = socket.read(&mut chunk).await.ok()?;
+        if read == 0 {
+            break;
+        }
+        buf.extend_from_slice(&chunk[..read]);
+    }
+
+    Some(String::from_utf8_lossy(&buf).to_string())
+}
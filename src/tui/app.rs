@@ -0,0 +1,218 @@
+//! 结果浏览器的状态与按键处理
+
+use crate::log_query::{normalize_level, DetailedLogResult, ExtractedLogMessage};
+use ratatui::crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use std::collections::BTreeMap;
+
+/// 左侧消息列表的分组方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Grouping {
+    /// 按 PSM 分组
+    Psm,
+    /// 按 Pod 分组
+    Pod,
+}
+
+impl Grouping {
+    /// 分组方式名称
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Psm => "PSM",
+            Self::Pod => "Pod",
+        }
+    }
+
+    fn key(self, message: &ExtractedLogMessage) -> String {
+        let value = match self {
+            Self::Psm => message.group.psm.as_deref(),
+            Self::Pod => message.group.pod_name.as_deref(),
+        };
+        value.unwrap_or("-").to_string()
+    }
+}
+
+/// 按键处理结果
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Action {
+    /// 无需额外处理
+    None,
+    /// 退出浏览器
+    Quit,
+    /// 将内容复制到剪贴板
+    Copy(String),
+}
+
+/// 结果浏览器状态
+pub struct App {
+    result: DetailedLogResult,
+    /// 分组方式
+    pub grouping: Grouping,
+    /// 当前级别过滤（已规范化），为空时显示全部
+    pub level: Option<String>,
+    /// 当前搜索关键字
+    pub search: String,
+    /// 是否正在输入搜索关键字
+    pub searching: bool,
+    /// 选中的消息在可见消息中的位置
+    pub selected: usize,
+    /// 状态栏提示
+    pub status: String,
+}
+
+impl App {
+    /// 创建浏览器状态
+    pub fn new(result: DetailedLogResult) -> Self {
+        Self {
+            result,
+            grouping: Grouping::Psm,
+            level: None,
+            search: String::new(),
+            searching: false,
+            selected: 0,
+            status: String::new(),
+        }
+    }
+
+    /// 查询结果
+    pub fn result(&self) -> &DetailedLogResult {
+        &self.result
+    }
+
+    /// 结果中出现过的日志级别（已规范化，排序去重）
+    pub fn levels(&self) -> Vec<String> {
+        let mut levels: Vec<String> = self
+            .result
+            .messages
+            .iter()
+            .filter_map(|message| message.level.as_deref())
+            .map(normalize_level)
+            .collect();
+        levels.sort();
+        levels.dedup();
+        levels
+    }
+
+    /// 按当前分组方式列出可见消息的下标，分组按名称排序
+    pub fn groups(&self) -> Vec<(String, Vec<usize>)> {
+        let mut groups: BTreeMap<String, Vec<usize>> = BTreeMap::new();
+        for (index, message) in self.result.messages.iter().enumerate() {
+            if self.is_visible(message) {
+                groups.entry(self.grouping.key(message)).or_default().push(index);
+            }
+        }
+        groups.into_iter().collect()
+    }
+
+    /// 按显示顺序列出可见消息的下标
+    pub fn visible(&self) -> Vec<usize> {
+        self.groups().into_iter().flat_map(|(_, indices)| indices).collect()
+    }
+
+    /// 当前选中的消息
+    pub fn selected_message(&self) -> Option<&ExtractedLogMessage> {
+        self.visible()
+            .get(self.selected)
+            .map(|&index| &self.result.messages[index])
+    }
+
+    fn is_visible(&self, message: &ExtractedLogMessage) -> bool {
+        if let Some(level) = &self.level {
+            if message.level.as_deref().map(normalize_level).as_ref() != Some(level) {
+                return false;
+            }
+        }
+        if self.search.is_empty() {
+            return true;
+        }
+        let needle = self.search.to_lowercase();
+        message.values.iter().any(|value| {
+            value.value.to_lowercase().contains(&needle)
+                || value.original_value.to_lowercase().contains(&needle)
+        })
+    }
+
+    /// 处理按键
+    pub fn handle_key(&mut self, key: KeyEvent) -> Action {
+        if self.searching {
+            self.handle_search_key(key);
+            return Action::None;
+        }
+
+        let count = self.visible().len();
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => return Action::Quit,
+            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                return Action::Quit
+            }
+            KeyCode::Down | KeyCode::Char('j') => {
+                self.selected = (self.selected + 1).min(count.saturating_sub(1));
+            }
+            KeyCode::Up | KeyCode::Char('k') => self.selected = self.selected.saturating_sub(1),
+            KeyCode::PageDown => self.selected = (self.selected + 10).min(count.saturating_sub(1)),
+            KeyCode::PageUp => self.selected = self.selected.saturating_sub(10),
+            KeyCode::Home | KeyCode::Char('g') => self.selected = 0,
+            KeyCode::End | KeyCode::Char('G') => self.selected = count.saturating_sub(1),
+            KeyCode::Tab => {
+                self.grouping = match self.grouping {
+                    Grouping::Psm => Grouping::Pod,
+                    Grouping::Pod => Grouping::Psm,
+                };
+                self.selected = 0;
+            }
+            KeyCode::Char('l') => self.cycle_level(),
+            KeyCode::Char('/') => {
+                self.searching = true;
+                self.search.clear();
+                self.selected = 0;
+            }
+            KeyCode::Char('y') => {
+                if let Some(text) = self.selected_message().map(original_text) {
+                    self.status = "已复制原始消息".to_string();
+                    return Action::Copy(text);
+                }
+            }
+            _ => {}
+        }
+        Action::None
+    }
+
+    fn handle_search_key(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Enter => self.searching = false,
+            KeyCode::Esc => {
+                self.searching = false;
+                self.search.clear();
+            }
+            KeyCode::Backspace => {
+                self.search.pop();
+            }
+            KeyCode::Char(c) => self.search.push(c),
+            _ => {}
+        }
+        self.selected = 0;
+    }
+
+    /// 依次切换级别过滤：全部 → 各个级别 → 全部
+    fn cycle_level(&mut self) {
+        let levels = self.levels();
+        self.level = match &self.level {
+            None => levels.first().cloned(),
+            Some(current) => levels
+                .iter()
+                .position(|level| level == current)
+                .and_then(|position| levels.get(position + 1))
+                .cloned(),
+        };
+        self.selected = 0;
+    }
+}
+
+/// 消息的原始（未过滤）内容，多个值按行拼接
+pub fn original_text(message: &ExtractedLogMessage) -> String {
+    message
+        .values
+        .iter()
+        .map(|value| value.original_value.as_str())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
@@ -0,0 +1,130 @@
+//! 交互式结果浏览器模块（需启用 `tui` 特性）
+//!
+//! 基于 ratatui 浏览 `DetailedLogResult`：左侧按 PSM/Pod 分组列出消息，
+//! 右侧显示选中消息的完整内容及过滤前的原始值，支持级别过滤、搜索和复制原始消息。
+
+mod app;
+mod ui;
+
+pub use app::{original_text, Action, App, Grouping};
+
+use crate::error::LogidError;
+use crate::log_query::DetailedLogResult;
+use base64::Engine;
+use ratatui::crossterm::event::{self, Event, KeyEventKind};
+use std::io::Write;
+
+/// 打开结果浏览器，按 q 或 Esc 退出
+pub fn run(result: DetailedLogResult) -> Result<(), LogidError> {
+    let mut terminal = ratatui::try_init()?;
+    let outcome = event_loop(&mut terminal, App::new(result));
+    ratatui::try_restore()?;
+    outcome
+}
+
+fn event_loop(terminal: &mut ratatui::DefaultTerminal, mut app: App) -> Result<(), LogidError> {
+    loop {
+        terminal.draw(|frame| ui::draw(frame, &app))?;
+
+        let Event::Key(key) = event::read()? else {
+            continue;
+        };
+        if key.kind != KeyEventKind::Press {
+            continue;
+        }
+        match app.handle_key(key) {
+            Action::None => {}
+            Action::Quit => return Ok(()),
+            Action::Copy(text) => copy_to_clipboard(&text)?,
+        }
+    }
+}
+
+/// 通过 OSC 52 转义序列写入剪贴板，SSH 会话中同样有效
+fn copy_to_clipboard(text: &str) -> Result<(), LogidError> {
+    let encoded = base64::engine::general_purpose::STANDARD.encode(text);
+    let mut stdout = std::io::stdout();
+    write!(stdout, "\x1b]52;c;{}\x07", encoded)?;
+    stdout.flush()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::log_query::ExtractedLogMessage;
+    use ratatui::crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+    fn message(id: &str, psm: &str, pod: &str, level: &str, msg: &str) -> ExtractedLogMessage {
+        let mut message = crate::test_support::message(psm, level, msg);
+        message.id = id.to_string();
+        message.group.pod_name = Some(pod.to_string());
+        message.values[0].original_value = format!("LogID=abc {}", msg);
+        message
+    }
+
+    fn press(app: &mut App, code: KeyCode) -> Action {
+        app.handle_key(KeyEvent::new(code, KeyModifiers::NONE))
+    }
+
+    #[test]
+    fn test_tui_grouping_filtering_and_copy() {
+        let result = DetailedLogResult {
+            logid: "logid-1".to_string(),
+            messages: vec![
+                message("1", "pay.api", "pod-b", "INFO", "request received"),
+                message("2", "user.api", "pod-a", "ERROR", "db timeout"),
+                message("3", "pay.api", "pod-a", "WARNING", "retrying"),
+            ],
+            meta: None,
+            tag_infos: None,
+            total_items: 3,
+            scan_time_range: None,
+            level_list: None,
+            timestamp: String::new(),
+            region: "us".to_string(),
+            region_display_name: "美区".to_string(),
+            truncated: false,
+            credential_expiry: None,
+            stats: None,
+            level_filtered: 0,
+            scan_widening: None,
+            context: None,
+        };
+        let mut app = App::new(result);
+
+        // 按 PSM 分组，组内保持原始顺序
+        assert_eq!(app.visible(), vec![0, 2, 1]);
+        press(&mut app, KeyCode::Tab);
+        assert_eq!(app.grouping, Grouping::Pod);
+        assert_eq!(app.visible(), vec![1, 2, 0]);
+
+        // 级别依次切换，WARNING 视为 WARN
+        assert_eq!(app.levels(), vec!["ERROR", "INFO", "WARN"]);
+        press(&mut app, KeyCode::Char('l'));
+        assert_eq!(app.visible(), vec![1]);
+        for _ in 0..3 {
+            press(&mut app, KeyCode::Char('l'));
+        }
+        assert_eq!(app.level, None);
+
+        // 搜索同时匹配过滤前后的内容
+        press(&mut app, KeyCode::Char('/'));
+        for c in "retry".chars() {
+            press(&mut app, KeyCode::Char(c));
+        }
+        press(&mut app, KeyCode::Enter);
+        assert_eq!(app.visible(), vec![2]);
+
+        let mut terminal = ratatui::Terminal::new(ratatui::backend::TestBackend::new(100, 20)).unwrap();
+        terminal.draw(|frame| ui::draw(frame, &app)).unwrap();
+        let screen: String = terminal.backend().buffer().content().iter().map(|cell| cell.symbol()).collect();
+        assert!(screen.contains("pod-a (1)") && screen.contains("LogID=abc retrying"));
+
+        assert_eq!(
+            press(&mut app, KeyCode::Char('y')),
+            Action::Copy("LogID=abc retrying".to_string())
+        );
+        assert_eq!(press(&mut app, KeyCode::Char('q')), Action::Quit);
+    }
+}
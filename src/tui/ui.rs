@@ -0,0 +1,140 @@
+//! 结果浏览器的界面绘制
+
+use crate::tui::app::App;
+use ratatui::layout::{Constraint, Direction, Layout};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, List, ListItem, ListState, Paragraph, Wrap};
+use ratatui::Frame;
+
+/// 绘制整个界面
+pub fn draw(frame: &mut Frame, app: &App) {
+    let [main, footer] = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(3), Constraint::Length(1)])
+        .areas(frame.area());
+    let [left, right] = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(40), Constraint::Percentage(60)])
+        .areas(main);
+
+    draw_list(frame, app, left);
+    draw_detail(frame, app, right);
+    frame.render_widget(Paragraph::new(footer_line(app)), footer);
+}
+
+/// 左侧：按分组列出消息，分组标题不可选中
+fn draw_list(frame: &mut Frame, app: &App, area: ratatui::layout::Rect) {
+    let messages = &app.result().messages;
+    let mut items = Vec::new();
+    let mut selected_row = None;
+    let mut position = 0;
+
+    for (name, indices) in app.groups() {
+        items.push(ListItem::new(Line::from(Span::styled(
+            format!("{} ({})", name, indices.len()),
+            Style::default().add_modifier(Modifier::BOLD),
+        ))));
+        for index in indices {
+            let message = &messages[index];
+            let level = message.level.as_deref().unwrap_or("-");
+            let text = message.values.first().map(|v| v.value.as_str()).unwrap_or_default();
+            if position == app.selected {
+                selected_row = Some(items.len());
+            }
+            items.push(ListItem::new(Line::from(vec![
+                Span::styled(format!("  {:<5} ", level), level_style(level)),
+                Span::raw(text.lines().next().unwrap_or_default().to_string()),
+            ])));
+            position += 1;
+        }
+    }
+
+    let title = format!(" {} · {} 条 · 按 {} 分组 ", app.result().logid, position, app.grouping.label());
+    let list = List::new(items)
+        .block(Block::default().borders(Borders::ALL).title(title))
+        .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+    let mut state = ListState::default().with_selected(selected_row);
+    frame.render_stateful_widget(list, area, &mut state);
+}
+
+/// 右侧：选中消息的完整内容，过滤前后不同时同时显示原始值
+fn draw_detail(frame: &mut Frame, app: &App, area: ratatui::layout::Rect) {
+    let mut lines = Vec::new();
+    if let Some(message) = app.selected_message() {
+        let field = |name: &str, value: Option<&str>| {
+            Line::from(vec![
+                Span::styled(format!("{:<8}", name), Style::default().fg(Color::DarkGray)),
+                Span::raw(value.unwrap_or("-").to_string()),
+            ])
+        };
+        lines.push(field("id", Some(&message.id)));
+        lines.push(field("level", message.level.as_deref()));
+        lines.push(field("time", message.time.as_deref()));
+        lines.push(field("psm", message.group.psm.as_deref()));
+        lines.push(field("pod", message.group.pod_name.as_deref()));
+        lines.push(field("ip", message.group.ipv4.as_deref()));
+        lines.push(field("location", message.location.as_deref()));
+        for annotation in &message.annotations {
+            lines.push(field("note", Some(annotation)));
+        }
+
+        for value in &message.values {
+            lines.push(Line::default());
+            lines.push(Line::from(Span::styled(
+                value.key.clone(),
+                Style::default().add_modifier(Modifier::BOLD),
+            )));
+            lines.extend(value.value.lines().map(|line| Line::from(line.to_string())));
+            if value.original_value != value.value {
+                lines.push(Line::from(Span::styled(
+                    "原始值:",
+                    Style::default().fg(Color::Yellow),
+                )));
+                lines.extend(
+                    value
+                        .original_value
+                        .lines()
+                        .map(|line| Line::from(line.to_string())),
+                );
+            }
+        }
+    } else {
+        lines.push(Line::from("没有匹配的消息"));
+    }
+
+    let detail = Paragraph::new(lines)
+        .block(Block::default().borders(Borders::ALL).title(" 消息详情 "))
+        .wrap(Wrap { trim: false });
+    frame.render_widget(detail, area);
+}
+
+fn footer_line(app: &App) -> Line<'static> {
+    if app.searching {
+        return Line::from(format!("搜索: {}▏ (Enter 确认, Esc 取消)", app.search));
+    }
+
+    let mut parts = vec!["j/k 移动".to_string(), "Tab 分组".to_string()];
+    parts.push(format!("l 级别[{}]", app.level.as_deref().unwrap_or("全部")));
+    parts.push(if app.search.is_empty() {
+        "/ 搜索".to_string()
+    } else {
+        format!("/ 搜索[{}]", app.search)
+    });
+    parts.push("y 复制原始值".to_string());
+    parts.push("q 退出".to_string());
+    if !app.status.is_empty() {
+        parts.push(app.status.clone());
+    }
+    Line::from(Span::styled(parts.join("  "), Style::default().fg(Color::DarkGray)))
+}
+
+fn level_style(level: &str) -> Style {
+    let color = match level.to_uppercase().as_str() {
+        "ERROR" | "FATAL" => Color::Red,
+        "WARN" | "WARNING" => Color::Yellow,
+        "INFO" => Color::Green,
+        _ => Color::Gray,
+    };
+    Style::default().fg(color)
+}